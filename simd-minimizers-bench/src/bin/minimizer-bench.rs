@@ -0,0 +1,88 @@
+//! Compare the throughput and density of the schemes in `simd_minimizers::schemes` on a FASTA or FASTQ file.
+//!
+//! Usage: `minimizer-bench <file> <scheme> <k> <w> [seed]`, where `scheme` is one of `Params::NAMES`, or `all`,
+//! and `k` and `w` are a single value (`21`), a comma-separated list (`15,21,31`), or an inclusive range (`11..=31`).
+//! A CSV line is written to stdout for each scheme, `k`, and `w`.
+//! Records shorter than `l=w+k-1` are skipped, and are not counted in `bases` and the throughput.
+//!
+//! Install with `cargo +nightly install --path simd-minimizers-bench --bin minimizer-bench`.
+use std::{fs::File, hint::black_box, io::BufReader, process::exit, time::Instant};
+
+use simd_minimizers::{fasta::Records, schemes::Params};
+
+fn usage() -> ! {
+    eprintln!("Usage: minimizer-bench <file> <scheme> <k> <w> [seed]");
+    eprintln!("  scheme: all, {}", Params::NAMES.join(", "));
+    eprintln!("  k, w:   a value (21), a list (15,21,31), or a range (11..=31)");
+    exit(1);
+}
+
+/// Parse `21`, `15,21,31`, or `11..=31`.
+fn parse_values(arg: &str) -> Option<Vec<usize>> {
+    if let Some((start, end)) = arg.split_once("..=") {
+        return Some((start.parse().ok()?..=end.parse().ok()?).collect());
+    }
+    arg.split(',').map(|x| x.parse().ok()).collect()
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.len() < 4 || args.len() > 5 {
+        usage();
+    }
+    let names = match args[1].as_str() {
+        "all" => Params::NAMES.to_vec(),
+        name if Params::NAMES.contains(&name) => vec![name],
+        _ => usage(),
+    };
+    let (Some(ks), Some(ws)) = (parse_values(&args[2]), parse_values(&args[3])) else {
+        usage()
+    };
+    // Only used by `random-order`.
+    let seed = args
+        .get(4)
+        .map_or(0, |seed| seed.parse().unwrap_or_else(|_| usage()));
+
+    let file = File::open(&args[0]).unwrap_or_else(|e| {
+        eprintln!("Could not open {}: {e}", args[0]);
+        exit(1);
+    });
+    let seqs = Records::new(BufReader::new(file))
+        .map(|record| record.unwrap().seq)
+        .collect::<Vec<_>>();
+
+    println!("scheme,k,w,bases,kmers,sampled,density,seconds,mbp_per_s");
+    for &name in &names {
+        for &k in &ks {
+            for &w in &ws {
+                if k == 0 || w == 0 {
+                    continue;
+                }
+                let scheme = Params::from_name(name, k, w, Some(seed)).unwrap().build();
+                let l = k + w - 1;
+                // Records shorter than `l` contain no window, and are skipped.
+                let bases = seqs
+                    .iter()
+                    .filter(|seq| seq.len() >= l)
+                    .map(|seq| seq.len())
+                    .sum::<usize>();
+                let kmers = seqs
+                    .iter()
+                    .filter(|seq| seq.len() >= l)
+                    .map(|seq| seq.len() - k + 1)
+                    .sum::<usize>();
+
+                let start = Instant::now();
+                let mut sampled = 0;
+                for seq in seqs.iter().filter(|seq| seq.len() >= l) {
+                    sampled += black_box(scheme.positions(seq)).len();
+                }
+                let seconds = start.elapsed().as_secs_f64();
+
+                let density = sampled as f64 / kmers as f64;
+                let throughput = bases as f64 / seconds / 1e6;
+                println!("{name},{k},{w},{bases},{kmers},{sampled},{density:.6},{seconds:.6},{throughput:.2}");
+            }
+        }
+    }
+}
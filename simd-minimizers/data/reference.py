@@ -0,0 +1,84 @@
+#!/usr/bin/env python3
+"""Independent naive implementation that generates `reference.tsv`.
+
+This script shares no code with the crate. It follows the definitions of the
+simd-minimizers paper (Groot Koerkamp and Martayan, 2025) directly, one window at a time:
+
+- Characters are encoded as A=0, C=1, T=2, G=3, with complement `c ^ 2`.
+- The 32-bit ntHash of a k-mer is `fw + rotl(rc, k-1)` (wrapping), where
+  `fw = fold(rotl(h, 1) ^ F[c])` over the k-mer and `rc = fold(rotr(h, 1) ^ F[c ^ 2])`;
+  only the forward part is used for forward minimizers.
+- A minimizer is the k-mer with the smallest upper 16 bits of its hash, taking the leftmost on ties.
+- A window of length l = k+w-1 is canonical when `2 * #(G, T) >= l`.
+  Canonical windows take the leftmost minimal canonical hash, and other windows the rightmost.
+- Consecutive equal positions are deduplicated.
+
+Usage: `python3 reference.py < reference.tsv > new.tsv`.
+The `k`, `w`, and sequence columns of the input are kept, and the expected positions are recomputed.
+"""
+import sys
+
+F = [
+    0x3C8BFBB395C60474 & 0xFFFFFFFF,
+    0x3193C18562A02B4C & 0xFFFFFFFF,
+    0x20323ED082572324 & 0xFFFFFFFF,
+    0x295549F54BE24456 & 0xFFFFFFFF,
+]
+CODE = {"A": 0, "C": 1, "T": 2, "G": 3}
+MASK = 0xFFFFFFFF
+
+
+def rotl(x, r):
+    r %= 32
+    return ((x << r) | (x >> (32 - r))) & MASK
+
+
+def rotr(x, r):
+    return rotl(x, 32 - r % 32)
+
+
+def nthash(kmer, canonical):
+    fw = 0
+    rc = 0
+    for c in kmer:
+        fw = rotl(fw, 1) ^ F[c]
+        rc = rotr(rc, 1) ^ F[c ^ 2]
+    if not canonical:
+        return fw
+    return (fw + rotl(rc, len(kmer) - 1)) & MASK
+
+
+def minimizer_positions(seq, k, w, canonical):
+    codes = [CODE[c] for c in seq]
+    l = k + w - 1
+    keys = [nthash(codes[i : i + k], canonical) >> 16 for i in range(len(codes) - k + 1)]
+    positions = []
+    for start in range(len(codes) - l + 1):
+        window = range(start, start + w)
+        low = min(keys[i] for i in window)
+        gt = sum(1 for c in codes[start : start + l] if c & 2)
+        if not canonical or 2 * gt >= l:
+            pos = min(i for i in window if keys[i] == low)
+        else:
+            pos = max(i for i in window if keys[i] == low)
+        if not positions or positions[-1] != pos:
+            positions.append(pos)
+    return positions
+
+
+def main():
+    print("# Reference cases for the `test-data` feature; see `src/reference.rs`.")
+    print("# Generated by the independent naive implementation in `data/reference.py`. Do not edit.")
+    print("# k\tw\tsequence\tminimizer_positions\tcanonical_minimizer_positions")
+    for line in sys.stdin:
+        if line.startswith("#") or not line.strip():
+            continue
+        k, w, seq = line.split("\t")[:3]
+        k, w = int(k), int(w)
+        fwd = minimizer_positions(seq, k, w, False)
+        can = minimizer_positions(seq, k, w, True)
+        print(f"{k}\t{w}\t{seq}\t{','.join(map(str, fwd))}\t{','.join(map(str, can))}")
+
+
+if __name__ == "__main__":
+    main()
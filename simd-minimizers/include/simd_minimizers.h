@@ -0,0 +1,50 @@
+#ifndef SIMD_MINIMIZERS_H
+#define SIMD_MINIMIZERS_H
+
+/* Generated by cbindgen from src/capi.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Returned instead of the number of positions when the arguments are invalid.
+#define SIMD_MINIMIZERS_ERROR SIZE_MAX
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Write the deduplicated positions of all minimizers of the ASCII DNA sequence `seq_ptr[0..len]` to `out_ptr[0..out_cap]`.
+//
+// Returns the number of positions. When this is larger than `out_cap`, nothing is written.
+// Returns [`SIMD_MINIMIZERS_ERROR`] when `k` or `w` is 0, when `len` is at least `2^32`, or for a null pointer with non-zero length.
+//
+// # Safety
+//
+// `seq_ptr` must be valid for reads of `len` bytes, and `out_ptr` must be valid for writes of `out_cap` values.
+size_t simd_minimizers_positions(const uint8_t *seq_ptr,
+                                 size_t len,
+                                 size_t k,
+                                 size_t w,
+                                 uint32_t *out_ptr,
+                                 size_t out_cap);
+
+// Like [`simd_minimizers_positions`], but for canonical minimizers. `l=w+k-1` should be odd.
+//
+// # Safety
+//
+// `seq_ptr` must be valid for reads of `len` bytes, and `out_ptr` must be valid for writes of `out_cap` values.
+size_t simd_minimizers_canonical_positions(const uint8_t *seq_ptr,
+                                           size_t len,
+                                           size_t k,
+                                           size_t w,
+                                           uint32_t *out_ptr,
+                                           size_t out_cap);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SIMD_MINIMIZERS_H */
@@ -2,7 +2,10 @@
 //! A kmer's hash is simply its bit representation, but with the first character inverted.
 //! When k > 16, only the last 16 characters are used.
 //!
-//! TODO: Figure out how this works with reverse complements.
+//! The _canonical_ anti-lex hash of 2-bit DNA is the minimum of the anti-lex hashes of the k-mer and its reverse complement.
+//! When k > 16, the reverse complement hash uses the last 16 characters of the reverse complement,
+//! i.e., the complement of the first 16 characters of the k-mer, so that both strands give the same hash.
+//! For k < 16, the canonical hash is shifted to the high bits, since the sliding window minimum only compares the upper 16 bits.
 
 use crate::nthash::Captures;
 use crate::S;
@@ -106,3 +109,128 @@ pub fn anti_lex_hash_mapper<'s, Sq: Seq<'s>>(k: usize, w: usize) -> impl FnMut(S
         h_fw ^ anti
     }
 }
+
+/// Returns a scalar iterator over the 32-bit lexicographic hashes of all k-mers in the sequence,
+/// i.e., the anti-lex hash without inverting the first character.
+pub fn lex_hash_seq_scalar<'s>(
+    seq: impl Seq<'s>,
+    k: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let (anti, _mask) = anti_and_mask(k, seq.bits_per_char());
+    anti_lex_hash_seq_scalar(seq, k).map(move |h| h ^ anti)
+}
+
+/// Like [`anti_lex_hash_mapper`], but for the lexicographic hash that does not invert the first character.
+pub fn lex_hash_mapper<'s, Sq: Seq<'s>>(k: usize, w: usize) -> impl FnMut(S) -> S + Clone {
+    let (anti, _mask) = anti_and_mask(k, Sq::BITS_PER_CHAR);
+    let anti = S::splat(anti);
+    let mut anti_lex = anti_lex_hash_mapper::<Sq>(k, w);
+    move |a| anti_lex(a) ^ anti
+}
+
+/// The number of characters of a k-mer that the anti-lex hash depends on.
+fn hashed_chars(k: usize) -> usize {
+    k.min(16)
+}
+
+/// The shift that moves the `2*hashed_chars(k)` bits of the canonical hash to the high bits.
+fn canonical_shift(k: usize) -> usize {
+    32 - 2 * hashed_chars(k)
+}
+
+/// Naively compute the 32-bit canonical anti-lex hash of a single k-mer.
+/// The hash is left-aligned in the 32 bits.
+/// Only 2-bit DNA is supported.
+pub fn canonical_anti_lex_hash_kmer<'s>(seq: impl Seq<'s>) -> u32 {
+    assert_eq!(seq.bits_per_char(), 2);
+    let k = seq.len();
+    let (anti, _mask) = anti_and_mask(k, 2);
+    // In the `ACTG` encoding, the complement of `a` is `a^2`.
+    let mut hrc: u32 = 0;
+    seq.iter_bp()
+        .take(hashed_chars(k))
+        .enumerate()
+        .for_each(|(i, a)| {
+            hrc |= (a as u32 ^ 2) << (2 * i);
+        });
+    anti_lex_hash_kmer(seq).min(hrc ^ anti) << canonical_shift(k)
+}
+
+/// Returns a scalar iterator over the 32-bit canonical anti-lex hashes of all k-mers in the sequence.
+/// Only 2-bit DNA is supported.
+pub fn canonical_anti_lex_hash_seq_scalar<'s>(
+    seq: impl Seq<'s>,
+    k: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> + Clone {
+    assert_eq!(seq.bits_per_char(), 2);
+    assert!(k > 0);
+    let m = hashed_chars(k);
+    let shift = canonical_shift(k);
+    let (anti, mask) = anti_and_mask(k, 2);
+    let mut hfw: u32 = 0;
+    let mut hrc: u32 = 0;
+    let mut add = seq.iter_bp();
+    // The last character of the first `m` of each k-mer.
+    let mut mid = seq.iter_bp();
+    add.by_ref().take(k - 1).for_each(|a| {
+        hfw = (hfw << 2) ^ (a as u32);
+    });
+    mid.by_ref().take(m - 1).for_each(|c| {
+        hrc = (hrc >> 2) | ((c as u32 ^ 2) << (2 * (m - 1)));
+    });
+    add.zip(mid).map(move |(a, c)| {
+        hfw = ((hfw << 2) ^ (a as u32)) & mask;
+        hrc = (hrc >> 2) | ((c as u32 ^ 2) << (2 * (m - 1)));
+        (hfw ^ anti).min(hrc ^ anti) << shift
+    })
+}
+
+/// Returns a simd-iterator over the 8 chunks 32-bit canonical anti-lex hashes of all k-mers in the sequence.
+/// Only 2-bit DNA is supported.
+/// Returned chunks overlap by w-1 hashes. Set w=1 for non-overlapping chunks.
+pub fn canonical_anti_lex_hash_seq_simd<'s, Sq: Seq<'s>>(
+    seq: Sq,
+    k: usize,
+    w: usize,
+) -> (impl ExactSizeIterator<Item = S> + Captures<&'s ()>, usize) {
+    let (add_mid, padding) = seq.par_iter_bp_delayed(k + w - 1, canonical_anti_lex_delay(k));
+
+    let mut it = add_mid.map(canonical_anti_lex_hash_mapper::<Sq>(k, w));
+    it.by_ref().take(k - 1).for_each(drop);
+
+    (it, padding)
+}
+
+/// The delay between the added character and the character passed alongside it to [`canonical_anti_lex_hash_mapper`].
+pub fn canonical_anti_lex_delay(k: usize) -> usize {
+    k - hashed_chars(k)
+}
+
+/// A function that 'eats' added bases and the bases [`canonical_anti_lex_delay`] before them, and returns the updated canonical hash.
+/// The first delayed bases must be 0.
+/// The first k-1 returned values will be useless.
+/// Only 2-bit DNA is supported.
+pub fn canonical_anti_lex_hash_mapper<'s, Sq: Seq<'s>>(
+    k: usize,
+    w: usize,
+) -> impl FnMut((S, S)) -> S + Clone {
+    assert_eq!(Sq::BITS_PER_CHAR, 2);
+    assert!(k > 0);
+    assert!(w > 0);
+
+    let m = hashed_chars(k);
+    let shift = canonical_shift(k) as i32;
+    let (anti, mask) = anti_and_mask(k, 2);
+    let anti = S::splat(anti);
+    let mask = S::splat(mask);
+    let two = S::splat(2);
+
+    let mut h_fw = S::splat(0);
+    let mut h_rc = S::splat(0);
+
+    move |(a, c)| {
+        h_fw = ((h_fw << 2) ^ a) & mask;
+        h_rc = (h_rc >> 2) | ((c ^ two) << (2 * (m - 1)) as i32);
+        (h_fw ^ anti).min(h_rc ^ anti) << shift
+    }
+}
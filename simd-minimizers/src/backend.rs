@@ -0,0 +1,359 @@
+//! Choosing between the SIMD and scalar implementations by benchmarking them on the current machine.
+//!
+//! The SIMD instruction set (AVX-512, AVX2, NEON, or none) is fixed at compile time by the enabled target features.
+//! Within a binary, the remaining choice is between the SIMD functions and their [`scalar`](crate::scalar) twins.
+//! The scalar versions can be faster for short sequences, and on some CPUs the SIMD versions gain less than expected.
+//!
+//! [`BackendCache::select`] benchmarks both on a random sequence of the typical length, and caches the faster one
+//! for each [`Function`], sequence type, and `(k, w, len)`, where `len` is rounded up to a power of two.
+//! The cache can be written to a file and read back on the next run. The file records the architecture and target features
+//! it was measured with, and a cache from a different build is ignored.
+//!
+//! ```
+//! use simd_minimizers::backend::{BackendCache, Function};
+//! use simd_minimizers::packed_seq::{PackedSeq, PackedSeqVec, SeqVec};
+//!
+//! let mut cache = BackendCache::new();
+//! let backend = cache.select::<PackedSeq>(Function::Canonical, 21, 11, 1000);
+//! let seq = PackedSeqVec::random(1000);
+//! let mut positions = vec![];
+//! backend.canonical_minimizer_positions(seq.as_slice(), 21, 11, &mut positions);
+//!
+//! let mut file = vec![];
+//! cache.write_to(&mut file).unwrap();
+//! let cache = BackendCache::read_from(&file[..]).unwrap();
+//! assert_eq!(cache.get::<PackedSeq>(Function::Canonical, 21, 11, 1000), Some(backend));
+//! ```
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use packed_seq::{Seq, SeqVec};
+
+/// The minimizer function to select a backend for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Function {
+    /// [`minimizer_positions`](crate::minimizer_positions).
+    Forward,
+    /// [`canonical_minimizer_positions`](crate::canonical_minimizer_positions).
+    Canonical,
+}
+
+impl Function {
+    fn name(self) -> &'static str {
+        match self {
+            Function::Forward => "forward",
+            Function::Canonical => "canonical",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "forward" => Some(Function::Forward),
+            "canonical" => Some(Function::Canonical),
+            _ => None,
+        }
+    }
+}
+
+/// An implementation of the minimizer functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The SIMD functions in the crate root.
+    Simd,
+    /// The functions in [`scalar`](crate::scalar).
+    Scalar,
+}
+
+impl Backend {
+    /// Deduplicated positions of all minimizers in the sequence, using this backend.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minimizer_positions<'s, S: Seq<'s>>(
+        self,
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        match self {
+            Backend::Simd => crate::minimizer_positions(seq, k, w, out_vec),
+            Backend::Scalar => crate::scalar::minimizer_positions_scalar(seq, k, w, out_vec),
+        }
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, using this backend.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn canonical_minimizer_positions<'s, S: Seq<'s>>(
+        self,
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        match self {
+            Backend::Simd => crate::canonical_minimizer_positions(seq, k, w, out_vec),
+            Backend::Scalar => {
+                crate::scalar::canonical_minimizer_positions_scalar(seq, k, w, out_vec)
+            }
+        }
+    }
+
+    /// Deduplicated positions of the minimizers computed by `function`, using this backend.
+    pub fn positions<'s, S: Seq<'s>>(
+        self,
+        function: Function,
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        match function {
+            Function::Forward => self.minimizer_positions(seq, k, w, out_vec),
+            Function::Canonical => self.canonical_minimizer_positions(seq, k, w, out_vec),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Simd => "simd",
+            Backend::Scalar => "scalar",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "simd" => Some(Backend::Simd),
+            "scalar" => Some(Backend::Scalar),
+            _ => None,
+        }
+    }
+}
+
+/// The architecture and SIMD target features of this build, e.g. `x86_64 avx2`.
+pub fn build_id() -> String {
+    let features = [
+        ("avx2", cfg!(target_feature = "avx2")),
+        ("avx512f", cfg!(target_feature = "avx512f")),
+        ("avx512vl", cfg!(target_feature = "avx512vl")),
+        ("neon", cfg!(target_feature = "neon")),
+    ];
+    let features: Vec<&str> = features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let features = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join("+")
+    };
+    format!("{} {features}", std::env::consts::ARCH)
+}
+
+const HEADER: &str = "simd-minimizers backends";
+
+/// The name of the sequence type `S` in the cache, e.g. `packed_seq::PackedSeqVec`.
+fn seq_name<'s, S: Seq<'s>>() -> &'static str {
+    std::any::type_name::<S::SeqVec>()
+}
+
+/// A cache key: the function, the name of the sequence type, `k`, `w`, and the rounded length.
+type Key = (Function, Cow<'static, str>, usize, usize, usize);
+
+fn key<'s, S: Seq<'s>>(function: Function, k: usize, w: usize, len: usize) -> Key {
+    (
+        function,
+        Cow::Borrowed(seq_name::<S>()),
+        k,
+        w,
+        len.next_power_of_two(),
+    )
+}
+
+/// The fastest backend for each [`Function`], sequence type, and `(k, w, len)`, with `len` rounded up to a power of two.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BackendCache {
+    backends: HashMap<Key, Backend>,
+}
+
+impl BackendCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached backend of `function` for sequences of type `S` and length about `len`, if any.
+    pub fn get<'s, S: Seq<'s>>(
+        &self,
+        function: Function,
+        k: usize,
+        w: usize,
+        len: usize,
+    ) -> Option<Backend> {
+        self.backends.get(&key::<S>(function, k, w, len)).copied()
+    }
+
+    /// The fastest backend of `function` for sequences of type `S` and length about `len`.
+    ///
+    /// On the first call for these parameters, both backends are benchmarked for a few milliseconds.
+    pub fn select<'s, S: Seq<'s>>(
+        &mut self,
+        function: Function,
+        k: usize,
+        w: usize,
+        len: usize,
+    ) -> Backend {
+        *self
+            .backends
+            .entry(key::<S>(function, k, w, len))
+            .or_insert_with(|| benchmark::<S::SeqVec>(function, k, w, len.next_power_of_two()))
+    }
+
+    /// Write the cache as text, with a header line containing the [`build_id`].
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{HEADER} {}", build_id())?;
+        let mut entries: Vec<_> = self.backends.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        for ((function, seq, k, w, len), backend) in entries {
+            writeln!(
+                writer,
+                "{} {seq} {k} {w} {len} {}",
+                function.name(),
+                backend.name()
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Read a cache written by [`BackendCache::write_to`].
+    ///
+    /// A cache written by a build with a different [`build_id`] is ignored, and an empty cache is returned.
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut lines = reader.lines();
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let Some(id) = header.strip_prefix(HEADER) else {
+            return Err(invalid("Missing backend cache header."));
+        };
+        let mut cache = Self::new();
+        if id.trim() != build_id() {
+            return Ok(cache);
+        }
+        for line in lines {
+            let line = line?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [function, seq, k, w, len, backend] = fields[..] else {
+                return Err(invalid("Backend cache lines must have 6 fields."));
+            };
+            let num = |x: &str| x.parse::<usize>().map_err(|_| invalid("Invalid number."));
+            let function =
+                Function::from_name(function).ok_or_else(|| invalid("Unknown function."))?;
+            let backend = Backend::from_name(backend).ok_or_else(|| invalid("Unknown backend."))?;
+            let key = (
+                function,
+                Cow::Owned(seq.to_string()),
+                num(k)?,
+                num(w)?,
+                num(len)?,
+            );
+            cache.backends.insert(key, backend);
+        }
+        Ok(cache)
+    }
+}
+
+/// The faster backend of `function` for a random sequence of type `V` and length `len`, timing each on about a million bases.
+fn benchmark<V: SeqVec>(function: Function, k: usize, w: usize, len: usize) -> Backend {
+    let seq = V::random(len.max(k + w - 1));
+    let seq = seq.as_slice();
+    let reps = (1usize << 20).div_ceil(seq.len());
+    let mut out_vec = vec![];
+    let mut time = |backend: Backend| {
+        // Warm up caches and allocations.
+        backend.positions(function, seq, k, w, &mut out_vec);
+        let start = Instant::now();
+        for _ in 0..reps {
+            out_vec.clear();
+            backend.positions(function, seq, k, w, &mut out_vec);
+        }
+        std::hint::black_box(&out_vec);
+        start.elapsed()
+    };
+    // Interleave the runs, and take the best of each, to reduce noise.
+    let (mut simd, mut scalar) = (Duration::MAX, Duration::MAX);
+    for _ in 0..3 {
+        simd = simd.min(time(Backend::Simd));
+        scalar = scalar.min(time(Backend::Scalar));
+    }
+    if scalar < simd {
+        Backend::Scalar
+    } else {
+        Backend::Simd
+    }
+}
+
+/// The process-wide cache used by [`select_backend`].
+static CACHE: LazyLock<Mutex<BackendCache>> = LazyLock::new(Mutex::default);
+
+/// The fastest backend of `function` for sequences of type `S` and length about `len`, using a process-wide [`BackendCache`].
+///
+/// The benchmark runs without holding the lock, so that other threads can keep using the cache meanwhile.
+/// When several threads benchmark the same parameters at once, the first result is kept.
+pub fn select_backend<'s, S: Seq<'s>>(
+    function: Function,
+    k: usize,
+    w: usize,
+    len: usize,
+) -> Backend {
+    if let Some(backend) = CACHE.lock().unwrap().get::<S>(function, k, w, len) {
+        return backend;
+    }
+    let backend = benchmark::<S::SeqVec>(function, k, w, len.next_power_of_two());
+    *CACHE
+        .lock()
+        .unwrap()
+        .backends
+        .entry(key::<S>(function, k, w, len))
+        .or_insert(backend)
+}
+
+/// Replace the process-wide cache, e.g. by one read from a file.
+pub fn set_global_cache(cache: BackendCache) {
+    *CACHE.lock().unwrap() = cache;
+}
+
+/// A copy of the process-wide cache, e.g. to write it to a file.
+pub fn global_cache() -> BackendCache {
+    CACHE.lock().unwrap().clone()
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using the backend selected by [`select_backend`].
+///
+/// The first call for each sequence type, `(k, w)`, and length class runs a short benchmark.
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_auto<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    select_backend::<S>(Function::Forward, k, w, seq.len()).minimizer_positions(seq, k, w, out_vec);
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using the backend selected by [`select_backend`].
+///
+/// The first call for each sequence type, `(k, w)`, and length class runs a short benchmark.
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn canonical_minimizer_positions_auto<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    select_backend::<S>(Function::Canonical, k, w, seq.len())
+        .canonical_minimizer_positions(seq, k, w, out_vec);
+}
@@ -0,0 +1,182 @@
+//! Benchmark entry points for the individual stages of the SIMD pipeline.
+//!
+//! [`BenchInput`] precomputes the input of each stage, so that each stage can be driven on its own:
+//! - [`hash`]: ntHash of all k-mers,
+//! - [`sliding_min`]: sliding window minimum of precomputed hashes,
+//! - [`dedup`]: collecting and deduplicating precomputed minimizer positions,
+//! - [`end_to_end`]: the full [`minimizer_positions`](crate::minimizer_positions).
+//!
+//! Each function does a fixed amount of work on the input, and is meant to be called from a benchmark harness,
+//! e.g. `b.iter(|| bench::hash(&input))` in `criterion`, or via [`measure`].
+//!
+//! ```
+//! use simd_minimizers::bench::{self, BenchInput, Stage};
+//! let input = BenchInput::random(100_000, 21, 11);
+//! for stage in Stage::ALL {
+//!     let time = bench::measure(&input, stage, 3);
+//!     eprintln!("{stage:?}: {:.2} ns/bp", time.as_nanos() as f64 / (3 * input.len()) as f64);
+//! }
+//! ```
+use std::time::{Duration, Instant};
+
+use crate::collect::collect_and_dedup_into;
+use crate::nthash::{nthash_mapper, NtHasher};
+use crate::sliding_min::{sliding_min_mapper, sliding_min_tree_mapper};
+use crate::S;
+use packed_seq::{PackedSeq, PackedSeqVec, Seq, SeqVec};
+
+/// A random sequence, and the precomputed input of each stage.
+pub struct BenchInput {
+    k: usize,
+    w: usize,
+    seq: PackedSeqVec,
+    /// The hash of each k-mer, including the `k-1` bogus leading values of each lane.
+    hashes: Vec<S>,
+    /// The minimizer position of each window.
+    positions: Vec<S>,
+    padding: usize,
+}
+
+/// The stages of the pipeline that can be benchmarked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// See [`hash`].
+    Hash,
+    /// See [`sliding_min`].
+    SlidingMin,
+    /// See [`dedup`].
+    Dedup,
+    /// See [`end_to_end`].
+    EndToEnd,
+}
+
+impl Stage {
+    /// All stages, in pipeline order.
+    pub const ALL: [Stage; 4] = [
+        Stage::Hash,
+        Stage::SlidingMin,
+        Stage::Dedup,
+        Stage::EndToEnd,
+    ];
+}
+
+impl BenchInput {
+    /// A random DNA sequence of length `len`, with the inputs of all stages for the given `k` and `w`.
+    pub fn random(len: usize, k: usize, w: usize) -> Self {
+        Self::new(PackedSeqVec::random(len), k, w)
+    }
+
+    /// Precompute the inputs of all stages for the given sequence.
+    pub fn new(seq: PackedSeqVec, k: usize, w: usize) -> Self {
+        let l = k + w - 1;
+        let (add_remove, padding) = seq.as_slice().par_iter_bp_delayed(l, k - 1);
+        let len = add_remove.len();
+        let hashes: Vec<S> = add_remove
+            .map(nthash_mapper::<false, PackedSeq, NtHasher>(k, w))
+            .collect();
+        let mut sliding_min = sliding_min_mapper::<true>(w, k, len);
+        let mut positions: Vec<S> = hashes.iter().map(|&h| sliding_min(h)).collect();
+        positions.drain(..(l - 1).min(len));
+        BenchInput {
+            k,
+            w,
+            seq,
+            hashes,
+            positions,
+            padding,
+        }
+    }
+
+    /// The length of the sequence.
+    pub fn len(&self) -> usize {
+        self.seq.len()
+    }
+
+    /// Whether the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.seq.len() == 0
+    }
+}
+
+/// Hash all k-mers. Returns the xor of all hashes.
+pub fn hash(input: &BenchInput) -> S {
+    let (add_remove, _padding) = input
+        .seq
+        .as_slice()
+        .par_iter_bp_delayed(input.k + input.w - 1, input.k - 1);
+    add_remove
+        .map(nthash_mapper::<false, PackedSeq, NtHasher>(
+            input.k, input.w,
+        ))
+        .fold(S::splat(0), |acc, h| acc ^ h)
+}
+
+/// Compute the sliding window minima of the precomputed hashes. Returns the xor of all minimizer positions.
+pub fn sliding_min(input: &BenchInput) -> S {
+    let mut sliding_min = sliding_min_mapper::<true>(input.w, input.k, input.hashes.len());
+    input
+        .hashes
+        .iter()
+        .fold(S::splat(0), |acc, &h| acc ^ sliding_min(h))
+}
+
+/// Like [`sliding_min`], but using the min tree for small windows.
+///
+/// Compare with [`sliding_min`] to choose [`SMALL_W`](crate::private::sliding_min::SMALL_W).
+/// Panics when `w` is larger than [`SMALL_W`](crate::private::sliding_min::SMALL_W).
+pub fn sliding_min_tree(input: &BenchInput) -> S {
+    fn run<const W: usize>(input: &BenchInput) -> S {
+        let mut sliding_min = sliding_min_tree_mapper::<true, W>(input.k, input.hashes.len());
+        input
+            .hashes
+            .iter()
+            .fold(S::splat(0), |acc, &h| acc ^ sliding_min(h))
+    }
+    match input.w {
+        1 => run::<1>(input),
+        2 => run::<2>(input),
+        3 => run::<3>(input),
+        4 => run::<4>(input),
+        5 => run::<5>(input),
+        w => panic!("The min tree is not instantiated for w={w}."),
+    }
+}
+
+/// Collect and deduplicate the precomputed minimizer positions into `out_vec`, which is cleared first.
+pub fn dedup(input: &BenchInput, out_vec: &mut Vec<u32>) {
+    out_vec.clear();
+    collect_and_dedup_into((input.positions.iter().copied(), input.padding), out_vec);
+}
+
+/// Compute the minimizer positions of the sequence into `out_vec`, which is cleared first.
+pub fn end_to_end(input: &BenchInput, out_vec: &mut Vec<u32>) {
+    out_vec.clear();
+    crate::minimizer_positions(input.seq.as_slice(), input.k, input.w, out_vec);
+}
+
+/// Run `stage` on `input` once, discarding the result.
+pub fn run(input: &BenchInput, stage: Stage, out_vec: &mut Vec<u32>) {
+    match stage {
+        Stage::Hash => {
+            std::hint::black_box(hash(input));
+        }
+        Stage::SlidingMin => {
+            std::hint::black_box(sliding_min(input));
+        }
+        Stage::Dedup => dedup(input, out_vec),
+        Stage::EndToEnd => end_to_end(input, out_vec),
+    }
+    std::hint::black_box(out_vec);
+}
+
+/// The total time of running `stage` on `input` `iterations` times, without an external harness.
+pub fn measure(input: &BenchInput, stage: Stage, iterations: usize) -> Duration {
+    let mut out_vec = vec![];
+    // Warm up caches and allocations.
+    run(input, stage, &mut out_vec);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        run(input, stage, &mut out_vec);
+    }
+    start.elapsed()
+}
@@ -0,0 +1,105 @@
+//! A plain bitvector over sequence positions, as output format for the selected positions.
+//!
+//! Bits are stored in `u64` words, least significant bit first, so that [`BitVec::as_words`] can be passed to
+//! succinct rank/select structures without conversion.
+
+/// A fixed-length bitvector, stored as `u64` words.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// An empty bitvector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A bitvector of `len` unset bits.
+    pub fn with_len(len: usize) -> Self {
+        BitVec {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// The number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the bitvector has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Grow or shrink to `len` bits. New bits are unset.
+    pub fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(64), 0);
+        self.len = len;
+        // Clear the bits past the end, so that they are unset when growing again.
+        if !len.is_multiple_of(64) {
+            *self.words.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+    }
+
+    /// Unset all bits, keeping the length.
+    pub fn clear_bits(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Whether bit `i` is set.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(
+            i < self.len,
+            "Bit {i} is out of bounds for length {}.",
+            self.len
+        );
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// Set bit `i`.
+    pub fn set(&mut self, i: usize) {
+        assert!(
+            i < self.len,
+            "Bit {i} is out of bounds for length {}.",
+            self.len
+        );
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    /// The number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The positions of the set bits, in increasing order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    64 * i + bit
+                })
+            })
+        })
+    }
+
+    /// The words of the bitvector. Bit `i` is bit `i % 64` of word `i / 64`, and bits past the end are unset.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// The words of the bitvector, without copying. See [`BitVec::as_words`].
+    pub fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+
+    /// Or `mask` into word `i`.
+    pub(crate) fn or_word(&mut self, i: usize, mask: u64) {
+        self.words[i] |= mask;
+    }
+}
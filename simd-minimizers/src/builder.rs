@@ -0,0 +1,277 @@
+//! Configuration of minimizer extraction that is chosen at run time.
+//!
+//! The free functions in the crate root select the hash width and canonicality at compile time.
+//! [`MinimizerBuilder`] instead stores these as plain values, so that configuration-driven applications
+//! can switch between them without code changes. It dispatches to the same SIMD kernels.
+use packed_seq::Seq;
+
+use crate::CoordinateConvention;
+
+/// The width of the hash that is used to order k-mers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HashWidth {
+    /// 32-bit ntHash, of which the upper 16 bits are compared. The fastest option.
+    #[default]
+    Bits32,
+    /// 64-bit ntHash, of which the upper 32 bits are compared.
+    /// Gives far fewer ties for large `k` on large genomes. Only 2-bit DNA is supported.
+    /// Supports both forward and canonical minimizers.
+    Bits64,
+}
+
+/// Which duplicate positions are removed from the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DedupScope {
+    /// Only consecutive equal positions are removed, i.e., each run of windows sharing a minimizer gives one position.
+    /// Canonical minimizer positions can then still repeat, and are not sorted.
+    #[default]
+    Adjacent,
+    /// Each position occurs at most once per call, and positions are sorted.
+    /// This takes an additional sort-unique pass over the positions of the sequence.
+    Global,
+}
+
+/// The parameters of common tools, for [`MinimizerBuilder::preset`].
+///
+/// Presets set `k`, `w`, canonicality, and the hash width to match each tool, so that the density and
+/// the windows that are sampled from agree. The k-mers are still ordered by ntHash, so the selected positions
+/// are not identical to those of the tool itself. Ties between equal hashes follow the rules of this crate:
+/// forward minimizers take the leftmost smallest k-mer, and canonical minimizers use the strand rule in the crate docs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// minimap2 `-x sr`: canonical minimizers with `k=21` and `w=11`.
+    Minimap2ShortRead,
+    /// Kraken 2 defaults: canonical minimizers of length 31 in each 35-mer, i.e. `k=31` and `w=5`.
+    /// Kraken 2's spaced seed and toggle mask are not applied.
+    Kraken2,
+    /// SSHash: forward minimizers with the given `k` and `w`, ordered by 64-bit hashes.
+    SSHash { k: usize, w: usize },
+}
+
+/// Parameters of minimizer extraction, set at run time.
+///
+/// [`MinimizerBuilder::build`] validates the parameters, and returns a [`MinimizerConfig`] that computes the minimizers.
+///
+/// ```
+/// use simd_minimizers::{HashWidth, MinimizerBuilder};
+/// use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+///
+/// let seq = PackedSeqVec::random(1000);
+/// let mut positions = vec![];
+/// let config = MinimizerBuilder::new(21, 11)
+///     .hash_width(HashWidth::Bits64)
+///     .build()
+///     .unwrap();
+/// config.positions(seq.as_slice(), &mut positions);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MinimizerBuilder {
+    config: MinimizerConfig,
+}
+
+/// The error returned by [`MinimizerBuilder::build`] for an unsupported combination of parameters.
+///
+/// All combinations of hash width and canonicality are currently supported, so this is never returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {}
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl MinimizerBuilder {
+    /// Forward minimizers with 32-bit hashes, for the given `k` and `w`.
+    pub fn new(k: usize, w: usize) -> Self {
+        MinimizerBuilder {
+            config: MinimizerConfig {
+                k,
+                w,
+                canonical: false,
+                hash_width: HashWidth::default(),
+                coordinates: CoordinateConvention::default(),
+                dedup: DedupScope::default(),
+            },
+        }
+    }
+
+    /// The parameters of a common tool. They can be overridden by the other builder methods.
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Minimap2ShortRead => MinimizerBuilder::new(21, 11).canonical(true),
+            Preset::Kraken2 => MinimizerBuilder::new(31, 5).canonical(true),
+            Preset::SSHash { k, w } => MinimizerBuilder::new(k, w).hash_width(HashWidth::Bits64),
+        }
+    }
+
+    /// Compute canonical minimizers. For even `l=w+k-1`, strand ties are resolved towards the forward strand.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.config.canonical = canonical;
+        self
+    }
+
+    /// Set the width of the hash.
+    pub fn hash_width(mut self, hash_width: HashWidth) -> Self {
+        self.config.hash_width = hash_width;
+        self
+    }
+
+    /// Set the convention of output coordinates. Defaults to 0-based, half-open spans.
+    pub fn coordinates(mut self, coordinates: CoordinateConvention) -> Self {
+        self.config.coordinates = coordinates;
+        self
+    }
+
+    /// Set which duplicate positions are removed. Defaults to [`DedupScope::Adjacent`].
+    pub fn dedup(mut self, dedup: DedupScope) -> Self {
+        self.config.dedup = dedup;
+        self
+    }
+
+    /// Validate the parameters.
+    pub fn build(self) -> Result<MinimizerConfig, BuildError> {
+        Ok(self.config)
+    }
+}
+
+/// A validated configuration of minimizer extraction, built by [`MinimizerBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MinimizerConfig {
+    k: usize,
+    w: usize,
+    canonical: bool,
+    hash_width: HashWidth,
+    coordinates: CoordinateConvention,
+    dedup: DedupScope,
+}
+
+impl MinimizerConfig {
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The number of k-mers in each window.
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    /// Whether canonical minimizers are computed.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// The width of the hash.
+    pub fn hash_width(&self) -> HashWidth {
+        self.hash_width
+    }
+
+    /// The convention of output coordinates.
+    pub fn coordinates(&self) -> CoordinateConvention {
+        self.coordinates
+    }
+
+    /// Which duplicate positions are removed.
+    pub fn dedup(&self) -> DedupScope {
+        self.dedup
+    }
+
+    /// For [`DedupScope::Global`], sort the values appended to `out_vec` since `start` and remove duplicates.
+    fn dedup_from(&self, out_vec: &mut Vec<u32>, start: usize) {
+        if self.dedup == DedupScope::Adjacent {
+            return;
+        }
+        out_vec[start..].sort_unstable();
+        let mut len = start;
+        for i in start..out_vec.len() {
+            if len == start || out_vec[i] != out_vec[len - 1] {
+                out_vec[len] = out_vec[i];
+                len += 1;
+            }
+        }
+        out_vec.truncate(len);
+    }
+
+    /// Convert the positions appended to `out_vec` since `start` to start coordinates.
+    fn shift_to_starts(&self, out_vec: &mut [u32], start: usize) {
+        let offset = self.coordinates.start_offset();
+        if offset != 0 {
+            out_vec[start..].iter_mut().for_each(|p| *p += offset);
+        }
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence, using SIMD,
+    /// as start coordinates in the configured [`CoordinateConvention`].
+    /// Duplicates are removed according to the configured [`DedupScope`].
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn positions<'s, S: Seq<'s>>(&self, seq: S, out_vec: &mut Vec<u32>) {
+        let (k, w) = (self.k, self.w);
+        let start = out_vec.len();
+        match (self.hash_width, self.canonical) {
+            (HashWidth::Bits32, false) => crate::minimizer_positions(seq, k, w, out_vec),
+            (HashWidth::Bits32, true) => crate::canonical_minimizer_positions(seq, k, w, out_vec),
+            (HashWidth::Bits64, false) => crate::minimizer_positions_hash64(seq, k, w, out_vec),
+            (HashWidth::Bits64, true) => {
+                crate::canonical_minimizer_positions_hash64(seq, k, w, out_vec)
+            }
+        }
+        self.dedup_from(out_vec, start);
+        self.shift_to_starts(out_vec, start);
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence,
+    /// as start coordinates in the configured [`CoordinateConvention`].
+    /// Duplicates are removed according to the configured [`DedupScope`].
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn positions_scalar<'s, S: Seq<'s>>(&self, seq: S, out_vec: &mut Vec<u32>) {
+        use crate::scalar::*;
+        let (k, w) = (self.k, self.w);
+        let start = out_vec.len();
+        match (self.hash_width, self.canonical) {
+            (HashWidth::Bits32, false) => minimizer_positions_scalar(seq, k, w, out_vec),
+            (HashWidth::Bits32, true) => canonical_minimizer_positions_scalar(seq, k, w, out_vec),
+            (HashWidth::Bits64, false) => minimizer_positions_hash64_scalar(seq, k, w, out_vec),
+            (HashWidth::Bits64, true) => {
+                canonical_minimizer_positions_hash64_scalar(seq, k, w, out_vec)
+            }
+        }
+        self.dedup_from(out_vec, start);
+        self.shift_to_starts(out_vec, start);
+    }
+
+    /// Start and end coordinates of all minimizer k-mers in the sequence, using SIMD,
+    /// in the configured [`CoordinateConvention`].
+    /// Duplicates are removed according to the configured [`DedupScope`].
+    ///
+    /// Coordinates are appended to reusable `start_vec` and `end_vec` to avoid allocations.
+    pub fn spans<'s, S: Seq<'s>>(&self, seq: S, start_vec: &mut Vec<u32>, end_vec: &mut Vec<u32>) {
+        let (k, w, c) = (self.k, self.w, self.coordinates);
+        let (start, end) = (start_vec.len(), end_vec.len());
+        match (self.hash_width, self.canonical) {
+            (HashWidth::Bits32, false) => crate::minimizer_spans(seq, k, w, c, start_vec, end_vec),
+            (HashWidth::Bits32, true) => {
+                crate::canonical_minimizer_spans(seq, k, w, c, start_vec, end_vec)
+            }
+            (HashWidth::Bits64, false) => {
+                let head_padding = crate::minimizers::minimizers64_seq_simd(seq, k, w);
+                let spans = crate::coords::spans_simd(head_padding, c, k);
+                crate::collect::collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+            }
+            (HashWidth::Bits64, true) => {
+                let head_padding = crate::minimizers::canonical_minimizers64_seq_simd(seq, k, w);
+                let spans = crate::coords::spans_simd(head_padding, c, k);
+                crate::collect::collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+            }
+        }
+        // Ends are starts plus a constant, so sorting both separately keeps them paired.
+        self.dedup_from(start_vec, start);
+        self.dedup_from(end_vec, end);
+    }
+}
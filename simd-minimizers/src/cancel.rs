@@ -0,0 +1,147 @@
+//! Cancellable extraction with progress reporting for long sequences.
+//!
+//! The sequence is processed in blocks of `block_len` characters that overlap by `l-1` characters,
+//! so that each window is contained in exactly one block.
+//! Between blocks, the cancellation flag is checked and progress is reported,
+//! so that the hot loop itself is not affected.
+//!
+//! The same blocks are used to stop early once enough distinct minimizers have been found, e.g. for screening.
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use packed_seq::Seq;
+
+/// Default number of characters processed between two cancellation checks.
+pub const DEFAULT_BLOCK_LEN: usize = 1 << 22;
+
+/// Error returned when an extraction was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "minimizer extraction was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Run `positions` on overlapping blocks of `seq`, and append the deduplicated positions to `out_vec`.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(block, out_vec)` must append the positions of all windows in `block` to `out_vec`.
+///
+/// Before each block, `cancel` is checked. After each block, `progress` is called with the number of characters processed so far.
+/// When cancelled, `out_vec` contains the positions of all blocks processed so far.
+pub fn positions_in_blocks<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    l: usize,
+    block_len: usize,
+    out_vec: &mut Vec<u32>,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize),
+    positions: impl FnMut(SEQ, &mut Vec<u32>),
+) -> Result<(), Cancelled> {
+    positions_in_blocks_with_next(
+        seq,
+        l,
+        block_len,
+        out_vec,
+        cancel,
+        progress,
+        |_| {},
+        positions,
+    )
+}
+
+/// Like [`positions_in_blocks`], but `next(block)` is called with the next block right before the current one is processed,
+/// e.g. to prefetch it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn positions_in_blocks_with_next<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    l: usize,
+    block_len: usize,
+    out_vec: &mut Vec<u32>,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize),
+    mut next: impl FnMut(SEQ),
+    mut positions: impl FnMut(SEQ, &mut Vec<u32>),
+) -> Result<(), Cancelled> {
+    let block_len = block_len.max(l);
+    let mut block_pos = vec![];
+    let mut start = 0;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Cancelled);
+        }
+        let end = (start + block_len).min(seq.len());
+        if end < seq.len() {
+            let next_start = end + 1 - l;
+            next(seq.slice(next_start..(next_start + block_len).min(seq.len())));
+        }
+        block_pos.clear();
+        positions(seq.slice(start..end), &mut block_pos);
+        for &pos in &block_pos {
+            let pos = pos + start as u32;
+            if out_vec.last() != Some(&pos) {
+                out_vec.push(pos);
+            }
+        }
+        progress(end);
+        if end == seq.len() {
+            return Ok(());
+        }
+        // The next block starts with the first window that was not yet processed.
+        start = end + 1 - l;
+    }
+}
+
+/// Run `positions` on overlapping blocks of `seq`, and append deduplicated positions to `out_vec`
+/// until `m` distinct minimizers have been found.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(block, out_vec)` must append the positions of all windows in `block` to `out_vec`.
+/// Two positions have the same minimizer when `key` returns equal values for them.
+/// The position of the `m`'th distinct minimizer is the last position appended.
+///
+/// The first block has length `first_block_len`, and each next block is twice as long,
+/// so that little work is wasted when the minimizers are found early, and few blocks are needed otherwise.
+pub fn first_distinct_positions_in_blocks<'s, SEQ: Seq<'s>, K: Eq + Hash>(
+    seq: SEQ,
+    l: usize,
+    first_block_len: usize,
+    m: usize,
+    out_vec: &mut Vec<u32>,
+    mut key: impl FnMut(u32) -> K,
+    mut positions: impl FnMut(SEQ, &mut Vec<u32>),
+) {
+    if m == 0 {
+        return;
+    }
+    let base = out_vec.len();
+    let mut distinct = HashSet::new();
+    let mut block_len = first_block_len.max(l);
+    let mut block_pos = vec![];
+    let mut start = 0;
+    loop {
+        let end = (start + block_len).min(seq.len());
+        block_pos.clear();
+        positions(seq.slice(start..end), &mut block_pos);
+        for &pos in &block_pos {
+            let pos = pos + start as u32;
+            if out_vec.len() == base || out_vec.last() != Some(&pos) {
+                out_vec.push(pos);
+                if distinct.insert(key(pos)) && distinct.len() == m {
+                    return;
+                }
+            }
+        }
+        if end == seq.len() {
+            return;
+        }
+        // The next block starts with the first window that was not yet processed.
+        start = end + 1 - l;
+        block_len *= 2;
+    }
+}
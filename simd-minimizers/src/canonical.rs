@@ -1,52 +1,60 @@
 //! Determine whether each window is canonical, when `#GT > #AC`.
+//!
+//! The strandedness score `#GT - #AC` of each window is also available on its own, e.g. for strand-bias diagnostics.
+//!
+//! For even window lengths `l=k+w-1`, windows with `#GT = #AC` are ties. These are deterministically
+//! considered canonical, so that the forward strand is preferred.
 use std::mem::transmute;
 
 use crate::S;
-use packed_seq::{PackedSeq, Seq};
+use packed_seq::Seq;
 use wide::{i32x8, CmpGt};
 
 use crate::nthash::Captures;
 
 /// An iterator over windows that returns for each whether it's canonical or not.
 /// Canonical windows have >half TG characters.
-/// Window length l=k+w-1 must be odd for this to never tie.
+/// Window length l=k+w-1 must be odd for this to never tie. Ties are canonical.
 pub fn canonical_windows_seq_scalar<'s>(
     seq: impl Seq<'s>,
     k: usize,
     w: usize,
 ) -> impl ExactSizeIterator<Item = bool> + Captures<&'s ()> {
-    let l = k + w - 1;
-    assert!(
-        l % 2 == 1,
-        "Window length {l}={k}+{w}-1 must be odd to guarantee canonicality"
-    );
+    strandedness_seq_scalar(seq, k + w - 1).map(|score| score >= 0)
+}
 
+/// An iterator over windows of length `l` that returns for each its strandedness score `#GT - #AC`.
+pub fn strandedness_seq_scalar<'s>(
+    seq: impl Seq<'s>,
+    l: usize,
+) -> impl ExactSizeIterator<Item = i32> + Captures<&'s ()> {
     let mut add = seq.iter_bp();
     let remove = seq.iter_bp();
 
-    // Cnt of odd characters, offset by -l/2 so >0 is canonical and <0 is not.
-    let mut cnt = -(l as isize);
+    // Twice the count of GT characters, offset by -l, so >0 is canonical and <0 is not.
+    // For even l, 0 is a tie, which is canonical.
+    let mut cnt = -(l as i32);
 
     add.by_ref().take(l - 1).for_each(|a| {
-        cnt += a as isize & 2;
+        cnt += a as i32 & 2;
     });
     add.zip(remove).map(move |(a, r)| {
-        cnt += a as isize & 2;
-        let is_canonical = cnt > 0;
-        cnt -= r as isize & 2;
-        is_canonical
+        cnt += a as i32 & 2;
+        let score = cnt;
+        cnt -= r as i32 & 2;
+        score
     })
 }
 
 /// An iterator over windows that returns for each whether it's canonical or not.
 /// Canonical windows have >half odd characters.
-/// Window length l=k+w-1 must be odd for this to never tie.
+/// Window length l=k+w-1 must be odd for this to never tie. Ties are canonical.
 ///
 /// Split the kmers of the sequence into 8 chunks of equal length ~len/8.
 /// Then compute of each of them in parallel using SIMD,
 /// and return the remaining few using the second iterator.
 pub fn canonical_windows_seq_simd<'s>(
-    seq: PackedSeq<'s>,
+    seq: impl Seq<'s>,
     k: usize,
     w: usize,
 ) -> (
@@ -62,15 +70,37 @@ pub fn canonical_windows_seq_simd<'s>(
     (head, padding)
 }
 
+/// Like [`canonical_windows_seq_simd`], but returns the strandedness score `#GT - #AC` of each window of length `l`.
+pub fn strandedness_seq_simd<'s>(
+    seq: impl Seq<'s>,
+    l: usize,
+) -> (
+    impl ExactSizeIterator<Item = i32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let (add_remove, padding) = seq.par_iter_bp_delayed(l, l - 1);
+
+    let mut head = add_remove.map(strandedness_mapper(l));
+    head.by_ref().take(l - 1).for_each(drop);
+
+    (head, padding)
+}
+
 /// NOTE: First l-1 values are bogus.
 pub fn canonical_mapper(k: usize, w: usize) -> impl FnMut((S, S)) -> i32x8 {
-    let l = k + w - 1;
-    assert!(
-        l % 2 == 1,
-        "Window length {l}={k}+{w}-1 must be odd to guarantee canonicality"
-    );
+    let mut strandedness = strandedness_mapper(k + w - 1);
 
-    // Cnt of odd characters, offset by -l/2 so >0 is canonical and <0 is not.
+    #[inline(always)]
+    move |(a, r)| strandedness((a, r)).cmp_gt(i32x8::splat(-1))
+}
+
+/// A function that 'eats' added and removed bases, and returns the strandedness score `#GT - #AC` of the window of length `l`.
+/// Windows with a score of at least 0 are canonical.
+///
+/// NOTE: First l-1 values are bogus.
+pub fn strandedness_mapper(l: usize) -> impl FnMut((S, S)) -> i32x8 {
+    // Twice the count of GT characters, offset by -l, so >0 is canonical and <0 is not.
+    // For even l, 0 is a tie, which is canonical.
     // TODO: Verify that the delayed removed characters are indeed 0.
     let mut cnt = i32x8::splat(-(l as i32));
     let two = i32x8::splat(2);
@@ -79,6 +109,6 @@ pub fn canonical_mapper(k: usize, w: usize) -> impl FnMut((S, S)) -> i32x8 {
     move |(a, r)| {
         cnt += unsafe { transmute::<_, i32x8>(a) } & two;
         cnt -= unsafe { transmute::<_, i32x8>(r) } & two;
-        cnt.cmp_gt(i32x8::splat(0))
+        cnt
     }
 }
@@ -0,0 +1,105 @@
+//! C API, enabled by the `capi` feature.
+//!
+//! These functions compute minimizer positions of an ASCII DNA sequence with the SIMD kernels, for tools written in C or C++.
+//! Build a static or dynamic library with
+//! `cargo rustc --release --features capi --crate-type staticlib` (or `cdylib`), and include `include/simd_minimizers.h`,
+//! which is generated by `cbindgen --config cbindgen.toml --output include/simd_minimizers.h`.
+//!
+//! Positions are written to a caller-provided buffer, and the functions never allocate memory that the caller must free.
+//! The number of positions is returned also when it exceeds the capacity of the buffer, in which case nothing is written,
+//! so that the caller can grow the buffer and retry.
+//!
+//! The sequence is packed to 2 bits per base first, so characters other than `ACGT` (in either case) are mapped to an arbitrary base.
+use std::slice;
+
+use crate::collect::{collect_and_dedup_into_slice, NeedCapacity};
+use crate::minimizers::{canonical_minimizers_seq_simd, minimizers_seq_simd};
+use crate::nthash::NtHasher;
+use packed_seq::{PackedSeqVec, SeqVec};
+
+/// Returned instead of the number of positions when the arguments are invalid.
+pub const SIMD_MINIMIZERS_ERROR: usize = usize::MAX;
+
+/// Validate the arguments and convert them to slices, or return `None` when they are invalid.
+unsafe fn slices<'a>(
+    seq_ptr: *const u8,
+    len: usize,
+    k: usize,
+    w: usize,
+    out_ptr: *mut u32,
+    out_cap: usize,
+) -> Option<(&'a [u8], &'a mut [u32])> {
+    if k == 0 || w == 0 || len >= 1 << 32 {
+        return None;
+    }
+    if (seq_ptr.is_null() && len > 0) || (out_ptr.is_null() && out_cap > 0) {
+        return None;
+    }
+    let seq = if len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(seq_ptr, len)
+    };
+    let out = if out_cap == 0 {
+        &mut [][..]
+    } else {
+        slice::from_raw_parts_mut(out_ptr, out_cap)
+    };
+    Some((seq, out))
+}
+
+fn written(result: Result<usize, NeedCapacity>) -> usize {
+    match result {
+        Ok(n) => n,
+        Err(NeedCapacity { needed }) => needed,
+    }
+}
+
+/// Write the deduplicated positions of all minimizers of the ASCII DNA sequence `seq_ptr[0..len]` to `out_ptr[0..out_cap]`.
+///
+/// Returns the number of positions. When this is larger than `out_cap`, nothing is written.
+/// Returns [`SIMD_MINIMIZERS_ERROR`] when `k` or `w` is 0, when `len` is at least `2^32`, or for a null pointer with non-zero length.
+///
+/// # Safety
+///
+/// `seq_ptr` must be valid for reads of `len` bytes, and `out_ptr` must be valid for writes of `out_cap` values.
+#[no_mangle]
+pub unsafe extern "C" fn simd_minimizers_positions(
+    seq_ptr: *const u8,
+    len: usize,
+    k: usize,
+    w: usize,
+    out_ptr: *mut u32,
+    out_cap: usize,
+) -> usize {
+    let Some((seq, out)) = slices(seq_ptr, len, k, w, out_ptr, out_cap) else {
+        return SIMD_MINIMIZERS_ERROR;
+    };
+    let seq = PackedSeqVec::from_ascii(seq);
+    let head_padding = minimizers_seq_simd::<_, NtHasher>(seq.as_slice(), k, w);
+    written(collect_and_dedup_into_slice(head_padding, out))
+}
+
+/// Like [`simd_minimizers_positions`], but for canonical minimizers.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// # Safety
+///
+/// `seq_ptr` must be valid for reads of `len` bytes, and `out_ptr` must be valid for writes of `out_cap` values.
+#[no_mangle]
+pub unsafe extern "C" fn simd_minimizers_canonical_positions(
+    seq_ptr: *const u8,
+    len: usize,
+    k: usize,
+    w: usize,
+    out_ptr: *mut u32,
+    out_cap: usize,
+) -> usize {
+    let Some((seq, out)) = slices(seq_ptr, len, k, w, out_ptr, out_cap) else {
+        return SIMD_MINIMIZERS_ERROR;
+    };
+    let seq = PackedSeqVec::from_ascii(seq);
+    let head_padding = canonical_minimizers_seq_simd::<_, NtHasher>(seq.as_slice(), k, w);
+    written(collect_and_dedup_into_slice(head_padding, out))
+}
@@ -1,13 +1,10 @@
 //! Collect (and dedup) SIMD-iterator values into a flat `Vec<u32>`.
-use std::{
-    array::{self, from_fn},
-    cell::RefCell,
-    mem::transmute,
-};
+use std::{array::from_fn, cell::RefCell, iter::zip, mem::transmute};
 
+use crate::bitvec::BitVec;
 use crate::S;
 use packed_seq::L;
-use wide::u32x8;
+use wide::{i32x8, u32x8};
 
 use crate::intrinsics::transpose;
 
@@ -20,14 +17,16 @@ pub fn collect((par_head, padding): (impl ExactSizeIterator<Item = S>, usize)) -
 
 /// Collect a SIMD-iterator into a single flat vector.
 /// Works by taking 8 elements from each stream, and transposing this SIMD-matrix before writing out the results.
-/// The `tail` is appended at the end.
+/// The values are appended to `out_vec`.
 #[inline(always)]
 pub fn collect_into(
     (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
     out_vec: &mut Vec<u32>,
 ) {
+    let base = out_vec.len();
     let len = par_head.len();
-    out_vec.resize(len * 8, 0);
+    out_vec.resize(base + len * 8, 0);
+    let out = &mut out_vec[base..];
 
     let mut m = [unsafe { transmute([0; 8]) }; 8];
     let mut i = 0;
@@ -37,8 +36,7 @@ pub fn collect_into(
             let t = transpose(m);
             for j in 0..8 {
                 unsafe {
-                    *out_vec
-                        .get_unchecked_mut(j * len + 8 * (i / 8)..)
+                    *out.get_unchecked_mut(j * len + 8 * (i / 8)..)
                         .split_first_chunk_mut::<8>()
                         .unwrap()
                         .0 = transmute(t[j]);
@@ -53,7 +51,7 @@ pub fn collect_into(
     let k = i % 8;
     for j in 0..8 {
         unsafe {
-            out_vec[j * len + 8 * (i / 8)..j * len + 8 * (i / 8) + k]
+            out[j * len + 8 * (i / 8)..j * len + 8 * (i / 8) + k]
                 .copy_from_slice(&transmute::<_, [u32; 8]>(t[j])[..k]);
         }
     }
@@ -61,8 +59,106 @@ pub fn collect_into(
     out_vec.resize(out_vec.len() - padding, 0);
 }
 
+/// The error returned when a caller-provided output slice is too short.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NeedCapacity {
+    /// The number of values that the output slice must hold.
+    pub needed: usize,
+}
+
+impl std::fmt::Display for NeedCapacity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The output slice must hold {} values.", self.needed)
+    }
+}
+
+impl std::error::Error for NeedCapacity {}
+
+/// Like [`collect_into`], but writes into a caller-provided slice instead of a `Vec`, and never allocates.
+///
+/// Returns the number of values written to the start of `out`.
+/// When `out` is too short, nothing is written, and [`NeedCapacity`] contains the required length,
+/// which is known before iterating the input.
+pub fn collect_into_slice(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out: &mut [u32],
+) -> Result<usize, NeedCapacity> {
+    let len = par_head.len();
+    let needed = len * 8 - padding;
+    if out.len() < needed {
+        return Err(NeedCapacity { needed });
+    }
+    let out = &mut out[..needed];
+
+    let mut m = [S::ZERO; 8];
+    let mut i = 0;
+    par_head.for_each(|x| {
+        m[i % 8] = x;
+        if i % 8 == 7 {
+            for (j, t) in transpose(m).iter().enumerate() {
+                // Values in the padding of the last lanes are not written.
+                let start = j * len + 8 * (i / 8);
+                if start < needed {
+                    let n = (needed - start).min(8);
+                    out[start..start + n].copy_from_slice(&t.as_array_ref()[..n]);
+                }
+            }
+        }
+        i += 1;
+    });
+
+    // Manually write the unfinished parts of length k=i%8.
+    let k = i % 8;
+    for (j, t) in transpose(m).iter().enumerate() {
+        let start = j * len + 8 * (i / 8);
+        if start < needed {
+            let n = (needed - start).min(k);
+            out[start..start + n].copy_from_slice(&t.as_array_ref()[..n]);
+        }
+    }
+    Ok(needed)
+}
+
+/// Reusable per-lane buffers for [`collect_and_dedup_into_with`], [`collect_and_dedup_with_index_into_with`], and [`collect_and_dedup_with_vals_into_with`].
+///
+/// The buffers grow to hold the deduplicated values of one SIMD lane, i.e., roughly `1/8` of the output.
+/// Owning them explicitly allows long-running services to reuse them across calls, and to release their memory.
+#[derive(Clone, Debug, Default)]
+pub struct CollectBuffers {
+    /// Deduplicated values of each lane, followed by their indices.
+    lanes: [Vec<u32>; 16],
+    /// The words of the values belonging to the deduplicated values of each lane,
+    /// or the second stream of [`collect_and_dedup_pair_into`].
+    vals: [[Vec<u32>; 8]; 2],
+}
+
+impl CollectBuffers {
+    /// Empty buffers. Does not allocate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The total capacity of the buffers, in number of `u32` values.
+    pub fn capacity(&self) -> usize {
+        self.lanes
+            .iter()
+            .chain(self.vals.iter().flatten())
+            .map(|v| v.capacity())
+            .sum()
+    }
+
+    /// Shrink the capacity of each buffer to at most `min_capacity` values, releasing memory.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        for v in self.lanes.iter_mut().chain(self.vals.iter_mut().flatten()) {
+            v.truncate(min_capacity);
+            v.shrink_to(min_capacity);
+        }
+    }
+}
+
 thread_local! {
-    static CACHE: RefCell<[Vec<u32>; 16]> = RefCell::new(array::from_fn(|_| Vec::new()));
+    /// Buffers used by the functions that do not take explicit [`CollectBuffers`].
+    static CACHE: RefCell<CollectBuffers> = RefCell::new(CollectBuffers::new());
 }
 
 /// Convenience wrapper around `collect_and_dedup_into`.
@@ -93,7 +189,213 @@ pub fn collect_and_dedup_into(
     (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
     out_vec: &mut Vec<u32>,
 ) {
-    collect_and_dedup_into_impl::<false>((par_head, padding), out_vec, &mut vec![]);
+    CACHE.with(|buffers| {
+        collect_and_dedup_into_with(&mut buffers.borrow_mut(), (par_head, padding), out_vec)
+    });
+}
+
+/// Like [`collect_and_dedup_into`], but using the given `buffers` instead of thread-local ones.
+#[inline(always)]
+pub fn collect_and_dedup_into_with(
+    buffers: &mut CollectBuffers,
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out_vec: &mut Vec<u32>,
+) {
+    collect_and_dedup_into_impl::<false, false>((par_head, padding), out_vec, &mut vec![], buffers);
+}
+
+/// Like [`collect_and_dedup_into`], but writes into a caller-provided slice instead of a `Vec`.
+///
+/// Returns the number of deduplicated values written to the start of `out`.
+/// Unlike [`collect_and_dedup_into`], the first value is never deduplicated against values before the output.
+/// The output itself is never allocated, but the thread-local per-lane buffers grow as needed, and are reused across calls.
+///
+/// The number of deduplicated values is only known after consuming the iterator.
+/// When `out` is too short, nothing is written, and [`NeedCapacity`] contains the required length,
+/// so that the input must be recomputed to retry.
+pub fn collect_and_dedup_into_slice(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out: &mut [u32],
+) -> Result<usize, NeedCapacity> {
+    CACHE.with(|buffers| {
+        let buffers = &mut buffers.borrow_mut();
+        let num_lanes = dedup_lanes::<false, false>((par_head, padding), buffers);
+        // Skip the values of each lane that continue the run of the previous lanes.
+        let mut last = None;
+        let lanes: [&[u32]; 8] = from_fn(|j| {
+            let mut lane = if j < num_lanes {
+                buffers.lanes[j].as_slice()
+            } else {
+                &[]
+            };
+            while !lane.is_empty() && Some(lane[0]) == last {
+                lane = &lane[1..];
+            }
+            if let Some(&x) = lane.last() {
+                last = Some(x);
+            }
+            lane
+        });
+        let needed = lanes.iter().map(|lane| lane.len()).sum();
+        if out.len() < needed {
+            return Err(NeedCapacity { needed });
+        }
+        let mut written = 0;
+        for lane in lanes {
+            out[written..written + lane.len()].copy_from_slice(lane);
+            written += lane.len();
+        }
+        Ok(written)
+    })
+}
+
+/// Like [`collect_into`], but appends the values as `u16`, which must fit.
+///
+/// Used for the offsets of minimizers within their window, which are less than `w`.
+pub fn collect_into_u16(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out_vec: &mut Vec<u16>,
+) {
+    let base = out_vec.len();
+    let len = par_head.len();
+    out_vec.resize(base + len * 8, 0);
+    let out = &mut out_vec[base..];
+    for (i, x) in par_head.enumerate() {
+        for (j, &x) in x.as_array_ref().iter().enumerate() {
+            out[j * len + i] = x as u16;
+        }
+    }
+    out_vec.truncate(out_vec.len() - padding);
+}
+
+/// Set the bits of all positions of a SIMD-iterator in `bitvec`, which must be long enough.
+///
+/// Each lane accumulates the bits of its current 64-bit word in a register, and only writes it out
+/// once a position in another word is found, so that repeated and nearby positions cost no memory accesses.
+pub fn collect_into_bitvec(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    bitvec: &mut BitVec,
+) {
+    let n = par_head.len();
+    assert!(padding <= L * n, "padding {padding} <= L {L} * n {n}");
+    // Number of non-padding values in each lane.
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+    let mut word_idx = [usize::MAX; L];
+    let mut word = [0u64; L];
+    for (i, x) in par_head.enumerate() {
+        for (lane, &pos) in x.as_array_ref().iter().enumerate() {
+            if i >= valid[lane] {
+                continue;
+            }
+            let pos = pos as usize;
+            if pos / 64 != word_idx[lane] {
+                if word_idx[lane] != usize::MAX {
+                    bitvec.or_word(word_idx[lane], word[lane]);
+                }
+                word_idx[lane] = pos / 64;
+                word[lane] = 0;
+            }
+            word[lane] |= 1 << (pos % 64);
+        }
+    }
+    for lane in 0..L {
+        if word_idx[lane] != usize::MAX {
+            bitvec.or_word(word_idx[lane], word[lane]);
+        }
+    }
+}
+
+/// Set bit `j*n+i` of `bitvec` when lane `j` of the `i`'th mask of a SIMD-iterator of length `n` is all ones.
+///
+/// `bitvec` must have length `8*n-padding`. Like [`collect_into_bitvec`], each lane accumulates its current 64-bit word in a register.
+pub fn collect_mask_into_bitvec(
+    (par_head, padding): (impl ExactSizeIterator<Item = i32x8>, usize),
+    bitvec: &mut BitVec,
+) {
+    let n = par_head.len();
+    assert_eq!(bitvec.len(), L * n - padding);
+    // Number of non-padding values in each lane.
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+    let mut word_idx = [usize::MAX; L];
+    let mut word = [0u64; L];
+    for (i, x) in par_head.enumerate() {
+        for (lane, &mask) in x.as_array_ref().iter().enumerate() {
+            if i >= valid[lane] || mask == 0 {
+                continue;
+            }
+            let pos = lane * n + i;
+            if pos / 64 != word_idx[lane] {
+                if word_idx[lane] != usize::MAX {
+                    bitvec.or_word(word_idx[lane], word[lane]);
+                }
+                word_idx[lane] = pos / 64;
+                word[lane] = 0;
+            }
+            word[lane] |= 1 << (pos % 64);
+        }
+    }
+    for lane in 0..L {
+        if word_idx[lane] != usize::MAX {
+            bitvec.or_word(word_idx[lane], word[lane]);
+        }
+    }
+}
+
+/// Like [`collect_into`], but appends the values plus `offset` as `u64`.
+///
+/// Used for sequences longer than `u32::MAX`, which are processed in blocks that start at `offset`.
+pub fn collect_into_u64(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    offset: u64,
+    out_vec: &mut Vec<u64>,
+) {
+    let base = out_vec.len();
+    let len = par_head.len();
+    out_vec.resize(base + len * 8, 0);
+    let out = &mut out_vec[base..];
+    for (i, x) in par_head.enumerate() {
+        for (j, &x) in x.as_array_ref().iter().enumerate() {
+            out[j * len + i] = x as u64 + offset;
+        }
+    }
+    out_vec.truncate(out_vec.len() - padding);
+}
+
+/// Like [`collect_and_dedup_into`], but appends the deduplicated values plus `offset` as `u64`.
+///
+/// A first value equal to the last value of `out_vec` is skipped as well, so that the positions of overlapping blocks can be concatenated.
+/// The values are deduplicated in the thread-local per-lane buffers, and widened while appending them to `out_vec`.
+pub fn collect_and_dedup_into_u64(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    offset: u64,
+    out_vec: &mut Vec<u64>,
+) {
+    CACHE.with(|buffers| {
+        let buffers = &mut buffers.borrow_mut();
+        let num_lanes = dedup_lanes::<false, false>((par_head, padding), buffers);
+        for lane in &buffers.lanes[..num_lanes] {
+            let last = out_vec.last().copied();
+            let skip = lane
+                .iter()
+                .take_while(|&&x| Some(x as u64 + offset) == last)
+                .count();
+            out_vec.extend(lane[skip..].iter().map(|&x| x as u64 + offset));
+        }
+    });
+}
+
+/// Deduplicate the values of each lane of a SIMD-iterator without padding, and call `f` with the deduplicated values of the 8 lanes.
+///
+/// Unlike the other collectors, the lanes are not concatenated, so that each lane can hold an independent stream.
+pub(crate) fn with_dedup_lanes<R>(
+    par_head: impl ExactSizeIterator<Item = S>,
+    f: impl FnOnce(&[Vec<u32>]) -> R,
+) -> R {
+    CACHE.with(|buffers| {
+        let buffers = &mut buffers.borrow_mut();
+        let num_lanes = dedup_lanes::<false, false>((par_head, 0), buffers);
+        f(&buffers.lanes[..num_lanes])
+    })
 }
 
 /// Collect a SIMD-iterator into a single vector, and duplicate adjacent equal elements.
@@ -106,7 +408,82 @@ pub fn collect_and_dedup_with_index_into(
     out_vec: &mut Vec<u32>,
     idx_vec: &mut Vec<u32>,
 ) {
-    collect_and_dedup_into_impl::<true>((par_head, padding), out_vec, idx_vec);
+    CACHE.with(|buffers| {
+        collect_and_dedup_with_index_into_with(
+            &mut buffers.borrow_mut(),
+            (par_head, padding),
+            out_vec,
+            idx_vec,
+        )
+    });
+}
+
+/// Like [`collect_and_dedup_with_index_into`], but using the given `buffers` instead of thread-local ones.
+#[inline(always)]
+pub fn collect_and_dedup_with_index_into_with(
+    buffers: &mut CollectBuffers,
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out_vec: &mut Vec<u32>,
+    idx_vec: &mut Vec<u32>,
+) {
+    collect_and_dedup_into_impl::<true, false>((par_head, padding), out_vec, idx_vec, buffers);
+}
+
+/// Like [`collect_and_dedup_with_index_into`], but keeps the _last_ occurrence of each run of equal elements.
+///
+/// The deduplicated input values are written in `out_vec` and the index of the stream it last appeared, i.e., the end of its super-k-mer, is written in `idx_vec`.
+/// As for the other collectors, a run continues the equal values at the end of `out_vec`, whose last value and index are then replaced.
+#[inline(always)]
+pub fn collect_and_dedup_with_last_index_into(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out_vec: &mut Vec<u32>,
+    idx_vec: &mut Vec<u32>,
+) {
+    CACHE.with(|buffers| {
+        collect_and_dedup_with_last_index_into_with(
+            &mut buffers.borrow_mut(),
+            (par_head, padding),
+            out_vec,
+            idx_vec,
+        )
+    });
+}
+
+/// Like [`collect_and_dedup_with_last_index_into`], but using the given `buffers` instead of thread-local ones.
+#[inline(always)]
+pub fn collect_and_dedup_with_last_index_into_with(
+    buffers: &mut CollectBuffers,
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    out_vec: &mut Vec<u32>,
+    idx_vec: &mut Vec<u32>,
+) {
+    collect_and_dedup_into_impl::<true, true>((par_head, padding), out_vec, idx_vec, buffers);
+}
+
+/// The lanes that only contain padding, and the index `padding_i` from which lane `padding_idx` contains padding.
+///
+/// Returns `(mask, padding_i, padding_idx, num_lanes)`, where `mask` is set for the lanes that only contain padding,
+/// and `num_lanes` is the number of lanes containing at least one actual value.
+#[inline(always)]
+fn padding_mask(len: usize, padding: usize) -> (u32x8, usize, usize, usize) {
+    let mut mask = u32x8::ZERO;
+    let mut padding_i = 0;
+    let mut padding_idx = 0;
+    let mut num_lanes = 8;
+    assert!(padding <= L * len, "padding {padding} <= L {L} * len {len}");
+    let mut remaining_padding = padding;
+    for i in (0..8).rev() {
+        if remaining_padding >= len {
+            mask.as_array_mut()[i] = u32::MAX;
+            remaining_padding -= len;
+            num_lanes = i;
+            continue;
+        }
+        padding_i = len - remaining_padding;
+        padding_idx = i;
+        break;
+    }
+    (mask, padding_i, padding_idx, num_lanes)
 }
 
 /// Collect a SIMD-iterator into a single vector, and duplicate adjacent equal elements.
@@ -114,98 +491,302 @@ pub fn collect_and_dedup_with_index_into(
 ///
 /// By default (when `SUPER` is false), the deduplicated input values are written in `out_vec`.
 /// When `SUPER` is true, the index of the stream in which the input value first appeared, i.e., the start of its super-k-mer, is additionale written in `idx_vec`.
+/// When `LAST` is true as well, the index of the stream in which the input value last appeared, i.e., the end of its super-k-mer, is written instead.
 #[inline(always)]
-fn collect_and_dedup_into_impl<const SUPER: bool>(
+fn collect_and_dedup_into_impl<const SUPER: bool, const LAST: bool>(
     (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
     out_vec: &mut Vec<u32>,
     idx_vec: &mut Vec<u32>,
+    buffers: &mut CollectBuffers,
 ) {
-    CACHE.with(|v| {
-        let mut v = v.borrow_mut();
-        let (v, v2) = v.split_at_mut(8);
-
-        let mut write_idx = [0; 8];
-        // Vec of last pushed elements in each lane.
-        let mut old = [S::MAX; 8];
-
-        let len = par_head.len();
-        let lane_offsets: [u32x8; 8] = from_fn(|i| u32x8::splat((i * len) as u32));
-        let offsets: [u32; 8] = from_fn(|i| i as u32);
-        let mut offsets: u32x8 = unsafe { transmute(offsets) };
-
-        let mut mask = u32x8::ZERO;
-        let mut padding_i = 0;
-        let mut padding_idx = 0;
-        assert!(padding <= L * len, "padding {padding} <= L {L} * len {len}");
-        let mut remaining_padding = padding;
-        for i in (0..8).rev() {
-            if remaining_padding >= len {
-                mask.as_array_mut()[i] = u32::MAX;
-                remaining_padding -= len;
-                continue;
+    let num_lanes = dedup_lanes::<SUPER, LAST>((par_head, padding), buffers);
+    let (v, v2) = buffers.lanes.split_at(8);
+
+    // Flatten v.
+    let (v, v2) = (&v[..num_lanes], &v2[..num_lanes]);
+    for (lane, lane2) in v.iter().zip(v2.iter()) {
+        let skip = run_len(out_vec, lane);
+        if LAST && skip > 0 {
+            // The run continuing from `out_vec` ends in this lane.
+            out_vec.pop();
+            idx_vec.pop();
+            out_vec.extend_from_slice(lane);
+            idx_vec.extend_from_slice(lane2);
+        } else {
+            out_vec.extend_from_slice(&lane[skip..]);
+            if SUPER {
+                idx_vec.extend_from_slice(&lane2[skip..]);
             }
-            padding_i = len - remaining_padding;
-            padding_idx = i;
-            break;
         }
+    }
+}
 
-        let mut m = [u32x8::ZERO; 8];
-        let mut i = 0;
-        par_head.for_each(|x| {
-            if i == padding_i {
-                mask.as_array_mut()[padding_idx] = u32::MAX;
-            }
-            let x = x | mask;
-            m[i % 8] = x;
-            if i % 8 == 7 {
-                let t = transpose(m);
-                for j in 0..8 {
-                    let lane = t[j];
-                    if write_idx[j] + 8 > v[j].len() {
-                        let new_len = v[j].len() + 1024;
-                        v[j].resize(new_len, 0);
-                        if SUPER {
-                            v2[j].resize(new_len, 0);
-                        }
+/// The number of leading values of `lane` that continue the run of equal values at the end of `out_vec`.
+///
+/// All deduplicating collectors use this rule, so that runs continue across the lanes,
+/// and across calls that append to the same `out_vec`.
+#[inline(always)]
+pub(crate) fn run_len(out_vec: &[u32], lane: &[u32]) -> usize {
+    match out_vec.last() {
+        Some(&last) => lane.iter().take_while(|&&x| x == last).count(),
+        None => 0,
+    }
+}
+
+/// The dedup kernels used by the collectors, selected at compile time ([`Native`]) or at run time ([`Avx512`]).
+trait Kernels {
+    unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize);
+    unsafe fn append_unique_vals_2(
+        old: S,
+        new: S,
+        vals: S,
+        vals2: S,
+        v: &mut [u32],
+        v2: &mut [u32],
+        write_idx: &mut usize,
+    );
+    unsafe fn append_unique_vals_last(
+        old: S,
+        new: S,
+        vals: S,
+        v: &mut [u32],
+        write_idx: &mut usize,
+    );
+    unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize);
+}
+
+/// The kernels selected at compile time.
+struct Native;
+
+impl Kernels for Native {
+    #[inline(always)]
+    unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+        unsafe { crate::intrinsics::append_unique_vals(old, new, vals, v, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_unique_vals_2(
+        old: S,
+        new: S,
+        vals: S,
+        vals2: S,
+        v: &mut [u32],
+        v2: &mut [u32],
+        write_idx: &mut usize,
+    ) {
+        unsafe { crate::intrinsics::append_unique_vals_2(old, new, vals, vals2, v, v2, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_unique_vals_last(
+        old: S,
+        new: S,
+        vals: S,
+        v: &mut [u32],
+        write_idx: &mut usize,
+    ) {
+        unsafe { crate::intrinsics::append_unique_vals_last(old, new, vals, v, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+        unsafe { crate::intrinsics::append_filtered_vals(keep, vals, v, write_idx) }
+    }
+}
+
+/// The AVX-512 kernels, which may only be used when [`has_avx512`](crate::intrinsics::has_avx512) returns true.
+#[cfg(target_arch = "x86_64")]
+struct Avx512;
+
+#[cfg(target_arch = "x86_64")]
+impl Kernels for Avx512 {
+    #[inline(always)]
+    unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+        use crate::intrinsics::dedup_avx512::append_unique_vals;
+        unsafe { append_unique_vals(old, new, vals, v, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_unique_vals_2(
+        old: S,
+        new: S,
+        vals: S,
+        vals2: S,
+        v: &mut [u32],
+        v2: &mut [u32],
+        write_idx: &mut usize,
+    ) {
+        use crate::intrinsics::dedup_avx512::append_unique_vals_2;
+        unsafe { append_unique_vals_2(old, new, vals, vals2, v, v2, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_unique_vals_last(
+        old: S,
+        new: S,
+        vals: S,
+        v: &mut [u32],
+        write_idx: &mut usize,
+    ) {
+        use crate::intrinsics::dedup_avx512::append_unique_vals_last;
+        unsafe { append_unique_vals_last(old, new, vals, v, write_idx) }
+    }
+    #[inline(always)]
+    unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+        use crate::intrinsics::dedup_avx512::append_filtered_vals;
+        unsafe { append_filtered_vals(keep, vals, v, write_idx) }
+    }
+}
+
+/// Deduplicate the values of each lane of a SIMD-iterator into the lane buffers, as in [`collect_and_dedup_into_impl`].
+///
+/// On x86_64, the AVX-512 `vpcompressd` kernels are used when the CPU supports them, also when they are not enabled at compile time.
+/// The entire loop, including the iterator, is then compiled for AVX-512.
+///
+/// Returns the number of lanes containing at least one actual value.
+#[inline(always)]
+fn dedup_lanes<const SUPER: bool, const LAST: bool>(
+    head_padding: (impl ExactSizeIterator<Item = S>, usize),
+    buffers: &mut CollectBuffers,
+) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if crate::intrinsics::has_avx512() {
+        // SAFETY: The CPU supports AVX-512.
+        return unsafe { dedup_lanes_avx512::<SUPER, LAST>(head_padding, buffers) };
+    }
+    dedup_lanes_with::<Native, SUPER, LAST>(head_padding, buffers)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn dedup_lanes_avx512<const SUPER: bool, const LAST: bool>(
+    head_padding: (impl ExactSizeIterator<Item = S>, usize),
+    buffers: &mut CollectBuffers,
+) -> usize {
+    dedup_lanes_with::<Avx512, SUPER, LAST>(head_padding, buffers)
+}
+
+/// [`dedup_lanes`] using the kernels `K`.
+#[inline(always)]
+fn dedup_lanes_with<K: Kernels, const SUPER: bool, const LAST: bool>(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    buffers: &mut CollectBuffers,
+) -> usize {
+    let (v, v2) = buffers.lanes.split_at_mut(8);
+
+    let mut write_idx = [0; 8];
+    // Vec of last pushed elements in each lane.
+    // Initialized from the first element of each lane, so that every `u32` value is valid.
+    let mut old = [S::ZERO; 8];
+    // When keeping the last occurrence, the indices of the elements in `old`, which are only written once their successor is known.
+    let mut old_idx = [S::ZERO; 8];
+
+    let len = par_head.len();
+    // Padding values extend the last run, so their indices are clamped to the last actual index.
+    let last_idx = (L * len).saturating_sub(padding + 1) as u32;
+    let lane_offsets: [u32x8; 8] = from_fn(|i| u32x8::splat((i * len) as u32));
+    let offsets: [u32; 8] = from_fn(|i| i as u32);
+    let mut offsets: u32x8 = unsafe { transmute(offsets) };
+
+    // Padding values are replaced by the previous value in their lane, so that they are deduplicated.
+    // Lanes that only contain padding are skipped entirely.
+    let (mut mask, padding_i, padding_idx, num_lanes) = padding_mask(len, padding);
+
+    let mut m = [u32x8::ZERO; 8];
+    let mut prev = u32x8::ZERO;
+    let mut i = 0;
+    par_head.for_each(|x| {
+        if i == padding_i {
+            mask.as_array_mut()[padding_idx] = u32::MAX;
+        }
+        let x = mask.blend(prev, x);
+        prev = x;
+        m[i % 8] = x;
+        if i % 8 == 7 {
+            let t = transpose(m);
+            for j in 0..8 {
+                let lane = t[j];
+                if LAST && i == 7 {
+                    old[j] = lane;
+                    old_idx[j] = offsets + lane_offsets[j];
+                    continue;
+                }
+                if i == 7 {
+                    old[j] = S::splat(!lane.as_array_ref()[0]);
+                }
+                if write_idx[j] + 8 > v[j].len() {
+                    let new_len = v[j].len() + 1024;
+                    v[j].resize(new_len, 0);
+                    if SUPER || LAST {
+                        v2[j].resize(new_len, 0);
                     }
-                    unsafe {
-                        if SUPER {
-                            crate::intrinsics::append_unique_vals_2(
-                                old[j],
-                                lane,
-                                lane,
-                                offsets + lane_offsets[j],
-                                &mut v[j],
-                                &mut v2[j],
-                                &mut write_idx[j],
-                            );
-                        } else {
-                            crate::intrinsics::append_unique_vals(
-                                old[j],
-                                lane,
-                                lane,
-                                &mut v[j],
-                                &mut write_idx[j],
-                            );
-                        }
-                        old[j] = lane;
+                }
+                unsafe {
+                    if LAST {
+                        let mut write_idx2 = write_idx[j];
+                        K::append_unique_vals_last(
+                            old[j],
+                            lane,
+                            old[j],
+                            &mut v[j],
+                            &mut write_idx[j],
+                        );
+                        K::append_unique_vals_last(
+                            old[j],
+                            lane,
+                            old_idx[j],
+                            &mut v2[j],
+                            &mut write_idx2,
+                        );
+                        old_idx[j] = offsets + lane_offsets[j];
+                    } else if SUPER {
+                        K::append_unique_vals_2(
+                            old[j],
+                            lane,
+                            lane,
+                            offsets + lane_offsets[j],
+                            &mut v[j],
+                            &mut v2[j],
+                            &mut write_idx[j],
+                        );
+                    } else {
+                        K::append_unique_vals(old[j], lane, lane, &mut v[j], &mut write_idx[j]);
                     }
+                    old[j] = lane;
                 }
-                offsets += u32x8::splat(8);
             }
-            i += 1;
-        });
+            offsets += u32x8::splat(8);
+        }
+        i += 1;
+    });
 
+    for j in 0..8 {
+        v[j].truncate(write_idx[j]);
+        if SUPER || LAST {
+            v2[j].truncate(write_idx[j]);
+        }
+    }
+
+    // Manually write the unfinished parts of length k=i%8.
+    let t = transpose(m);
+    let k = i % 8;
+    if LAST {
+        // The elements of `old` are still pending, followed by the unfinished part.
         for j in 0..8 {
-            v[j].truncate(write_idx[j]);
-            if SUPER {
-                v2[j].truncate(write_idx[j]);
+            let mut tail = vec![];
+            if i >= 8 {
+                tail.extend(zip(*old[j].as_array_ref(), *old_idx[j].as_array_ref()));
+            }
+            let lane = t[j].as_array_ref();
+            for (p, &x) in lane.iter().take(k).enumerate() {
+                tail.push((
+                    x,
+                    offsets.as_array_ref()[p] + lane_offsets[j].as_array_ref()[p],
+                ));
+            }
+            for (p, &(x, idx)) in tail.iter().enumerate() {
+                if tail.get(p + 1).map(|y| y.0) != Some(x) {
+                    v[j].push(x);
+                    v2[j].push(idx.min(last_idx));
+                }
             }
         }
-
-        // Manually write the unfinished parts of length k=i%8.
-        let t = transpose(m);
-        let k = i % 8;
+    } else {
         for j in 0..8 {
             let lane = t[j].as_array_ref();
             for (p, x) in lane.iter().take(k).enumerate() {
@@ -217,36 +798,436 @@ fn collect_and_dedup_into_impl<const SUPER: bool>(
                 }
             }
         }
+    }
+    num_lanes
+}
+
+/// Collect a SIMD-iterator of pairs into two flat vectors, deduplicating both streams independently in a single pass.
+///
+/// The first values are appended to `out_vec` and the second values to `out_vec2`, each as by [`collect_and_dedup_into`].
+/// The second stream is deduplicated into the buffers that otherwise hold the words of values, so that neither stream is buffered in full.
+pub fn collect_and_dedup_pair_into(
+    head_padding: (impl ExactSizeIterator<Item = (S, S)>, usize),
+    out_vec: &mut Vec<u32>,
+    out_vec2: &mut Vec<u32>,
+) {
+    CACHE.with(|buffers| {
+        let buffers = &mut *buffers.borrow_mut();
+        #[cfg(target_arch = "x86_64")]
+        if crate::intrinsics::has_avx512() {
+            // SAFETY: The CPU supports AVX-512.
+            return unsafe { dedup_pair_avx512(head_padding, out_vec, out_vec2, buffers) };
+        }
+        dedup_pair_with::<Native>(head_padding, out_vec, out_vec2, buffers);
+    });
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn dedup_pair_avx512(
+    head_padding: (impl ExactSizeIterator<Item = (S, S)>, usize),
+    out_vec: &mut Vec<u32>,
+    out_vec2: &mut Vec<u32>,
+    buffers: &mut CollectBuffers,
+) {
+    dedup_pair_with::<Avx512>(head_padding, out_vec, out_vec2, buffers)
+}
+
+/// [`collect_and_dedup_pair_into`] using the kernels `K`.
+#[inline(always)]
+fn dedup_pair_with<K: Kernels>(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, S)>, usize),
+    out_vec: &mut Vec<u32>,
+    out_vec2: &mut Vec<u32>,
+    buffers: &mut CollectBuffers,
+) {
+    let mut bufs: [&mut [Vec<u32>]; 2] = [&mut buffers.lanes[..8], &mut buffers.vals[0][..]];
 
-        // Flatten v.
-        if SUPER {
-            for (lane, lane2) in v.iter().zip(v2.iter()) {
-                let mut lane = lane.as_slice();
-                let mut lane2 = lane2.as_slice();
-                while !lane.is_empty() && Some(lane[0]) == out_vec.last().copied() {
-                    lane = &lane[1..];
-                    lane2 = &lane2[1..];
+    let mut write_idx = [[0; 8]; 2];
+    let mut old = [[S::ZERO; 8]; 2];
+
+    let len = par_head.len();
+    let (mut mask, padding_i, padding_idx, num_lanes) = padding_mask(len, padding);
+
+    let mut m = [[S::ZERO; 8]; 2];
+    let mut prev = [S::ZERO; 2];
+    let mut i = 0;
+    par_head.for_each(|(x, y)| {
+        if i == padding_i {
+            mask.as_array_mut()[padding_idx] = u32::MAX;
+        }
+        for (s, x) in [x, y].into_iter().enumerate() {
+            let x = mask.blend(prev[s], x);
+            prev[s] = x;
+            m[s][i % 8] = x;
+        }
+        if i % 8 == 7 {
+            for s in 0..2 {
+                let t = transpose(m[s]);
+                for j in 0..8 {
+                    let lane = t[j];
+                    if i == 7 {
+                        old[s][j] = S::splat(!lane.as_array_ref()[0]);
+                    }
+                    let buf = &mut bufs[s][j];
+                    if write_idx[s][j] + 8 > buf.len() {
+                        buf.resize(buf.len() + 1024, 0);
+                    }
+                    unsafe {
+                        K::append_unique_vals(old[s][j], lane, lane, buf, &mut write_idx[s][j]);
+                    }
+                    old[s][j] = lane;
                 }
-                out_vec.extend_from_slice(lane);
-                idx_vec.extend_from_slice(lane2);
             }
-        } else {
-            for lane in v.iter() {
-                let mut lane = lane.as_slice();
-                while !lane.is_empty() && Some(lane[0]) == out_vec.last().copied() {
-                    lane = &lane[1..];
+        }
+        i += 1;
+    });
+
+    // Manually write the unfinished parts of length k=i%8, and flatten the lanes.
+    let k = i % 8;
+    for (s, out_vec) in [out_vec, out_vec2].into_iter().enumerate() {
+        let t = transpose(m[s]);
+        for j in 0..8 {
+            let buf = &mut bufs[s][j];
+            buf.truncate(write_idx[s][j]);
+            for &x in &t[j].as_array_ref()[..k] {
+                if buf.last() != Some(&x) {
+                    buf.push(x);
                 }
-                out_vec.extend_from_slice(lane);
             }
         }
+        for lane in &bufs[s][..num_lanes] {
+            let skip = run_len(out_vec, lane);
+            out_vec.extend_from_slice(&lane[skip..]);
+        }
+    }
+}
 
-        // If we had padding, pop the last element.
-        if out_vec.last() == Some(&u32::MAX) {
-            assert!(padding > 0);
-            out_vec.pop();
-            if SUPER {
-                idx_vec.pop();
+/// Convenience wrapper around `collect_and_dedup_with_kmers_into`.
+pub fn collect_and_dedup_with_kmers(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; 2])>, usize),
+) -> (Vec<u32>, Vec<u64>) {
+    let mut v = vec![];
+    let mut v2 = vec![];
+    collect_and_dedup_with_kmers_into((par_head, padding), &mut v, &mut v2);
+    (v, v2)
+}
+
+/// Collect a SIMD-iterator of positions and k-mer values into flat vectors, and dedup adjacent equal positions.
+///
+/// The k-mer values are given as their low and high 32 bits.
+/// The deduplicated positions are written in `out_vec`, and the k-mer value at each of them in `kmer_vec`.
+/// Padded values at the end of the last lanes are skipped.
+pub fn collect_and_dedup_with_kmers_into(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; 2])>, usize),
+    out_vec: &mut Vec<u32>,
+    kmer_vec: &mut Vec<u64>,
+) {
+    collect_and_dedup_with_vals_into((par_head, padding), out_vec, kmer_vec);
+}
+
+/// Collect a SIMD-iterator of positions and k-mer values into the k-mer values of the deduplicated positions.
+///
+/// This is the _minimizer-space_ representation of a sequence: one k-mer per super-k-mer, in order of occurrence.
+/// Equal k-mers at different positions are all kept.
+pub fn collect_and_dedup_kmers_into(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; 2])>, usize),
+    kmer_vec: &mut Vec<u64>,
+) {
+    let mut positions = vec![];
+    collect_and_dedup_with_vals_into((par_head, padding), &mut positions, kmer_vec);
+}
+
+/// Collect a SIMD-iterator of positions and hashes into flat vectors, and dedup adjacent equal positions.
+///
+/// The deduplicated positions are written in `out_vec`, and the hash at each of them in `hash_vec`.
+/// Padded values at the end of the last lanes are skipped.
+pub fn collect_and_dedup_with_hashes_into(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, S)>, usize),
+    out_vec: &mut Vec<u32>,
+    hash_vec: &mut Vec<u32>,
+) {
+    let par_head = par_head.map(|(pos, hash)| (pos, [hash]));
+    collect_and_dedup_with_vals_into((par_head, padding), out_vec, hash_vec);
+}
+
+/// Values that are collected alongside deduplicated positions, as `W` SIMD words of 32 bits each.
+pub trait CollectVal<const W: usize>: Copy {
+    /// The value with the given words, least significant first.
+    fn from_words(words: [u32; W]) -> Self;
+}
+
+impl CollectVal<1> for u32 {
+    #[inline(always)]
+    fn from_words([x]: [u32; 1]) -> Self {
+        x
+    }
+}
+
+impl CollectVal<1> for bool {
+    #[inline(always)]
+    fn from_words([x]: [u32; 1]) -> Self {
+        x != 0
+    }
+}
+
+impl CollectVal<2> for u64 {
+    #[inline(always)]
+    fn from_words([lo, hi]: [u32; 2]) -> Self {
+        lo as u64 | (hi as u64) << 32
+    }
+}
+
+/// Collect a SIMD-iterator of positions and values into flat vectors, and dedup adjacent equal positions.
+///
+/// The deduplicated positions are written in `out_vec`, and the value belonging to the first occurrence of each of them in `val_vec`.
+/// Padded values at the end of the last lanes are skipped.
+#[inline(always)]
+pub fn collect_and_dedup_with_vals_into<const W: usize, T: CollectVal<W>>(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<u32>,
+    val_vec: &mut Vec<T>,
+) {
+    CACHE.with(|buffers| {
+        collect_and_dedup_with_vals_into_with(
+            &mut buffers.borrow_mut(),
+            (par_head, padding),
+            out_vec,
+            val_vec,
+        )
+    });
+}
+
+/// Like [`collect_and_dedup_with_vals_into`], but using the given `buffers` instead of thread-local ones.
+///
+/// Works like [`collect_and_dedup_into`], where each word of the values is written using the same deduplication mask as the positions.
+#[inline(always)]
+pub fn collect_and_dedup_with_vals_into_with<const W: usize, T: CollectVal<W>>(
+    buffers: &mut CollectBuffers,
+    head_padding: (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<u32>,
+    val_vec: &mut Vec<T>,
+) {
+    assert!(W <= 2, "At most 2 words per value are supported.");
+    #[cfg(target_arch = "x86_64")]
+    if crate::intrinsics::has_avx512() {
+        // SAFETY: The CPU supports AVX-512.
+        return unsafe { dedup_vals_avx512(buffers, head_padding, out_vec, val_vec) };
+    }
+    dedup_vals_with::<Native, W, T>(buffers, head_padding, out_vec, val_vec)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn dedup_vals_avx512<const W: usize, T: CollectVal<W>>(
+    buffers: &mut CollectBuffers,
+    head_padding: (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<u32>,
+    val_vec: &mut Vec<T>,
+) {
+    dedup_vals_with::<Avx512, W, T>(buffers, head_padding, out_vec, val_vec)
+}
+
+/// [`collect_and_dedup_with_vals_into_with`] using the kernels `K`.
+#[inline(always)]
+fn dedup_vals_with<K: Kernels, const W: usize, T: CollectVal<W>>(
+    buffers: &mut CollectBuffers,
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<u32>,
+    val_vec: &mut Vec<T>,
+) {
+    let v = &mut buffers.lanes[..8];
+    let vals = &mut buffers.vals[..W];
+
+    let mut write_idx = [0; 8];
+    let mut old = [S::ZERO; 8];
+
+    let len = par_head.len();
+    let (mut mask, padding_i, padding_idx, num_lanes) = padding_mask(len, padding);
+
+    let mut m = [u32x8::ZERO; 8];
+    let mut mv = [[u32x8::ZERO; 8]; W];
+    let mut prev = u32x8::ZERO;
+    let mut prev_vals = [u32x8::ZERO; W];
+    let mut i = 0;
+    par_head.for_each(|(x, xv)| {
+        if i == padding_i {
+            mask.as_array_mut()[padding_idx] = u32::MAX;
+        }
+        let x = mask.blend(prev, x);
+        prev = x;
+        m[i % 8] = x;
+        for w in 0..W {
+            let y = mask.blend(prev_vals[w], xv[w]);
+            prev_vals[w] = y;
+            mv[w][i % 8] = y;
+        }
+        if i % 8 == 7 {
+            let t = transpose(m);
+            let tv: [[S; 8]; W] = from_fn(|w| transpose(mv[w]));
+            for j in 0..8 {
+                let lane = t[j];
+                if i == 7 {
+                    old[j] = S::splat(!lane.as_array_ref()[0]);
+                }
+                if write_idx[j] + 8 > v[j].len() {
+                    let new_len = v[j].len() + 1024;
+                    v[j].resize(new_len, 0);
+                }
+                unsafe {
+                    for w in 0..W {
+                        if vals[w][j].len() < v[j].len() {
+                            vals[w][j].resize(v[j].len(), 0);
+                        }
+                        let mut write_idx2 = write_idx[j];
+                        K::append_unique_vals(
+                            old[j],
+                            lane,
+                            tv[w][j],
+                            &mut vals[w][j],
+                            &mut write_idx2,
+                        );
+                    }
+                    K::append_unique_vals(old[j], lane, lane, &mut v[j], &mut write_idx[j]);
+                }
+                old[j] = lane;
             }
         }
-    })
+        i += 1;
+    });
+
+    for j in 0..8 {
+        v[j].truncate(write_idx[j]);
+        for vals in vals.iter_mut() {
+            vals[j].truncate(write_idx[j]);
+        }
+    }
+
+    // Manually write the unfinished parts of length k=i%8.
+    let t = transpose(m);
+    let tv: [[S; 8]; W] = from_fn(|w| transpose(mv[w]));
+    let k = i % 8;
+    for j in 0..8 {
+        for p in 0..k {
+            let x = t[j].as_array_ref()[p];
+            if v[j].last() != Some(&x) {
+                v[j].push(x);
+                for w in 0..W {
+                    vals[w][j].push(tv[w][j].as_array_ref()[p]);
+                }
+            }
+        }
+    }
+
+    // Flatten the lanes, dropping duplicates at the lane boundaries and of the last value in `out_vec`.
+    for j in 0..num_lanes {
+        let skip = run_len(out_vec, &v[j]);
+        out_vec.extend_from_slice(&v[j][skip..]);
+        val_vec.extend((skip..v[j].len()).map(|p| T::from_words(from_fn(|w| vals[w][j][p]))));
+    }
+}
+
+/// Collect a SIMD-iterator of keep masks and values into a flat vector, keeping only the values whose mask is all ones.
+///
+/// Works by taking 8 elements from each stream and transposing them, as for [`collect_and_dedup_into`],
+/// after which the kept values of each lane are moved to the front using the same shuffles as for deduplication.
+/// Values are written in input order. Padded values at the end of the last lanes are skipped.
+pub fn collect_filtered_into<const W: usize, T: CollectVal<W>>(
+    head_padding: (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<T>,
+) {
+    assert!(W <= 2, "At most 2 words per value are supported.");
+    CACHE.with(|buffers| {
+        let buffers = &mut *buffers.borrow_mut();
+        #[cfg(target_arch = "x86_64")]
+        if crate::intrinsics::has_avx512() {
+            // SAFETY: The CPU supports AVX-512.
+            return unsafe { filter_avx512(head_padding, out_vec, buffers) };
+        }
+        filter_with::<Native, W, T>(head_padding, out_vec, buffers);
+    });
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn filter_avx512<const W: usize, T: CollectVal<W>>(
+    head_padding: (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<T>,
+    buffers: &mut CollectBuffers,
+) {
+    filter_with::<Avx512, W, T>(head_padding, out_vec, buffers)
+}
+
+/// [`collect_filtered_into`] using the kernels `K`.
+#[inline(always)]
+fn filter_with<K: Kernels, const W: usize, T: CollectVal<W>>(
+    (par_head, padding): (impl ExactSizeIterator<Item = (S, [S; W])>, usize),
+    out_vec: &mut Vec<T>,
+    buffers: &mut CollectBuffers,
+) {
+    let vals = &mut buffers.vals[..W];
+
+    let len = par_head.len();
+    assert!(padding <= L * len, "padding {padding} <= L {L} * len {len}");
+    // Number of non-padding values in each lane.
+    let valid: [usize; 8] = from_fn(|j| (L * len - padding).saturating_sub(j * len).min(len));
+    let mut write_idx = [0; 8];
+
+    let mut mk = [u32x8::ZERO; 8];
+    let mut mv = [[u32x8::ZERO; 8]; W];
+    let mut i = 0;
+    par_head.for_each(|(keep, xv)| {
+        mk[i % 8] = keep;
+        for w in 0..W {
+            mv[w][i % 8] = xv[w];
+        }
+        if i % 8 == 7 {
+            append_filtered_block::<K, W>(i - 7, mk, mv, &valid, vals, &mut write_idx);
+        }
+        i += 1;
+    });
+
+    // The unfinished block of length k=i%8, of which the remaining values are dropped.
+    let k = i % 8;
+    if k > 0 {
+        mk[k..].fill(u32x8::ZERO);
+        append_filtered_block::<K, W>(i - k, mk, mv, &valid, vals, &mut write_idx);
+    }
+
+    for j in 0..8 {
+        out_vec.extend((0..write_idx[j]).map(|p| T::from_words(from_fn(|w| vals[w][j][p]))));
+    }
+}
+
+/// Append the kept values of a block of 8 consecutive SIMD elements, starting at index `start` of each lane,
+/// to the per-lane buffers `bufs`.
+#[inline(always)]
+fn append_filtered_block<K: Kernels, const W: usize>(
+    start: usize,
+    keep: [S; 8],
+    vals: [[S; 8]; W],
+    valid: &[usize; 8],
+    bufs: &mut [[Vec<u32>; 8]],
+    write_idx: &mut [usize; 8],
+) {
+    let keep = transpose(keep);
+    let vals: [[S; 8]; W] = from_fn(|w| transpose(vals[w]));
+    for j in 0..8 {
+        let mut keep = keep[j];
+        // Drop the padding at the end of the lane.
+        if start + 8 > valid[j] {
+            let n = valid[j].saturating_sub(start);
+            keep &= S::new(from_fn(|p| if p < n { u32::MAX } else { 0 }));
+        }
+        let mut new_idx = write_idx[j];
+        for w in 0..W {
+            let buf = &mut bufs[w][j];
+            if write_idx[j] + 8 > buf.len() {
+                buf.resize(buf.len() + 1024, 0);
+            }
+            new_idx = write_idx[j];
+            unsafe { K::append_filtered_vals(keep, vals[w][j], buf, &mut new_idx) };
+        }
+        write_idx[j] = new_idx;
+    }
 }
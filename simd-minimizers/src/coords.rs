@@ -0,0 +1,58 @@
+//! Coordinate conventions of output positions, for formats such as BED, GFF, and SAM.
+//!
+//! Positions are 0-based internally. The start and end coordinates of the selected k-mers in a [`CoordinateConvention`]
+//! are obtained by adding a constant to each position, which is done on the SIMD lanes before they are collected.
+//! Since this preserves the order and equality of positions, deduplication is unaffected.
+use crate::S;
+
+/// The convention of start and end coordinates of a k-mer at 0-based position `pos`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CoordinateConvention {
+    /// Start `pos`, end `pos+k` (exclusive). As in BED and Rust ranges.
+    #[default]
+    ZeroBasedHalfOpen,
+    /// Start `pos`, end `pos+k-1` (inclusive).
+    ZeroBasedClosed,
+    /// Start `pos+1`, end `pos+k` (inclusive). As in GFF, SAM, and VCF.
+    OneBasedClosed,
+}
+
+impl CoordinateConvention {
+    /// The offset that is added to a 0-based position to obtain the start coordinate.
+    pub fn start_offset(self) -> u32 {
+        match self {
+            Self::ZeroBasedHalfOpen | Self::ZeroBasedClosed => 0,
+            Self::OneBasedClosed => 1,
+        }
+    }
+
+    /// The offset that is added to a 0-based position to obtain the end coordinate of a k-mer of length `k`.
+    pub fn end_offset(self, k: usize) -> u32 {
+        match self {
+            Self::ZeroBasedHalfOpen | Self::OneBasedClosed => k as u32,
+            Self::ZeroBasedClosed => k as u32 - 1,
+        }
+    }
+
+    /// The start coordinate of the k-mer at 0-based position `pos`.
+    pub fn start(self, pos: u32) -> u32 {
+        pos + self.start_offset()
+    }
+
+    /// The end coordinate of the k-mer of length `k` at 0-based position `pos`.
+    pub fn end(self, pos: u32, k: usize) -> u32 {
+        pos + self.end_offset(k)
+    }
+}
+
+/// Convert a SIMD-iterator of 0-based positions of k-mers of length `k` to start coordinates in `convention`,
+/// paired with their end coordinates, to be collected using `collect_and_dedup_with_vals_into`.
+pub fn spans_simd(
+    (par_head, padding): (impl ExactSizeIterator<Item = S>, usize),
+    convention: CoordinateConvention,
+    k: usize,
+) -> (impl ExactSizeIterator<Item = (S, [S; 1])>, usize) {
+    let start = S::splat(convention.start_offset());
+    let end = S::splat(convention.end_offset(k));
+    (par_head.map(move |pos| (pos + start, [pos + end])), padding)
+}
@@ -0,0 +1,128 @@
+//! Sampling representative super-k-mers from a corpus, e.g. as training samples for a zstd dictionary.
+//!
+//! Each record is split into super-k-mers, and a super-k-mer is sampled when the hash of its minimizer is below a threshold.
+//! Since this only depends on the minimizer, repeated content is sampled consistently across records,
+//! and each minimizer hash is sampled only once, so that the samples are representative of the corpus without duplicates.
+//!
+//! The threshold starts at the given `density`, the fraction of distinct minimizers that is sampled.
+//! Memory is bounded by `max_bytes`: whenever the samples exceed it, the density is halved and samples above the new threshold are dropped,
+//! like a bottom-k sketch. The result is the same as when sampling with the final density from the start.
+//!
+//! ```
+//! use simd_minimizers::dictionary::DictionarySampler;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//!
+//! let mut sampler = DictionarySampler::new(21, 31, 0.5, 1 << 20);
+//! for _ in 0..10 {
+//!     sampler.add(PackedSeqVec::random(10000).as_slice());
+//! }
+//! let (data, sizes) = sampler.continuous_samples();
+//! assert_eq!(data.len(), sizes.iter().sum::<usize>());
+//! assert!(data.len() <= 1 << 20);
+//! ```
+use std::collections::BTreeMap;
+
+use packed_seq::Seq;
+
+/// Streaming sampler of super-k-mers by the hash of their minimizer.
+#[derive(Clone, Debug)]
+pub struct DictionarySampler {
+    k: usize,
+    w: usize,
+    /// Super-k-mers whose minimizer hash is below this threshold are sampled.
+    threshold: u64,
+    max_bytes: usize,
+    /// The ASCII characters of each sampled super-k-mer, by the hash of its minimizer.
+    samples: BTreeMap<u32, Vec<u8>>,
+    bytes: usize,
+    // Reusable buffers.
+    pos: Vec<u32>,
+    hashes: Vec<u32>,
+    ranges: Vec<(u32, u32, u32)>,
+}
+
+impl DictionarySampler {
+    /// Sample a `density` fraction of the super-k-mers of `k`-mer minimizers in windows of `w` k-mers,
+    /// using at most `max_bytes` bytes for the samples.
+    pub fn new(k: usize, w: usize, density: f64, max_bytes: usize) -> Self {
+        assert!(
+            density > 0.0 && density <= 1.0,
+            "The density must be in (0, 1], but got {density}."
+        );
+        DictionarySampler {
+            k,
+            w,
+            threshold: (density * (1u64 << 32) as f64) as u64,
+            max_bytes,
+            samples: BTreeMap::new(),
+            bytes: 0,
+            pos: vec![],
+            hashes: vec![],
+            ranges: vec![],
+        }
+    }
+
+    /// The current sampling density, which is lowered when the samples exceed `max_bytes`.
+    pub fn density(&self) -> f64 {
+        self.threshold as f64 / (1u64 << 32) as f64
+    }
+
+    /// The total number of bytes of the samples.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Sample the super-k-mers of a record.
+    pub fn add<'s, S: Seq<'s>>(&mut self, seq: S) {
+        let (k, w) = (self.k, self.w);
+        self.pos.clear();
+        self.hashes.clear();
+        self.ranges.clear();
+        crate::minimizer_positions_and_hashes(seq, k, w, &mut self.pos, &mut self.hashes);
+        crate::super_kmer_ranges_from_positions(seq.len(), k, w, &self.pos, &mut self.ranges);
+
+        // Ranges are a subsequence of the sorted positions, so their hashes are found by a linear scan.
+        let mut i = 0;
+        for &(start, end, min_pos) in &self.ranges {
+            while self.pos[i] != min_pos {
+                i += 1;
+            }
+            let hash = self.hashes[i];
+            if hash as u64 >= self.threshold || self.samples.contains_key(&hash) {
+                continue;
+            }
+            let sample = (start as usize..end as usize)
+                .map(|j| seq.get_ascii(j))
+                .collect::<Vec<u8>>();
+            self.bytes += sample.len();
+            self.samples.insert(hash, sample);
+            while self.bytes > self.max_bytes {
+                self.lower_threshold();
+            }
+        }
+    }
+
+    /// Halve the threshold, and drop the samples above it.
+    fn lower_threshold(&mut self) {
+        self.threshold /= 2;
+        let removed = self.samples.split_off(&(self.threshold as u32));
+        self.bytes -= removed.values().map(Vec::len).sum::<usize>();
+    }
+
+    /// The sampled super-k-mers as ASCII characters, in order of their minimizer hash.
+    pub fn samples(&self) -> impl Iterator<Item = &[u8]> {
+        self.samples.values().map(Vec::as_slice)
+    }
+
+    /// All samples concatenated, and the length of each of them,
+    /// as taken by e.g. `zstd::dict::from_continuous`.
+    pub fn continuous_samples(&self) -> (Vec<u8>, Vec<usize>) {
+        let mut data = Vec::with_capacity(self.bytes);
+        let mut sizes = Vec::with_capacity(self.samples.len());
+        for sample in self.samples() {
+            data.extend_from_slice(sample);
+            sizes.push(sample.len());
+        }
+        (data, sizes)
+    }
+}
@@ -0,0 +1,208 @@
+//! Differences between position lists of successive versions of a reference, to update indexes incrementally.
+//!
+//! [`diff_positions`] computes a [`PositionPatch`] with the positions that were removed from and added to a
+//! strictly increasing position list, such as the output of [`minimizer_positions`](crate::minimizer_positions).
+//! Canonical positions must be sorted and deduplicated first.
+//! Long runs of unchanged positions are skipped 8 at a time using SIMD.
+//! [`apply_patch`] recomputes the new list from the old one.
+//!
+//! [`PositionPatch::to_bytes`] stores a patch compactly: the number of removed and added positions,
+//! followed by the differences between consecutive removed and added positions, each as a LEB128 varint.
+//!
+//! ```
+//! use simd_minimizers::diff::{apply_patch, diff_positions, PositionPatch};
+//!
+//! let old = [3, 10, 17, 25, 31];
+//! let new = [3, 12, 17, 25, 31, 40];
+//! let patch = diff_positions(&old, &new);
+//! assert_eq!(patch.removed, [10]);
+//! assert_eq!(patch.added, [12, 40]);
+//!
+//! let patch = PositionPatch::from_bytes(&patch.to_bytes()).unwrap();
+//! let mut out = vec![];
+//! apply_patch(&old, &patch, &mut out).unwrap();
+//! assert_eq!(out, new);
+//! ```
+use std::io;
+
+use crate::S;
+
+/// The positions that were removed from and added to a strictly increasing position list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionPatch {
+    /// Positions in the old list but not in the new list, in increasing order.
+    pub removed: Vec<u32>,
+    /// Positions in the new list but not in the old list, in increasing order.
+    pub added: Vec<u32>,
+}
+
+/// Error returned by [`apply_patch`] when the patch was not computed from the given old list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatchMismatch {
+    /// A position of the patch that is inconsistent with the old list.
+    pub pos: u32,
+}
+
+impl std::fmt::Display for PatchMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "position {} of the patch does not match the old position list",
+            self.pos
+        )
+    }
+}
+
+impl std::error::Error for PatchMismatch {}
+
+impl PositionPatch {
+    /// Whether the old and new lists are equal.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// Encode the patch as LEB128 varints of the list lengths and of the differences between consecutive positions.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        write_varint(&mut bytes, self.removed.len() as u32);
+        write_varint(&mut bytes, self.added.len() as u32);
+        for list in [&self.removed, &self.added] {
+            let mut prev = 0;
+            for &pos in list {
+                write_varint(&mut bytes, pos - prev);
+                prev = pos;
+            }
+        }
+        bytes
+    }
+
+    /// Decode a patch written by [`PositionPatch::to_bytes`].
+    ///
+    /// Returns an error when the bytes are truncated or malformed.
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let num_removed = read_varint(&mut bytes)? as usize;
+        let num_added = read_varint(&mut bytes)? as usize;
+        let mut read_list = |len: usize| -> io::Result<Vec<u32>> {
+            // Each position takes at least one byte.
+            let mut list = Vec::with_capacity(len.min(bytes.len()));
+            let mut prev: u32 = 0;
+            for i in 0..len {
+                let delta = read_varint(&mut bytes)?;
+                if i > 0 && delta == 0 {
+                    return Err(invalid_data("positions in a patch must be increasing"));
+                }
+                prev = prev
+                    .checked_add(delta)
+                    .ok_or_else(|| invalid_data("position in a patch overflows"))?;
+                list.push(prev);
+            }
+            Ok(list)
+        };
+        let removed = read_list(num_removed)?;
+        let added = read_list(num_added)?;
+        if !bytes.is_empty() {
+            return Err(invalid_data("trailing bytes after a patch"));
+        }
+        Ok(PositionPatch { removed, added })
+    }
+}
+
+/// The patch that turns the strictly increasing list `old` into the strictly increasing list `new`.
+pub fn diff_positions(old: &[u32], new: &[u32]) -> PositionPatch {
+    let mut patch = PositionPatch::default();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        // Skip blocks of 8 equal positions.
+        if i + 8 <= old.len() && j + 8 <= new.len() {
+            let a = S::new(old[i..i + 8].try_into().unwrap());
+            let b = S::new(new[j..j + 8].try_into().unwrap());
+            if a == b {
+                i += 8;
+                j += 8;
+                continue;
+            }
+        }
+        match old[i].cmp(&new[j]) {
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+            std::cmp::Ordering::Less => {
+                patch.removed.push(old[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                patch.added.push(new[j]);
+                j += 1;
+            }
+        }
+    }
+    patch.removed.extend_from_slice(&old[i..]);
+    patch.added.extend_from_slice(&new[j..]);
+    patch
+}
+
+/// Append the new position list, obtained by applying `patch` to the strictly increasing list `old`, to `out_vec`.
+///
+/// Returns an error when a removed position is not in `old`, or an added position already is.
+/// `out_vec` then contains a prefix of the new list.
+pub fn apply_patch(
+    old: &[u32],
+    patch: &PositionPatch,
+    out_vec: &mut Vec<u32>,
+) -> Result<(), PatchMismatch> {
+    out_vec.reserve(old.len() + patch.added.len() - patch.removed.len().min(old.len()));
+    let mut removed = patch.removed.iter().copied().peekable();
+    let mut added = patch.added.iter().copied().peekable();
+    for &pos in old {
+        while let Some(a) = added.next_if(|&a| a <= pos) {
+            if a == pos {
+                return Err(PatchMismatch { pos });
+            }
+            out_vec.push(a);
+        }
+        match removed.peek() {
+            Some(&r) if r < pos => return Err(PatchMismatch { pos: r }),
+            Some(&r) if r == pos => {
+                removed.next();
+            }
+            _ => out_vec.push(pos),
+        }
+    }
+    if let Some(r) = removed.next() {
+        return Err(PatchMismatch { pos: r });
+    }
+    out_vec.extend(added);
+    Ok(())
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut x: u32) {
+    while x >= 0x80 {
+        bytes.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    bytes.push(x as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> io::Result<u32> {
+    let mut x: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| invalid_data("truncated patch"))?;
+        *bytes = rest;
+        let bits = (byte & 0x7f) as u32;
+        if shift == 28 && bits > 0xf {
+            return Err(invalid_data("varint in a patch overflows"));
+        }
+        x |= bits << shift;
+        if byte < 0x80 {
+            return Ok(x);
+        }
+    }
+    Err(invalid_data("varint in a patch overflows"))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,99 @@
+//! Downsampling of seeds that keeps a (weaker) window guarantee.
+//!
+//! Keeping only a fraction of the minimizers, or those with the smallest hashes, can leave long stretches without any seed.
+//! Here, the selection is done in a single left-to-right pass that also repairs coverage:
+//! whenever the next selected seed would leave a window of `max_window` k-mers without a seed,
+//! the unselected seed with the smallest hash among those that close the gap is re-added.
+//! When the input satisfies the window guarantee for `w <= max_window`, as minimizer positions do, the output satisfies it for `max_window`.
+//! Sensitivity thus degrades gracefully, from the full set of seeds down to a density of about `1/max_window`.
+
+/// Which seeds are selected before coverage is repaired.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Downsample {
+    /// Seeds whose hash is below `fraction * 2^32`.
+    Fraction(f64),
+    /// The given number of seeds with the smallest hashes. Ties at the threshold are broken towards the leftmost seeds.
+    TopK(usize),
+}
+
+/// Select seeds from sorted `positions` with `hashes` according to `mode`, and re-add seeds so that every window of
+/// `max_window` consecutive k-mers of a sequence of length `seq_len` contains a selected position.
+///
+/// Selected positions are appended to `out_vec` in order.
+pub fn downsample_with_coverage(
+    positions: &[u32],
+    hashes: &[u32],
+    seq_len: usize,
+    k: usize,
+    max_window: usize,
+    mode: Downsample,
+    out_vec: &mut Vec<u32>,
+) {
+    assert_eq!(positions.len(), hashes.len());
+    assert!(max_window > 0);
+    debug_assert!(positions.is_sorted());
+
+    // Seeds with a hash below `threshold` are selected, and the first `ties` seeds with a hash equal to it.
+    let (threshold, mut ties) = match mode {
+        Downsample::Fraction(fraction) => {
+            assert!((0.0..=1.0).contains(&fraction));
+            ((fraction * (1u64 << 32) as f64).ceil() as u64, 0)
+        }
+        Downsample::TopK(n) if n >= hashes.len() => (1 << 32, 0),
+        Downsample::TopK(0) => (0, 0),
+        Downsample::TopK(n) => {
+            let mut sorted = hashes.to_vec();
+            let (_, &mut threshold, _) = sorted.select_nth_unstable(n - 1);
+            let below = hashes.iter().filter(|&&h| h < threshold).count();
+            (threshold as u64, n - below)
+        }
+    };
+
+    let num_kmers = (seq_len + 1).saturating_sub(k);
+    let max_window = max_window as i64;
+    // The last selected position, initially just before the sequence.
+    let mut last = -1i64;
+    // Unselected seeds since `last`, as `(position, hash)`.
+    let mut candidates: Vec<(u32, u32)> = vec![];
+
+    // Re-add locally minimal candidates until a seed at position `p` does not leave a gap.
+    let repair = |p: i64, last: &mut i64, candidates: &mut Vec<(u32, u32)>, out: &mut Vec<u32>| {
+        while p > *last + max_window {
+            let reachable = candidates.partition_point(|&(q, _)| (q as i64) <= *last + max_window);
+            let Some((j, &(q, _))) = candidates[..reachable]
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, &(_, h))| h)
+            else {
+                // The input itself has a gap here.
+                return;
+            };
+            out.push(q);
+            *last = q as i64;
+            candidates.drain(..=j);
+        }
+    };
+
+    for (&p, &h) in positions.iter().zip(hashes) {
+        let h64 = h as u64;
+        let selected = h64 < threshold || (h64 == threshold && ties > 0);
+        if h64 == threshold && ties > 0 {
+            ties -= 1;
+        }
+        if (p as usize) >= num_kmers {
+            continue;
+        }
+        if selected {
+            repair(p as i64, &mut last, &mut candidates, out_vec);
+            out_vec.push(p);
+            last = p as i64;
+            candidates.clear();
+        } else {
+            candidates.push((p, h));
+        }
+    }
+    // The last window of `max_window` k-mers must contain a seed as well.
+    if num_kmers as i64 >= max_window {
+        repair(num_kmers as i64, &mut last, &mut candidates, out_vec);
+    }
+}
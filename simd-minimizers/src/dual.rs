@@ -0,0 +1,139 @@
+//! Minimizers of two aligned sequences of equal length, such as the two haplotypes of a diploid genome
+//! or a path of a pangenome and its reference.
+//!
+//! Both sequences are split into the same 8 chunks and go through the minimizer pipeline in lockstep,
+//! so that each SIMD step yields the minimizers of the same windows of both sequences.
+//! A window is reported when its minimizers differ: either their positions differ, or their k-mers have a different hash.
+//! Runs of windows in which the sequences are equal thus cost no output.
+use std::array::from_fn;
+
+use crate::minimizers::minimizers_seq_scalar;
+use crate::nthash::{hash_at_pos_mapper, nthash_mapper, nthash_seq_scalar, Captures, CharHasher};
+use crate::sliding_min::sliding_min_mapper;
+use crate::S;
+use packed_seq::{Seq, L};
+
+/// A window whose minimizer differs between two aligned sequences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MinimizerMismatch {
+    /// The index of the window, i.e., the position of its first character.
+    pub window: u32,
+    /// The position of the minimizer of the window in the first sequence.
+    pub pos_a: u32,
+    /// The position of the minimizer of the window in the second sequence.
+    pub pos_b: u32,
+}
+
+fn check_lengths(len_a: usize, len_b: usize) {
+    assert_eq!(
+        len_a, len_b,
+        "Dual minimizers require sequences of equal length."
+    );
+}
+
+/// Returns an iterator over the windows whose minimizers differ between `seq_a` and `seq_b`.
+pub fn dual_minimizers_seq_scalar<'s, H: CharHasher>(
+    seq_a: impl Seq<'s>,
+    seq_b: impl Seq<'s>,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = MinimizerMismatch> + Captures<&'s ()> {
+    check_lengths(seq_a.len(), seq_b.len());
+    let hashes_a: Vec<u32> = nthash_seq_scalar::<false, H>(seq_a, k).collect();
+    let hashes_b: Vec<u32> = nthash_seq_scalar::<false, H>(seq_b, k).collect();
+    let pos_a = minimizers_seq_scalar::<H>(seq_a, k, w);
+    let pos_b = minimizers_seq_scalar::<H>(seq_b, k, w);
+    pos_a
+        .zip(pos_b)
+        .enumerate()
+        .filter(move |&(_, (pa, pb))| pa != pb || hashes_a[pa as usize] != hashes_b[pb as usize])
+        .map(|(window, (pos_a, pos_b))| MinimizerMismatch {
+            window: window as u32,
+            pos_a,
+            pos_b,
+        })
+}
+
+/// Split the windows of both sequences into the same 8 chunks, and return in parallel using SIMD
+/// whether the minimizers of each window are equal, as an all-ones mask, and the minimizer positions in both sequences.
+pub fn dual_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq_a: SEQ,
+    seq_b: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, S, S)> + Captures<&'s ()>,
+    usize,
+) {
+    check_lengths(seq_a.len(), seq_b.len());
+    let l = k + w - 1;
+
+    // Both sequences have the same length, and thus the same chunks and padding.
+    let (add_remove_a, padding) = seq_a.par_iter_bp_delayed(l, k - 1);
+    let (add_remove_b, _) = seq_b.par_iter_bp_delayed(l, k - 1);
+    let len = add_remove_a.len();
+
+    let mut nthash_a = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut nthash_b = nthash_a.clone();
+    let mut sliding_min_a = sliding_min_mapper::<true>(w, k, len);
+    let mut sliding_min_b = sliding_min_mapper::<true>(w, k, len);
+    let mut hash_at_pos_a = hash_at_pos_mapper(k, w, len);
+    let mut hash_at_pos_b = hash_at_pos_a.clone();
+
+    let mut head = add_remove_a.zip(add_remove_b).map(move |(a, b)| {
+        let hash_a = nthash_a(a);
+        let hash_b = nthash_b(b);
+        let pos_a = sliding_min_a(hash_a);
+        let pos_b = sliding_min_b(hash_b);
+        let equal =
+            pos_a.cmp_eq(pos_b) & hash_at_pos_a(hash_a, pos_a).cmp_eq(hash_at_pos_b(hash_b, pos_b));
+        (equal, pos_a, pos_b)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// The windows whose minimizers differ between `seq_a` and `seq_b`, using SIMD.
+///
+/// Windows are appended to `out_vec` in increasing order.
+pub fn dual_minimizers_into<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq_a: SEQ,
+    seq_b: SEQ,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<MinimizerMismatch>,
+) {
+    let (head, padding) = dual_minimizers_seq_simd::<SEQ, H>(seq_a, seq_b, k, w);
+
+    let n = head.len();
+    assert!(padding <= L * n, "padding {padding} <= L {L} * n {n}");
+    // Number of non-padding windows in each lane.
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+
+    // Mismatches are rare, so they are buffered per lane and concatenated at the end.
+    let mut lanes: [Vec<MinimizerMismatch>; L] = from_fn(|_| vec![]);
+    let all_equal = S::splat(u32::MAX);
+    head.enumerate().for_each(|(i, (equal, pos_a, pos_b))| {
+        if equal == all_equal {
+            return;
+        }
+        let (equal, pos_a, pos_b) = (
+            equal.as_array_ref(),
+            pos_a.as_array_ref(),
+            pos_b.as_array_ref(),
+        );
+        for lane in 0..L {
+            if equal[lane] == 0 && i < valid[lane] {
+                lanes[lane].push(MinimizerMismatch {
+                    window: (lane * n + i) as u32,
+                    pos_a: pos_a[lane],
+                    pos_b: pos_b[lane],
+                });
+            }
+        }
+    });
+    for lane in lanes {
+        out_vec.extend(lane);
+    }
+}
@@ -0,0 +1,71 @@
+//! Validation of parameters for the non-panicking `try_` entry points.
+//!
+//! The default functions assert their requirements, and panic when they are not met.
+//! The `try_` functions instead check them up front using [`check_params`], and return an [`Error`].
+use packed_seq::Seq;
+
+use crate::{sliding_min::MAX_W, MAX_K};
+
+/// The error returned by the `try_` functions for invalid parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// `k` is 0.
+    KZero,
+    /// `k` is larger than [`MAX_K`].
+    KTooLarge { k: usize },
+    /// `w` is 0.
+    WZero,
+    /// `w` is larger than the largest supported window of `2^15-1` k-mers.
+    WTooLarge { w: usize },
+    /// The sequence of length `len` is shorter than a single window of `l=k+w-1` characters.
+    WindowLargerThanSequence { l: usize, len: usize },
+    /// The sequence of length `len` is too long for 32-bit positions.
+    SequenceTooLong { len: usize },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::KZero => write!(f, "k must be positive."),
+            Error::KTooLarge { k } => write!(f, "k={k} is larger than the maximum {MAX_K}."),
+            Error::WZero => write!(f, "w must be positive."),
+            Error::WTooLarge { w } => write!(f, "w={w} is larger than the maximum {MAX_W}."),
+            Error::WindowLargerThanSequence { l, len } => write!(
+                f,
+                "The window length l={l} is larger than the sequence length {len}."
+            ),
+            Error::SequenceTooLong { len } => write!(
+                f,
+                "The sequence length {len} is too long for 32-bit positions. Split it first."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Check that `k`, `w`, and the length of `seq` are supported.
+///
+/// Each of the 8 lanes is padded by up to a window and a word, so sequences are limited to somewhat less than `2^32` characters.
+pub fn check_params<'s>(seq: impl Seq<'s>, k: usize, w: usize) -> Result<(), Error> {
+    if k == 0 {
+        return Err(Error::KZero);
+    }
+    if k > MAX_K {
+        return Err(Error::KTooLarge { k });
+    }
+    if w == 0 {
+        return Err(Error::WZero);
+    }
+    if w > MAX_W {
+        return Err(Error::WTooLarge { w });
+    }
+    let (l, len) = (k + w - 1, seq.len());
+    if len < l {
+        return Err(Error::WindowLargerThanSequence { l, len });
+    }
+    if len + 8 * (l + 64) >= 1 << 32 {
+        return Err(Error::SequenceTooLong { len });
+    }
+    Ok(())
+}
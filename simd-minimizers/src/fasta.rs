@@ -0,0 +1,168 @@
+//! Streaming FASTA/FASTQ ingestion, enabled by the `fasta` feature.
+//!
+//! Records are read one at a time, packed into a [`PackedSeqVec`], and their canonical minimizers are computed using SIMD.
+//! Characters other than `ACGTacgt` (e.g. `N`) are masked out, so that no window containing them is sampled,
+//! and positions are always relative to the start of their own record.
+//!
+//! ```no_run
+//! for record in simd_minimizers::fasta::minimizers_from_fasta("genome.fa", 21, 11).unwrap() {
+//!     let (id, positions) = record.unwrap();
+//!     println!("{id}: {} minimizers", positions.len());
+//! }
+//! ```
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use packed_seq::{PackedSeqVec, SeqVec};
+
+use crate::masked::acgt_mask;
+
+/// A FASTA or FASTQ record.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    /// The header up to the first whitespace, without the leading `>` or `@`.
+    pub id: String,
+    /// The sequence, with line breaks removed.
+    pub seq: Vec<u8>,
+}
+
+/// An iterator over the records of a FASTA or FASTQ file.
+///
+/// The format is detected per record from the first character of its header line.
+/// FASTA records end at the next `>` header, and FASTQ records must have their sequence on a single line.
+pub struct Records<R> {
+    reader: R,
+    line: Vec<u8>,
+    /// The header of the next record, when it has already been read.
+    header: Option<Vec<u8>>,
+}
+
+impl<R: BufRead> Records<R> {
+    /// Read records from `reader`.
+    pub fn new(reader: R) -> Self {
+        Records {
+            reader,
+            line: vec![],
+            header: None,
+        }
+    }
+
+    /// Read the next line into `self.line`, without the trailing line break.
+    /// Returns `false` at the end of the input.
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        while let Some(b'\n' | b'\r') = self.line.last() {
+            self.line.pop();
+        }
+        Ok(true)
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        let header = match self.header.take() {
+            Some(header) => header,
+            None => loop {
+                if !self.read_line()? {
+                    return Ok(None);
+                }
+                if !self.line.is_empty() {
+                    break std::mem::take(&mut self.line);
+                }
+            },
+        };
+        let id = header[1..]
+            .split(|c| c.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+        let mut record = Record {
+            id: String::from_utf8_lossy(id).into_owned(),
+            seq: vec![],
+        };
+
+        match header[0] {
+            b'>' => {
+                while self.read_line()? {
+                    if self.line.first() == Some(&b'>') {
+                        self.header = Some(std::mem::take(&mut self.line));
+                        break;
+                    }
+                    record.seq.extend_from_slice(&self.line);
+                }
+            }
+            b'@' => {
+                if !self.read_line()? {
+                    return Err(invalid_data("FASTQ record without sequence"));
+                }
+                record.seq.extend_from_slice(&self.line);
+                if !self.read_line()? || self.line.first() != Some(&b'+') {
+                    return Err(invalid_data("FASTQ record without '+' separator"));
+                }
+                if !self.read_line()? || self.line.len() != record.seq.len() {
+                    return Err(invalid_data(
+                        "FASTQ quality line does not match the sequence length",
+                    ));
+                }
+            }
+            c => {
+                return Err(invalid_data(&format!(
+                    "record header must start with '>' or '@', found {:?}",
+                    c as char
+                )))
+            }
+        }
+        Ok(Some(record))
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// The record id and deduplicated canonical minimizer positions of each record in `reader`.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+/// Windows containing characters other than `ACGTacgt` are skipped.
+pub fn minimizers_from_reader<R: BufRead>(
+    reader: R,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = io::Result<(String, Vec<u32>)>> {
+    let mut packed = PackedSeqVec::default();
+    Records::new(reader).map(move |record| {
+        let record = record?;
+        packed.clear();
+        packed.push_ascii(&record.seq);
+        let mut positions = vec![];
+        crate::canonical_minimizer_positions_masked(
+            packed.as_slice(),
+            k,
+            w,
+            &acgt_mask(&record.seq),
+            &mut positions,
+        );
+        Ok((record.id, positions))
+    })
+}
+
+/// The record id and deduplicated canonical minimizer positions of each record in the FASTA or FASTQ file at `path`.
+///
+/// See [`minimizers_from_reader`].
+pub fn minimizers_from_fasta(
+    path: impl AsRef<Path>,
+    k: usize,
+    w: usize,
+) -> io::Result<impl Iterator<Item = io::Result<(String, Vec<u32>)>>> {
+    let file = File::open(path)?;
+    Ok(minimizers_from_reader(BufReader::new(file), k, w))
+}
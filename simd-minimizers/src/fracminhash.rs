@@ -0,0 +1,118 @@
+//! FracMinHash sketches: all k-mers whose hash is at most a fixed fraction of the hash range.
+//!
+//! Unlike minimizers, the sampled k-mers do not depend on their neighbours, so that sketches of different sequences can be compared directly,
+//! as done by sourmash. This module uses ntHash instead of MurmurHash3, so the hashes are not compatible with sourmash;
+//! see [`sourmash`](crate::sourmash) for that.
+//!
+//! The hashes are computed in the same SIMD pass as for minimizers, and compared against the threshold.
+//! The retained hashes of each lane are then moved to the front using the shuffles that are used to deduplicate minimizer positions.
+//! Hashes are returned in order of their k-mer, and are not deduplicated.
+use crate::nthash::{nthash_mapper, nthash_seq_scalar, Captures, CharHasher};
+use crate::nthash64::{nthash64_mapper, nthash64_seq_scalar};
+use crate::S;
+use packed_seq::Seq;
+
+fn check_fraction(fraction: f64) {
+    assert!(
+        0. < fraction && fraction <= 1.,
+        "FracMinHash requires a fraction in (0, 1], but got {fraction}."
+    );
+}
+
+/// The largest 32-bit hash that is kept, for the given `fraction` of k-mers in `(0, 1]`.
+pub fn max_hash32(fraction: f64) -> u32 {
+    check_fraction(fraction);
+    (u32::MAX as f64 * fraction) as u32
+}
+
+/// The largest 64-bit hash that is kept, for the given `fraction` of k-mers in `(0, 1]`.
+pub fn max_hash64(fraction: f64) -> u64 {
+    check_fraction(fraction);
+    (u64::MAX as f64 * fraction) as u64
+}
+
+/// Returns an iterator over the 32-bit ntHashes of the k-mers that are at most `max_hash32(fraction)`.
+///
+/// Set `RC` to true for canonical ntHash.
+pub fn fracminhash_seq_scalar<'s, const RC: bool, H: CharHasher>(
+    seq: impl Seq<'s>,
+    k: usize,
+    fraction: f64,
+) -> impl Iterator<Item = u32> + Captures<&'s ()> {
+    let max_hash = max_hash32(fraction);
+    nthash_seq_scalar::<RC, H>(seq, k).filter(move |&h| h <= max_hash)
+}
+
+/// Split the k-mers of the sequence into 8 chunks of equal length ~len/8,
+/// and return in parallel using SIMD their 32-bit ntHash, and whether it is kept, as an all-ones mask.
+///
+/// Set `RC` to true for canonical ntHash.
+pub fn fracminhash_seq_simd<'s, const RC: bool, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    fraction: f64,
+) -> (
+    impl ExactSizeIterator<Item = (S, [S; 1])> + Captures<&'s ()>,
+    usize,
+) {
+    let max_hash = S::splat(max_hash32(fraction));
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k, k - 1);
+
+    let mut nthash = nthash_mapper::<RC, SEQ, H>(k, 1);
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let hash = nthash((a, rk));
+        (hash.min(max_hash).cmp_eq(hash), [hash])
+    });
+
+    head.by_ref().take(k - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Returns an iterator over the 64-bit ntHashes of the k-mers that are at most `max_hash64(fraction)`.
+///
+/// Set `RC` to true for canonical ntHash. Only 2-bit DNA is supported.
+pub fn fracminhash64_seq_scalar<'s, const RC: bool, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    fraction: f64,
+) -> impl Iterator<Item = u64> + Captures<&'s ()> {
+    let max_hash = max_hash64(fraction);
+    nthash64_seq_scalar::<RC, SEQ>(seq, k).filter(move |&h| h <= max_hash)
+}
+
+/// Split the k-mers of the sequence into 8 chunks of equal length ~len/8,
+/// and return in parallel using SIMD whether their 64-bit ntHash is kept, as an all-ones mask,
+/// and the low and high halves of the hash.
+///
+/// Set `RC` to true for canonical ntHash. Only 2-bit DNA is supported.
+pub fn fracminhash64_seq_simd<'s, const RC: bool, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    fraction: f64,
+) -> (
+    impl ExactSizeIterator<Item = (S, [S; 2])> + Captures<&'s ()>,
+    usize,
+) {
+    assert_eq!(SEQ::BITS_PER_CHAR, 2, "64-bit ntHash requires 2-bit DNA.");
+    let max_hash = max_hash64(fraction);
+    let max_lo = S::splat(max_hash as u32);
+    let max_hi = S::splat((max_hash >> 32) as u32);
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k, k - 1);
+
+    let mut nthash = nthash64_mapper::<RC, SEQ>(k, 1);
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let hash = nthash((a, rk));
+        // `hi < max_hi`, or `hi == max_hi` and `lo <= max_lo`.
+        let hi_eq = hash.hi.cmp_eq(max_hi);
+        let hi_le = hash.hi.min(max_hi).cmp_eq(hash.hi);
+        let lo_le = hash.lo.min(max_lo).cmp_eq(hash.lo);
+        (hi_eq.blend(lo_le, hi_le), [hash.lo, hash.hi])
+    });
+
+    head.by_ref().take(k - 1).for_each(drop);
+    (head, padding)
+}
@@ -0,0 +1,41 @@
+//! GC content of each window, computed in the same SIMD pass as the minimizers.
+//!
+//! In the 2-bit encoding `A=0, C=1, T=2, G=3`, the `C` and `G` characters are exactly those with the lowest bit set.
+use crate::nthash::Captures;
+use crate::S;
+use packed_seq::Seq;
+
+/// An iterator over windows of length `l` that returns the number of `C` and `G` characters in each.
+pub fn gc_windows_seq_scalar<'s>(
+    seq: impl Seq<'s>,
+    l: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let mut add = seq.iter_bp();
+    let remove = seq.iter_bp();
+
+    let mut cnt = 0;
+    add.by_ref().take(l - 1).for_each(|a| {
+        cnt += a as u32 & 1;
+    });
+    add.zip(remove).map(move |(a, r)| {
+        cnt += a as u32 & 1;
+        let gc = cnt;
+        cnt -= r as u32 & 1;
+        gc
+    })
+}
+
+/// A function that 'eats' added and removed bases, and returns the number of `C` and `G` characters in the window.
+/// The distance between them must be `l`, and the first `l` removed bases must be 0.
+/// The first `l-1` returned values are for incomplete windows.
+pub fn gc_mapper() -> impl FnMut((S, S)) -> S + Clone {
+    let mut cnt = S::splat(0);
+    let one = S::splat(1);
+
+    #[inline(always)]
+    move |(a, r)| {
+        cnt += a & one;
+        cnt -= r & one;
+        cnt
+    }
+}
@@ -0,0 +1,148 @@
+//! Statistics on the quality of k-mer hash functions, to choose a hasher for selection quality.
+//!
+//! [`analyze`] measures a hash function on a set of k-mers, e.g. all k-mers of a real genome,
+//! and [`analyze_random`] on uniformly random k-mers. Both return a [`HashReport`] with:
+//! - the _avalanche_: the average fraction of output bits that flips when a single base is substituted, ideally `0.5`;
+//! - the _bias_ of each output bit: the fraction of k-mers for which it is set, ideally `0.5`;
+//! - the number of _collisions_ among distinct k-mers, for the full hash and its upper 16 bits that are compared by
+//!   the sliding window minimum, together with the number expected for a uniformly random hash.
+//!
+//! K-mers are ASCII `ACGT` strings. The hashes of this crate are available as [`nthash`], [`canonical_nthash`], and [`anti_lex`],
+//! and any other hasher can be passed as a closure.
+//!
+//! ```
+//! use simd_minimizers::hash_quality::{analyze_random, anti_lex, nthash};
+//!
+//! let nt = analyze_random(21, 1000, 1, nthash);
+//! let lex = analyze_random(21, 1000, 1, anti_lex);
+//! assert!(nt.avalanche > lex.avalanche);
+//! ```
+use packed_seq::AsciiSeq;
+
+use crate::anti_lex::anti_lex_hash_kmer;
+use crate::nthash::{nthash_kmer, NtHasher};
+
+/// Statistics of a hash function over a set of k-mers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HashReport {
+    /// The number of k-mers that were analyzed.
+    pub num_kmers: usize,
+    /// The number of distinct k-mers.
+    pub num_distinct: usize,
+    /// The average fraction of the 32 output bits that flips when a single base is substituted.
+    pub avalanche: f64,
+    /// For each output bit, the fraction of k-mers for which it is set.
+    pub bit_bias: [f64; 32],
+    /// The largest deviation of [`HashReport::bit_bias`] from `0.5`.
+    pub max_bias: f64,
+    /// The number of distinct k-mers minus the number of distinct 32-bit hashes.
+    pub collisions: usize,
+    /// The expected number of [`HashReport::collisions`] for a uniformly random hash.
+    pub expected_collisions: f64,
+    /// The number of distinct k-mers minus the number of distinct upper 16 bits of the hashes.
+    pub collisions_upper16: usize,
+    /// The expected number of [`HashReport::collisions_upper16`] for a uniformly random hash.
+    pub expected_collisions_upper16: f64,
+}
+
+/// The forward 32-bit ntHash of an ASCII k-mer, as used by [`minimizer_positions`](crate::minimizer_positions).
+pub fn nthash(kmer: &[u8]) -> u32 {
+    nthash_kmer::<false, NtHasher>(AsciiSeq(kmer))
+}
+
+/// The canonical 32-bit ntHash of an ASCII k-mer, as used by [`canonical_minimizer_positions`](crate::canonical_minimizer_positions).
+pub fn canonical_nthash(kmer: &[u8]) -> u32 {
+    nthash_kmer::<true, NtHasher>(AsciiSeq(kmer))
+}
+
+/// The 32-bit anti-lex hash of an ASCII k-mer.
+pub fn anti_lex(kmer: &[u8]) -> u32 {
+    anti_lex_hash_kmer(AsciiSeq(kmer))
+}
+
+/// Measure `hash` on the given ASCII `ACGT` k-mers.
+pub fn analyze<K: AsRef<[u8]>>(kmers: &[K], hash: impl Fn(&[u8]) -> u32) -> HashReport {
+    let mut ones = [0usize; 32];
+    let mut flipped = 0u64;
+    let mut substitutions = 0u64;
+    let mut buf = vec![];
+    for kmer in kmers {
+        let kmer = kmer.as_ref();
+        let h = hash(kmer);
+        for (bit, ones) in ones.iter_mut().enumerate() {
+            *ones += ((h >> bit) & 1) as usize;
+        }
+        buf.clear();
+        buf.extend_from_slice(kmer);
+        for i in 0..kmer.len() {
+            for &c in b"ACGT" {
+                if c == kmer[i] {
+                    continue;
+                }
+                buf[i] = c;
+                flipped += (h ^ hash(&buf)).count_ones() as u64;
+                substitutions += 1;
+            }
+            buf[i] = kmer[i];
+        }
+    }
+
+    let n = kmers.len();
+    let bit_bias = ones.map(|ones| ones as f64 / n.max(1) as f64);
+    let max_bias = bit_bias
+        .iter()
+        .map(|&b| (b - 0.5).abs())
+        .fold(0.0, f64::max);
+
+    let mut distinct = kmers.iter().map(|kmer| kmer.as_ref()).collect::<Vec<_>>();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let mut hashes = distinct.iter().map(|kmer| hash(kmer)).collect::<Vec<_>>();
+    hashes.sort_unstable();
+    hashes.dedup();
+    let distinct_hashes = hashes.len();
+    hashes.iter_mut().for_each(|h| *h >>= 16);
+    hashes.dedup();
+    let m = distinct.len();
+
+    HashReport {
+        num_kmers: n,
+        num_distinct: m,
+        avalanche: flipped as f64 / (32 * substitutions.max(1)) as f64,
+        bit_bias,
+        max_bias,
+        collisions: m - distinct_hashes,
+        expected_collisions: expected_collisions(m, 32),
+        collisions_upper16: m - hashes.len(),
+        expected_collisions_upper16: expected_collisions(m, 16),
+    }
+}
+
+/// Measure `hash` on `n` uniformly random k-mers, generated deterministically from `seed`.
+pub fn analyze_random(k: usize, n: usize, seed: u64, hash: impl Fn(&[u8]) -> u32) -> HashReport {
+    analyze(&random_kmers(k, n, seed), hash)
+}
+
+/// `n` uniformly random ASCII `ACGT` k-mers, generated deterministically from `seed`.
+pub fn random_kmers(k: usize, n: usize, seed: u64) -> Vec<Vec<u8>> {
+    // SplitMix64.
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    (0..n)
+        .map(|_| (0..k).map(|_| b"ACGT"[(next() >> 62) as usize]).collect())
+        .collect()
+}
+
+/// The expected number of collisions among `m` distinct keys hashed uniformly into `bits` bits.
+fn expected_collisions(m: usize, bits: u32) -> f64 {
+    let buckets = (1u64 << bits) as f64;
+    let m = m as f64;
+    // The expected number of distinct hashes is `buckets * (1 - (1 - 1/buckets)^m)`.
+    m - buckets * -(m * (-1.0 / buckets).ln_1p()).exp_m1()
+}
@@ -0,0 +1,100 @@
+//! An incremental ntHash of a k-mer that is extended one base at a time.
+//!
+//! [`NtHasher`] computes exactly the same 32-bit forward and canonical ntHash as the SIMD pipeline,
+//! e.g. for tools that extend sequences base by base, such as assembly graph traversals.
+//! Bases use the 2-bit encoding of `packed_seq`, where `A=0`, `C=1`, `T=2`, `G=3`.
+//!
+//! ```
+//! use simd_minimizers::incremental::NtHasher;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, Seq, SeqVec};
+//!
+//! let seq = PackedSeqVec::from_ascii(b"ACGTTGCA");
+//! let mut hasher = NtHasher::new(5);
+//! for base in seq.as_slice().iter_bp() {
+//!     hasher.push(base);
+//! }
+//! // The hash of `TTGCA`.
+//! let fwd = hasher.current_fwd().unwrap();
+//! let canonical = hasher.current_canonical().unwrap();
+//! ```
+use std::collections::VecDeque;
+
+use crate::nthash::{self, CharHasher};
+use packed_seq::{complement_base, PackedSeq};
+
+/// The ntHash of a k-mer that is extended one base at a time on either strand.
+#[derive(Clone)]
+pub struct NtHasher {
+    k: usize,
+    hasher: nthash::NtHasher,
+    /// The last (up to) `k` bases, in forward orientation.
+    kmer: VecDeque<u8>,
+    /// The forward hash of `kmer`.
+    fwd: u32,
+    /// The reverse complement hash of `kmer`, not yet rotated by `k-1`.
+    rc: u32,
+}
+
+impl NtHasher {
+    /// An empty hasher for k-mers of length `k`.
+    pub fn new(k: usize) -> Self {
+        assert!(k > 0);
+        NtHasher {
+            k,
+            hasher: nthash::NtHasher::new::<PackedSeq>(k),
+            kmer: VecDeque::with_capacity(k),
+            fwd: 0,
+            rc: 0,
+        }
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Append `base` to the end of the forward k-mer, and drop its first base once it has length `k`.
+    pub fn push(&mut self, base: u8) {
+        assert!(base < 4, "Bases must be 2-bit encoded.");
+        let h = &self.hasher;
+        if self.kmer.len() == self.k {
+            let r = self.kmer.pop_front().unwrap();
+            self.fwd ^= h.f_rot(r);
+            self.rc ^= h.c(r).rotate_right(self.k as u32 - 1);
+        }
+        self.fwd = self.fwd.rotate_left(1) ^ h.f(base);
+        self.rc = self.rc.rotate_right(1) ^ h.c(base);
+        self.kmer.push_back(base);
+    }
+
+    /// Append `base` to the end of the reverse complement k-mer, and drop its first base once it has length `k`.
+    ///
+    /// Equivalently, the complement of `base` is prepended to the forward k-mer, and its last base is dropped.
+    pub fn push_rc(&mut self, base: u8) {
+        assert!(base < 4, "Bases must be 2-bit encoded.");
+        let h = &self.hasher;
+        if self.kmer.len() == self.k {
+            let r = self.kmer.pop_back().unwrap();
+            self.fwd = (self.fwd ^ h.f(r)).rotate_right(1);
+            self.rc = (self.rc ^ h.c(r)).rotate_left(1);
+        }
+        let base = complement_base(base);
+        let len = self.kmer.len() as u32;
+        self.fwd ^= h.f(base).rotate_left(len);
+        self.rc ^= h.c(base).rotate_right(len);
+        self.kmer.push_front(base);
+    }
+
+    /// The forward hash of the current k-mer, or `None` when fewer than `k` bases were pushed.
+    pub fn current_fwd(&self) -> Option<u32> {
+        (self.kmer.len() == self.k).then_some(self.fwd)
+    }
+
+    /// The canonical hash of the current k-mer, or `None` when fewer than `k` bases were pushed.
+    pub fn current_canonical(&self) -> Option<u32> {
+        (self.kmer.len() == self.k).then(|| {
+            self.fwd
+                .wrapping_add(self.rc.rotate_left(self.k as u32 - 1))
+        })
+    }
+}
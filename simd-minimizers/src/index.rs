@@ -0,0 +1,204 @@
+//! An index from minimizers to their positions, as a seed index for mapping.
+//!
+//! [`MinimizerIndex::build`] computes the minimizers of a sequence and their hashes in a single SIMD pass,
+//! and radix sorts them by hash into a hash table, so that [`MinimizerIndex::lookup`] returns all positions of a k-mer.
+//! The table is a sorted array of distinct hashes, with a directory indexed by the high bits of the hash
+//! that points to the (on average one) hashes in each bucket.
+//!
+//! Minimizers are identified by their 32-bit k-mer hash, so that (rarely) the positions of a different k-mer with the same hash are returned.
+//! Compare the k-mers at the returned positions to the query to rule this out.
+//!
+//! ```
+//! use simd_minimizers::index::MinimizerIndex;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, Seq, SeqVec};
+//!
+//! let seq = PackedSeqVec::random(10000);
+//! let (k, w) = (21, 11);
+//! let index = MinimizerIndex::build(seq.as_slice(), k, w);
+//! let mut positions = vec![];
+//! simd_minimizers::minimizer_positions(seq.as_slice(), k, w, &mut positions);
+//! for &pos in &positions {
+//!     let kmer = seq.slice(pos as usize..pos as usize + k);
+//!     assert!(index.lookup(kmer).contains(&pos));
+//! }
+//! ```
+use packed_seq::Seq;
+
+use crate::nthash::{MulHasher, NtHasher};
+use crate::partition::kmer_hash;
+
+/// A hash table from the minimizers of a sequence to their sorted positions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinimizerIndex {
+    k: usize,
+    w: usize,
+    canonical: bool,
+    /// The number of high bits of the hash that select a bucket.
+    bucket_bits: u32,
+    /// The hashes in bucket `b` are `hashes[buckets[b]..buckets[b+1]]`.
+    buckets: Vec<u32>,
+    /// The distinct minimizer hashes, sorted.
+    hashes: Vec<u32>,
+    /// The positions of `hashes[i]` are `positions[offsets[i]..offsets[i+1]]`.
+    offsets: Vec<u32>,
+    positions: Vec<u32>,
+}
+
+impl MinimizerIndex {
+    /// Index the minimizers of `seq`, as returned by [`minimizer_positions`](crate::minimizer_positions).
+    pub fn build<'s, S: Seq<'s>>(seq: S, k: usize, w: usize) -> Self {
+        let mut positions = vec![];
+        let mut hashes = vec![];
+        crate::minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+        Self::from_sorted_positions(k, w, false, hashes, positions)
+    }
+
+    /// Index the canonical minimizers of `seq`, as returned by [`canonical_minimizer_positions`](crate::canonical_minimizer_positions).
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    pub fn build_canonical<'s, S: Seq<'s>>(seq: S, k: usize, w: usize) -> Self {
+        let mut positions = vec![];
+        let mut hashes = vec![];
+        crate::canonical_minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+        // Canonical minimizer positions are not sorted, and can repeat non-adjacently.
+        let mut pairs = std::iter::zip(positions, hashes).collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs.dedup();
+        let (positions, hashes) = pairs.into_iter().unzip();
+        Self::from_sorted_positions(k, w, true, hashes, positions)
+    }
+
+    fn from_sorted_positions(
+        k: usize,
+        w: usize,
+        canonical: bool,
+        mut hashes: Vec<u32>,
+        mut positions: Vec<u32>,
+    ) -> Self {
+        // Stable, so that the positions of each hash remain sorted.
+        radix_sort_by_key(&mut hashes, &mut positions);
+
+        let mut index = MinimizerIndex {
+            k,
+            w,
+            canonical,
+            ..Default::default()
+        };
+        for (i, &hash) in hashes.iter().enumerate() {
+            if index.hashes.last() != Some(&hash) {
+                index.hashes.push(hash);
+                index.offsets.push(i as u32);
+            }
+        }
+        index.offsets.push(positions.len() as u32);
+        index.positions = positions;
+
+        // About one distinct hash per bucket.
+        let bucket_bits = index.hashes.len().next_power_of_two().trailing_zeros();
+        let num_buckets = 1usize << bucket_bits;
+        let mut buckets = vec![0; num_buckets + 1];
+        for &hash in &index.hashes {
+            buckets[bucket(hash, bucket_bits) + 1] += 1;
+        }
+        for b in 0..num_buckets {
+            buckets[b + 1] += buckets[b];
+        }
+        index.bucket_bits = bucket_bits;
+        index.buckets = buckets;
+        index
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The number of k-mers in each window.
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    /// The number of distinct minimizers.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether the index contains no minimizers.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// The total number of minimizer positions.
+    pub fn num_positions(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// The sorted positions of the minimizers with the given (canonical, for canonical indices) hash.
+    pub fn lookup_hash(&self, hash: u32) -> &[u32] {
+        if self.is_empty() {
+            return &[];
+        }
+        let b = bucket(hash, self.bucket_bits);
+        let (start, end) = (self.buckets[b] as usize, self.buckets[b + 1] as usize);
+        match self.hashes[start..end].binary_search(&hash) {
+            Ok(i) => {
+                let i = start + i;
+                &self.positions[self.offsets[i] as usize..self.offsets[i + 1] as usize]
+            }
+            Err(_) => &[],
+        }
+    }
+
+    /// The sorted positions where `kmer` is a minimizer.
+    ///
+    /// For canonical indices, these are the positions of both `kmer` and its reverse complement.
+    pub fn lookup<'s, S: Seq<'s>>(&self, kmer: S) -> &[u32] {
+        assert_eq!(
+            kmer.len(),
+            self.k,
+            "The k-mer must have length k={}.",
+            self.k
+        );
+        let hash = if self.canonical {
+            crate::canonical_kmer_hash(kmer)
+        } else if S::BITS_PER_CHAR == 2 {
+            kmer_hash::<NtHasher>(kmer, self.k)
+        } else {
+            kmer_hash::<MulHasher>(kmer, self.k)
+        };
+        self.lookup_hash(hash)
+    }
+}
+
+/// The bucket of `hash` given by its high `bucket_bits` bits.
+fn bucket(hash: u32, bucket_bits: u32) -> usize {
+    ((hash as u64) >> (32 - bucket_bits)) as usize
+}
+
+/// Stable LSD radix sort of `keys` and the corresponding `vals`, 8 bits at a time.
+fn radix_sort_by_key(keys: &mut Vec<u32>, vals: &mut Vec<u32>) {
+    let n = keys.len();
+    let mut keys_tmp = vec![0; n];
+    let mut vals_tmp = vec![0; n];
+    for shift in (0..32).step_by(8) {
+        let mut counts = [0usize; 257];
+        for &key in keys.iter() {
+            counts[((key >> shift) & 0xff) as usize + 1] += 1;
+        }
+        // All keys have the same byte.
+        if counts.contains(&n) {
+            continue;
+        }
+        for b in 0..256 {
+            counts[b + 1] += counts[b];
+        }
+        for (&key, &val) in std::iter::zip(keys.iter(), vals.iter()) {
+            let b = ((key >> shift) & 0xff) as usize;
+            keys_tmp[counts[b]] = key;
+            vals_tmp[counts[b]] = val;
+            counts[b] += 1;
+        }
+        std::mem::swap(keys, &mut keys_tmp);
+        std::mem::swap(vals, &mut vals_tmp);
+    }
+}
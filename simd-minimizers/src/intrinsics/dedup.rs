@@ -1,65 +1,26 @@
 use crate::S;
+#[cfg(any(target_feature = "avx2", target_feature = "neon"))]
 use core::mem::transmute;
 
+#[cfg(any(
+    target_feature = "neon",
+    all(
+        target_feature = "avx2",
+        not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+    )
+))]
 const L: usize = 256 / 32;
 
-/// Dedup adjacent `new` values (starting with the last element of `old`).
-/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
-#[inline(always)]
-#[cfg(not(any(target_feature = "avx2", target_feature = "neon")))]
-pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
-    unsafe {
-        let old = old.to_array();
-        let new = new.to_array();
-        let vals = vals.to_array();
-        let mut prec = old[7];
-        for (i, &curr) in new.iter().enumerate() {
-            if curr != prec {
-                v.as_mut_ptr().add(*write_idx).write(vals[i]);
-                *write_idx += 1;
-                prec = curr;
-            }
-        }
-    }
-}
-
-/// Dedup adjacent `new` values (starting with the last element of `old`).
-/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]` and `vals2` to `v2[write_idx]`.
-#[inline(always)]
-#[cfg(not(any(target_feature = "avx2", target_feature = "neon")))]
-pub unsafe fn append_unique_vals_2(
-    old: S,
-    new: S,
-    vals: S,
-    vals2: S,
-    v: &mut [u32],
-    v2: &mut [u32],
-    write_idx: &mut usize,
-) {
-    unsafe {
-        let old = old.to_array();
-        let new = new.to_array();
-        let vals = vals.to_array();
-        let vals2 = vals2.to_array();
-        let mut prec = old[7];
-        for (i, &curr) in new.iter().enumerate() {
-            if curr != prec {
-                v.as_mut_ptr().add(*write_idx).write(vals[i]);
-                v2.as_mut_ptr().add(*write_idx).write(vals2[i]);
-                *write_idx += 1;
-                prec = curr;
-            }
-        }
-    }
-}
-
 /// Dedup adjacent `new` values (starting with the last element of `old`).
 /// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
 ///
 /// Based on Daniel Lemire's blog.
 /// <https://lemire.me/blog/2017/04/10/removing-duplicates-from-lists-quickly/>
 /// <https://github.com/lemire/Code-used-on-Daniel-Lemire-s-blog/blob/edfd0e8b809d9a57527a7990c4bb44b9d1d05a69/2017/04/10/removeduplicates.cpp>
-#[cfg(target_feature = "avx2")]
+#[cfg(all(
+    target_feature = "avx2",
+    not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+))]
 #[inline(always)]
 pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
     unsafe {
@@ -88,7 +49,10 @@ pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_i
 /// Based on Daniel Lemire's blog.
 /// <https://lemire.me/blog/2017/04/10/removing-duplicates-from-lists-quickly/>
 /// <https://github.com/lemire/Code-used-on-Daniel-Lemire-s-blog/blob/edfd0e8b809d9a57527a7990c4bb44b9d1d05a69/2017/04/10/removeduplicates.cpp>
-#[cfg(target_feature = "avx2")]
+#[cfg(all(
+    target_feature = "avx2",
+    not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+))]
 #[inline(always)]
 pub unsafe fn append_unique_vals_2(
     old: S,
@@ -122,6 +86,42 @@ pub unsafe fn append_unique_vals_2(
     }
 }
 
+/// Dedup adjacent `old` values (followed by the first element of `new`), keeping the last element of each run.
+/// If an element is different from the succeeding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Like [`append_unique_vals`], but compares with the successor instead of the predecessor.
+#[cfg(all(
+    target_feature = "avx2",
+    not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+))]
+#[inline(always)]
+pub unsafe fn append_unique_vals_last(
+    old: S,
+    new: S,
+    vals: S,
+    v: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let old = transmute(old);
+        let new = transmute(new);
+        let vals = transmute(vals);
+
+        let recon = _mm256_blend_epi32(old, new, 0b00000001);
+        let movebyone_mask = _mm256_set_epi32(0, 7, 6, 5, 4, 3, 2, 1); // rotate shuffle
+        let vec_tmp = _mm256_permutevar8x32_epi32(recon, movebyone_mask);
+
+        let m = _mm256_movemask_ps(transmute(_mm256_cmpeq_epi32(vec_tmp, old))) as usize;
+        let numberofnewvalues = L - m.count_ones() as usize;
+        let key = transmute(UNIQSHUF[m]);
+        let val = _mm256_permutevar8x32_epi32(vals, key);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += numberofnewvalues;
+    }
+}
+
 /// Dedup adjacent `new` values (starting with the last element of `old`).
 /// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
 ///
@@ -132,8 +132,7 @@ pub unsafe fn append_unique_vals_2(
 #[cfg(target_feature = "neon")]
 pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
     unsafe {
-        use core::arch::aarch64::{vpaddd_u64, vpaddlq_u32, vqtbl2q_u8, vst1_u32_x4};
-        use wide::u32x4;
+        use core::arch::aarch64::{vqtbl2q_u8, vst1_u32_x4};
 
         let new_old_mask = S::new([
             u32::MAX,
@@ -156,12 +155,7 @@ pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_i
         let prec: S = transmute((r1, r2));
 
         let dup = prec.cmp_eq(new);
-        let (d1, d2): (u32x4, u32x4) = transmute(dup);
-        let pow1 = u32x4::new([1, 2, 4, 8]);
-        let pow2 = u32x4::new([16, 32, 64, 128]);
-        let m1 = vpaddd_u64(vpaddlq_u32(transmute(d1 & pow1)));
-        let m2 = vpaddd_u64(vpaddlq_u32(transmute(d2 & pow2)));
-        let m = (m1 | m2) as usize;
+        let m = movemask_neon(dup);
 
         let numberofnewvalues = L - m.count_ones() as usize;
         let key = UNIQSHUF[m];
@@ -194,8 +188,7 @@ pub unsafe fn append_unique_vals_2(
     write_idx: &mut usize,
 ) {
     unsafe {
-        use core::arch::aarch64::{vpaddd_u64, vpaddlq_u32, vqtbl2q_u8, vst1_u32_x4};
-        use wide::u32x4;
+        use core::arch::aarch64::{vqtbl2q_u8, vst1_u32_x4};
 
         let new_old_mask = S::new([
             u32::MAX,
@@ -218,12 +211,7 @@ pub unsafe fn append_unique_vals_2(
         let prec: S = transmute((r1, r2));
 
         let dup = prec.cmp_eq(new);
-        let (d1, d2): (u32x4, u32x4) = transmute(dup);
-        let pow1 = u32x4::new([1, 2, 4, 8]);
-        let pow2 = u32x4::new([16, 32, 64, 128]);
-        let m1 = vpaddd_u64(vpaddlq_u32(transmute(d1 & pow1)));
-        let m2 = vpaddd_u64(vpaddlq_u32(transmute(d2 & pow2)));
-        let m = (m1 | m2) as usize;
+        let m = movemask_neon(dup);
 
         let numberofnewvalues = L - m.count_ones() as usize;
         let key = UNIQSHUF[m];
@@ -243,8 +231,127 @@ pub unsafe fn append_unique_vals_2(
     }
 }
 
+/// Dedup adjacent `old` values (followed by the first element of `new`), keeping the last element of each run.
+/// If an element is different from the succeeding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Like [`append_unique_vals`], but compares with the successor instead of the predecessor.
+#[inline(always)]
+#[cfg(target_feature = "neon")]
+pub unsafe fn append_unique_vals_last(
+    old: S,
+    new: S,
+    vals: S,
+    v: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::aarch64::{vqtbl2q_u8, vst1_u32_x4};
+
+        let new_old_mask = S::new([u32::MAX, 0, 0, 0, 0, 0, 0, 0]);
+        let recon = new_old_mask.blend(new, old);
+
+        let rotate_idx = S::new([1, 2, 3, 4, 5, 6, 7, 0]);
+        let idx = rotate_idx * S::splat(0x04_04_04_04) + S::splat(0x03_02_01_00);
+        let (i1, i2) = transmute(idx);
+        let t = transmute(recon);
+        let r1 = vqtbl2q_u8(t, i1);
+        let r2 = vqtbl2q_u8(t, i2);
+        let succ: S = transmute((r1, r2));
+
+        let dup = succ.cmp_eq(old);
+        let m = movemask_neon(dup);
+
+        let numberofnewvalues = L - m.count_ones() as usize;
+        let key = UNIQSHUF[m];
+        let idx = key * S::splat(0x04_04_04_04) + S::splat(0x03_02_01_00);
+        let (i1, i2) = transmute(idx);
+        let t = transmute(vals);
+        let r1 = vqtbl2q_u8(t, i1);
+        let r2 = vqtbl2q_u8(t, i2);
+        let val: S = transmute((r1, r2));
+        vst1_u32_x4(v.as_mut_ptr().add(*write_idx), transmute(val));
+        *write_idx += numberofnewvalues;
+    }
+}
+
+/// Append the elements of `vals` for which `keep` is all ones to `v[write_idx]`.
+///
+/// Uses the `UNIQSHUF` table of the dedup functions, indexed by the mask of dropped elements.
+#[cfg(all(
+    target_feature = "avx2",
+    not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+))]
+#[inline(always)]
+pub unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let keep = transmute::<S, __m256i>(keep);
+        let vals = transmute::<S, __m256i>(vals);
+
+        let m = (!_mm256_movemask_ps(_mm256_castsi256_ps(keep)) & 0xff) as usize;
+        let numberofnewvalues = L - m.count_ones() as usize;
+        let key = transmute(UNIQSHUF[m]);
+        let val = _mm256_permutevar8x32_epi32(vals, key);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += numberofnewvalues;
+    }
+}
+
+/// Append the elements of `vals` for which `keep` is all ones to `v[write_idx]`.
+///
+/// Uses the `UNIQSHUF` table of the dedup functions, indexed by the mask of dropped elements.
+#[inline(always)]
+#[cfg(target_feature = "neon")]
+pub unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        use core::arch::aarch64::{vqtbl2q_u8, vst1_u32_x4};
+
+        let drop = keep ^ S::splat(u32::MAX);
+        let m = movemask_neon(drop);
+
+        let numberofnewvalues = L - m.count_ones() as usize;
+        let key = UNIQSHUF[m];
+        let idx = key * S::splat(0x04_04_04_04) + S::splat(0x03_02_01_00);
+        let (i1, i2) = transmute(idx);
+        let t = transmute(vals);
+        let r1 = vqtbl2q_u8(t, i1);
+        let r2 = vqtbl2q_u8(t, i2);
+        let val: S = transmute((r1, r2));
+        vst1_u32_x4(v.as_mut_ptr().add(*write_idx), transmute(val));
+        *write_idx += numberofnewvalues;
+    }
+}
+
+/// The mask of elements of `m` that are all ones, with bit `i` set for element `i`.
+///
+/// Both 128-bit halves are masked with their bit weights and OR-ed together before a single horizontal add,
+/// so that only one reduction is needed instead of one per half.
+#[inline(always)]
+#[cfg(target_feature = "neon")]
+unsafe fn movemask_neon(m: S) -> usize {
+    unsafe {
+        use core::arch::aarch64::vaddvq_u32;
+        use wide::u32x4;
+
+        let (m1, m2): (u32x4, u32x4) = transmute(m);
+        let pow1 = u32x4::new([1, 2, 4, 8]);
+        let pow2 = u32x4::new([16, 32, 64, 128]);
+        vaddvq_u32(transmute((m1 & pow1) | (m2 & pow2))) as usize
+    }
+}
+
 /// For each of 256 masks of which elements are different than their predecessor,
 /// a shuffle that sends those new elements to the beginning.
+/// Also used by `append_filtered_vals`, indexed by the mask of dropped elements.
+/// Not needed for AVX-512, which has a native compress instruction.
+#[cfg(any(
+    target_feature = "neon",
+    all(
+        target_feature = "avx2",
+        not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+    )
+))]
 #[rustfmt::skip]
 const UNIQSHUF: [S; 256] = unsafe {transmute([
 0,1,2,3,4,5,6,7,
@@ -507,7 +614,7 @@ const UNIQSHUF: [S; 256] = unsafe {transmute([
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use crate::intrinsics::{append_unique_vals, append_unique_vals_last};
     use std::time::Instant;
     use wide::u32x8 as S;
     const L: usize = 8;
@@ -547,4 +654,38 @@ mod test {
             assert_eq!(v1, v2, "Failure for\n      : {v:?}");
         }
     }
+
+    #[test]
+    fn test_append_unique_vals_last() {
+        let len: usize = 1 << 16;
+        for max in [len / 10, len / 3, len, len * 3] {
+            let mut v: Vec<u32> = (0..len)
+                .map(|_| rand::random::<u32>() % max as u32)
+                .collect();
+            v.sort();
+
+            // Index of the last element of each run.
+            let expected: Vec<u32> = (0..len)
+                .filter(|&i| i + 1 == len || v[i] != v[i + 1])
+                .map(|i| i as u32)
+                .collect();
+
+            const IDX: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+            let chunks: Vec<S> = (0..(len / L))
+                .map(|i| S::new(IDX.map(|j| v[i * L + j])))
+                .collect();
+            let mut idx = vec![0; len + L];
+            let mut write_idx = 0;
+            for i in 0..chunks.len() {
+                // The last element of the last chunk is always kept.
+                let new = chunks.get(i + 1).copied().unwrap_or(!chunks[i]);
+                let vals = S::new(IDX.map(|j| (i * L + j) as u32));
+                unsafe {
+                    append_unique_vals_last(chunks[i], new, vals, &mut idx, &mut write_idx);
+                }
+            }
+            idx.truncate(write_idx);
+            assert_eq!(idx, expected, "Failure for max={max}");
+        }
+    }
 }
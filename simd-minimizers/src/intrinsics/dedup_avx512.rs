@@ -0,0 +1,170 @@
+//! Dedup kernels using the AVX-512 `vpcompressd` instruction, which replaces the `UNIQSHUF` table of the AVX2 kernels.
+//!
+//! These are compiled on all x86_64 targets. When `avx512f` and `avx512vl` are enabled at compile time they are the default kernels,
+//! and otherwise the collect step selects them at run time when [`has_avx512`] detects support.
+use crate::S;
+use core::mem::transmute;
+
+/// Whether the CPU supports `avx512f` and `avx512vl`.
+///
+/// Always true when they are enabled at compile time. Otherwise, the result of run-time detection, which is cached by `std`.
+#[inline(always)]
+pub fn has_avx512() -> bool {
+    cfg!(all(target_feature = "avx512f", target_feature = "avx512vl"))
+        || (std::arch::is_x86_feature_detected!("avx512f")
+            && std::arch::is_x86_feature_detected!("avx512vl"))
+}
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Uses AVX-512 `vpcompressd` to move the new elements to the front, instead of the `UNIQSHUF` table.
+#[target_feature(enable = "avx512f,avx512vl")]
+#[inline]
+pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let old = transmute::<S, __m256i>(old);
+        let new = transmute::<S, __m256i>(new);
+        let vals = transmute::<S, __m256i>(vals);
+
+        // [old[7], new[0], ..., new[6]]
+        let prec = _mm256_alignr_epi32::<7>(new, old);
+        let m = _mm256_cmpneq_epi32_mask(prec, new);
+        // A full store followed by a bump of `write_idx` is faster than a masked compress-store on some CPUs.
+        let val = _mm256_maskz_compress_epi32(m, vals);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += m.count_ones() as usize;
+    }
+}
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]` and `vals2` to `v2[write_idx]`.
+///
+/// Uses AVX-512 `vpcompressd` to move the new elements to the front, instead of the `UNIQSHUF` table.
+#[target_feature(enable = "avx512f,avx512vl")]
+#[inline]
+pub unsafe fn append_unique_vals_2(
+    old: S,
+    new: S,
+    vals: S,
+    vals2: S,
+    v: &mut [u32],
+    v2: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let old = transmute::<S, __m256i>(old);
+        let new = transmute::<S, __m256i>(new);
+        let vals = transmute::<S, __m256i>(vals);
+        let vals2 = transmute::<S, __m256i>(vals2);
+
+        // [old[7], new[0], ..., new[6]]
+        let prec = _mm256_alignr_epi32::<7>(new, old);
+        let m = _mm256_cmpneq_epi32_mask(prec, new);
+        let val = _mm256_maskz_compress_epi32(m, vals);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        let val2 = _mm256_maskz_compress_epi32(m, vals2);
+        _mm256_storeu_si256(v2.as_mut_ptr().add(*write_idx) as *mut __m256i, val2);
+        *write_idx += m.count_ones() as usize;
+    }
+}
+
+/// Dedup adjacent `old` values (followed by the first element of `new`), keeping the last element of each run.
+/// If an element is different from the succeeding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Like [`append_unique_vals`], but compares with the successor instead of the predecessor.
+#[target_feature(enable = "avx512f,avx512vl")]
+#[inline]
+pub unsafe fn append_unique_vals_last(
+    old: S,
+    new: S,
+    vals: S,
+    v: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let old = transmute::<S, __m256i>(old);
+        let new = transmute::<S, __m256i>(new);
+        let vals = transmute::<S, __m256i>(vals);
+
+        // [old[1], ..., old[7], new[0]]
+        let succ = _mm256_alignr_epi32::<1>(new, old);
+        let m = _mm256_cmpneq_epi32_mask(succ, old);
+        let val = _mm256_maskz_compress_epi32(m, vals);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += m.count_ones() as usize;
+    }
+}
+
+/// Append the elements of `vals` for which `keep` is all ones to `v[write_idx]`.
+///
+/// Uses AVX-512 `vpcompressd` to move the kept elements to the front, instead of the `UNIQSHUF` table.
+#[target_feature(enable = "avx512f,avx512vl")]
+#[inline]
+pub unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let keep = transmute::<S, __m256i>(keep);
+        let vals = transmute::<S, __m256i>(vals);
+
+        let m = _mm256_cmpneq_epi32_mask(keep, _mm256_setzero_si256());
+        let val = _mm256_maskz_compress_epi32(m, vals);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += m.count_ones() as usize;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_append_unique_vals_avx512() {
+        if !has_avx512() {
+            return;
+        }
+        let len = 1 << 16;
+        for max in [len / 10, len, len * 10] {
+            let mut v: Vec<u32> = (0..len).map(|_| rand::random::<u32>() % max).collect();
+            v.sort();
+            let mut expected = v.clone();
+            expected.dedup();
+
+            let chunks: Vec<S> = v
+                .chunks_exact(8)
+                .map(|c| S::new(c.try_into().unwrap()))
+                .collect();
+            let mut out = vec![0; v.len() + 8];
+            let mut write_idx = 0;
+            let mut old = S::MAX;
+            for &new in &chunks {
+                unsafe { append_unique_vals(old, new, new, &mut out, &mut write_idx) };
+                old = new;
+            }
+            out.truncate(write_idx);
+            assert_eq!(out, expected, "max={max}");
+
+            let keep: Vec<S> = chunks
+                .iter()
+                .map(|&c| (c & S::splat(1)).cmp_eq(S::ZERO))
+                .collect();
+            let mut out = vec![0; v.len() + 8];
+            let mut write_idx = 0;
+            for (&keep, &vals) in keep.iter().zip(&chunks) {
+                unsafe { append_filtered_vals(keep, vals, &mut out, &mut write_idx) };
+            }
+            out.truncate(write_idx);
+            assert!(out
+                .iter()
+                .copied()
+                .eq(v.iter().copied().filter(|x| x % 2 == 0)));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Portable versions of the functions in `dedup`, for targets without AVX2, AVX-512, or NEON, such as riscv64 and wasm32.
+//!
+//! The comparisons use `wide`, and the values are written one at a time without branches:
+//! each value is written to `v[write_idx]`, and `write_idx` only advances when it is kept.
+//! Like the SIMD versions, this may write up to 8 values, so `v` must have space for 8 values starting at `write_idx`.
+use std::iter::zip;
+
+use crate::S;
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
+#[inline(always)]
+pub unsafe fn append_unique_vals(old: S, new: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        for (val, dup) in zip(vals.to_array(), dup_mask(old, new)) {
+            v.as_mut_ptr().add(*write_idx).write(val);
+            *write_idx += (dup == 0) as usize;
+        }
+    }
+}
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]` and `vals2` to `v2[write_idx]`.
+#[inline(always)]
+pub unsafe fn append_unique_vals_2(
+    old: S,
+    new: S,
+    vals: S,
+    vals2: S,
+    v: &mut [u32],
+    v2: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        let vals = zip(vals.to_array(), vals2.to_array());
+        for ((val, val2), dup) in zip(vals, dup_mask(old, new)) {
+            v.as_mut_ptr().add(*write_idx).write(val);
+            v2.as_mut_ptr().add(*write_idx).write(val2);
+            *write_idx += (dup == 0) as usize;
+        }
+    }
+}
+
+/// Dedup adjacent `old` values (followed by the first element of `new`), keeping the last element of each run.
+/// If an element is different from the succeeding element, append the corresponding element of `vals` to `v[write_idx]`.
+#[inline(always)]
+pub unsafe fn append_unique_vals_last(
+    old: S,
+    new: S,
+    vals: S,
+    v: &mut [u32],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        let o = old.to_array();
+        let succ = S::new([o[1], o[2], o[3], o[4], o[5], o[6], o[7], new.to_array()[0]]);
+        for (val, dup) in zip(vals.to_array(), old.cmp_eq(succ).to_array()) {
+            v.as_mut_ptr().add(*write_idx).write(val);
+            *write_idx += (dup == 0) as usize;
+        }
+    }
+}
+
+/// Append the elements of `vals` for which `keep` is all ones to `v[write_idx]`.
+#[inline(always)]
+pub unsafe fn append_filtered_vals(keep: S, vals: S, v: &mut [u32], write_idx: &mut usize) {
+    unsafe {
+        for (val, keep) in zip(vals.to_array(), keep.to_array()) {
+            v.as_mut_ptr().add(*write_idx).write(val);
+            *write_idx += (keep != 0) as usize;
+        }
+    }
+}
+
+/// All ones for the elements of `new` that equal their predecessor, starting with the last element of `old`.
+#[inline(always)]
+fn dup_mask(old: S, new: S) -> [u32; 8] {
+    let n = new.to_array();
+    let prec = S::new([old.to_array()[7], n[0], n[1], n[2], n[3], n[4], n[5], n[6]]);
+    new.cmp_eq(prec).to_array()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_dedup() {
+        let len = 1 << 12;
+        for max in [2, 10, 1000, u32::MAX] {
+            let mut v: Vec<u32> = (0..len).map(|_| rand::random::<u32>() % max).collect();
+            v.sort();
+            let chunks = v
+                .chunks_exact(8)
+                .map(|c| S::new(c.try_into().unwrap()))
+                .collect::<Vec<_>>();
+
+            let mut first = v.clone();
+            first.dedup();
+            let mut out = vec![0; len + 8];
+            let mut out2 = vec![0; len + 8];
+            let mut write_idx = 0;
+            let mut old = S::MAX;
+            for &new in &chunks {
+                unsafe {
+                    append_unique_vals_2(old, new, new, !new, &mut out, &mut out2, &mut write_idx)
+                };
+                old = new;
+            }
+            assert_eq!(out[..write_idx], first);
+            assert!(out2[..write_idx].iter().eq(first.iter().map(|x| !x)));
+
+            let mut write_idx = 0;
+            let mut old = S::MAX;
+            for &new in &chunks {
+                unsafe { append_unique_vals(old, new, new, &mut out, &mut write_idx) };
+                old = new;
+            }
+            assert_eq!(out[..write_idx], first);
+
+            let mut last = vec![];
+            for i in 0..len {
+                if i + 1 == len || v[i] != v[i + 1] {
+                    last.push(i as u32);
+                }
+            }
+            let mut write_idx = 0;
+            for (i, &old) in chunks.iter().enumerate() {
+                let new = chunks.get(i + 1).copied().unwrap_or(!old);
+                let vals = S::new(std::array::from_fn(|j| (8 * i + j) as u32));
+                unsafe { append_unique_vals_last(old, new, vals, &mut out, &mut write_idx) };
+            }
+            assert_eq!(out[..write_idx], last);
+
+            let mut write_idx = 0;
+            for &vals in &chunks {
+                let keep = (vals & S::splat(1)).cmp_eq(S::splat(0));
+                unsafe { append_filtered_vals(keep, vals, &mut out, &mut write_idx) };
+            }
+            assert!(out[..write_idx]
+                .iter()
+                .eq(v.iter().filter(|&&x| x % 2 == 0)));
+        }
+    }
+}
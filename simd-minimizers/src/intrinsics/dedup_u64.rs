@@ -0,0 +1,149 @@
+//! Like [`append_unique_vals`](super::append_unique_vals), for 4 `u64` values at a time.
+//!
+//! AVX2 compares all 4 values at once and uses a 16-entry shuffle table,
+//! while NEON processes two `u64x2` halves using a 4-entry byte shuffle table each.
+use wide::u64x4;
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Always writes 4 values, so `v` must have space for 4 values starting at `write_idx`.
+#[inline(always)]
+#[cfg(not(any(target_feature = "avx2", target_feature = "neon")))]
+pub unsafe fn append_unique_vals_u64(
+    old: u64x4,
+    new: u64x4,
+    vals: u64x4,
+    v: &mut [u64],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        let old = old.to_array();
+        let new = new.to_array();
+        let vals = vals.to_array();
+        let mut prec = old[3];
+        for (i, &curr) in new.iter().enumerate() {
+            if curr != prec {
+                v.as_mut_ptr().add(*write_idx).write(vals[i]);
+                *write_idx += 1;
+                prec = curr;
+            }
+        }
+    }
+}
+
+/// For each mask of duplicate 64-bit lanes, the 32-bit lanes of the remaining values, moved to the front.
+#[cfg(target_feature = "avx2")]
+const UNIQSHUF_U64X4: [[u32; 8]; 16] = {
+    let mut table = [[0; 8]; 16];
+    let mut m = 0;
+    while m < 16 {
+        let mut len = 0;
+        let mut j = 0;
+        while j < 4 {
+            if m & (1 << j) == 0 {
+                table[m][2 * len] = 2 * j as u32;
+                table[m][2 * len + 1] = 2 * j as u32 + 1;
+                len += 1;
+            }
+            j += 1;
+        }
+        m += 1;
+    }
+    table
+};
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Always writes 4 values, so `v` must have space for 4 values starting at `write_idx`.
+#[inline(always)]
+#[cfg(target_feature = "avx2")]
+pub unsafe fn append_unique_vals_u64(
+    old: u64x4,
+    new: u64x4,
+    vals: u64x4,
+    v: &mut [u64],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::x86_64::*;
+        use core::mem::transmute;
+
+        let old: __m256i = transmute(old);
+        let new: __m256i = transmute(new);
+        let vals: __m256i = transmute(vals);
+
+        // [old[3], new[0], new[1], new[2]]
+        let rotated = _mm256_permute4x64_epi64::<0b10_01_00_11>(new);
+        let old_last = _mm256_permute4x64_epi64::<0b11_11_11_11>(old);
+        let prec = _mm256_blend_epi32::<0b0000_0011>(rotated, old_last);
+
+        let m = _mm256_movemask_pd(_mm256_castsi256_pd(_mm256_cmpeq_epi64(prec, new))) as usize;
+        let numberofnewvalues = 4 - m.count_ones() as usize;
+        let key = transmute::<[u32; 8], __m256i>(UNIQSHUF_U64X4[m]);
+        let val = _mm256_permutevar8x32_epi32(vals, key);
+        _mm256_storeu_si256(v.as_mut_ptr().add(*write_idx) as *mut __m256i, val);
+        *write_idx += numberofnewvalues;
+    }
+}
+
+/// For each mask of duplicate 64-bit lanes, the bytes of the remaining values, moved to the front.
+#[cfg(target_feature = "neon")]
+const UNIQSHUF_U64X2: [[u8; 16]; 4] = {
+    let mut table = [[0; 16]; 4];
+    let mut m = 0;
+    while m < 4 {
+        let mut len = 0;
+        let mut j = 0;
+        while j < 2 {
+            if m & (1 << j) == 0 {
+                let mut b = 0;
+                while b < 8 {
+                    table[m][8 * len + b] = (8 * j + b) as u8;
+                    b += 1;
+                }
+                len += 1;
+            }
+            j += 1;
+        }
+        m += 1;
+    }
+    table
+};
+
+/// Dedup adjacent `new` values (starting with the last element of `old`).
+/// If an element is different from the preceding element, append the corresponding element of `vals` to `v[write_idx]`.
+///
+/// Always writes 4 values, so `v` must have space for 4 values starting at `write_idx`.
+#[inline(always)]
+#[cfg(target_feature = "neon")]
+pub unsafe fn append_unique_vals_u64(
+    old: u64x4,
+    new: u64x4,
+    vals: u64x4,
+    v: &mut [u64],
+    write_idx: &mut usize,
+) {
+    unsafe {
+        use core::arch::aarch64::*;
+        use core::mem::transmute;
+
+        let (_, old_hi): (uint64x2_t, uint64x2_t) = transmute(old);
+        let (new_lo, new_hi): (uint64x2_t, uint64x2_t) = transmute(new);
+        let (vals_lo, vals_hi): (uint64x2_t, uint64x2_t) = transmute(vals);
+
+        let mut half = |prev: uint64x2_t, new: uint64x2_t, vals: uint64x2_t| {
+            // [prev[1], new[0]]
+            let prec = vextq_u64::<1>(prev, new);
+            let dup = vceqq_u64(prec, new);
+            let m = (vgetq_lane_u64::<0>(dup) & 1 | (vgetq_lane_u64::<1>(dup) & 1) << 1) as usize;
+            let key = vld1q_u8(UNIQSHUF_U64X2[m].as_ptr());
+            let val = vqtbl1q_u8(vreinterpretq_u8_u64(vals), key);
+            vst1q_u64(v.as_mut_ptr().add(*write_idx), vreinterpretq_u64_u8(val));
+            *write_idx += 2 - m.count_ones() as usize;
+        };
+        half(old_hi, new_lo, vals_lo);
+        half(new_lo, new_hi, vals_hi);
+    }
+}
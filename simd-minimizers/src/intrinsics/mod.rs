@@ -1,7 +1,58 @@
+#[cfg(any(
+    target_feature = "neon",
+    all(
+        target_feature = "avx2",
+        not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+    )
+))]
 mod dedup;
+// Without compile-time AVX-512, only the kernels that are dispatched at run time are used.
+#[cfg(target_arch = "x86_64")]
+#[cfg_attr(
+    not(all(target_feature = "avx512f", target_feature = "avx512vl")),
+    allow(dead_code)
+)]
+pub mod dedup_avx512;
+// Used on targets without AVX2, AVX-512, or NEON.
+#[cfg_attr(
+    any(
+        target_feature = "avx2",
+        target_feature = "neon",
+        all(target_feature = "avx512f", target_feature = "avx512vl")
+    ),
+    allow(dead_code)
+)]
+mod dedup_scalar;
+mod dedup_u64;
+mod prefetch;
 mod table_lookup;
 mod transpose;
 
-pub use dedup::{append_unique_vals, append_unique_vals_2};
+#[cfg(any(
+    target_feature = "neon",
+    all(
+        target_feature = "avx2",
+        not(all(target_feature = "avx512f", target_feature = "avx512vl"))
+    )
+))]
+pub use dedup::{
+    append_filtered_vals, append_unique_vals, append_unique_vals_2, append_unique_vals_last,
+};
+#[cfg(target_arch = "x86_64")]
+pub use dedup_avx512::has_avx512;
+#[cfg(all(target_feature = "avx512f", target_feature = "avx512vl"))]
+pub use dedup_avx512::{
+    append_filtered_vals, append_unique_vals, append_unique_vals_2, append_unique_vals_last,
+};
+#[cfg(not(any(
+    target_feature = "avx2",
+    target_feature = "neon",
+    all(target_feature = "avx512f", target_feature = "avx512vl")
+)))]
+pub use dedup_scalar::{
+    append_filtered_vals, append_unique_vals, append_unique_vals_2, append_unique_vals_last,
+};
+pub use dedup_u64::append_unique_vals_u64;
+pub use prefetch::{prefetch_read, PrefetchSeq};
 pub use table_lookup::table_lookup;
 pub use transpose::transpose;
@@ -0,0 +1,78 @@
+use packed_seq::{AsciiSeq, PackedSeq, Seq};
+
+/// Hint that the cache line containing `p` will be read soon.
+///
+/// Uses `prfm pldl1keep` on aarch64 and `prefetcht0` on x86. On other targets this is a no-op.
+/// Prefetching never faults, so `p` does not need to be a valid pointer.
+#[inline(always)]
+pub fn prefetch_read<T>(p: *const T) {
+    _prefetch_read(p as *const u8)
+}
+
+#[inline(always)]
+#[cfg(target_arch = "aarch64")]
+fn _prefetch_read(p: *const u8) {
+    unsafe {
+        core::arch::asm!("prfm pldl1keep, [{0}]", in(reg) p, options(nostack, readonly, preserves_flags));
+    }
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+fn _prefetch_read(p: *const u8) {
+    unsafe {
+        use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch(p as *const i8, _MM_HINT_T0);
+    }
+}
+
+#[inline(always)]
+#[cfg(not(any(
+    target_arch = "aarch64",
+    all(target_arch = "x86_64", target_feature = "sse")
+)))]
+fn _prefetch_read(_p: *const u8) {}
+
+/// The size in bytes of a cache line, i.e. the stride between prefetches.
+const CACHE_LINE_BYTES: usize = 64;
+
+/// A [`Seq`] whose underlying bytes can be prefetched.
+///
+/// Used by [`minimizer_positions_cache_blocked`](crate::minimizer_positions_cache_blocked)
+/// to prefetch the start of each lane of the next tile.
+pub trait PrefetchSeq<'s>: Seq<'s> {
+    /// Hint that the `bytes` bytes of data starting at character `pos` will be read soon.
+    fn prefetch(&self, pos: usize, bytes: usize);
+}
+
+/// Prefetch the cache lines covering `bytes` bytes starting at `p`.
+#[inline(always)]
+fn prefetch_bytes(p: *const u8, bytes: usize) {
+    for i in (0..bytes).step_by(CACHE_LINE_BYTES) {
+        prefetch_read(p.wrapping_add(i));
+    }
+}
+
+impl<'s> PrefetchSeq<'s> for PackedSeq<'s> {
+    #[inline(always)]
+    fn prefetch(&self, pos: usize, bytes: usize) {
+        prefetch_bytes(
+            self.seq.as_ptr().wrapping_add((self.offset + pos) / 4),
+            bytes,
+        );
+    }
+}
+
+impl<'s> PrefetchSeq<'s> for AsciiSeq<'s> {
+    #[inline(always)]
+    fn prefetch(&self, pos: usize, bytes: usize) {
+        prefetch_bytes(self.0.as_ptr().wrapping_add(pos), bytes);
+    }
+}
+
+impl<'s> PrefetchSeq<'s> for &'s [u8] {
+    #[inline(always)]
+    fn prefetch(&self, pos: usize, bytes: usize) {
+        prefetch_bytes(self.as_ptr().wrapping_add(pos), bytes);
+    }
+}
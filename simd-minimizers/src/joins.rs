@@ -0,0 +1,126 @@
+//! Cross-check the SIMD output at the joins between its 8 chunks against scalar code.
+//!
+//! The SIMD pipelines split the windows of a sequence into 8 chunks, one per lane, via [`Seq::par_iter_bp_delayed`].
+//! Each lane starts `l-1` characters before its first window, and the last lane is padded at the end.
+//! Mistakes in a custom [`Seq`] implementation typically show up exactly there, as wrong minimizers
+//! for the windows just before or after a join.
+//!
+//! [`check_minimizer_joins`] and [`check_canonical_minimizer_joins`] recompute the minimizers of the `l` windows
+//! on either side of each join using scalar code, and report the first mismatch as a [`JoinMismatch`].
+//! With the `check-joins` feature, [`minimizer_positions`](crate::minimizer_positions) and
+//! [`canonical_minimizer_positions`](crate::canonical_minimizer_positions) run this check on every call, and panic on a mismatch.
+use packed_seq::Seq;
+
+use crate::collect::collect;
+use crate::minimizers::{
+    canonical_minimizers_seq_scalar, canonical_minimizers_seq_simd, minimizer, minimizers_seq_simd,
+};
+use crate::nthash::{CharHasher, MulHasher, NtHasher};
+
+/// A window near a join between SIMD chunks whose minimizer differs from the scalar one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JoinMismatch {
+    /// The lane that computed the window.
+    pub lane: usize,
+    /// The index of the window in the sequence.
+    pub window: usize,
+    /// The minimizer position returned by SIMD.
+    pub simd: u32,
+    /// The minimizer position returned by scalar code.
+    pub scalar: u32,
+}
+
+impl std::fmt::Display for JoinMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Window {} in lane {} has its minimizer at position {} using SIMD, but at {} using scalar code.",
+            self.window, self.lane, self.simd, self.scalar
+        )
+    }
+}
+
+impl std::error::Error for JoinMismatch {}
+
+/// Check the minimizers of [`minimizer_positions`](crate::minimizer_positions) near the joins between SIMD chunks.
+pub fn check_minimizer_joins<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+) -> Result<(), JoinMismatch> {
+    if S::BITS_PER_CHAR == 2 {
+        check_joins::<_, NtHasher, false>(seq, k, w)
+    } else {
+        check_joins::<_, MulHasher, false>(seq, k, w)
+    }
+}
+
+/// Check the minimizers of [`canonical_minimizer_positions`](crate::canonical_minimizer_positions) near the joins between SIMD chunks.
+pub fn check_canonical_minimizer_joins<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+) -> Result<(), JoinMismatch> {
+    if S::BITS_PER_CHAR == 2 {
+        check_joins::<_, NtHasher, true>(seq, k, w)
+    } else {
+        check_joins::<_, MulHasher, true>(seq, k, w)
+    }
+}
+
+fn check_joins<'s, S: Seq<'s>, H: CharHasher, const CANONICAL: bool>(
+    seq: S,
+    k: usize,
+    w: usize,
+) -> Result<(), JoinMismatch> {
+    let l = k + w - 1;
+    if seq.len() < l {
+        return Ok(());
+    }
+    let (simd, lane_len) = if CANONICAL {
+        let head_padding = canonical_minimizers_seq_simd::<_, H>(seq, k, w);
+        let lane_len = head_padding.0.len();
+        (collect(head_padding), lane_len)
+    } else {
+        let head_padding = minimizers_seq_simd::<_, H>(seq, k, w);
+        let lane_len = head_padding.0.len();
+        (collect(head_padding), lane_len)
+    };
+
+    // The start of each lane, and the end of the last one.
+    for join in (0..=8).map(|lane| lane * lane_len) {
+        for window in join.saturating_sub(l)..(join + l).min(simd.len()) {
+            let lmer = seq.slice(window..window + l);
+            let scalar = window as u32
+                + if CANONICAL {
+                    canonical_minimizers_seq_scalar::<H>(lmer, k, w)
+                        .next()
+                        .unwrap()
+                } else {
+                    minimizer::<H>(lmer, k) as u32
+                };
+            if simd[window] != scalar {
+                return Err(JoinMismatch {
+                    lane: window / lane_len,
+                    window,
+                    simd: simd[window],
+                    scalar,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Panic with the [`JoinMismatch`] of [`check_minimizer_joins`] or [`check_canonical_minimizer_joins`], if any.
+#[cfg(feature = "check-joins")]
+pub(crate) fn assert_joins<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, canonical: bool) {
+    let result = if canonical {
+        check_canonical_minimizer_joins(seq, k, w)
+    } else {
+        check_minimizer_joins(seq, k, w)
+    };
+    if let Err(mismatch) = result {
+        panic!("{mismatch} (k={k}, w={w}, len={})", seq.len());
+    }
+}
@@ -0,0 +1,180 @@
+//! Packed k-mer values of the minimizers, computed alongside the minimizer positions.
+//!
+//! K-mers are packed using `b = BITS_PER_CHAR` bits per character, with the first character in the lowest bits.
+//! This requires `k * b <= 64`.
+//!
+//! For canonical minimizers, the canonical k-mer value is the minimum of the packed values of the k-mer and its reverse complement.
+//! This is only supported for 2-bit DNA.
+use std::array::from_fn;
+
+use crate::S;
+use packed_seq::{complement_base, complement_base_simd, PackedSeq, Seq};
+
+/// Naively compute the packed value of a single k-mer.
+pub fn kmer_value<'s>(seq: impl Seq<'s>) -> u64 {
+    let b = seq.bits_per_char();
+    assert!(seq.len() * b <= 64);
+    seq.iter_bp()
+        .enumerate()
+        .fold(0, |kmer, (i, a)| kmer | (a as u64) << (b * i))
+}
+
+/// Naively compute the canonical packed value of a single 2-bit k-mer.
+pub fn canonical_kmer_value<'s>(seq: impl Seq<'s>) -> u64 {
+    assert_eq!(
+        seq.bits_per_char(),
+        2,
+        "Canonical k-mers require 2-bit DNA."
+    );
+    let k = seq.len();
+    let fwd = kmer_value(seq);
+    let rc = seq.iter_bp().enumerate().fold(0, |kmer, (i, a)| {
+        kmer | (complement_base(a) as u64) << (2 * (k - 1 - i))
+    });
+    fwd.min(rc)
+}
+
+/// The number of chunks of 8 positions that [`gather_kmers`] prefetches ahead.
+const GATHER_PREFETCH: usize = 4;
+
+/// Append the packed values of the k-mers at `positions` of a 2-bit packed sequence to `out_vec`.
+///
+/// Since k-mers are packed in the same order as the bases of [`PackedSeq`], each k-mer is a single unaligned
+/// 128-bit load followed by a shift and a mask. Positions are processed 8 at a time, so that the shifts are vectorized.
+/// The words of the chunk [`GATHER_PREFETCH`] chunks ahead are prefetched, since sparse positions miss the hardware prefetcher.
+/// Requires `k <= 32`.
+pub fn gather_kmers(seq: PackedSeq, positions: &[u32], k: usize, out_vec: &mut Vec<u64>) {
+    assert!(k <= 32, "2-bit k-mers require k <= 32.");
+    assert!(
+        positions.iter().all(|&pos| pos as usize + k <= seq.len()),
+        "K-mer positions must be inside the sequence."
+    );
+    let mask = if k == 32 {
+        u64::MAX
+    } else {
+        (1 << (2 * k)) - 1
+    };
+    let bytes = seq.seq;
+    // The 16 bytes starting at byte `i`, padded with zeros past the end.
+    let read = |i: usize| -> u128 {
+        match bytes.get(i..i + 16) {
+            Some(word) => u128::from_le_bytes(word.try_into().unwrap()),
+            None => {
+                let mut word = [0; 16];
+                let tail = &bytes[i..];
+                word[..tail.len()].copy_from_slice(tail);
+                u128::from_le_bytes(word)
+            }
+        }
+    };
+    let bit = |pos: u32| 2 * (seq.offset + pos as usize);
+
+    out_vec.reserve(positions.len());
+    let chunks = positions.chunks_exact(8);
+    let rest = chunks.remainder();
+    for (c, chunk) in chunks.enumerate() {
+        let ahead = 8 * (c + GATHER_PREFETCH);
+        if let Some(ahead) = positions.get(ahead..ahead + 8) {
+            for &pos in ahead {
+                crate::intrinsics::prefetch_read(bytes[bit(pos) / 8..].as_ptr());
+            }
+        }
+        let bits: [usize; 8] = from_fn(|i| bit(chunk[i]));
+        let words: [u128; 8] = from_fn(|i| read(bits[i] / 8));
+        out_vec.extend(from_fn::<u64, 8, _>(|i| {
+            (words[i] >> (bits[i] % 8)) as u64 & mask
+        }));
+    }
+    out_vec.extend(rest.iter().map(|&pos| {
+        let bit = bit(pos);
+        (read(bit / 8) >> (bit % 8)) as u64 & mask
+    }));
+}
+
+/// A function that 'eats' added bases and the current minimizer positions, and returns the k-mer values at those positions.
+///
+/// Keeps a rolling k-mer per lane, and a ring buffer of the last `w` k-mers of each lane.
+/// K-mer values are split into their low and high 32 bits, so that they are rolled using SIMD.
+/// When `CANONICAL` is true, the reverse complement k-mer is rolled as well, and the canonical k-mer values are returned.
+/// `len` is the number of values in each chunk, as passed to `sliding_min_mapper`.
+/// The first `k+w-2` returned values are bogus, since they correspond to incomplete windows.
+pub fn kmer_mapper<'s, const CANONICAL: bool, SEQ: Seq<'s>>(
+    k: usize,
+    w: usize,
+    len: usize,
+) -> impl FnMut(S, S) -> [S; 2] + Clone {
+    let b = SEQ::BITS_PER_CHAR;
+    assert!(k > 0);
+    assert!(w > 0);
+    assert!(
+        k * b <= 64,
+        "k={k} characters of {b} bits do not fit in a u64 k-mer value"
+    );
+    if CANONICAL {
+        assert_eq!(b, 2, "Canonical k-mers require 2-bit DNA.");
+    }
+
+    // Number of windows in each lane, and thus the offset of each lane.
+    let n = len.saturating_sub(k + w - 2);
+    let lane_offsets = S::new(std::array::from_fn(|l| (l * n) as u32));
+    // Position of the last character in the k-mer value.
+    let shift = (b * (k - 1)) as u32;
+    let mask = u64::MAX >> (64 - b * k);
+    let (mask_lo, mask_hi) = (S::splat(mask as u32), S::splat((mask >> 32) as u32));
+    let b = b as u32;
+
+    // Low and high words of the forward and reverse complement k-mers.
+    let mut kmer = [S::ZERO; 2];
+    let mut rc = [S::ZERO; 2];
+    let mut ring_buf = vec![[S::ZERO; 2]; w];
+    // Index of the character that is added next.
+    let mut i = 0;
+    // Ring buffer slot of the last complete k-mer.
+    let mut slot = 0;
+
+    #[inline(always)]
+    move |a, pos| {
+        let [lo, hi] = kmer;
+        kmer = [(lo >> b) | (hi << (32 - b)), hi >> b];
+        if shift >= 32 {
+            kmer[1] |= a << (shift - 32);
+        } else {
+            kmer[0] |= a << shift;
+        }
+        if CANONICAL {
+            let [lo, hi] = rc;
+            rc = [
+                ((lo << 2) | complement_base_simd(a)) & mask_lo,
+                ((hi << 2) | (lo >> 30)) & mask_hi,
+            ];
+        }
+        // The k-mer starting at lane-local position `i-(k-1)` is now complete.
+        if i + 1 >= k {
+            slot = (i + 1 - k) % w;
+            ring_buf[slot] = if CANONICAL {
+                // Take the minimum of the two 64-bit values, comparing the high words first.
+                let hi_eq = kmer[1].cmp_eq(rc[1]);
+                let hi_le = kmer[1].min(rc[1]).cmp_eq(kmer[1]);
+                let lo_le = kmer[0].min(rc[0]).cmp_eq(kmer[0]);
+                let fwd_le = hi_eq.blend(lo_le, hi_le);
+                [fwd_le.blend(kmer[0], rc[0]), fwd_le.blend(kmer[1], rc[1])]
+            } else {
+                kmer
+            };
+        }
+        let last = (i + 1).saturating_sub(k) as u32;
+        i += 1;
+
+        // The slot of each position is `slot - (last - pos)`, modulo `w`.
+        // Bogus positions of incomplete windows are clamped to a valid slot.
+        let delta = S::splat(last) - (pos - lane_offsets);
+        let slots = (S::splat(slot as u32 + w as u32) - delta).min(S::splat(2 * w as u32 - 1));
+        // Subtract `w` when that does not underflow.
+        let slots = slots.min(slots - S::splat(w as u32)).to_array();
+        std::array::from_fn(|word| {
+            S::new(std::array::from_fn(|l| {
+                ring_buf[slots[l] as usize][word].as_array_ref()[l]
+            }))
+        })
+    }
+}
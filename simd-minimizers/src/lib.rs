@@ -41,14 +41,20 @@
 //! This works as follows:
 //! 1. ntHash is modified to use the canonical version that computes the xor of the hash of the forward and reverse complement k-mer.
 //! 2. Compute the leftmost and rightmost minimal k-mer.
-//! 3. Compute the 'preferred' strand of the current window as the one with more `TG` characters. This requires `l=w+k-1` to be odd for proper tie-breaking.
+//! 3. Compute the 'preferred' strand of the current window as the one with more `TG` characters. For odd `l=w+k-1` this never ties.
 //! 4. Return either the leftmost or rightmost smallest k-mer, depending on the preferred strand.
 //!
+//! When `l` is even, windows with as many `TG` as `AC` characters prefer the forward strand, and thus return the leftmost smallest k-mer.
+//! This is deterministic, and the same for the scalar and SIMD versions, but not canonical:
+//! when the smallest hash occurs more than once in such a window, the reverse complement window may sample a different k-mer.
+//! Use odd `l` when strand-independence is required.
+//!
 //! ## Input types
 //!
 //! This crate depends on [`packed-seq`] to handle generic types of input sequences.
 //! Most commonly, one should use [`packed_seq::PackedSeqVec`] for packed DNA sequences, but one can also simply wrap a sequence of `ACTGactg` characters in [`packed_seq::AsciiSeqVec`].
 //! Additionally, [`simd-minimizers`] works on general (ASCII) `&[u8]` text.
+//! For other alphabets, such as amino acids, [`minimizer_positions_bytes`] hashes each byte using a pseudo-random table.
 //!
 //! The main function provided by [`packed_seq`] is [`packed_seq::Seq::iter_bp`], which splits the input into 8 chunks and iterates them in parallel using SIMD.
 //!
@@ -66,13 +72,32 @@
 //!
 //! This library depends on AVX2 or NEON SIMD instructions to achieve good performance.
 //! Make sure to compile with `-C target-cpu=native` to enable these instructions.
+//! On x86_64, AVX-512 (`avx512f` and `avx512vl`) is used by all deduplicating and filtering collectors when it is enabled,
+//! or otherwise when it is detected at run time.
 //!
 //! All functions take a `out_vec: &mut Vec<u32>` parameter to which positions are _appended_.
 //! For best performance, re-use the same `out_vec` between invocations, and [`Vec::clear`] it before or after each call.
 //!
+//! ## Output order
+//!
+//! The positions appended by forward schemes, i.e., random minimizers and their spaced, weighted, 64-bit, masked, per-record, and multithreaded variants,
+//! mod-minimizers with `t` congruent to `k` modulo `w`, and syncmers, are non-decreasing,
+//! also across the boundaries of the 8 chunks and of the blocks or threads that long sequences are split into.
+//! This is checked by debug assertions using [`is_sorted`], so that consumers can binary search the positions.
+//! Canonical minimizer positions are _not_ sorted, since consecutive windows can prefer a different strand,
+//! and neither are mod-minimizer positions for other `t`.
+//! Sort (and dedup) them first when needed, or use [`DedupScope::Global`] with [`MinimizerBuilder`].
+//!
 //! ## Features
 //!
-//! - `hide-simd-warning`: If your system does not support AVX2 or NEON, enable this feature to disable the compile warning that will be shown.
+//! - `hide-simd-warning`: If your system does not support AVX2 or NEON, e.g. on riscv64 or wasm32, a portable fallback is used.
+//!   Enable this feature to disable the compile warning that will be shown.
+//! - `rayon`: Enables [`minimizers_par_chunks`] and [`canonical_minimizers_par_chunks`], which split long sequences over multiple threads.
+//! - `fasta`: Enables the `fasta` module, which streams the records of a FASTA/FASTQ file and computes the canonical minimizers of each.
+//! - `test-data`: Enables the `reference` module, which re-verifies shipped reference outputs on the current machine.
+//! - `arrow`: Enables `MinimizerResult::to_arrow`, which converts the minimizers to an Arrow `RecordBatch`.
+//! - `capi`: Enables the `capi` module, with `extern "C"` functions for C and C++ tools, declared in `include/simd_minimizers.h`.
+//! - `check-joins`: Cross-checks [`minimizer_positions`] and [`canonical_minimizer_positions`] against scalar code near the joins between SIMD chunks on every call, see [`check_minimizer_joins`].
 //!
 //! ## Examples
 //!
@@ -128,11 +153,66 @@ mod intrinsics;
 
 // Re-exported modules.
 mod anti_lex;
+pub mod backend;
+pub mod bench;
+mod bitvec;
+mod builder;
+mod cancel;
 mod canonical;
+#[cfg(feature = "capi")]
+pub mod capi;
 mod collect;
+mod coords;
+pub mod dictionary;
+pub mod diff;
+mod downsample;
+mod dual;
+mod error;
+#[cfg(feature = "fasta")]
+pub mod fasta;
+mod fracminhash;
+mod gc;
+pub mod hash_quality;
+pub mod incremental;
+pub mod index;
+mod joins;
+mod kmer;
+mod linearize;
+mod long_k;
+mod masked;
 mod minimizers;
+mod mod_minimizers;
+mod multiplicity;
+mod neighborhood;
 mod nthash;
+mod nthash64;
+pub mod packed_blocks;
+pub mod packed_file;
+#[cfg(feature = "rayon")]
+mod par;
+mod partition;
+mod pos;
+mod pos64;
+mod records;
+#[cfg(feature = "test-data")]
+pub mod reference;
+pub mod repeats;
+mod result;
+pub mod schemes;
+mod screen;
+mod seeds;
+pub mod signer;
+pub mod simd_utils;
+mod sketch;
 mod sliding_min;
+pub mod sourmash;
+pub mod stats;
+mod strobemers;
+mod syncmers;
+mod validate;
+mod verify;
+mod weighted;
+mod windows;
 
 #[cfg(test)]
 mod test;
@@ -142,36 +222,145 @@ pub mod private {
     pub mod anti_lex {
         pub use crate::anti_lex::*;
     }
+    pub mod cancel {
+        pub use crate::cancel::*;
+    }
     pub mod canonical {
         pub use crate::canonical::*;
     }
     pub mod collect {
         pub use crate::collect::*;
     }
+    pub mod coords {
+        pub use crate::coords::*;
+    }
+    pub mod downsample {
+        pub use crate::downsample::*;
+    }
+    pub mod dual {
+        pub use crate::dual::*;
+    }
+    pub mod error {
+        pub use crate::error::*;
+    }
+    pub mod fracminhash {
+        pub use crate::fracminhash::*;
+    }
+    pub mod gc {
+        pub use crate::gc::*;
+    }
+    pub mod joins {
+        pub use crate::joins::*;
+    }
+    pub mod kmer {
+        pub use crate::kmer::*;
+    }
+    pub mod linearize {
+        pub use crate::linearize::*;
+    }
+    pub mod long_k {
+        pub use crate::long_k::*;
+    }
+    pub mod masked {
+        pub use crate::masked::*;
+    }
     pub mod minimizers {
         pub use crate::minimizers::*;
     }
+    pub mod mod_minimizers {
+        pub use crate::mod_minimizers::*;
+    }
     pub mod nthash {
         pub use crate::nthash::*;
     }
+    pub mod nthash64 {
+        pub use crate::nthash64::*;
+    }
+    #[cfg(feature = "rayon")]
+    pub mod par {
+        pub use crate::par::*;
+    }
+    pub mod pos64 {
+        pub use crate::pos64::*;
+    }
+    pub mod records {
+        pub use crate::records::*;
+    }
+    pub mod screen {
+        pub use crate::screen::*;
+    }
+    pub mod seeds {
+        pub use crate::seeds::*;
+    }
     pub mod sliding_min {
         pub use crate::sliding_min::*;
     }
+    pub mod strobemers {
+        pub use crate::strobemers::*;
+    }
+    pub mod syncmers {
+        pub use crate::syncmers::*;
+    }
+    pub mod weighted {
+        pub use crate::weighted::*;
+    }
+    pub mod windows {
+        pub use crate::windows::*;
+    }
     pub use packed_seq::u32x8 as S;
 }
 
 /// Re-export of the `packed-seq` crate.
 pub use packed_seq;
 
-use collect::{collect_and_dedup_into, collect_and_dedup_with_index_into};
+pub use bitvec::BitVec;
+pub use builder::{BuildError, DedupScope, HashWidth, MinimizerBuilder, MinimizerConfig, Preset};
+pub use cancel::Cancelled;
+pub use collect::{CollectBuffers, NeedCapacity};
+pub use coords::CoordinateConvention;
+pub use downsample::{downsample_with_coverage, Downsample};
+pub use dual::MinimizerMismatch;
+pub use error::{check_params, Error};
+pub use intrinsics::PrefetchSeq;
+pub use joins::{check_canonical_minimizer_joins, check_minimizer_joins, JoinMismatch};
+pub use linearize::{linearize, Linearize};
+pub use long_k::MAX_K;
+pub use masked::acgt_mask;
+pub use multiplicity::{MinimizerCounts, SeedUniqueness};
+pub use neighborhood::{
+    minimizer_neighborhoods, minimizer_neighborhoods_records, neighborhood_range,
+};
+pub use partition::{minimizer_bucket, Partition};
+pub use pos::{PackedSuperPos, Pos};
+pub use result::{Minimizer, MinimizerParts, MinimizerResult};
+pub use screen::ScreenCounts;
+pub use seeds::{hash64, sketch_seq_simd, sketch_seq_simd_into, Seed};
+pub use sketch::{MultiSketch, SketchLevel};
+pub use strobemers::Strobemer;
+pub use validate::{first_non_acgt, non_acgt_positions, validate_acgt, InvalidBase};
+pub use verify::{is_sorted, verify_coverage, Gap};
+
+use collect::{
+    collect_and_dedup_into, collect_and_dedup_into_u64, collect_and_dedup_into_with,
+    collect_and_dedup_kmers_into, collect_and_dedup_pair_into, collect_and_dedup_with_hashes_into,
+    collect_and_dedup_with_index_into, collect_and_dedup_with_kmers_into,
+    collect_and_dedup_with_last_index_into, collect_and_dedup_with_vals_into, collect_into_bitvec,
+};
 use itertools::Itertools;
 use minimizers::{
-    canonical_minimizers_seq_scalar, canonical_minimizers_seq_simd, minimizers_seq_scalar,
-    minimizers_seq_simd,
+    canonical_minimizer_gc_seq_simd, canonical_minimizer_hashes_seq_simd,
+    canonical_minimizer_kmers_seq_simd, canonical_minimizer_strand_seq_simd,
+    canonical_minimizers64_seq_scalar, canonical_minimizers64_seq_simd,
+    canonical_minimizers_seq_scalar, canonical_minimizers_seq_simd, minimizer_and_hash_seq_simd,
+    minimizer_gc_seq_simd, minimizer_kmers_seq_simd, minimizers64_seq_scalar,
+    minimizers64_seq_simd, minimizers_fwd_rc_seq_simd, minimizers_seq_scalar, minimizers_seq_simd,
+    spaced_minimizers_seq_scalar, spaced_minimizers_seq_simd,
 };
-use nthash::{MulHasher, NtHasher};
+use nthash::{nthash_seq_scalar, ByteHasher, Captures, CharHasher, MulHasher, NtHasher};
 use packed_seq::u32x8 as S;
-use packed_seq::Seq;
+use packed_seq::{Seq, SeqVec, L};
+use std::iter::zip;
+use std::sync::atomic::AtomicBool;
 
 /// Minimizer position of a single window.
 pub fn one_minimizer<'s, S: Seq<'s>>(seq: S, k: usize) -> usize {
@@ -193,19 +382,85 @@ pub fn one_canonical_minimizer<'s, S: Seq<'s>>(seq: S, k: usize) -> usize {
 /// Deduplicated positions of all minimizers in the sequence, using SIMD.
 ///
 /// Positions are appended to a reusable `out_vec` to avoid allocations.
+///
+/// For windows of at most [`SMALL_W`](private::sliding_min::SMALL_W) k-mers,
+/// a fully unrolled sliding window minimum that is specialized for the value of `w` is used.
 pub fn minimizer_positions<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u32>) {
     if S::BITS_PER_CHAR == 2 {
-        let head_padding = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
-        collect_and_dedup_into(head_padding, out_vec);
+        minimizer_positions_with_hasher::<_, NtHasher>(seq, k, w, out_vec);
     } else {
-        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
-        collect_and_dedup_into(head_padding, out_vec);
+        minimizer_positions_with_hasher::<_, MulHasher>(seq, k, w, out_vec);
+    }
+}
+
+fn minimizer_positions_with_hasher<'s, S: Seq<'s>, H: CharHasher>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    use minimizers::small_w_minimizers_seq_simd as small_w;
+    let start = out_vec.len();
+    match w {
+        1 => collect_and_dedup_into(small_w::<_, H, 1>(seq, k), out_vec),
+        2 => collect_and_dedup_into(small_w::<_, H, 2>(seq, k), out_vec),
+        3 => collect_and_dedup_into(small_w::<_, H, 3>(seq, k), out_vec),
+        4 => collect_and_dedup_into(small_w::<_, H, 4>(seq, k), out_vec),
+        5 => collect_and_dedup_into(small_w::<_, H, 5>(seq, k), out_vec),
+        _ => collect_and_dedup_into(minimizers_seq_simd::<_, H>(seq, k, w), out_vec),
+    }
+    debug_assert!(is_sorted(&out_vec[start..]));
+    #[cfg(feature = "check-joins")]
+    joins::assert_joins(seq, k, w, false);
+}
+
+/// The position of the minimizer of each window in the sequence, using SIMD, as a lazy iterator.
+///
+/// Positions are not deduplicated, so that `.enumerate()` gives `(window_index, pos)` pairs,
+/// and `.dedup()` gives the same positions as [`minimizer_positions`]. See [`linearize`].
+pub fn window_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+) -> Linearize<impl ExactSizeIterator<Item = packed_seq::u32x8> + Captures<&'s ()>> {
+    let (head, padding) = if S::BITS_PER_CHAR == 2 {
+        let (head, padding) = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        (itertools::Either::Left(head), padding)
+    } else {
+        let (head, padding) = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        (itertools::Either::Right(head), padding)
+    };
+    linearize((head, padding))
+}
+
+/// Deduplicated positions of all minimizers of a sequence over a general alphabet, such as amino acids, using SIMD.
+///
+/// Each byte of `seq` is a symbol, and is hashed using a pseudo-random table over all 256 byte values,
+/// instead of the mulHash used for `&[u8]` by [`minimizer_positions`].
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_bytes(seq: &[u8], k: usize, w: usize, out_vec: &mut Vec<u32>) {
+    let start = out_vec.len();
+    collect_and_dedup_into(minimizers::minimizers_seq_simd_bytes(seq, k, w), out_vec);
+    debug_assert!(is_sorted(&out_vec[start..]));
+}
+
+/// The offset of the minimizer within each window, in `0..w`, using SIMD.
+///
+/// Unlike [`minimizer_positions`], this returns one value for every window, relative to the start of the window.
+/// Offsets are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_offsets<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u16>) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizers::minimizer_offsets_seq_simd::<_, NtHasher>(seq, k, w);
+        collect::collect_into_u16(head_padding, out_vec);
+    } else {
+        let head_padding = minimizers::minimizer_offsets_seq_simd::<_, MulHasher>(seq, k, w);
+        collect::collect_into_u16(head_padding, out_vec);
     }
 }
 
 /// Deduplicated positions of all canonical minimizers in the sequence, using SIMD.
 ///
-/// `l=w+k-1` must be odd to determine the strand of each window.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
 ///
 /// Positions are appended to a reusable `out_vec` to avoid allocations.
 pub fn canonical_minimizer_positions<'s, S: Seq<'s>>(
@@ -221,6 +476,228 @@ pub fn canonical_minimizer_positions<'s, S: Seq<'s>>(
         let head_padding = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
         collect_and_dedup_into(head_padding, out_vec);
     }
+    #[cfg(feature = "check-joins")]
+    joins::assert_joins(seq, k, w, true);
+}
+
+/// Deduplicated positions of all forward minimizers of the sequence and of its reverse complement, using SIMD.
+///
+/// These are not canonical minimizers: `rc_vec` receives exactly the output of [`minimizer_positions`] on the reverse complement sequence,
+/// i.e., positions on the reverse complement strand in its own order.
+/// Both are computed in a single pass, using the reverse complement rolling hash that ntHash maintains for canonical hashes.
+/// Only 2-bit DNA is supported.
+///
+/// Positions are appended to reusable `fwd_vec` and `rc_vec` to avoid allocations.
+pub fn minimizer_positions_fwd_rc<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    fwd_vec: &mut Vec<u32>,
+    rc_vec: &mut Vec<u32>,
+) {
+    assert_eq!(
+        S::BITS_PER_CHAR,
+        2,
+        "Reverse complement minimizers require 2-bit DNA."
+    );
+    let head_padding = minimizers_fwd_rc_seq_simd::<_, NtHasher>(seq, k, w);
+    // Both streams are deduplicated in the same pass over the windows.
+    // The reverse complement positions are still forward positions of windows in forward order,
+    // so they are converted before deduplicating against the last position in `rc_vec`.
+    let mut rc_pos = vec![];
+    collect_and_dedup_pair_into(head_padding, fwd_vec, &mut rc_pos);
+    let n = seq.len();
+    rc_pos.reverse();
+    rc_pos
+        .iter_mut()
+        .for_each(|p| *p = (n - k - *p as usize) as u32);
+    let skip = collect::run_len(rc_vec, &rc_pos);
+    rc_vec.extend_from_slice(&rc_pos[skip..]);
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using SIMD, without panicking on invalid parameters.
+///
+/// Returns an [`Error`] when [`check_params`] rejects `k`, `w`, or the sequence length, and leaves `out_vec` unchanged.
+/// Otherwise, this equals [`minimizer_positions`].
+pub fn try_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) -> Result<(), Error> {
+    check_params(seq, k, w)?;
+    minimizer_positions(seq, k, w, out_vec);
+    Ok(())
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using SIMD, without panicking on invalid parameters.
+///
+/// See [`try_minimizer_positions`] and [`canonical_minimizer_positions`].
+pub fn try_canonical_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) -> Result<(), Error> {
+    check_params(seq, k, w)?;
+    canonical_minimizer_positions(seq, k, w, out_vec);
+    Ok(())
+}
+
+/// Start and end coordinates of all minimizer k-mers in the sequence, using SIMD.
+///
+/// These are the k-mers at the positions of [`minimizer_positions`], with coordinates in the given `convention`.
+/// The conversion is done on the SIMD lanes before collection, so it comes at no extra cost.
+///
+/// Coordinates are appended to reusable `start_vec` and `end_vec` to avoid allocations.
+pub fn minimizer_spans<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    convention: CoordinateConvention,
+    start_vec: &mut Vec<u32>,
+    end_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        let spans = coords::spans_simd(head_padding, convention, k);
+        collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+    } else {
+        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        let spans = coords::spans_simd(head_padding, convention, k);
+        collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+    }
+}
+
+/// Start and end coordinates of all canonical minimizer k-mers in the sequence, using SIMD.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_spans`].
+pub fn canonical_minimizer_spans<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    convention: CoordinateConvention,
+    start_vec: &mut Vec<u32>,
+    end_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = canonical_minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        let spans = coords::spans_simd(head_padding, convention, k);
+        collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+    } else {
+        let head_padding = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        let spans = coords::spans_simd(head_padding, convention, k);
+        collect_and_dedup_with_vals_into(spans, start_vec, end_vec);
+    }
+}
+
+/// Set the bits of all minimizer positions of the sequence in `bitvec`, using SIMD.
+///
+/// This is the same set of positions as [`minimizer_positions`], as a bitvector over sequence positions,
+/// e.g. for succinct index construction. The bitvector is grown to the length of the sequence if needed, and existing bits are kept.
+pub fn minimizer_positions_bitvec<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, bitvec: &mut BitVec) {
+    if bitvec.len() < seq.len() {
+        bitvec.resize(seq.len());
+    }
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_into_bitvec(head_padding, bitvec);
+    } else {
+        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_into_bitvec(head_padding, bitvec);
+    }
+}
+
+/// Set the bits of all canonical minimizer positions of the sequence in `bitvec`, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_bitvec`].
+pub fn canonical_minimizer_positions_bitvec<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    bitvec: &mut BitVec,
+) {
+    if bitvec.len() < seq.len() {
+        bitvec.resize(seq.len());
+    }
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = canonical_minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_into_bitvec(head_padding, bitvec);
+    } else {
+        let head_padding = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_into_bitvec(head_padding, bitvec);
+    }
+}
+
+/// Set bit `i` of `bitvec` when window `i` of the sequence is canonical, using SIMD.
+///
+/// Window `i` consists of the `l=w+k-1` characters starting at position `i`, and is canonical when at least half of them are `G` or `T`.
+/// This is the strand that canonical minimizers use to choose between the leftmost and rightmost smallest k-mer.
+/// `bitvec` is resized to the number of windows, and its previous bits are cleared.
+pub fn canonical_windows_bitvec<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, bitvec: &mut BitVec) {
+    bitvec.resize((seq.len() + 1).saturating_sub(k + w - 1));
+    bitvec.clear_bits();
+    if bitvec.is_empty() {
+        return;
+    }
+    collect::collect_mask_into_bitvec(canonical::canonical_windows_seq_simd(seq, k, w), bitvec);
+}
+
+/// The strandedness score `#GT - #AC` of each window of `l` characters in the sequence, using SIMD.
+///
+/// Windows with a score of at least 0 are canonical, as in [`canonical_windows_bitvec`] for `l=k+w-1`.
+/// Scores are appended to a reusable `out_vec` to avoid allocations.
+pub fn window_strandedness<'s, S: Seq<'s>>(seq: S, l: usize, out_vec: &mut Vec<i32>) {
+    assert!(l > 0);
+    if seq.len() < l {
+        return;
+    }
+    let (head, padding) = canonical::strandedness_seq_simd(seq, l);
+    let head = head.map(|x| packed_seq::u32x8::from(x.to_array().map(|x| x as u32)));
+    out_vec.extend(linearize((head, padding)).map(|x| x as i32));
+}
+
+/// Like [`minimizer_positions`], but using the given reusable `buffers` instead of thread-local ones.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_with_buffers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    buffers: &mut CollectBuffers,
+    out_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_into_with(buffers, head_padding, out_vec);
+    } else {
+        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_into_with(buffers, head_padding, out_vec);
+    }
+}
+
+/// Like [`canonical_minimizer_positions`], but using the given reusable `buffers` instead of thread-local ones.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn canonical_minimizer_positions_with_buffers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    buffers: &mut CollectBuffers,
+    out_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = canonical_minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_into_with(buffers, head_padding, out_vec);
+    } else {
+        let head_padding = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_into_with(buffers, head_padding, out_vec);
+    }
 }
 
 /// Deduplicated positions of all minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
@@ -244,7 +721,7 @@ pub fn minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
 
 /// Deduplicated positions of all canonical minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
 ///
-/// `l=w+k-1` must be odd to determine the strand of each window.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
 ///
 /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
 pub fn canonical_minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
@@ -263,91 +740,1764 @@ pub fn canonical_minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
     }
 }
 
-/// Variants that always use mulHash, instead of the default ntHash for DNA and mulHash for text.
-pub mod mul_hash {
-    use super::*;
-
-    /// Deduplicated positions of all minimizers in the sequence, using SIMD.
-    ///
-    /// Positions are appended to a reusable `out_vec` to avoid allocations.
-    pub fn minimizer_positions<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u32>) {
-        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
-        collect_and_dedup_into(head_padding, out_vec);
+/// Deduplicated positions of all minimizers in the sequence with the _last_ window of the corresponding super-k-mers, using SIMD.
+///
+/// Like [`minimizer_and_superkmer_positions`], but `sk_end_vec` contains the index of the last window in which each minimizer is minimal,
+/// so that the super-k-mer of `min_pos_vec[i]` covers windows `sk_pos..=sk_end_vec[i]`.
+///
+/// Positions are appended to reusable `min_pos_vec` and `sk_end_vec` to avoid allocations.
+pub fn minimizer_and_superkmer_end_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    sk_end_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_tail = minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_with_last_index_into(head_tail, min_pos_vec, sk_end_vec);
+    } else {
+        let head_tail = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_last_index_into(head_tail, min_pos_vec, sk_end_vec);
     }
+}
 
-    /// Deduplicated positions of all canonical minimizers in the sequence, using SIMD.
-    ///
-    /// `l=w+k-1` must be odd to determine the strand of each window.
-    ///
-    /// Positions are appended to a reusable `out_vec` to avoid allocations.
-    pub fn canonical_minimizer_positions<'s, S: Seq<'s>>(
-        seq: S,
-        k: usize,
-        w: usize,
-        out_vec: &mut Vec<u32>,
-    ) {
+/// Deduplicated positions of all canonical minimizers in the sequence with the _last_ window of the corresponding super-k-mers, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_and_superkmer_end_positions`].
+pub fn canonical_minimizer_and_superkmer_end_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    sk_end_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_tail = canonical_minimizers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_with_last_index_into(head_tail, min_pos_vec, sk_end_vec);
+    } else {
         let head_tail = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
-        collect_and_dedup_into(head_tail, out_vec);
+        collect_and_dedup_with_last_index_into(head_tail, min_pos_vec, sk_end_vec);
     }
+}
 
-    /// Deduplicated positions of all minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
-    ///
-    /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
-    pub fn minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
-        seq: S,
-        k: usize,
-        w: usize,
-        min_pos_vec: &mut Vec<u32>,
-        sk_pos_vec: &mut Vec<u32>,
-    ) {
-        let head_tail = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
-        collect_and_dedup_with_index_into(head_tail, min_pos_vec, sk_pos_vec);
-    }
+/// Like [`minimizer_positions`], but appends typed [`Pos`] positions to `out_vec`.
+pub fn minimizer_positions_typed<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<Pos>,
+) {
+    pos::with_raw_vec(out_vec, |out_vec| minimizer_positions(seq, k, w, out_vec));
+}
 
-    /// Deduplicated positions of all canonical minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
-    ///
-    /// `l=w+k-1` must be odd to determine the strand of each window.
-    ///
-    /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
-    pub fn canonical_minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
-        seq: S,
-        k: usize,
-        w: usize,
-        min_pos_vec: &mut Vec<u32>,
-        sk_pos_vec: &mut Vec<u32>,
-    ) {
-        let head_tail = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
-        collect_and_dedup_with_index_into(head_tail, min_pos_vec, sk_pos_vec);
-    }
+/// Like [`canonical_minimizer_positions`], but appends typed [`Pos`] positions to `out_vec`.
+pub fn canonical_minimizer_positions_typed<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<Pos>,
+) {
+    pos::with_raw_vec(out_vec, |out_vec| {
+        canonical_minimizer_positions(seq, k, w, out_vec)
+    });
 }
 
-/// Scalar variants that are nearly always slower.
-///
-/// Can be used for testing and debugging.
-pub mod scalar {
-    use super::*;
+/// Like [`minimizer_and_superkmer_positions`], but appends each minimizer position and super-k-mer start as a [`PackedSuperPos`] to `out_vec`.
+pub fn minimizer_and_superkmer_positions_packed<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<PackedSuperPos>,
+) {
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    minimizer_and_superkmer_positions(seq, k, w, &mut min_pos_vec, &mut sk_pos_vec);
+    append_packed_super_pos(&min_pos_vec, &sk_pos_vec, out_vec);
+}
+
+/// Like [`canonical_minimizer_and_superkmer_positions`], but appends each minimizer position and super-k-mer start as a [`PackedSuperPos`] to `out_vec`.
+pub fn canonical_minimizer_and_superkmer_positions_packed<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<PackedSuperPos>,
+) {
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    canonical_minimizer_and_superkmer_positions(seq, k, w, &mut min_pos_vec, &mut sk_pos_vec);
+    append_packed_super_pos(&min_pos_vec, &sk_pos_vec, out_vec);
+}
+
+/// Pack and append the output of the `_and_superkmer` functions to `out_vec`,
+/// skipping a leading minimizer that equals the last one of `out_vec`, as the raw functions do.
+fn append_packed_super_pos(
+    min_pos_vec: &[u32],
+    sk_pos_vec: &[u32],
+    out_vec: &mut Vec<PackedSuperPos>,
+) {
+    let last = out_vec.last().map(|p| p.min_pos());
+    out_vec.extend(
+        PackedSuperPos::pack(min_pos_vec, sk_pos_vec).skip_while(|p| Some(p.min_pos()) == last),
+    );
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using 64-bit ntHash and SIMD.
+///
+/// The default functions compare only the upper 16 bits of a 32-bit hash, which gives many ties for large `k` on large genomes.
+/// This version compares the upper 32 bits of a 64-bit hash instead, and is somewhat slower.
+/// Only 2-bit DNA is supported.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_hash64<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    let head_padding = minimizers64_seq_simd(seq, k, w);
+    collect_and_dedup_into(head_padding, out_vec);
+    debug_assert!(is_sorted(&out_vec[start..]));
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using 64-bit ntHash and SIMD.
+///
+/// Like [`minimizer_positions_hash64`], the upper 32 bits of the canonical 64-bit ntHash are compared.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+/// Only 2-bit DNA is supported.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn canonical_minimizer_positions_hash64<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let head_padding = canonical_minimizers64_seq_simd(seq, k, w);
+    collect_and_dedup_into(head_padding, out_vec);
+}
+
+/// Deduplicated positions of all minimizers of long k-mers, up to `k <= MAX_K`, comparing full 128-bit ntHashes using SIMD.
+///
+/// The rolling hash is kept in two 64-bit words per lane, so that only characters 128 positions apart share a rotation,
+/// and for `k > 128` k-mers that agree after folding modulo 128 collide.
+/// Other distinct k-mers tie with probability about `2^-128`, and ties are broken towards the leftmost position.
+/// The output equals [`scalar::minimizer_positions_long_k_scalar`].
+/// Only 2-bit DNA is supported.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_long_k<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    let head_padding = long_k::minimizers128_seq_simd(seq, k, w);
+    collect_and_dedup_into(head_padding, out_vec);
+    debug_assert!(is_sorted(&out_vec[start..]));
+}
+
+/// Deduplicated positions of all spaced-seed minimizers in the sequence, using SIMD.
+///
+/// The k-mers have length `k = mask.len()`, and only their characters at positions `i` with `mask[i]` (care positions) are hashed,
+/// so that k-mers that differ only at don't-care positions have the same hash.
+/// When all positions are care positions, this equals [`minimizer_positions`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn spaced_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    mask: &[bool],
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = spaced_minimizers_seq_simd::<_, NtHasher>(seq, mask, w);
+        collect_and_dedup_into(head_padding, out_vec);
+    } else {
+        let head_padding = spaced_minimizers_seq_simd::<_, MulHasher>(seq, mask, w);
+        collect_and_dedup_into(head_padding, out_vec);
+    }
+    debug_assert!(is_sorted(&out_vec[start..]));
+}
+
+/// Deduplicated positions of all mod-minimizers in the sequence, using SIMD.
+///
+/// For each window, the position `x` of the smallest `t`-mer is found, and the k-mer at position `x mod w` in the window is sampled.
+/// For low density, use `t = r + ((k-r) mod w)` for some small `r` like 4.
+/// Positions are only sorted when `t` is congruent to `k` modulo `w`, as for this choice.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn mod_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    t: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = mod_minimizers::mod_minimizers_seq_simd::<_, NtHasher>(seq, k, w, t);
+        collect_and_dedup_into(head_padding, out_vec);
+    } else {
+        let head_padding = mod_minimizers::mod_minimizers_seq_simd::<_, MulHasher>(seq, k, w, t);
+        collect_and_dedup_into(head_padding, out_vec);
+    }
+    debug_assert!(t % w != k % w || is_sorted(&out_vec[start..]));
+}
+
+/// Deduplicated positions of all minimizers in the sequence, where the weight of each k-mer is added to its hash, using SIMD.
+///
+/// `weights[i]` is the weight of the k-mer starting at position `i`, e.g. derived from its mappability or base qualities,
+/// and there must be at least one weight per k-mer.
+/// The weight is added (saturating) to the upper 16 bits of the hash that are compared,
+/// so that k-mers with a higher weight are less likely to be selected.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn weighted_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    weights: &'s [u16],
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding =
+            weighted::weighted_minimizers_seq_simd::<_, NtHasher>(seq, weights, k, w);
+        collect_and_dedup_into(head_padding, out_vec);
+    } else {
+        let head_padding =
+            weighted::weighted_minimizers_seq_simd::<_, MulHasher>(seq, weights, k, w);
+        collect_and_dedup_into(head_padding, out_vec);
+    }
+    debug_assert!(is_sorted(&out_vec[start..]));
+}
+
+/// Deduplicated positions of all minimizers of each record, using SIMD.
+///
+/// Instead of splitting each record into 8 chunks, each SIMD lane processes a different record.
+/// This is faster than calling [`minimizer_positions`] on each record when records are short.
+///
+/// `out_vecs` is resized to `seqs.len()` if needed, and the positions of record `i` are appended to `out_vecs[i]`.
+pub fn minimizer_positions_records<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    out_vecs: &mut Vec<Vec<u32>>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_simd::<false, _, NtHasher>(seqs, k, w, out_vecs);
+    } else {
+        records::minimizers_records_simd::<false, _, MulHasher>(seqs, k, w, out_vecs);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers of each record, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_records`].
+pub fn canonical_minimizer_positions_records<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    out_vecs: &mut Vec<Vec<u32>>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_simd::<true, _, NtHasher>(seqs, k, w, out_vecs);
+    } else {
+        records::minimizers_records_simd::<true, _, MulHasher>(seqs, k, w, out_vecs);
+    }
+}
+
+/// Deduplicated positions of all minimizers of each record in a flat, compressed sparse row (CSR) layout, using SIMD.
+///
+/// The positions of all records are appended to `positions`, and the end of the positions of each record is appended to `offsets`,
+/// so that record `i` of the first call has positions `positions[offsets[i]..offsets[i+1]]`.
+/// When `offsets` is empty, the current length of `positions` is pushed first.
+/// Subsequent calls with the same vectors append the next batch of records to the same layout.
+///
+/// Records are processed as in [`minimizer_positions_records`], but without allocating a vector per record.
+pub fn minimizer_positions_records_csr<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    positions: &mut Vec<u32>,
+    offsets: &mut Vec<usize>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_csr_simd::<false, _, NtHasher>(seqs, k, w, positions, offsets);
+    } else {
+        records::minimizers_records_csr_simd::<false, _, MulHasher>(seqs, k, w, positions, offsets);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers of each record in a flat, compressed sparse row (CSR) layout, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_records_csr`].
+pub fn canonical_minimizer_positions_records_csr<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    positions: &mut Vec<u32>,
+    offsets: &mut Vec<usize>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_csr_simd::<true, _, NtHasher>(seqs, k, w, positions, offsets);
+    } else {
+        records::minimizers_records_csr_simd::<true, _, MulHasher>(seqs, k, w, positions, offsets);
+    }
+}
+
+/// Deduplicated positions of all minimizers of each record, with separate parameters `params[i] = (k, w)` for record `i`, using SIMD.
+///
+/// Records with the same parameters are grouped internally, and each group is processed as in [`minimizer_positions_records`].
+///
+/// `out_vecs` is resized to `seqs.len()` if needed, and the positions of record `i` are appended to `out_vecs[i]`.
+pub fn minimizer_positions_records_with_params<'s, S: Seq<'s>>(
+    seqs: &[S],
+    params: &[(usize, usize)],
+    out_vecs: &mut Vec<Vec<u32>>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_with_params_simd::<false, _, NtHasher>(seqs, params, out_vecs);
+    } else {
+        records::minimizers_records_with_params_simd::<false, _, MulHasher>(seqs, params, out_vecs);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers of each record, with separate parameters `params[i] = (k, w)` for record `i`, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_records_with_params`].
+pub fn canonical_minimizer_positions_records_with_params<'s, S: Seq<'s>>(
+    seqs: &[S],
+    params: &[(usize, usize)],
+    out_vecs: &mut Vec<Vec<u32>>,
+) where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    if S::BITS_PER_CHAR == 2 {
+        records::minimizers_records_with_params_simd::<true, _, NtHasher>(seqs, params, out_vecs);
+    } else {
+        records::minimizers_records_with_params_simd::<true, _, MulHasher>(seqs, params, out_vecs);
+    }
+}
+
+/// Lazily compute the deduplicated positions of all minimizers of each `(id, record)`, using SIMD.
+///
+/// Records are only pulled from `records` when the consumer asks for more results, in batches of about a million bases,
+/// so that streaming pipelines can write out the positions of each record without holding all outputs in memory.
+/// Within a batch, short records are processed as in [`minimizer_positions_records`].
+/// `id` can be any identifier of the record, such as its name or index, and is returned with its positions.
+pub fn minimizer_positions_records_iter<Id, V: SeqVec>(
+    records: impl IntoIterator<Item = (Id, V)>,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = (Id, Vec<u32>)>
+where
+    for<'s> V::Seq<'s>: Seq<'s, SeqVec = V>,
+{
+    records::records_iter::<false, _, _>(records, k, w)
+}
+
+/// Lazily compute the deduplicated positions of all canonical minimizers of each `(id, record)`, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_records_iter`].
+pub fn canonical_minimizer_positions_records_iter<Id, V: SeqVec>(
+    records: impl IntoIterator<Item = (Id, V)>,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = (Id, Vec<u32>)>
+where
+    for<'s> V::Seq<'s>: Seq<'s, SeqVec = V>,
+{
+    records::records_iter::<true, _, _>(records, k, w)
+}
+
+/// Deduplicated positions of minimizers in the sequence, up to and including the first occurrence of the `m`'th distinct minimizer, using SIMD.
+///
+/// Two minimizers are distinct when their k-mers differ.
+/// The sequence is processed in blocks of increasing length, and extraction stops once `m` distinct minimizers have been found,
+/// e.g. when a record has enough anchors for classification.
+/// The output is a prefix of the output of [`minimizer_positions`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn first_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    m: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    cancel::first_distinct_positions_in_blocks(
+        seq,
+        k + w - 1,
+        first_block_len(k, w, m),
+        m,
+        out_vec,
+        |pos| minimizer_kmer_chars::<false, _>(seq, k, pos),
+        |block, out_vec| minimizer_positions(block, k, w, out_vec),
+    )
+}
+
+/// Deduplicated positions of canonical minimizers in the sequence, up to and including the first occurrence of the `m`'th distinct minimizer, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+/// For 2-bit DNA, a k-mer and its reverse complement are the same minimizer.
+///
+/// See [`first_minimizer_positions`].
+/// The output is a prefix of the output of [`canonical_minimizer_positions`].
+pub fn first_canonical_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    m: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    cancel::first_distinct_positions_in_blocks(
+        seq,
+        k + w - 1,
+        first_block_len(k, w, m),
+        m,
+        out_vec,
+        |pos| minimizer_kmer_chars::<true, _>(seq, k, pos),
+        |block, out_vec| canonical_minimizer_positions(block, k, w, out_vec),
+    )
+}
+
+/// The characters of the k-mer at `pos`, or for canonical 2-bit DNA, the smaller of those of the k-mer and its reverse complement.
+fn minimizer_kmer_chars<'s, const CANONICAL: bool, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    pos: u32,
+) -> Vec<u8> {
+    let pos = pos as usize;
+    let fwd = seq.slice(pos..pos + k).iter_bp().collect::<Vec<u8>>();
+    if CANONICAL && S::BITS_PER_CHAR == 2 {
+        let rc = fwd
+            .iter()
+            .rev()
+            .map(|&c| packed_seq::complement_base(c))
+            .collect();
+        fwd.min(rc)
+    } else {
+        fwd
+    }
+}
+
+/// Length of a block that is expected to contain `m` random minimizers, which have density `2/(w+1)`.
+fn first_block_len(k: usize, w: usize, m: usize) -> usize {
+    k + w - 2 + (m * (w + 1)).div_ceil(2)
+}
+
+/// Deduplicated `u64` positions of all minimizers in the sequence, using SIMD.
+///
+/// Use this for sequences or concatenations longer than `u32::MAX` characters.
+/// The sequence is processed in blocks of `2^31` characters, so that the output is the same as for [`minimizer_positions`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_u64<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u64>) {
+    pos64::positions_in_blocks_u64(
+        seq,
+        k + w - 1,
+        pos64::MAX_BLOCK_LEN,
+        out_vec,
+        |block, offset, out_vec| {
+            if S::BITS_PER_CHAR == 2 {
+                let head_padding = minimizers_seq_simd::<_, NtHasher>(block, k, w);
+                collect_and_dedup_into_u64(head_padding, offset, out_vec);
+            } else {
+                let head_padding = minimizers_seq_simd::<_, MulHasher>(block, k, w);
+                collect_and_dedup_into_u64(head_padding, offset, out_vec);
+            }
+        },
+    );
+}
+
+/// Deduplicated `u64` positions of all canonical minimizers in the sequence, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_u64`].
+pub fn canonical_minimizer_positions_u64<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u64>,
+) {
+    pos64::positions_in_blocks_u64(
+        seq,
+        k + w - 1,
+        pos64::MAX_BLOCK_LEN,
+        out_vec,
+        |block, offset, out_vec| {
+            if S::BITS_PER_CHAR == 2 {
+                let head_padding = canonical_minimizers_seq_simd::<_, NtHasher>(block, k, w);
+                collect_and_dedup_into_u64(head_padding, offset, out_vec);
+            } else {
+                let head_padding = canonical_minimizers_seq_simd::<_, MulHasher>(block, k, w);
+                collect_and_dedup_into_u64(head_padding, offset, out_vec);
+            }
+        },
+    );
+}
+
+/// Positions of the leftmost minimum of each window of `w` consecutive `values`, using SIMD.
+///
+/// This runs the same two-stack sliding window minimum as the minimizer pipeline on arbitrary data,
+/// such as quality scores or coverage tracks. Unlike for k-mer hashes, all 32 bits of each value are compared.
+/// One position is appended to `out_vec` for each window, and `values[pos]` is the minimum of the window.
+pub fn sliding_window_min_simd(values: &[u32], w: usize, out_vec: &mut Vec<u32>) {
+    collect::collect_into(sliding_min::sliding_window_min_seq_simd(values, w), out_vec);
+}
+
+/// Iterator adapter over the positions of the leftmost minimum of each window of `w` consecutive values.
+///
+/// This scalar version works on streams that are not in memory.
+/// See [`sliding_window_min_simd`].
+pub fn sliding_window_min(
+    values: impl ExactSizeIterator<Item = u32>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> {
+    sliding_min::sliding_min64_scalar(values.map(|x| (x as u64) << 32), w)
+}
+
+/// Iterate over all windows of length `l` of `seq`, in the chunked order used by the SIMD pipeline.
+///
+/// The windows are split into 8 chunks of consecutive windows, one per SIMD lane,
+/// and each item contains the start and a view of the next window of each chunk.
+/// The last chunk is padded with empty windows that start at `seq.len()`; the number of such padding lanes is returned as well.
+///
+/// Use [`map_windows_simd`] to compute one value per window and collect the values in order.
+pub fn par_windows<'s, S: Seq<'s>>(
+    seq: S,
+    l: usize,
+) -> (
+    impl ExactSizeIterator<Item = (packed_seq::u32x8, [S; 8])>,
+    usize,
+) {
+    windows::par_windows_seq(seq, l)
+}
+
+/// Compute one `u32` value per window of length `l` of `seq` using `f`, and append the values to `out_vec` in order of the windows.
+///
+/// `f` is called with the starts and views of the windows in the 8 SIMD lanes, as for [`par_windows`],
+/// and returns the value of each lane. Values of padding lanes are discarded.
+pub fn map_windows_simd<'s, S: Seq<'s>>(
+    seq: S,
+    l: usize,
+    mut f: impl FnMut(packed_seq::u32x8, [S; 8]) -> packed_seq::u32x8,
+    out_vec: &mut Vec<u32>,
+) {
+    let (it, padding) = windows::par_windows_seq(seq, l);
+    collect::collect_into(
+        (it.map(|(starts, views)| f(starts, views)), padding),
+        out_vec,
+    );
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using SIMD, with cancellation and progress reporting.
+///
+/// The sequence is processed in blocks of a few million characters.
+/// Before each block `cancel` is checked, and after each block `progress` is called with the number of characters processed so far.
+/// The output is the same as for [`minimizer_positions`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+/// When cancelled, `out_vec` contains the positions in the part of the sequence that was processed.
+pub fn minimizer_positions_cancellable<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+    cancel: &AtomicBool,
+    progress: impl FnMut(usize),
+) -> Result<(), Cancelled> {
+    cancel::positions_in_blocks(
+        seq,
+        k + w - 1,
+        cancel::DEFAULT_BLOCK_LEN,
+        out_vec,
+        cancel,
+        progress,
+        |block, out_vec| minimizer_positions(block, k, w, out_vec),
+    )
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using SIMD, with cancellation and progress reporting.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_cancellable`].
+pub fn canonical_minimizer_positions_cancellable<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+    cancel: &AtomicBool,
+    progress: impl FnMut(usize),
+) -> Result<(), Cancelled> {
+    cancel::positions_in_blocks(
+        seq,
+        k + w - 1,
+        cancel::DEFAULT_BLOCK_LEN,
+        out_vec,
+        cancel,
+        progress,
+        |block, out_vec| canonical_minimizer_positions(block, k, w, out_vec),
+    )
+}
+
+/// The number of bytes of sequence data in each tile of [`minimizer_positions_cache_blocked`], about the size of an L2 cache.
+pub const CACHE_BLOCK_BYTES: usize = 1 << 19;
+
+/// The number of characters in [`CACHE_BLOCK_BYTES`] of data of type `S`.
+fn cache_block_len<'s, S: Seq<'s>>() -> usize {
+    CACHE_BLOCK_BYTES * 8 / S::BITS_PER_CHAR
+}
+
+/// The number of bytes prefetched at the start of each lane of the next tile.
+const TILE_PREFETCH_BYTES: usize = 512;
+
+/// Prefetch the start of each of the 8 lanes of `tile`.
+///
+/// The hardware prefetcher follows each lane once it is streaming, but the first accesses of the 8 distant lanes
+/// of a new tile would otherwise all miss.
+fn prefetch_lane_starts<'s, S: PrefetchSeq<'s>>(tile: S) {
+    let lane_len = tile.len().div_ceil(L);
+    for lane in 0..L {
+        tile.prefetch(lane * lane_len, TILE_PREFETCH_BYTES);
+    }
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using SIMD, processing long sequences in cache-sized tiles.
+///
+/// [`minimizer_positions`] splits the entire sequence into 8 chunks, so that for sequences of hundreds of megabases,
+/// the 8 lanes stream from distant memory regions and the pipeline becomes memory bound.
+/// Here, sequences longer than [`CACHE_BLOCK_BYTES`] of data are instead processed in overlapping tiles of that size,
+/// so that the 8 lanes of each tile read from a region that fits in L2.
+/// The start of each lane of the next tile is prefetched before the current tile is processed.
+/// Shorter sequences are processed directly. The output is the same as for [`minimizer_positions`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_cache_blocked<'s, S: PrefetchSeq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let block_len = cache_block_len::<S>();
+    if seq.len() <= block_len {
+        return minimizer_positions(seq, k, w, out_vec);
+    }
+    let never = AtomicBool::new(false);
+    let _ = cancel::positions_in_blocks_with_next(
+        seq,
+        k + w - 1,
+        block_len,
+        out_vec,
+        &never,
+        |_| {},
+        prefetch_lane_starts,
+        |block, out_vec| minimizer_positions(block, k, w, out_vec),
+    );
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using SIMD, processing long sequences in cache-sized tiles.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_cache_blocked`].
+pub fn canonical_minimizer_positions_cache_blocked<'s, S: PrefetchSeq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    let block_len = cache_block_len::<S>();
+    if seq.len() <= block_len {
+        return canonical_minimizer_positions(seq, k, w, out_vec);
+    }
+    let never = AtomicBool::new(false);
+    let _ = cancel::positions_in_blocks_with_next(
+        seq,
+        k + w - 1,
+        block_len,
+        out_vec,
+        &never,
+        |_| {},
+        prefetch_lane_starts,
+        |block, out_vec| canonical_minimizer_positions(block, k, w, out_vec),
+    );
+}
+
+/// Deduplicated positions of all minimizers of windows that only contain valid characters, using SIMD.
+///
+/// `valid[i]` tells whether character `i` is valid, and must have the same length as the sequence.
+/// Windows containing an invalid character, e.g. an `N`, are skipped. Use [`acgt_mask`] to build the mask from ASCII input.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_masked<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    valid: &[bool],
+    out_vec: &mut Vec<u32>,
+) {
+    masked::positions_in_valid_runs(seq, k + w - 1, valid, out_vec, |run, out_vec| {
+        minimizer_positions(run, k, w, out_vec)
+    });
+}
+
+/// Deduplicated positions of all canonical minimizers of windows that only contain valid characters, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_masked`].
+pub fn canonical_minimizer_positions_masked<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    valid: &[bool],
+    out_vec: &mut Vec<u32>,
+) {
+    masked::positions_in_valid_runs(seq, k + w - 1, valid, out_vec, |run, out_vec| {
+        canonical_minimizer_positions(run, k, w, out_vec)
+    });
+}
+
+/// Deduplicated positions of all minimizers of windows without `N`s in a stream of 2-bit packed blocks, using SIMD.
+///
+/// The blocks are not decoded to ASCII: runs of valid bases are copied in packed form and processed directly.
+/// Positions are relative to the start of the first block. See the [`packed_blocks`] module.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_packed_blocks<'a>(
+    blocks: impl IntoIterator<Item = packed_blocks::PackedBlock<'a>>,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    packed_blocks::positions_in_packed_blocks(
+        blocks,
+        k + w - 1,
+        cancel::DEFAULT_BLOCK_LEN,
+        out_vec,
+        |seq, out_vec| minimizer_positions(seq, k, w, out_vec),
+    );
+}
+
+/// Deduplicated positions of all canonical minimizers of windows without `N`s in a stream of 2-bit packed blocks, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_packed_blocks`].
+pub fn canonical_minimizer_positions_packed_blocks<'a>(
+    blocks: impl IntoIterator<Item = packed_blocks::PackedBlock<'a>>,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    packed_blocks::positions_in_packed_blocks(
+        blocks,
+        k + w - 1,
+        cancel::DEFAULT_BLOCK_LEN,
+        out_vec,
+        |seq, out_vec| canonical_minimizer_positions(seq, k, w, out_vec),
+    );
+}
+
+/// Count the selected canonical minimizer positions of the sequence, and how many of them are contained in a filter, using SIMD.
+///
+/// For each position returned by [`canonical_minimizer_positions`], the [`canonical_kmer_hash`] of its k-mer is passed to `contains` exactly once, e.g. a Bloom filter query.
+/// Positions are not collected, which makes this faster than calling [`canonical_minimizer_positions`] and querying afterwards.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+pub fn canonical_minimizer_hits<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    contains: impl FnMut(u32) -> bool,
+) -> ScreenCounts {
+    if S::BITS_PER_CHAR == 2 {
+        screen::canonical_minimizer_hits_simd::<_, NtHasher>(seq, k, w, contains)
+    } else {
+        screen::canonical_minimizer_hits_simd::<_, MulHasher>(seq, k, w, contains)
+    }
+}
+
+/// [`canonical_minimizer_hits`] for each record, appended to `out_vec`.
+pub fn canonical_minimizer_hits_records<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    mut contains: impl FnMut(u32) -> bool,
+    out_vec: &mut Vec<ScreenCounts>,
+) {
+    out_vec.extend(
+        seqs.iter()
+            .map(|&seq| canonical_minimizer_hits(seq, k, w, &mut contains)),
+    );
+}
+
+/// The canonical hash of a single k-mer, which is the same on both strands.
+///
+/// This is the value passed to the filter by [`canonical_minimizer_hits`],
+/// and can be used to build the filter from reference minimizers.
+pub fn canonical_kmer_hash<'s, S: Seq<'s>>(kmer: S) -> u32 {
+    if S::BITS_PER_CHAR == 2 {
+        screen::canonical_kmer_hash::<NtHasher>(kmer)
+    } else {
+        screen::canonical_kmer_hash::<MulHasher>(kmer)
+    }
+}
+
+/// Positions of all syncmers in the sequence, using SIMD.
+///
+/// A k-mer is a syncmer when the offset of its smallest s-mer, i.e., the leftmost one with the smallest hash, is one of `offsets`.
+/// Each offset must be at most `k-s`.
+/// Unlike minimizers, this does not depend on a window size.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn syncmer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    s: usize,
+    offsets: &[usize],
+    out_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        syncmers::syncmers_seq_simd::<_, NtHasher>(seq, k, s, offsets, out_vec);
+    } else {
+        syncmers::syncmers_seq_simd::<_, MulHasher>(seq, k, s, offsets, out_vec);
+    }
+}
+
+/// Positions of all open syncmers in the sequence, using SIMD: the k-mers whose smallest s-mer is at offset `t`.
+///
+/// See [`syncmer_positions`].
+pub fn open_syncmer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    s: usize,
+    t: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    syncmer_positions(seq, k, s, &[t], out_vec);
+}
+
+/// Positions of all closed syncmers in the sequence, using SIMD: the k-mers whose smallest s-mer is their first or last one.
+///
+/// See [`syncmer_positions`].
+pub fn closed_syncmer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    s: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    assert!(0 < s && s <= k, "Syncmers require 0 < s <= k.");
+    syncmer_positions(seq, k, s, &[0, k - s], out_vec);
+}
+
+/// All randstrobes of order 2 in the sequence, using SIMD, as used as seeds by strobealign.
+///
+/// Each k-mer is linked to the k-mer in the window `w_min..=w_max` positions after it whose hash xor its own hash is smallest.
+/// Only k-mers whose full window is contained in the sequence start a randstrobe.
+/// See [`Strobemer`] for the output, and `private::strobemers` for details.
+///
+/// Strobemers are appended to a reusable `out_vec` in order of their first strobe.
+pub fn randstrobes<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+    out_vec: &mut Vec<Strobemer>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        strobemers::strobemers_into::<true, _, NtHasher>(seq, k, w_min, w_max, out_vec);
+    } else {
+        strobemers::strobemers_into::<true, _, MulHasher>(seq, k, w_min, w_max, out_vec);
+    }
+}
+
+/// All minstrobes of order 2 in the sequence, using SIMD.
+///
+/// Each k-mer is linked to the k-mer with the smallest hash in the window `w_min..=w_max` positions after it.
+///
+/// See [`randstrobes`].
+pub fn minstrobes<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+    out_vec: &mut Vec<Strobemer>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        strobemers::strobemers_into::<false, _, NtHasher>(seq, k, w_min, w_max, out_vec);
+    } else {
+        strobemers::strobemers_into::<false, _, MulHasher>(seq, k, w_min, w_max, out_vec);
+    }
+}
+
+/// The windows whose minimizers differ between two aligned sequences of equal length, using SIMD,
+/// such as the two haplotypes of a diploid genome.
+///
+/// Both sequences are processed in lockstep. A window is reported when its minimizer positions differ,
+/// or when the minimizer k-mers at the same position have different hashes.
+/// See [`MinimizerMismatch`] for the output, and `private::dual` for details.
+///
+/// Panics when the sequences have different lengths.
+/// Windows are appended to a reusable `out_vec` in increasing order.
+pub fn minimizer_mismatches<'s, S: Seq<'s>>(
+    seq_a: S,
+    seq_b: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<MinimizerMismatch>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        dual::dual_minimizers_into::<_, NtHasher>(seq_a, seq_b, k, w, out_vec);
+    } else {
+        dual::dual_minimizers_into::<_, MulHasher>(seq_a, seq_b, k, w, out_vec);
+    }
+}
+
+/// The 32-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range, using SIMD.
+///
+/// This is a FracMinHash sketch: the retained k-mers do not depend on their context, so that sketches of different sequences can be compared directly.
+/// `fraction` must be in `(0, 1]`. See `private::fracminhash` for details.
+///
+/// Hashes are appended to a reusable `out_vec` in order of their k-mer. They are not sorted or deduplicated.
+pub fn fracminhash<'s, S: Seq<'s>>(seq: S, k: usize, fraction: f64, out_vec: &mut Vec<u32>) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding =
+            fracminhash::fracminhash_seq_simd::<false, _, NtHasher>(seq, k, fraction);
+        collect::collect_filtered_into(head_padding, out_vec);
+    } else {
+        let head_padding =
+            fracminhash::fracminhash_seq_simd::<false, _, MulHasher>(seq, k, fraction);
+        collect::collect_filtered_into(head_padding, out_vec);
+    }
+}
+
+/// The canonical 32-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range, using SIMD.
+///
+/// Both strands of a k-mer have the same hash. See [`fracminhash`].
+pub fn canonical_fracminhash<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    fraction: f64,
+    out_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = fracminhash::fracminhash_seq_simd::<true, _, NtHasher>(seq, k, fraction);
+        collect::collect_filtered_into(head_padding, out_vec);
+    } else {
+        let head_padding =
+            fracminhash::fracminhash_seq_simd::<true, _, MulHasher>(seq, k, fraction);
+        collect::collect_filtered_into(head_padding, out_vec);
+    }
+}
+
+/// The 64-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range, using SIMD.
+///
+/// Only 2-bit DNA is supported. See [`fracminhash`].
+pub fn fracminhash64<'s, S: Seq<'s>>(seq: S, k: usize, fraction: f64, out_vec: &mut Vec<u64>) {
+    let head_padding = fracminhash::fracminhash64_seq_simd::<false, _>(seq, k, fraction);
+    collect::collect_filtered_into(head_padding, out_vec);
+}
+
+/// The canonical 64-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range, using SIMD.
+///
+/// Only 2-bit DNA is supported. See [`fracminhash`].
+pub fn canonical_fracminhash64<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    fraction: f64,
+    out_vec: &mut Vec<u64>,
+) {
+    let head_padding = fracminhash::fracminhash64_seq_simd::<true, _>(seq, k, fraction);
+    collect::collect_filtered_into(head_padding, out_vec);
+}
+
+/// Deduplicated positions of all minimizers in the sequence, using SIMD on multiple threads.
+///
+/// The sequence is split into chunks that overlap by `k+w-2` characters, which are processed in parallel
+/// on `num_threads` threads, and the results are stitched together.
+/// The output is the same as for [`minimizer_positions`].
+/// When `num_threads` is 0, or equals the size of the current rayon thread pool, that pool is used.
+/// Otherwise, a thread pool is built for each call, so prefer calling this inside [`rayon::ThreadPool::install`] with `num_threads = 0` in a loop.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+#[cfg(feature = "rayon")]
+pub fn minimizers_par_chunks<'s, S: Seq<'s> + Send + Sync>(
+    seq: S,
+    k: usize,
+    w: usize,
+    num_threads: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    par::positions_par(seq, k + w - 1, num_threads, out_vec, |chunk, out_vec| {
+        minimizer_positions(chunk, k, w, out_vec)
+    });
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, using SIMD on multiple threads.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizers_par_chunks`].
+#[cfg(feature = "rayon")]
+pub fn canonical_minimizers_par_chunks<'s, S: Seq<'s> + Send + Sync>(
+    seq: S,
+    k: usize,
+    w: usize,
+    num_threads: usize,
+    out_vec: &mut Vec<u32>,
+) {
+    par::positions_par(seq, k + w - 1, num_threads, out_vec, |chunk, out_vec| {
+        canonical_minimizer_positions(chunk, k, w, out_vec)
+    });
+}
+
+/// All super-k-mers of the sequence as `(start, end, minimizer_pos)` triples, using SIMD.
+///
+/// A super-k-mer is a maximal run of consecutive windows that share the same minimizer.
+/// `start..end` is the half-open range of characters covered by these windows,
+/// from the start of the first window to the end of the last window.
+///
+/// Super-k-mers are appended to a reusable `out_vec` to avoid allocations.
+pub fn super_kmers<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<(u32, u32, u32)>) {
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    minimizer_and_superkmer_positions(seq, k, w, &mut min_pos_vec, &mut sk_pos_vec);
+    super_kmer_ranges(seq.len(), k, w, &min_pos_vec, &sk_pos_vec, out_vec);
+}
+
+/// All canonical super-k-mers of the sequence as `(start, end, minimizer_pos)` triples, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`super_kmers`].
+pub fn canonical_super_kmers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<(u32, u32, u32)>,
+) {
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    canonical_minimizer_and_superkmer_positions(seq, k, w, &mut min_pos_vec, &mut sk_pos_vec);
+    super_kmer_ranges(seq.len(), k, w, &min_pos_vec, &sk_pos_vec, out_vec);
+}
+
+/// Partition the super-k-mers of the sequence over `num_buckets` buckets by their minimizer, using SIMD.
+///
+/// Each super-k-mer goes to the [`minimizer_bucket`] of its minimizer k-mer, and is appended to the concatenation of that bucket.
+/// Consecutive super-k-mers overlap by `l-1 = k+w-2` characters, which are thus repeated when they go to different buckets.
+/// This is the partitioning step of disk-based k-mer counters such as KMC and Gerbil, where `l` is the k-mer length to be counted.
+pub fn partition_by_minimizer<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    num_buckets: usize,
+) -> Partition<S::SeqVec>
+where
+    S::SeqVec: SeqVec<Seq<'s> = S>,
+{
+    assert!(num_buckets > 0);
+    let mut partition = Partition {
+        seqs: (0..num_buckets).map(|_| S::SeqVec::default()).collect(),
+        offsets: vec![vec![0]; num_buckets],
+    };
+    let mut ranges = vec![];
+    super_kmers(seq, k, w, &mut ranges);
+    for (start, end, min_pos) in ranges {
+        let min_pos = min_pos as usize;
+        let bucket = minimizer_bucket(seq.slice(min_pos..min_pos + k), num_buckets);
+        partition.seqs[bucket].push_seq(seq.slice(start as usize..end as usize));
+        partition.offsets[bucket].push(partition.seqs[bucket].len());
+    }
+    partition
+}
+
+/// Append the `(start, end, minimizer_pos)` character range of each super-k-mer to `out_vec`,
+/// given the `min_pos_vec` and `sk_pos_vec` outputs of the `_and_superkmer` functions for a sequence of length `seq_len`.
+///
+/// This gives the same ranges as [`super_kmers`] and [`canonical_super_kmers`], without recomputing the minimizers.
+pub fn super_kmer_ranges(
+    seq_len: usize,
+    k: usize,
+    w: usize,
+    min_pos_vec: &[u32],
+    sk_pos_vec: &[u32],
+    out_vec: &mut Vec<(u32, u32, u32)>,
+) {
+    assert_eq!(min_pos_vec.len(), sk_pos_vec.len());
+    let l = (k + w - 1) as u32;
+    // The first window after the last super-k-mer.
+    let num_windows = (seq_len as u32 + 1).saturating_sub(l);
+    let next_starts = sk_pos_vec.iter().skip(1).copied().chain([num_windows]);
+    out_vec.extend(
+        zip(zip(sk_pos_vec, next_starts), min_pos_vec)
+            .map(|((&start, next_start), &min_pos)| (start, next_start + l - 1, min_pos)),
+    );
+}
+
+/// Append the `(start, end, minimizer_pos)` character ranges of super-k-mers to `out_vec`,
+/// for consumers that only stored the sorted minimizer positions `min_pos_vec` of a sequence of length `seq_len`.
+///
+/// Positions alone do not determine which minimizer a window containing several of them selects.
+/// Here, each window is assigned to the leftmost position it contains, so that the windows of
+/// the super-k-mer of `min_pos_vec[i]` are `min_pos_vec[i-1]+1..=min_pos_vec[i]`.
+/// This matches [`super_kmers`] when no window contains more than one minimizer, and otherwise
+/// gives a partition of the windows in which every window still contains its assigned minimizer.
+/// Positions that are not the leftmost position of any window are skipped.
+///
+/// `min_pos_vec` must be sorted and satisfy [`verify_coverage`], as the output of [`minimizer_positions`] does.
+pub fn super_kmer_ranges_from_positions(
+    seq_len: usize,
+    k: usize,
+    w: usize,
+    min_pos_vec: &[u32],
+    out_vec: &mut Vec<(u32, u32, u32)>,
+) {
+    let l = (k + w - 1) as u32;
+    let num_windows = (seq_len as u32 + 1).saturating_sub(l);
+    let mut start = 0;
+    for (i, &min_pos) in min_pos_vec.iter().enumerate() {
+        let end = if i + 1 == min_pos_vec.len() {
+            num_windows
+        } else {
+            (min_pos + 1).min(num_windows)
+        };
+        if start < end {
+            out_vec.push((start, end + l - 1, min_pos));
+            start = end;
+        }
+    }
+}
+
+/// The packed values of the k-mers at the given `positions` of a 2-bit packed sequence, 8 positions at a time.
+///
+/// K-mers are packed with the first character in the lowest bits, as for [`minimizer_positions_and_kmers`].
+/// This requires `k <= 32`, and all k-mers must be inside the sequence.
+/// Use this to resolve positions that were computed earlier, e.g. by [`minimizer_positions`].
+///
+/// K-mers are appended to a reusable `out_vec` to avoid allocations.
+pub fn extract_kmers_simd(
+    seq: packed_seq::PackedSeq,
+    positions: &[u32],
+    k: usize,
+    out_vec: &mut Vec<u64>,
+) {
+    kmer::gather_kmers(seq, positions, k, out_vec);
+}
+
+/// Deduplicated positions of all minimizers in the sequence with their packed k-mer values, using SIMD.
+///
+/// K-mers are packed using `BITS_PER_CHAR` bits per character, with the first character in the lowest bits.
+/// This requires `k * BITS_PER_CHAR <= 64`.
+///
+/// Positions and k-mers are appended to reusable `min_pos_vec` and `kmer_vec` to avoid allocations.
+pub fn minimizer_positions_and_kmers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    kmer_vec: &mut Vec<u64>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizer_kmers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_with_kmers_into(head_padding, min_pos_vec, kmer_vec);
+    } else {
+        let head_padding = minimizer_kmers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_kmers_into(head_padding, min_pos_vec, kmer_vec);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, and the canonical packed k-mer value at each of them, using SIMD.
+///
+/// The canonical k-mer value is the minimum of the packed values of the k-mer and its reverse complement,
+/// using 2 bits per character with the first character in the lowest bits.
+/// This requires 2-bit DNA and `k <= 32`.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// Positions and k-mers are appended to reusable `min_pos_vec` and `kmer_vec` to avoid allocations.
+pub fn canonical_minimizer_positions_and_kmers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    kmer_vec: &mut Vec<u64>,
+) {
+    assert_eq!(S::BITS_PER_CHAR, 2, "Canonical k-mers require 2-bit DNA.");
+    let head_padding = canonical_minimizer_kmers_seq_simd::<_, NtHasher>(seq, k, w);
+    collect_and_dedup_with_kmers_into(head_padding, min_pos_vec, kmer_vec);
+}
+
+/// The minimizer-space representation of the sequence: the packed k-mer value of each minimizer, in order of occurrence, using SIMD.
+///
+/// There is one k-mer per super-k-mer, i.e., per deduplicated minimizer position, as used by minimizer-space de Bruijn graphs.
+/// K-mers are packed as for [`minimizer_positions_and_kmers`].
+///
+/// K-mers are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_space<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u64>) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizer_kmers_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_kmers_into(head_padding, out_vec);
+    } else {
+        let head_padding = minimizer_kmers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_kmers_into(head_padding, out_vec);
+    }
+}
+
+/// The canonical minimizer-space representation of the sequence: the canonical packed k-mer value of each canonical minimizer, in order of occurrence, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+/// Only 2-bit DNA with `k <= 32` is supported.
+///
+/// See [`minimizer_space`].
+pub fn canonical_minimizer_space<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<u64>,
+) {
+    assert_eq!(S::BITS_PER_CHAR, 2, "Canonical k-mers require 2-bit DNA.");
+    let head_padding = canonical_minimizer_kmers_seq_simd::<_, NtHasher>(seq, k, w);
+    collect_and_dedup_kmers_into(head_padding, out_vec);
+}
+
+/// Deduplicated positions of all minimizers in the sequence, and the hash of each of them, using SIMD.
+///
+/// The hashes come from the same pass that finds the minimizers, and can be used to bucket minimizers without rehashing.
+/// They equal the (forward) ntHash for DNA and mulHash for text of the k-mer at each position.
+///
+/// Positions and hashes are appended to reusable `min_pos_vec` and `hash_vec` to avoid allocations.
+pub fn minimizer_positions_and_hashes<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    hash_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = minimizer_and_hash_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_with_hashes_into(head_padding, min_pos_vec, hash_vec);
+    } else {
+        let head_padding = minimizer_and_hash_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_hashes_into(head_padding, min_pos_vec, hash_vec);
+    }
+}
+
+/// A subsample of the positions of all minimizers in the sequence, that still has a seed in every window of `max_window >= w` k-mers.
+///
+/// Minimizers are selected by their hash according to `mode`, and locally minimal minimizers are re-added where needed to keep coverage.
+/// See [`downsample_with_coverage`].
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn downsampled_minimizer_positions<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    max_window: usize,
+    mode: Downsample,
+    out_vec: &mut Vec<u32>,
+) {
+    assert!(
+        max_window >= w,
+        "max_window={max_window} must be at least w={w} to be satisfiable."
+    );
+    let mut min_pos_vec = vec![];
+    let mut hash_vec = vec![];
+    minimizer_positions_and_hashes(seq, k, w, &mut min_pos_vec, &mut hash_vec);
+    downsample_with_coverage(
+        &min_pos_vec,
+        &hash_vec,
+        seq.len(),
+        k,
+        max_window,
+        mode,
+        out_vec,
+    );
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, and the canonical hash of each of them, using SIMD.
+///
+/// The hash of each minimizer equals [`canonical_kmer_hash`] of its k-mer.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// Positions and hashes are appended to reusable `min_pos_vec` and `hash_vec` to avoid allocations.
+pub fn canonical_minimizer_positions_and_hashes<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    hash_vec: &mut Vec<u32>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let head_padding = canonical_minimizer_hashes_seq_simd::<_, NtHasher>(seq, k, w);
+        collect_and_dedup_with_hashes_into(head_padding, min_pos_vec, hash_vec);
+    } else {
+        let head_padding = canonical_minimizer_hashes_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_hashes_into(head_padding, min_pos_vec, hash_vec);
+    }
+}
+
+/// Remove minimizer `positions` in `seq` whose k-mer is selected more than `max_freq` times, as for minimap2's high-frequency filter.
+///
+/// The k-mer at each position is identified by its hash, as in [`minimizer_positions_and_hashes`].
+/// Positions are typically the output of [`minimizer_positions`], and are filtered in place.
+pub fn filter_frequent_minimizers<'s, S: Seq<'s>>(
+    positions: &mut Vec<u32>,
+    seq: S,
+    k: usize,
+    max_freq: u32,
+) {
+    multiplicity::with_hash_buffer(|hashes| {
+        hashes.extend(positions.iter().map(|&pos| {
+            let kmer = seq.slice(pos as usize..pos as usize + k);
+            if S::BITS_PER_CHAR == 2 {
+                nthash::nthash_kmer::<false, NtHasher>(kmer)
+            } else {
+                nthash::nthash_kmer::<false, MulHasher>(kmer)
+            }
+        }));
+        multiplicity::retain_infrequent(positions, 0, hashes, max_freq);
+    });
+}
+
+/// Remove canonical minimizer `positions` in `seq` whose k-mer is selected more than `max_freq` times on either strand.
+///
+/// The k-mer at each position is identified by its [`canonical_kmer_hash`].
+/// Positions are typically the output of [`canonical_minimizer_positions`], and are filtered in place.
+pub fn filter_frequent_canonical_minimizers<'s, S: Seq<'s>>(
+    positions: &mut Vec<u32>,
+    seq: S,
+    k: usize,
+    max_freq: u32,
+) {
+    multiplicity::with_hash_buffer(|hashes| {
+        hashes.extend(
+            positions
+                .iter()
+                .map(|&pos| canonical_kmer_hash(seq.slice(pos as usize..pos as usize + k))),
+        );
+        multiplicity::retain_infrequent(positions, 0, hashes, max_freq);
+    });
+}
+
+/// Deduplicated positions of all minimizers in the sequence whose k-mer is selected at most `max_freq` times, using SIMD.
+///
+/// A first pass computes the minimizers and their hashes, and a second pass drops the minimizers whose hash is over-represented.
+/// The output equals [`minimizer_positions`] followed by [`filter_frequent_minimizers`], but does not hash the k-mers twice.
+///
+/// Positions are appended to a reusable `out_vec` to avoid allocations.
+pub fn minimizer_positions_max_freq<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    max_freq: u32,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    multiplicity::with_hash_buffer(|hashes| {
+        minimizer_positions_and_hashes(seq, k, w, out_vec, hashes);
+        multiplicity::retain_infrequent(out_vec, start, hashes, max_freq);
+    });
+}
+
+/// The `n` most frequent minimizers of the sequence, as `(hash, count)`, using SIMD.
+///
+/// Each selected minimizer position is counted once, and minimizers are identified by their hash, as in [`minimizer_positions_and_hashes`].
+/// The minimizers are appended to `out_vec` by decreasing count, and ties by increasing hash.
+/// This is a quick summary of repeats or adapters in a record, without counting over the full dataset.
+pub fn most_frequent_minimizers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    n: usize,
+    out_vec: &mut Vec<(u32, u32)>,
+) {
+    let mut positions = vec![];
+    multiplicity::with_hash_buffer(|hashes| {
+        minimizer_positions_and_hashes(seq, k, w, &mut positions, hashes);
+        multiplicity::most_frequent(hashes, n, out_vec);
+    });
+}
+
+/// The `n` most frequent canonical minimizers of the sequence, as `(hash, count)`, using SIMD.
+///
+/// Both strands of a k-mer are counted together. For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`most_frequent_minimizers`].
+pub fn canonical_most_frequent_minimizers<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    n: usize,
+    out_vec: &mut Vec<(u32, u32)>,
+) {
+    let mut positions = vec![];
+    multiplicity::with_hash_buffer(|hashes| {
+        canonical_minimizer_positions_and_hashes(seq, k, w, &mut positions, hashes);
+        multiplicity::most_frequent(hashes, n, out_vec);
+    });
+}
+
+/// The `n` most frequent minimizers of each record, as `(hash, count)`, using SIMD.
+///
+/// `out_vecs` is resized to `seqs.len()` if needed, and the minimizers of record `i` are appended to `out_vecs[i]`.
+///
+/// See [`most_frequent_minimizers`].
+pub fn most_frequent_minimizers_records<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    n: usize,
+    out_vecs: &mut Vec<Vec<(u32, u32)>>,
+) {
+    if out_vecs.len() < seqs.len() {
+        out_vecs.resize(seqs.len(), vec![]);
+    }
+    for (&seq, out_vec) in zip(seqs, out_vecs) {
+        most_frequent_minimizers(seq, k, w, n, out_vec);
+    }
+}
+
+/// The `n` most frequent canonical minimizers of each record, as `(hash, count)`, using SIMD.
+///
+/// See [`most_frequent_minimizers_records`] and [`canonical_most_frequent_minimizers`].
+pub fn canonical_most_frequent_minimizers_records<'s, S: Seq<'s>>(
+    seqs: &[S],
+    k: usize,
+    w: usize,
+    n: usize,
+    out_vecs: &mut Vec<Vec<(u32, u32)>>,
+) {
+    if out_vecs.len() < seqs.len() {
+        out_vecs.resize(seqs.len(), vec![]);
+    }
+    for (&seq, out_vec) in zip(seqs, out_vecs) {
+        canonical_most_frequent_minimizers(seq, k, w, n, out_vec);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence whose k-mer is selected at most `max_freq` times on either strand, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_max_freq`].
+pub fn canonical_minimizer_positions_max_freq<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    max_freq: u32,
+    out_vec: &mut Vec<u32>,
+) {
+    let start = out_vec.len();
+    multiplicity::with_hash_buffer(|hashes| {
+        canonical_minimizer_positions_and_hashes(seq, k, w, out_vec, hashes);
+        multiplicity::retain_infrequent(out_vec, start, hashes, max_freq);
+    });
+}
+
+/// Deduplicated positions of all minimizers in the sequence, and the GC content of the first window each of them is minimal in, using SIMD.
+///
+/// The GC content is the number of `C` and `G` characters in the window of length `l=k+w-1`, computed in the same pass.
+/// Divide by `l` to get the GC fraction. This requires 2-bit DNA.
+///
+/// Positions and GC counts are appended to reusable `min_pos_vec` and `gc_vec` to avoid allocations.
+pub fn minimizer_positions_and_gc<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    gc_vec: &mut Vec<u32>,
+) {
+    assert_eq!(S::BITS_PER_CHAR, 2, "GC content requires 2-bit DNA.");
+    let (head, padding) = minimizer_gc_seq_simd::<_, NtHasher>(seq, k, w);
+    let head = head.map(|(pos, gc)| (pos, [gc]));
+    collect_and_dedup_with_vals_into((head, padding), min_pos_vec, gc_vec);
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, and the strand of the first window each of them is minimal in, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// The strand is `true` for the forward strand, when the window has more `TG` characters than `AC` characters
+/// and the leftmost minimal k-mer is selected, and `false` for the reverse strand, when the rightmost one is selected.
+/// Positions are appended to `min_pos_vec`, and strands to `strand_vec`.
+pub fn canonical_minimizer_positions_and_strands<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    strand_vec: &mut Vec<bool>,
+) {
+    if S::BITS_PER_CHAR == 2 {
+        let (head, padding) = canonical_minimizer_strand_seq_simd::<_, NtHasher>(seq, k, w);
+        let head = head.map(|(pos, fwd)| (pos, [fwd]));
+        collect_and_dedup_with_vals_into((head, padding), min_pos_vec, strand_vec);
+    } else {
+        let (head, padding) = canonical_minimizer_strand_seq_simd::<_, MulHasher>(seq, k, w);
+        let head = head.map(|(pos, fwd)| (pos, [fwd]));
+        collect_and_dedup_with_vals_into((head, padding), min_pos_vec, strand_vec);
+    }
+}
+
+/// Deduplicated positions of all canonical minimizers in the sequence, and the GC content of the first window each of them is minimal in, using SIMD.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// See [`minimizer_positions_and_gc`].
+pub fn canonical_minimizer_positions_and_gc<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    min_pos_vec: &mut Vec<u32>,
+    gc_vec: &mut Vec<u32>,
+) {
+    assert_eq!(S::BITS_PER_CHAR, 2, "GC content requires 2-bit DNA.");
+    let (head, padding) = canonical_minimizer_gc_seq_simd::<_, NtHasher>(seq, k, w);
+    let head = head.map(|(pos, gc)| (pos, [gc]));
+    collect_and_dedup_with_vals_into((head, padding), min_pos_vec, gc_vec);
+}
+
+/// Variants that always use mulHash, instead of the default ntHash for DNA and mulHash for text.
+pub mod mul_hash {
+    use super::*;
+
+    /// Deduplicated positions of all minimizers in the sequence, using SIMD.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minimizer_positions<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, out_vec: &mut Vec<u32>) {
+        let head_padding = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_into(head_padding, out_vec);
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, using SIMD.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn canonical_minimizer_positions<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        let head_tail = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_into(head_tail, out_vec);
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
+    ///
+    /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
+    pub fn minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        sk_pos_vec: &mut Vec<u32>,
+    ) {
+        let head_tail = minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_index_into(head_tail, min_pos_vec, sk_pos_vec);
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence with starting positions of the corresponding super-k-mers, using SIMD.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
+    pub fn canonical_minimizer_and_superkmer_positions<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        sk_pos_vec: &mut Vec<u32>,
+    ) {
+        let head_tail = canonical_minimizers_seq_simd::<_, MulHasher>(seq, k, w);
+        collect_and_dedup_with_index_into(head_tail, min_pos_vec, sk_pos_vec);
+    }
+}
+
+/// Scalar variants that are nearly always slower.
+///
+/// Can be used for testing and debugging.
+pub mod scalar {
+    use super::*;
 
     /// Deduplicated positions of all minimizers in the sequence.
     /// This scalar version can be faster for short sequences.
     ///
     /// Positions are appended to a reusable `out_vec` to avoid allocations.
-    pub fn minimizer_positions_scalar<'s, S: Seq<'s>>(
+    pub fn minimizer_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(minimizers_seq_scalar::<NtHasher>(seq, k, w).dedup());
+        } else {
+            out_vec.extend(minimizers_seq_scalar::<MulHasher>(seq, k, w).dedup());
+        }
+    }
+
+    /// Deduplicated positions of all minimizers of a sequence over a general alphabet, such as amino acids.
+    ///
+    /// See [`minimizer_positions_bytes`](super::minimizer_positions_bytes).
+    pub fn minimizer_positions_bytes_scalar(
+        seq: &[u8],
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        out_vec.extend(minimizers_seq_scalar::<ByteHasher>(seq, k, w).dedup());
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence, where the weight of each k-mer is added to its hash.
+    ///
+    /// See [`weighted_minimizer_positions`](super::weighted_minimizer_positions).
+    pub fn weighted_minimizer_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        weights: &'s [u16],
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(
+                weighted::weighted_minimizers_seq_scalar::<NtHasher>(seq, weights, k, w).dedup(),
+            );
+        } else {
+            out_vec.extend(
+                weighted::weighted_minimizers_seq_scalar::<MulHasher>(seq, weights, k, w).dedup(),
+            );
+        }
+    }
+
+    /// The offset of the minimizer within each window, in `0..w`.
+    ///
+    /// See [`minimizer_offsets`](super::minimizer_offsets).
+    pub fn minimizer_offsets_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u16>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(minimizers::minimizer_offsets_seq_scalar::<NtHasher>(
+                seq, k, w,
+            ));
+        } else {
+            out_vec.extend(minimizers::minimizer_offsets_seq_scalar::<MulHasher>(
+                seq, k, w,
+            ));
+        }
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence, using 64-bit ntHash.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minimizer_positions_hash64_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        out_vec.extend(minimizers64_seq_scalar(seq, k, w).dedup());
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, using 64-bit ntHash.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn canonical_minimizer_positions_hash64_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        out_vec.extend(canonical_minimizers64_seq_scalar(seq, k, w).dedup());
+    }
+
+    /// Deduplicated positions of all minimizers of long k-mers, up to `k <= MAX_K`, comparing full 128-bit ntHashes.
+    ///
+    /// The rolling hash is kept in two 64-bit words, so that only characters 128 positions apart share a rotation,
+    /// and for `k > 128` k-mers that agree after folding modulo 128 collide.
+    /// Other distinct k-mers tie with probability about `2^-128`, and ties are broken towards the leftmost position.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minimizer_positions_long_k_scalar<'s, S: Seq<'s>>(
         seq: S,
         k: usize,
         w: usize,
         out_vec: &mut Vec<u32>,
+    ) {
+        out_vec.extend(long_k::minimizers128_seq_scalar(seq, k, w).dedup());
+    }
+
+    /// Deduplicated positions of all spaced-seed minimizers in the sequence, with `k = mask.len()`.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn spaced_minimizer_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        mask: &[bool],
+        w: usize,
+        out_vec: &mut Vec<u32>,
     ) {
         if S::BITS_PER_CHAR == 2 {
-            out_vec.extend(minimizers_seq_scalar::<NtHasher>(seq, k, w).dedup());
+            out_vec.extend(spaced_minimizers_seq_scalar::<NtHasher>(seq, mask, w).dedup());
         } else {
-            out_vec.extend(minimizers_seq_scalar::<MulHasher>(seq, k, w).dedup());
+            out_vec.extend(spaced_minimizers_seq_scalar::<MulHasher>(seq, mask, w).dedup());
+        }
+    }
+
+    /// The strandedness score `#GT - #AC` of each window of `l` characters in the sequence.
+    ///
+    /// Scores are appended to a reusable `out_vec` to avoid allocations.
+    pub fn window_strandedness_scalar<'s, S: Seq<'s>>(seq: S, l: usize, out_vec: &mut Vec<i32>) {
+        assert!(l > 0);
+        if seq.len() < l {
+            return;
         }
+        out_vec.extend(canonical::strandedness_seq_scalar(seq, l));
     }
 
     /// Deduplicated positions of all canonical minimizers in the sequence.
     /// This scalar version can be faster for short sequences.
     ///
-    /// `l=w+k-1` must be odd to determine the strand of each window.
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
     ///
     /// Positions are appended to a reusable `out_vec` to avoid allocations.
     pub fn canonical_minimizer_positions_scalar<'s, S: Seq<'s>>(
@@ -363,6 +2513,42 @@ pub mod scalar {
         }
     }
 
+    /// Start and end coordinates of all minimizer k-mers in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Coordinates are appended to reusable `start_vec` and `end_vec` to avoid allocations.
+    pub fn minimizer_spans_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        convention: CoordinateConvention,
+        start_vec: &mut Vec<u32>,
+        end_vec: &mut Vec<u32>,
+    ) {
+        let mut positions = vec![];
+        minimizer_positions_scalar(seq, k, w, &mut positions);
+        start_vec.extend(positions.iter().map(|&p| convention.start(p)));
+        end_vec.extend(positions.iter().map(|&p| convention.end(p, k)));
+    }
+
+    /// Start and end coordinates of all canonical minimizer k-mers in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Coordinates are appended to reusable `start_vec` and `end_vec` to avoid allocations.
+    pub fn canonical_minimizer_spans_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        convention: CoordinateConvention,
+        start_vec: &mut Vec<u32>,
+        end_vec: &mut Vec<u32>,
+    ) {
+        let mut positions = vec![];
+        canonical_minimizer_positions_scalar(seq, k, w, &mut positions);
+        start_vec.extend(positions.iter().map(|&p| convention.start(p)));
+        end_vec.extend(positions.iter().map(|&p| convention.end(p, k)));
+    }
+
     /// Deduplicated positions of all minimizers in the sequence with starting positions of the corresponding super-k-mers.
     /// This scalar version can be faster for short sequences.
     ///
@@ -396,7 +2582,7 @@ pub mod scalar {
     /// Deduplicated positions of all canonical minimizers in the sequence with starting positions of the corresponding super-k-mers.
     /// This scalar version can be faster for short sequences.
     ///
-    /// `l=w+k-1` must be odd to determine the strand of each window.
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
     ///
     /// Positions are appended to reusable `min_pos_vec` and `sk_pos_vec` to avoid allocations.
     pub fn canonical_minimizer_and_superkmer_positions_scalar<'s, S: Seq<'s>>(
@@ -426,4 +2612,366 @@ pub mod scalar {
             sk_pos_vec.extend(sk_pos);
         }
     }
+
+    /// Deduplicated positions of all minimizers in the sequence with the last window of the corresponding super-k-mers.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to reusable `min_pos_vec` and `sk_end_vec` to avoid allocations.
+    pub fn minimizer_and_superkmer_end_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        sk_end_vec: &mut Vec<u32>,
+    ) {
+        let min_pos = if S::BITS_PER_CHAR == 2 {
+            minimizers_seq_scalar::<NtHasher>(seq, k, w).collect_vec()
+        } else {
+            minimizers_seq_scalar::<MulHasher>(seq, k, w).collect_vec()
+        };
+        append_last_of_runs(&min_pos, min_pos_vec, sk_end_vec);
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence with the last window of the corresponding super-k-mers.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions are appended to reusable `min_pos_vec` and `sk_end_vec` to avoid allocations.
+    pub fn canonical_minimizer_and_superkmer_end_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        sk_end_vec: &mut Vec<u32>,
+    ) {
+        let min_pos = if S::BITS_PER_CHAR == 2 {
+            canonical_minimizers_seq_scalar::<NtHasher>(seq, k, w).collect_vec()
+        } else {
+            canonical_minimizers_seq_scalar::<MulHasher>(seq, k, w).collect_vec()
+        };
+        append_last_of_runs(&min_pos, min_pos_vec, sk_end_vec);
+    }
+
+    /// Append the last element of each run of equal `min_pos` and its index.
+    fn append_last_of_runs(min_pos: &[u32], min_pos_vec: &mut Vec<u32>, sk_end_vec: &mut Vec<u32>) {
+        for (i, &pos) in min_pos.iter().enumerate() {
+            if i == 0 && min_pos_vec.last() == Some(&pos) {
+                // The run continuing from `min_pos_vec` ends in this sequence.
+                min_pos_vec.pop();
+                sk_end_vec.pop();
+            }
+            if min_pos.get(i + 1) != Some(&pos) {
+                min_pos_vec.push(pos);
+                sk_end_vec.push(i as u32);
+            }
+        }
+    }
+
+    /// The packed values of the k-mers at the given `positions` of the sequence.
+    /// Unlike [`extract_kmers_simd`], this supports all input types, with `k * BITS_PER_CHAR <= 64`.
+    ///
+    /// K-mers are appended to a reusable `out_vec` to avoid allocations.
+    pub fn extract_kmers_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        positions: &[u32],
+        k: usize,
+        out_vec: &mut Vec<u64>,
+    ) {
+        out_vec.extend(
+            positions
+                .iter()
+                .map(|&pos| kmer::kmer_value(seq.slice(pos as usize..pos as usize + k))),
+        );
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence with their packed k-mer values.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions and k-mers are appended to reusable `min_pos_vec` and `kmer_vec` to avoid allocations.
+    pub fn minimizer_positions_and_kmers_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        kmer_vec: &mut Vec<u64>,
+    ) {
+        let start = min_pos_vec.len();
+        minimizer_positions_scalar(seq, k, w, min_pos_vec);
+        kmer_vec.extend(
+            min_pos_vec[start..]
+                .iter()
+                .map(|&pos| kmer::kmer_value(seq.slice(pos as usize..pos as usize + k))),
+        );
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, and the canonical packed k-mer value at each of them.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions and k-mers are appended to reusable `min_pos_vec` and `kmer_vec` to avoid allocations.
+    pub fn canonical_minimizer_positions_and_kmers_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        kmer_vec: &mut Vec<u64>,
+    ) {
+        let start = min_pos_vec.len();
+        canonical_minimizer_positions_scalar(seq, k, w, min_pos_vec);
+        kmer_vec.extend(
+            min_pos_vec[start..]
+                .iter()
+                .map(|&pos| kmer::canonical_kmer_value(seq.slice(pos as usize..pos as usize + k))),
+        );
+    }
+
+    /// Deduplicated positions of all minimizers in the sequence, and the hash of each of them.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions and hashes are appended to reusable `min_pos_vec` and `hash_vec` to avoid allocations.
+    pub fn minimizer_positions_and_hashes_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        hash_vec: &mut Vec<u32>,
+    ) {
+        let start = min_pos_vec.len();
+        minimizer_positions_scalar(seq, k, w, min_pos_vec);
+        let hashes = if S::BITS_PER_CHAR == 2 {
+            nthash_seq_scalar::<false, NtHasher>(seq, k).collect_vec()
+        } else {
+            nthash_seq_scalar::<false, MulHasher>(seq, k).collect_vec()
+        };
+        hash_vec.extend(min_pos_vec[start..].iter().map(|&pos| hashes[pos as usize]));
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, and the canonical hash of each of them.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    ///
+    /// Positions and hashes are appended to reusable `min_pos_vec` and `hash_vec` to avoid allocations.
+    pub fn canonical_minimizer_positions_and_hashes_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        hash_vec: &mut Vec<u32>,
+    ) {
+        let start = min_pos_vec.len();
+        canonical_minimizer_positions_scalar(seq, k, w, min_pos_vec);
+        let hashes = if S::BITS_PER_CHAR == 2 {
+            nthash_seq_scalar::<true, NtHasher>(seq, k).collect_vec()
+        } else {
+            nthash_seq_scalar::<true, MulHasher>(seq, k).collect_vec()
+        };
+        hash_vec.extend(min_pos_vec[start..].iter().map(|&pos| hashes[pos as usize]));
+    }
+
+    /// Deduplicated positions of all canonical minimizers in the sequence, and the strand of the first window each of them is minimal in.
+    ///
+    /// See [`canonical_minimizer_positions_and_strands`](super::canonical_minimizer_positions_and_strands).
+    pub fn canonical_minimizer_positions_and_strands_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        min_pos_vec: &mut Vec<u32>,
+        strand_vec: &mut Vec<bool>,
+    ) {
+        let positions = if S::BITS_PER_CHAR == 2 {
+            canonical_minimizers_seq_scalar::<NtHasher>(seq, k, w).collect_vec()
+        } else {
+            canonical_minimizers_seq_scalar::<MulHasher>(seq, k, w).collect_vec()
+        };
+        let strands = canonical::canonical_windows_seq_scalar(seq, k, w);
+        for (pos, fwd) in zip(positions, strands).dedup_by(|a, b| a.0 == b.0) {
+            min_pos_vec.push(pos);
+            strand_vec.push(fwd);
+        }
+    }
+
+    /// Positions of all syncmers in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn syncmer_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        s: usize,
+        offsets: &[usize],
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(syncmers::syncmers_seq_scalar::<NtHasher>(
+                seq, k, s, offsets,
+            ));
+        } else {
+            out_vec.extend(syncmers::syncmers_seq_scalar::<MulHasher>(
+                seq, k, s, offsets,
+            ));
+        }
+    }
+
+    /// All randstrobes of order 2 in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Strobemers are appended to a reusable `out_vec` to avoid allocations.
+    pub fn randstrobes_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w_min: usize,
+        w_max: usize,
+        out_vec: &mut Vec<Strobemer>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(strobemers::strobemers_seq_scalar::<true, NtHasher>(
+                seq, k, w_min, w_max,
+            ));
+        } else {
+            out_vec.extend(strobemers::strobemers_seq_scalar::<true, MulHasher>(
+                seq, k, w_min, w_max,
+            ));
+        }
+    }
+
+    /// All minstrobes of order 2 in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Strobemers are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minstrobes_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w_min: usize,
+        w_max: usize,
+        out_vec: &mut Vec<Strobemer>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(strobemers::strobemers_seq_scalar::<false, NtHasher>(
+                seq, k, w_min, w_max,
+            ));
+        } else {
+            out_vec.extend(strobemers::strobemers_seq_scalar::<false, MulHasher>(
+                seq, k, w_min, w_max,
+            ));
+        }
+    }
+
+    /// The windows whose minimizers differ between two aligned sequences of equal length.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Windows are appended to a reusable `out_vec` to avoid allocations.
+    pub fn minimizer_mismatches_scalar<'s, S: Seq<'s>>(
+        seq_a: S,
+        seq_b: S,
+        k: usize,
+        w: usize,
+        out_vec: &mut Vec<MinimizerMismatch>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(dual::dual_minimizers_seq_scalar::<NtHasher>(
+                seq_a, seq_b, k, w,
+            ));
+        } else {
+            out_vec.extend(dual::dual_minimizers_seq_scalar::<MulHasher>(
+                seq_a, seq_b, k, w,
+            ));
+        }
+    }
+
+    /// The 32-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Hashes are appended to a reusable `out_vec` to avoid allocations.
+    pub fn fracminhash_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        fraction: f64,
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(fracminhash::fracminhash_seq_scalar::<false, NtHasher>(
+                seq, k, fraction,
+            ));
+        } else {
+            out_vec.extend(fracminhash::fracminhash_seq_scalar::<false, MulHasher>(
+                seq, k, fraction,
+            ));
+        }
+    }
+
+    /// The canonical 32-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Hashes are appended to a reusable `out_vec` to avoid allocations.
+    pub fn canonical_fracminhash_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        fraction: f64,
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(fracminhash::fracminhash_seq_scalar::<true, NtHasher>(
+                seq, k, fraction,
+            ));
+        } else {
+            out_vec.extend(fracminhash::fracminhash_seq_scalar::<true, MulHasher>(
+                seq, k, fraction,
+            ));
+        }
+    }
+
+    /// The 64-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range.
+    /// This scalar version can be faster for short sequences. Only 2-bit DNA is supported.
+    ///
+    /// Hashes are appended to a reusable `out_vec` to avoid allocations.
+    pub fn fracminhash64_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        fraction: f64,
+        out_vec: &mut Vec<u64>,
+    ) {
+        out_vec.extend(fracminhash::fracminhash64_seq_scalar::<false, S>(
+            seq, k, fraction,
+        ));
+    }
+
+    /// The canonical 64-bit ntHashes of all k-mers in the sequence that are at most a `fraction` of the hash range.
+    /// This scalar version can be faster for short sequences. Only 2-bit DNA is supported.
+    ///
+    /// Hashes are appended to a reusable `out_vec` to avoid allocations.
+    pub fn canonical_fracminhash64_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        fraction: f64,
+        out_vec: &mut Vec<u64>,
+    ) {
+        out_vec.extend(fracminhash::fracminhash64_seq_scalar::<true, S>(
+            seq, k, fraction,
+        ));
+    }
+
+    /// Deduplicated positions of all mod-minimizers in the sequence.
+    /// This scalar version can be faster for short sequences.
+    ///
+    /// Positions are appended to a reusable `out_vec` to avoid allocations.
+    pub fn mod_minimizer_positions_scalar<'s, S: Seq<'s>>(
+        seq: S,
+        k: usize,
+        w: usize,
+        t: usize,
+        out_vec: &mut Vec<u32>,
+    ) {
+        if S::BITS_PER_CHAR == 2 {
+            out_vec.extend(
+                mod_minimizers::mod_minimizers_seq_scalar::<NtHasher>(seq, k, w, t).dedup(),
+            );
+        } else {
+            out_vec.extend(
+                mod_minimizers::mod_minimizers_seq_scalar::<MulHasher>(seq, k, w, t).dedup(),
+            );
+        }
+    }
 }
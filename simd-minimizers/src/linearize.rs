@@ -0,0 +1,122 @@
+//! Lazily iterate the values of a SIMD iterator in sequence order, instead of collecting them.
+//!
+//! The SIMD pipelines return `(head, padding)`, where lane `j` of `head` contains the `j`th chunk of the output.
+//! [`linearize`] turns this into a plain iterator over `u32` values in the same order as [`collect`](crate::private::collect::collect).
+//!
+//! Values of lane 0 are yielded as soon as they are computed.
+//! Since all lanes are computed in lockstep, lanes 1 to 7 are buffered until lane 0 is done,
+//! so this holds on to up to `7/8` of the output, but never the output as a whole.
+//!
+//! ```
+//! use itertools::Itertools;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//!
+//! let seq = PackedSeqVec::random(1000);
+//! let (k, w) = (21, 11);
+//! // The position of the minimizer of each window.
+//! for (window, pos) in simd_minimizers::window_minimizer_positions(seq.as_slice(), k, w).enumerate() {
+//!     assert!(window <= pos as usize && pos as usize < window + w);
+//! }
+//! // Deduplicated minimizer positions.
+//! let positions = simd_minimizers::window_minimizer_positions(seq.as_slice(), k, w)
+//!     .dedup()
+//!     .collect_vec();
+//! let mut expected = vec![];
+//! simd_minimizers::minimizer_positions(seq.as_slice(), k, w, &mut expected);
+//! assert_eq!(positions, expected);
+//! ```
+use std::iter::zip;
+
+use crate::intrinsics::transpose;
+use crate::S;
+
+/// Iterator over the values of a SIMD iterator in sequence order. See [`linearize`].
+#[derive(Clone, Debug)]
+pub struct Linearize<I> {
+    head: I,
+    head_done: bool,
+    /// Values of lane 0 that have not been yielded yet.
+    front: [u32; 8],
+    front_idx: usize,
+    front_len: usize,
+    /// Values of lanes 1 to 7, buffered until lane 0 is done.
+    lanes: [Vec<u32>; 7],
+    lane: usize,
+    lane_idx: usize,
+    /// The number of values left to yield, excluding the padding.
+    remaining: usize,
+}
+
+/// Lazily yield the values of `(head, padding)` in sequence order, i.e., lane by lane, dropping the `padding` at the end.
+pub fn linearize<I: ExactSizeIterator<Item = S>>((head, padding): (I, usize)) -> Linearize<I> {
+    let remaining = head.len() * 8 - padding;
+    Linearize {
+        head,
+        head_done: false,
+        front: [0; 8],
+        front_idx: 0,
+        front_len: 0,
+        lanes: Default::default(),
+        lane: 0,
+        lane_idx: 0,
+        remaining,
+    }
+}
+
+impl<I: Iterator<Item = S>> Linearize<I> {
+    /// Transpose the next (up to) 8 SIMD values into lane 0 and the buffers of the other lanes.
+    /// Returns `false` when `head` is exhausted.
+    fn refill(&mut self) -> bool {
+        if self.head_done {
+            return false;
+        }
+        let mut m = [S::ZERO; 8];
+        let mut n = 0;
+        for x in self.head.by_ref().take(8) {
+            m[n] = x;
+            n += 1;
+        }
+        if n == 0 {
+            self.head_done = true;
+            return false;
+        }
+        let t = transpose(m);
+        self.front[..n].copy_from_slice(&t[0].as_array_ref()[..n]);
+        self.front_idx = 0;
+        self.front_len = n;
+        for (lane, row) in zip(&mut self.lanes, &t[1..]) {
+            lane.extend_from_slice(&row.as_array_ref()[..n]);
+        }
+        true
+    }
+}
+
+impl<I: Iterator<Item = S>> Iterator for Linearize<I> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if self.front_idx == self.front_len && !self.refill() {
+            // Lane 0 is done; continue with the buffered lanes.
+            while self.lane_idx == self.lanes[self.lane].len() {
+                self.lanes[self.lane] = vec![];
+                self.lane += 1;
+                self.lane_idx = 0;
+            }
+            self.lane_idx += 1;
+            return Some(self.lanes[self.lane][self.lane_idx - 1]);
+        }
+        self.front_idx += 1;
+        Some(self.front[self.front_idx - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I: Iterator<Item = S>> ExactSizeIterator for Linearize<I> {}
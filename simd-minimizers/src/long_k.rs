@@ -0,0 +1,282 @@
+//! 128-bit ntHash for long k-mers, up to [`MAX_K`] characters.
+//!
+//! ntHash rotates the seed of each character by its distance to the end of the k-mer.
+//! With `b`-bit hashes, characters `b` positions apart are rotated by the same amount,
+//! so that their seeds are xored, and equal characters cancel.
+//! For `k > b`, the hash thus only depends on the k-mer folded modulo `b`.
+//!
+//! The rolling state is kept in two 64-bit words: a `u128` in the scalar path, and two [`S64`]s in the SIMD path,
+//! so that this only happens for `k > 128`.
+//! For `k <= MAX_K < 2 * 128`, each rotation class contains at most two characters.
+//! Both paths compare the full 128-bit hashes, and break ties towards the leftmost position.
+//!
+//! The seeds are chosen such that xoring the two 64-bit words of the 128-bit hash gives exactly the 64-bit ntHash.
+//! Only 2-bit DNA is supported.
+use std::array::from_fn;
+use std::collections::VecDeque;
+use std::ops::BitXor;
+
+use crate::intrinsics::table_lookup;
+use crate::nthash::Captures;
+use crate::nthash64::{HASHES_F as HASHES_F64, S64};
+use crate::sliding_min::sliding_min128_mapper;
+use crate::S;
+use packed_seq::{complement_base, Seq};
+
+/// The largest supported k-mer length.
+pub const MAX_K: usize = 255;
+
+/// Arbitrary high words of the 128-bit seeds.
+const HASHES_HI: [u64; 4] = [
+    0x530c_7f50_0e28_0585,
+    0xa2db_9965_a3f7_580b,
+    0x95bb_2740_cb9a_37a8,
+    0xebe8_d5cc_0bd0_7f2b,
+];
+
+/// 128-bit seeds, whose words xor to the 64-bit ntHash seeds.
+const HASHES_F: [u128; 4] = {
+    let mut f = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        f[i] = (HASHES_HI[i] as u128) << 64 | (HASHES_HI[i] ^ HASHES_F64[i]) as u128;
+        i += 1;
+    }
+    f
+};
+
+/// Eight 128-bit lanes, stored as their low and high 64-bit words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct S128 {
+    /// The low 64 bits of each lane.
+    pub lo: S64,
+    /// The high 64 bits of each lane.
+    pub hi: S64,
+}
+
+impl S128 {
+    #[inline(always)]
+    pub fn splat(x: u128) -> Self {
+        S128 {
+            lo: S64::splat(x as u64),
+            hi: S64::splat((x >> 64) as u64),
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_array(self) -> [u128; 8] {
+        let (lo, hi) = (self.lo.to_array(), self.hi.to_array());
+        from_fn(|i| lo[i] as u128 | (hi[i] as u128) << 64)
+    }
+
+    /// Rotate each lane left by 1.
+    #[inline(always)]
+    pub fn rotate_left_1(self) -> Self {
+        let [w0, w1, w2, w3] = self.words();
+        S128 {
+            lo: S64 {
+                lo: (w0 << 1) | (w3 >> 31),
+                hi: (w1 << 1) | (w0 >> 31),
+            },
+            hi: S64 {
+                lo: (w2 << 1) | (w1 >> 31),
+                hi: (w3 << 1) | (w2 >> 31),
+            },
+        }
+    }
+
+    /// The mask of the lanes where `self < other`, or where they are equal and `tie` is set, comparing the full unsigned 128-bit values.
+    #[inline(always)]
+    pub fn lt_or(self, other: Self, tie: S) -> S {
+        // Compare from the least to the most significant word, so that each more significant word overrides unless it is equal.
+        let mut mask = tie;
+        for (a, b) in self.words().into_iter().zip(other.words()) {
+            let le = a.min(b).cmp_eq(a);
+            mask = a.cmp_eq(b).blend(mask, le);
+        }
+        mask
+    }
+
+    /// Take the lanes of `self` where `mask` is set, and of `other` elsewhere.
+    #[inline(always)]
+    pub fn blend(self, other: Self, mask: S) -> Self {
+        let (a, b) = (self.words(), other.words());
+        let [w0, w1, w2, w3] = from_fn(|i| mask.blend(a[i], b[i]));
+        S128 {
+            lo: S64 { lo: w0, hi: w1 },
+            hi: S64 { lo: w2, hi: w3 },
+        }
+    }
+
+    /// The four 32-bit words of each lane, least significant first.
+    #[inline(always)]
+    fn words(self) -> [S; 4] {
+        [self.lo.lo, self.lo.hi, self.hi.lo, self.hi.hi]
+    }
+}
+
+impl BitXor for S128 {
+    type Output = S128;
+    #[inline(always)]
+    fn bitxor(self, rhs: S128) -> S128 {
+        S128 {
+            lo: self.lo ^ rhs.lo,
+            hi: self.hi ^ rhs.hi,
+        }
+    }
+}
+
+/// The four 32-bit words of a 128-bit table, each repeated in both 128-bit halves of a SIMD register for [`table_lookup`].
+#[derive(Clone, Copy)]
+struct Table128([S; 4]);
+
+impl Table128 {
+    fn new(t: [u128; 4]) -> Self {
+        Table128(from_fn(|w| {
+            S::new(from_fn(|i| (t[i % 4] >> (32 * w)) as u32))
+        }))
+    }
+
+    #[inline(always)]
+    fn lookup(self, b: S) -> S128 {
+        let [w0, w1, w2, w3] = self.0.map(|t| table_lookup(t, b));
+        S128 {
+            lo: S64 { lo: w0, hi: w1 },
+            hi: S64 { lo: w2, hi: w3 },
+        }
+    }
+}
+
+/// Xor the two 64-bit words of a 128-bit hash.
+///
+/// `fold64(nthash128)` equals the 64-bit ntHash of the same k-mer.
+pub fn fold64(h: u128) -> u64 {
+    h as u64 ^ (h >> 64) as u64
+}
+
+/// Returns an iterator over the 128-bit ntHashes of all k-mers in the sequence.
+/// Set `RC` to true for canonical ntHash.
+///
+/// Requires `k <= MAX_K`.
+pub fn nthash128_seq_scalar<'s, const RC: bool, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+) -> impl ExactSizeIterator<Item = u128> + Captures<&'s ()> + Clone {
+    assert!(k > 0);
+    assert!(k <= MAX_K, "k={k} is larger than the maximum {MAX_K}.");
+    assert_eq!(
+        SEQ::BITS_PER_CHAR,
+        2,
+        "128-bit ntHash only supports 2-bit DNA."
+    );
+    let rot = k as u32 - 1;
+    let f = HASHES_F;
+    let c: [u128; 4] = std::array::from_fn(|i| HASHES_F[complement_base(i as u8) as usize]);
+    let f_rot = f.map(|h| h.rotate_left(rot));
+    let c_rot = c.map(|h| h.rotate_left(rot));
+
+    let mut hfw: u128 = 0;
+    let mut hrc: u128 = 0;
+    let mut add = seq.iter_bp();
+    let remove = seq.iter_bp();
+    add.by_ref().take(k - 1).for_each(|a| {
+        hfw = hfw.rotate_left(1) ^ f[a as usize];
+        if RC {
+            hrc = hrc.rotate_right(1) ^ c_rot[a as usize];
+        }
+    });
+    add.zip(remove).map(move |(a, r)| {
+        let hfw_out = hfw.rotate_left(1) ^ f[a as usize];
+        hfw = hfw_out ^ f_rot[r as usize];
+        if RC {
+            let hrc_out = hrc.rotate_right(1) ^ c_rot[a as usize];
+            hrc = hrc_out ^ c[r as usize];
+            hfw_out.wrapping_add(hrc_out)
+        } else {
+            hfw_out
+        }
+    })
+}
+
+/// A function that 'eats' added and removed bases, and returns the updated forward 128-bit ntHash.
+/// The distance between them must be k-1, and the first k-1 removed bases must be 0.
+/// The first k-1 returned values will be useless.
+///
+/// Requires `k <= MAX_K`.
+pub fn nthash128_mapper<'s, SEQ: Seq<'s>>(
+    k: usize,
+    w: usize,
+) -> impl FnMut((S, S)) -> S128 + Clone {
+    assert!(k > 0);
+    assert!(w > 0);
+    assert!(k <= MAX_K, "k={k} is larger than the maximum {MAX_K}.");
+    assert_eq!(
+        SEQ::BITS_PER_CHAR,
+        2,
+        "128-bit ntHash only supports 2-bit DNA."
+    );
+    let rot = k as u32 - 1;
+    let f = Table128::new(HASHES_F);
+    let f_rot = Table128::new(HASHES_F.map(|h| h.rotate_left(rot)));
+
+    let mut fw = 0u128;
+    for _ in 0..k - 1 {
+        fw = fw.rotate_left(1) ^ HASHES_F[0];
+    }
+    let mut h_fw = S128::splat(fw);
+
+    move |(a, r)| {
+        let hfw_out = h_fw.rotate_left_1() ^ f.lookup(a);
+        h_fw = hfw_out ^ f_rot.lookup(r);
+        hfw_out
+    }
+}
+
+/// Returns an iterator over the absolute position of the minimizer of each window, comparing full 128-bit ntHashes.
+/// Ties are broken towards the leftmost position.
+///
+/// Requires `k <= MAX_K`.
+pub fn minimizers128_seq_scalar<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = u32> + Captures<&'s ()> {
+    assert!(w > 0);
+    // Candidates for the minimum of the current window, with increasing positions and non-decreasing hashes.
+    let mut queue = VecDeque::<(u128, u32)>::new();
+    nthash128_seq_scalar::<false, SEQ>(seq, k)
+        .enumerate()
+        .filter_map(move |(pos, h)| {
+            let pos = pos as u32;
+            while queue.back().is_some_and(|&(back, _)| back > h) {
+                queue.pop_back();
+            }
+            queue.push_back((h, pos));
+            let start = (pos + 1).checked_sub(w as u32)?;
+            while queue[0].1 < start {
+                queue.pop_front();
+            }
+            Some(queue[0].1)
+        })
+}
+
+/// Like [`minimizers128_seq_scalar`], but using SIMD, with the same full 128-bit comparisons and leftmost tie-breaking.
+///
+/// Requires `k <= MAX_K`.
+pub fn minimizers128_seq_simd<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (impl ExactSizeIterator<Item = S> + Captures<&'s ()>, usize) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash128_mapper::<SEQ>(k, w);
+    let mut sliding_min = sliding_min128_mapper(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| sliding_min(nthash((a, rk))));
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
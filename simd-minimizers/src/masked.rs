@@ -0,0 +1,65 @@
+//! Minimizers of sequences containing invalid characters, such as `N` and other IUPAC codes.
+//!
+//! Packed sequences silently map such characters to arbitrary bases.
+//! Here, the caller marks each character as valid or not, and windows containing an invalid character are skipped.
+//! The sequence is split into maximal runs of valid characters, each of which is processed independently using SIMD,
+//! and the results are shifted by the start of the run.
+use std::ops::Range;
+
+use crate::collect::run_len;
+use crate::validate::is_acgt;
+use packed_seq::Seq;
+
+/// Whether each character of an ASCII sequence is one of `ACGTacgt`.
+pub fn acgt_mask(seq: &[u8]) -> Vec<bool> {
+    seq.iter().map(|&c| is_acgt(c)).collect()
+}
+
+/// The maximal runs of `true` values in `valid` that have length at least `l`.
+pub fn valid_runs(valid: &[bool], l: usize) -> Vec<Range<usize>> {
+    let mut runs = vec![];
+    let mut i = 0;
+    while i < valid.len() {
+        let Some(start) = valid[i..].iter().position(|&v| v).map(|p| i + p) else {
+            break;
+        };
+        let end = valid[start..]
+            .iter()
+            .position(|&v| !v)
+            .map_or(valid.len(), |p| start + p);
+        if end - start >= l {
+            runs.push(start..end);
+        }
+        i = end;
+    }
+    runs
+}
+
+/// Run `positions` on each run of valid characters of `seq`, and append the shifted positions to `out_vec`.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(run, out_vec)` must append the positions of all windows in `run` to `out_vec`.
+pub fn positions_in_valid_runs<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    l: usize,
+    valid: &[bool],
+    out_vec: &mut Vec<u32>,
+    mut positions: impl FnMut(SEQ, &mut Vec<u32>),
+) {
+    assert_eq!(
+        valid.len(),
+        seq.len(),
+        "The mask must have the same length as the sequence."
+    );
+    let mut run_pos = vec![];
+    for run in valid_runs(valid, l) {
+        run_pos.clear();
+        positions(seq.slice(run.clone()), &mut run_pos);
+        for pos in &mut run_pos {
+            *pos += run.start as u32;
+        }
+        // Positions in disjoint runs differ, so only the first one can equal the last position of a previous call.
+        let skip = run_len(out_vec, &run_pos);
+        out_vec.extend_from_slice(&run_pos[skip..]);
+    }
+}
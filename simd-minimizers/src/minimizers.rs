@@ -3,17 +3,32 @@ use std::iter::zip;
 
 use crate::{
     canonical,
-    nthash::{Captures, CharHasher},
+    error::{check_params, Error},
+    nthash::{ByteHasher, Captures, CharHasher},
 };
 
 use super::{
+    anti_lex::{
+        canonical_anti_lex_delay, canonical_anti_lex_hash_mapper,
+        canonical_anti_lex_hash_seq_scalar,
+    },
     canonical::canonical_mapper,
-    nthash::{nthash_mapper, nthash_seq_scalar},
-    sliding_min::{sliding_lr_min_mapper, sliding_min_mapper, sliding_min_scalar},
+    gc::gc_mapper,
+    kmer::kmer_mapper,
+    nthash::{
+        hash_at_pos_mapper, nthash_fw_rc_mapper, nthash_mapper, nthash_seq_scalar,
+        spaced_nthash_mapper, spaced_nthash_seq_scalar,
+    },
+    nthash64::{nthash64_mapper, nthash64_seq_scalar},
+    sliding_min::{
+        sliding_lr_min64_mapper, sliding_lr_min64_scalar, sliding_lr_min_mapper,
+        sliding_min64_mapper, sliding_min64_scalar, sliding_min_mapper, sliding_min_scalar,
+        sliding_min_tree_mapper,
+    },
 };
 use itertools::Itertools;
 use packed_seq::Seq;
-use wide::u32x8;
+use wide::{i32x8, u32x8};
 
 /// Returns the minimizer of a window using a naive linear scan.
 pub fn minimizer<'s, H: CharHasher>(seq: impl Seq<'s>, k: usize) -> usize {
@@ -65,6 +80,294 @@ pub fn minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
     (head, padding)
 }
 
+/// Returns an iterator over the minimizer positions of each window on both strands, in a single pass.
+///
+/// Each item is a pair of the leftmost position with the smallest forward hash,
+/// and the rightmost position with the smallest reverse complement hash, i.e., the leftmost smallest k-mer of the reverse complement window.
+/// Both are positions on the forward strand. The reverse complement k-mer at position `p` starts at `n-k-p` on the reverse complement sequence.
+pub fn minimizers_fwd_rc_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash_fw_rc_mapper::<SEQ, H>(k, w);
+    let mut fw_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+    let mut rc_min = sliding_min_mapper::<false>(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let (fw, rc) = nthash((a, rk));
+        (fw_min(fw), rc_min(rc))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like [`minimizers_seq_simd`], but returns an [`Error`] instead of panicking when [`check_params`] rejects the parameters.
+pub fn try_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> Result<
+    (
+        impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+        usize,
+    ),
+    Error,
+> {
+    check_params(seq, k, w)?;
+    Ok(minimizers_seq_simd::<_, H>(seq, k, w))
+}
+
+/// Like [`minimizers_seq_simd`], for a sequence of bytes over a general alphabet, such as amino acids.
+///
+/// Each byte is a symbol, and k-mers are hashed using [`ByteHasher`].
+pub fn minimizers_seq_simd_bytes<'s>(
+    seq: &'s [u8],
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    minimizers_seq_simd::<_, ByteHasher>(seq, k, w)
+}
+
+/// Like `minimizers_seq_simd`, but for a small window size `W` that is known at compile time.
+///
+/// Uses `sliding_min_tree_mapper` instead of the two-stacks sliding window minimum.
+pub fn small_w_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher, const W: usize>(
+    seq: SEQ,
+    k: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + W - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + W - 1, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, W);
+    let mut sliding_min = sliding_min_tree_mapper::<true, W>(k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let nthash = nthash((a, rk));
+        sliding_min(nthash)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Returns an iterator over the offset of the minimizer within each window, in `0..w`, instead of its absolute position.
+pub fn minimizer_offsets_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    k: usize,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u16> + Captures<&'s ()> {
+    minimizers_seq_scalar::<H>(seq, k, w)
+        .enumerate()
+        .map(|(i, pos)| (pos - i as u32) as u16)
+}
+
+/// Like `minimizers_seq_simd`, but returns the offset of the minimizer within each window, in `0..w`, instead of its absolute position.
+///
+/// The start of the window is subtracted in the same pass.
+pub fn minimizer_offsets_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+    // The absolute start of the window that ends at the added character, in each lane.
+    let n = add_remove.len().saturating_sub(l - 1);
+    let mut start: u32x8 = std::array::from_fn(|j| (j * n).wrapping_sub(l - 1) as u32).into();
+    let one = u32x8::splat(1);
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let nthash = nthash((a, rk));
+        let offset = sliding_min(nthash) - start;
+        start += one;
+        offset
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `minimizers_seq_simd`, but additionally returns the packed k-mer value of the minimizer of each window.
+///
+/// The k-mers are computed in the same pass, using a rolling k-mer per lane, and returned as their low and high 32 bits.
+/// See the `kmer` module for the packing format.
+pub fn minimizer_kmers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, [u32x8; 2])> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+    let mut kmers = kmer_mapper::<false, SEQ>(k, w, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let nthash = nthash((a, rk));
+        let pos = sliding_min(nthash);
+        (pos, kmers(a, pos))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `minimizers_seq_simd`, but additionally returns the hash of the minimizer of each window.
+///
+/// The hashes are taken from the same ntHash pass that is used to find the minimizers.
+pub fn minimizer_and_hash_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+    let mut hash_at_pos = hash_at_pos_mapper(k, w, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let nthash = nthash((a, rk));
+        let pos = sliding_min(nthash);
+        (pos, hash_at_pos(nthash, pos))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `minimizers_seq_simd`, but additionally returns the number of `C` and `G` characters in each window.
+///
+/// Only meaningful for 2-bit DNA.
+pub fn minimizer_gc_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+    let mut gc = gc_mapper();
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        (sliding_min(nthash), gc((a, rl)))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Returns an iterator over the absolute positions of the minimizers of a sequence, using spaced-seed hashes with `k = mask.len()`.
+/// Returns one value for each window of size `w+k-1` in the input.
+pub fn spaced_minimizers_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    mask: &[bool],
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let it = spaced_nthash_seq_scalar::<H>(seq, mask);
+    sliding_min_scalar::<true>(it, w)
+}
+
+/// Like [`minimizers_seq_simd`], but using spaced-seed hashes with `k = mask.len()`.
+/// Only the positions `i` with `mask[i]` contribute to the hash of each k-mer.
+pub fn spaced_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    mask: &[bool],
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let k = mask.len();
+    let l = k + w - 1;
+
+    // The spaced hash keeps its own buffer of removed bases.
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = spaced_nthash_mapper::<SEQ, H>(mask);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, _rk)| {
+        let nthash = nthash(a);
+        sliding_min(nthash)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `minimizers_seq_scalar`, but using 64-bit ntHash, of which the upper 32 bits are compared.
+pub fn minimizers64_seq_scalar<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let it = nthash64_seq_scalar::<false, SEQ>(seq, k);
+    sliding_min64_scalar(it, w)
+}
+
+/// Like `minimizers_seq_simd`, but using 64-bit ntHash, of which the upper 32 bits are compared.
+pub fn minimizers64_seq_simd<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash64_mapper::<false, SEQ>(k, w);
+    let mut sliding_min = sliding_min64_mapper(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| sliding_min(nthash((a, rk))));
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 // TRULY CANONICAL MINIMIZERS BELOW HERE
 // The minimizers above can take a canonical hash, but do not correctly break ties.
@@ -122,3 +425,227 @@ pub fn canonical_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
     head.by_ref().take(l - 1).for_each(drop);
     (head, padding)
 }
+
+/// Like [`canonical_minimizers_seq_scalar`], but using 64-bit ntHash, of which the upper 32 bits are compared.
+pub fn canonical_minimizers64_seq_scalar<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let kmer_hashes = nthash64_seq_scalar::<true, SEQ>(seq, k);
+    let lr = sliding_lr_min64_scalar(kmer_hashes, w);
+    let canonical = canonical::canonical_windows_seq_scalar(seq, k, w);
+    zip(canonical, lr).map(
+        |(canonical, (left, right))| {
+            if canonical {
+                left
+            } else {
+                right
+            }
+        },
+    )
+}
+
+/// Like [`canonical_minimizers_seq_simd`], but using 64-bit ntHash, of which the upper 32 bits are compared.
+pub fn canonical_minimizers64_seq_simd<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash64_mapper::<true, SEQ>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min64_mapper(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(nthash);
+        unsafe { std::mem::transmute::<_, u32x8>(canonical).blend(lmin, rmin) }
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like [`canonical_minimizers_seq_scalar`], but using the canonical anti-lex hash instead of ntHash.
+/// Only 2-bit DNA is supported.
+pub fn canonical_anti_lex_minimizers_seq_scalar<'s>(
+    seq: impl Seq<'s>,
+    k: usize,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let kmer_hashes = canonical_anti_lex_hash_seq_scalar(seq, k);
+    let left = sliding_min_scalar::<true>(kmer_hashes.clone(), w);
+    let right = sliding_min_scalar::<false>(kmer_hashes, w);
+    let canonical = canonical::canonical_windows_seq_scalar(seq, k, w);
+    zip(canonical, zip(left, right)).map(
+        |(canonical, (left, right))| {
+            if canonical {
+                left
+            } else {
+                right
+            }
+        },
+    )
+}
+
+/// Like [`canonical_minimizers_seq_simd`], but using the canonical anti-lex hash instead of ntHash.
+/// Only 2-bit DNA is supported.
+pub fn canonical_anti_lex_minimizers_seq_simd<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) =
+        seq.par_iter_bp_delayed_2(k + w - 1, canonical_anti_lex_delay(k), l);
+
+    let mut anti_lex = canonical_anti_lex_hash_mapper::<SEQ>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rm, rl)| {
+        let hash = anti_lex((a, rm));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(hash);
+        unsafe { std::mem::transmute::<i32x8, u32x8>(canonical).blend(lmin, rmin) }
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `canonical_minimizers_seq_simd`, but additionally returns the canonical packed k-mer value of the minimizer of each window.
+///
+/// The forward and reverse complement k-mers are rolled in the same pass.
+/// Only supported for 2-bit DNA.
+pub fn canonical_minimizer_kmers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, [u32x8; 2])> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+    let mut kmers = kmer_mapper::<true, SEQ>(k, w, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(nthash);
+        let pos = unsafe { std::mem::transmute::<i32x8, u32x8>(canonical).blend(lmin, rmin) };
+        (pos, kmers(a, pos))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like [`canonical_minimizers_seq_simd`], but also returns the canonical-window mask of each window,
+/// which is all ones when the window is read on the forward strand and the leftmost minimum is selected.
+pub fn canonical_minimizer_strand_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = unsafe { std::mem::transmute::<i32x8, u32x8>(canonical((a, rl))) };
+        let (lmin, rmin) = sliding_min(nthash);
+        (canonical.blend(lmin, rmin), canonical)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `canonical_minimizers_seq_simd`, but additionally returns the number of `C` and `G` characters in each window.
+///
+/// Only meaningful for 2-bit DNA.
+pub fn canonical_minimizer_gc_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+    let mut gc = gc_mapper();
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(nthash);
+        let pos = unsafe { std::mem::transmute::<i32x8, u32x8>(canonical).blend(lmin, rmin) };
+        (pos, gc((a, rl)))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Like `canonical_minimizers_seq_simd`, but additionally returns the canonical hash of the minimizer of each window.
+pub fn canonical_minimizer_hashes_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (u32x8, u32x8)> + Captures<&'s ()>,
+    usize,
+) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+    let mut hash_at_pos = hash_at_pos_mapper(k, w, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(nthash);
+        let pos = unsafe { std::mem::transmute::<i32x8, u32x8>(canonical).blend(lmin, rmin) };
+        (pos, hash_at_pos(nthash, pos))
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
@@ -0,0 +1,104 @@
+//! Mod-minimizers, which have lower density than random minimizers when `k` is large compared to `w`.
+//!
+//! Each window of `l=w+k-1` characters contains `l-t+1` t-mers, for some small `t <= k`.
+//! Let `x` be the position of the smallest t-mer in the window, relative to the start of the window.
+//! Then the k-mer at position `x mod w` is sampled.
+//!
+//! Typically, `t = r + ((k-r) mod w)` for some small `r` like 4, so that `t ≡ k (mod w)`.
+//!
+//! See: Groot Koerkamp and Pibiri, The mod-minimizer: a simple and efficient sampling algorithm for long k-mers, WABI 2024.
+use crate::nthash::{nthash_mapper, nthash_seq_scalar, Captures, CharHasher};
+use crate::sliding_min::{sliding_min_mapper, sliding_min_scalar};
+use itertools::Itertools;
+use packed_seq::Seq;
+use wide::u32x8;
+
+fn check_params(k: usize, w: usize, t: usize) {
+    assert!(w > 0);
+    assert!(
+        0 < t && t <= k,
+        "The t-mer length t={t} must satisfy 0 < t <= k={k}"
+    );
+}
+
+/// Returns the mod-minimizer of a window using a naive linear scan.
+pub fn mod_minimizer<'s, H: CharHasher>(seq: impl Seq<'s>, k: usize, t: usize) -> usize {
+    let w = seq.len() - k + 1;
+    check_params(k, w, t);
+    let x = nthash_seq_scalar::<false, H>(seq, t)
+        .map(|x| x & 0xffff_0000)
+        .position_min()
+        .unwrap();
+    x % w
+}
+
+/// Returns an iterator over the absolute positions of the mod-minimizers of a sequence.
+/// Returns one value for each window of size `w+k-1` in the input. Use
+/// `Itertools::dedup()` to obtain the distinct positions of the mod-minimizers.
+///
+/// Prefer `mod_minimizers_seq_simd` that internally uses SIMD.
+pub fn mod_minimizers_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    k: usize,
+    w: usize,
+    t: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    check_params(k, w, t);
+    // Number of t-mers in each window.
+    let wt = w + k - t;
+    let it = nthash_seq_scalar::<false, H>(seq, t);
+    sliding_min_scalar::<true>(it, wt)
+        .enumerate()
+        .map(move |(start, x)| {
+            let start = start as u32;
+            start + (x - start) % w as u32
+        })
+}
+
+/// Split the windows of the sequence into 8 chunks of equal length ~len/8.
+/// Then return the positions of the mod-minimizers of each of them in parallel using SIMD.
+pub fn mod_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+    t: usize,
+) -> (
+    impl ExactSizeIterator<Item = u32x8> + Captures<&'s ()>,
+    usize,
+) {
+    check_params(k, w, t);
+    let l = k + w - 1;
+    // Number of t-mers in each window.
+    let wt = l - t + 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(l, t - 1);
+    let len = add_remove.len();
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(t, wt);
+    let mut sliding_min = sliding_min_mapper::<true>(wt, t, len);
+
+    // The start of the current window in each lane.
+    // This is offset by -(l-1), so that it is correct once the first window is complete.
+    let n = len.saturating_sub(l - 1);
+    let mut window_start: u32x8 =
+        std::array::from_fn(|lane| (lane * n).wrapping_sub(l - 1) as u32).into();
+    let w_simd = u32x8::splat(w as u32);
+    // The relative position of the smallest t-mer is less than `wt`,
+    // so this many conditional subtractions compute it modulo `w`.
+    let reductions = (wt - 1) / w;
+
+    let mut head = add_remove.map(move |(a, rt)| {
+        let x = sliding_min(nthash((a, rt)));
+        let mut rel = x - window_start;
+        for _ in 0..reductions {
+            let ge = rel.min(w_simd).cmp_eq(w_simd);
+            rel -= ge & w_simd;
+        }
+        let pos = window_start + rel;
+        window_start += u32x8::splat(1);
+        pos
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
@@ -0,0 +1,223 @@
+//! Dataset-wide multiplicity of minimizers, to annotate repetitive anchors.
+//!
+//! A first pass [`MinimizerCounts::add`]s all sequences of a dataset, counting how often each minimizer is selected.
+//! A second pass [`MinimizerCounts::annotate`]s the minimizers of a sequence with these counts,
+//! so that mappers can down-weight anchors in repeats without an external k-mer counter.
+//! [`MinimizerCounts::seed_uniqueness`] summarizes this per record into a [`SeedUniqueness`] score, to triage reads before alignment.
+//!
+//! [`retain_infrequent`] instead filters the minimizers of a single sequence by their multiplicity within that sequence,
+//! similar to the high-frequency filter of minimap2.
+//! [`most_frequent`] returns the most frequent minimizers of a single sequence, e.g. to detect repeats or adapters in a record.
+//!
+//! Minimizers are identified by their 32-bit k-mer hash, so that hash collisions can (rarely) inflate counts.
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use packed_seq::Seq;
+
+/// Summary of how unique the minimizers (seeds) of a record are in a [`MinimizerCounts`] index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SeedUniqueness {
+    /// The number of selected minimizer positions.
+    pub num_seeds: usize,
+    /// The number of seeds whose minimizer occurs exactly once in the index.
+    pub num_unique: usize,
+    /// The number of seeds whose minimizer does not occur in the index.
+    pub num_absent: usize,
+}
+
+impl SeedUniqueness {
+    /// Maximum [`SeedUniqueness::score`], as for MAPQ.
+    pub const MAX_SCORE: u8 = 60;
+
+    /// The fraction of seeds that is unique in the index, or 0 for records without seeds.
+    pub fn fraction_unique(&self) -> f64 {
+        if self.num_seeds == 0 {
+            return 0.0;
+        }
+        self.num_unique as f64 / self.num_seeds as f64
+    }
+
+    /// A MAPQ-style score `-10 log10(1 - fraction_unique)`, rounded and capped at [`SeedUniqueness::MAX_SCORE`].
+    ///
+    /// Records whose seeds are all repetitive or absent score 0, and records with only unique seeds score the maximum.
+    pub fn score(&self) -> u8 {
+        let repetitive = 1.0 - self.fraction_unique();
+        if repetitive <= 0.0 {
+            return Self::MAX_SCORE;
+        }
+        (-10.0 * repetitive.log10())
+            .round()
+            .min(Self::MAX_SCORE as f64) as u8
+    }
+}
+
+/// The number of times each minimizer was selected in a dataset.
+#[derive(Clone, Debug, Default)]
+pub struct MinimizerCounts {
+    k: usize,
+    w: usize,
+    canonical: bool,
+    counts: HashMap<u32, u32>,
+    positions: Vec<u32>,
+    hashes: Vec<u32>,
+}
+
+impl MinimizerCounts {
+    /// Count (canonical) minimizers with the given `k` and `w`.
+    ///
+    /// For canonical minimizers, both strands of a k-mer are counted together.
+    pub fn new(k: usize, w: usize, canonical: bool) -> Self {
+        MinimizerCounts {
+            k,
+            w,
+            canonical,
+            ..Default::default()
+        }
+    }
+
+    /// Compute the positions and hashes of the minimizers of `seq` into the internal buffers.
+    fn minimizers<'s, S: Seq<'s>>(&mut self, seq: S) {
+        self.positions.clear();
+        self.hashes.clear();
+        if self.canonical {
+            crate::canonical_minimizer_positions_and_hashes(
+                seq,
+                self.k,
+                self.w,
+                &mut self.positions,
+                &mut self.hashes,
+            );
+        } else {
+            crate::minimizer_positions_and_hashes(
+                seq,
+                self.k,
+                self.w,
+                &mut self.positions,
+                &mut self.hashes,
+            );
+        }
+    }
+
+    /// Count each selected minimizer position of `seq`.
+    pub fn add<'s, S: Seq<'s>>(&mut self, seq: S) {
+        self.minimizers(seq);
+        for &hash in &self.hashes {
+            *self.counts.entry(hash).or_default() += 1;
+        }
+    }
+
+    /// The number of times the minimizer with k-mer `hash` was selected.
+    pub fn count(&self, hash: u32) -> u32 {
+        self.counts.get(&hash).copied().unwrap_or(0)
+    }
+
+    /// The number of distinct minimizers.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Whether no minimizers were counted.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Append `(pos, count)` to `out_vec` for each selected minimizer position of `seq`,
+    /// where `count` is the number of times its minimizer was selected in the dataset.
+    ///
+    /// Minimizers that were not counted have count 0.
+    pub fn annotate<'s, S: Seq<'s>>(&mut self, seq: S, out_vec: &mut Vec<(u32, u32)>) {
+        self.minimizers(seq);
+        out_vec.extend(
+            self.positions
+                .iter()
+                .zip(&self.hashes)
+                .map(|(&pos, &hash)| (pos, self.counts.get(&hash).copied().unwrap_or(0))),
+        );
+    }
+
+    /// The uniqueness of the minimizers of `seq` in this index.
+    pub fn seed_uniqueness<'s, S: Seq<'s>>(&mut self, seq: S) -> SeedUniqueness {
+        self.minimizers(seq);
+        let mut stats = SeedUniqueness {
+            num_seeds: self.hashes.len(),
+            ..Default::default()
+        };
+        for hash in &self.hashes {
+            match self.counts.get(hash) {
+                None => stats.num_absent += 1,
+                Some(1) => stats.num_unique += 1,
+                Some(_) => {}
+            }
+        }
+        stats
+    }
+
+    /// Append the [`SeedUniqueness`] of each record in `seqs` to `out_vec`.
+    pub fn seed_uniqueness_records<'s, S: Seq<'s>>(
+        &mut self,
+        seqs: &[S],
+        out_vec: &mut Vec<SeedUniqueness>,
+    ) {
+        out_vec.extend(seqs.iter().map(|&seq| self.seed_uniqueness(seq)));
+    }
+}
+
+thread_local! {
+    /// Buffers used by [`with_hash_buffer`] and [`retain_infrequent`], reused across calls.
+    static HASHES: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+    static COUNTS: RefCell<HashMap<u32, u32>> = RefCell::new(HashMap::new());
+}
+
+/// Call `f` with an empty thread-local buffer for hashes, to avoid allocations.
+pub fn with_hash_buffer<R>(f: impl FnOnce(&mut Vec<u32>) -> R) -> R {
+    HASHES.with(|hashes| {
+        let mut hashes = hashes.borrow_mut();
+        hashes.clear();
+        f(&mut hashes)
+    })
+}
+
+/// Remove the positions in `positions[start..]` whose hash occurs more than `max_freq` times in `hashes`.
+///
+/// `hashes[i]` is the hash of the minimizer at `positions[start + i]`. Positions are filtered in place.
+pub fn retain_infrequent(positions: &mut Vec<u32>, start: usize, hashes: &[u32], max_freq: u32) {
+    assert_eq!(positions.len() - start, hashes.len());
+    COUNTS.with(|counts| {
+        let mut counts = counts.borrow_mut();
+        counts.clear();
+        for &hash in hashes {
+            *counts.entry(hash).or_default() += 1;
+        }
+        let mut write_idx = start;
+        for (i, hash) in hashes.iter().enumerate() {
+            if counts[hash] <= max_freq {
+                positions[write_idx] = positions[start + i];
+                write_idx += 1;
+            }
+        }
+        positions.truncate(write_idx);
+    });
+}
+
+/// Append the `n` most frequent hashes in `hashes` to `out_vec`, as `(hash, count)`.
+///
+/// These are ordered by decreasing count, and ties by increasing hash. Fewer than `n` are appended when `hashes` has fewer distinct values.
+pub fn most_frequent(hashes: &[u32], n: usize, out_vec: &mut Vec<(u32, u32)>) {
+    COUNTS.with(|counts| {
+        let mut counts = counts.borrow_mut();
+        counts.clear();
+        for &hash in hashes {
+            *counts.entry(hash).or_default() += 1;
+        }
+        let start = out_vec.len();
+        out_vec.extend(counts.iter().map(|(&hash, &count)| (hash, count)));
+        let top = &mut out_vec[start..];
+        let order = |a: &(u32, u32), b: &(u32, u32)| b.1.cmp(&a.1).then(a.0.cmp(&b.0));
+        if n < top.len() {
+            top.select_nth_unstable_by(n, order);
+            out_vec.truncate(start + n);
+        }
+        out_vec[start..].sort_unstable_by(order);
+    });
+}
@@ -0,0 +1,53 @@
+//! Extraction of the sequence around each minimizer.
+//!
+//! For each minimizer position, the k-mer plus `flank` characters on either side is copied into a single packed [`SeqVec`],
+//! e.g. to generate training data for basecalling or other ML tasks.
+//! Flanks are clamped at the ends of the sequence (or record), so that neighborhoods near the ends are shorter.
+use std::ops::Range;
+
+use packed_seq::{Seq, SeqVec};
+
+/// The range of characters of `seq_len` covered by the k-mer at `pos` and `flank` characters on either side.
+#[inline(always)]
+pub fn neighborhood_range(pos: u32, seq_len: usize, k: usize, flank: usize) -> Range<usize> {
+    let pos = pos as usize;
+    pos.saturating_sub(flank)..(pos + k + flank).min(seq_len)
+}
+
+/// Append the neighborhood of each minimizer `positions` in `seq` to `out_seq`.
+///
+/// The neighborhood is the k-mer at the position plus `flank` characters on either side, clamped at the ends of `seq`.
+/// The range of each neighborhood in `out_seq` is appended to `out_ranges`.
+/// Its range in `seq` is given by [`neighborhood_range`].
+pub fn minimizer_neighborhoods<'s, V: SeqVec>(
+    seq: V::Seq<'s>,
+    positions: &[u32],
+    k: usize,
+    flank: usize,
+    out_seq: &mut V,
+    out_ranges: &mut Vec<Range<usize>>,
+) {
+    out_ranges.reserve(positions.len());
+    for &pos in positions {
+        let range = neighborhood_range(pos, seq.len(), k, flank);
+        out_ranges.push(out_seq.push_seq(seq.slice(range)));
+    }
+}
+
+/// Append the neighborhood of each minimizer of each record to `out_seq`, with flanks clamped at the ends of each record.
+///
+/// `positions[i]` are the minimizer positions in `seqs[i]`, as returned by the `_records` functions.
+/// The ranges in `out_seq` of all neighborhoods are appended to `out_ranges`, in order of records and positions.
+pub fn minimizer_neighborhoods_records<'s, V: SeqVec>(
+    seqs: &[V::Seq<'s>],
+    positions: &[Vec<u32>],
+    k: usize,
+    flank: usize,
+    out_seq: &mut V,
+    out_ranges: &mut Vec<Range<usize>>,
+) {
+    assert_eq!(seqs.len(), positions.len());
+    for (&seq, positions) in seqs.iter().zip(positions) {
+        minimizer_neighborhoods(seq, positions, k, flank, out_seq, out_ranges);
+    }
+}
@@ -146,6 +146,76 @@ impl CharHasher for MulHasher {
     }
 }
 
+/// Pseudo-random values for each of the 256 byte values, generated using SplitMix64.
+const HASHES_BYTE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut x: u64 = 0;
+    let mut i = 0;
+    while i < 256 {
+        x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        table[i] = (z >> 32) as u32;
+        i += 1;
+    }
+    table
+};
+
+/// ntHash with a pseudo-random table value for each of the 256 byte values, for general alphabets such as amino acids.
+///
+/// Unlike mulHash, symbols whose values only differ in a few bits still get unrelated hashes.
+/// There is no complement, so `c` equals `f` and canonical hashes are not strand-independent.
+#[derive(Clone)]
+pub struct ByteHasher {
+    rot: u32,
+}
+
+impl ByteHasher {
+    #[inline(always)]
+    fn lookup(&self, b: u32x8, rot: u32) -> u32x8 {
+        u32x8::new(
+            b.to_array()
+                .map(|b| HASHES_BYTE[b as u8 as usize].rotate_left(rot)),
+        )
+    }
+}
+
+impl CharHasher for ByteHasher {
+    fn new<'s, SEQ: Seq<'s>>(k: usize) -> Self {
+        ByteHasher {
+            rot: (k as u32 - 1) % 32,
+        }
+    }
+
+    fn f(&self, b: u8) -> u32 {
+        HASHES_BYTE[b as usize]
+    }
+    fn c(&self, b: u8) -> u32 {
+        HASHES_BYTE[b as usize]
+    }
+    fn f_rot(&self, b: u8) -> u32 {
+        HASHES_BYTE[b as usize].rotate_left(self.rot)
+    }
+    fn c_rot(&self, b: u8) -> u32 {
+        HASHES_BYTE[b as usize].rotate_left(self.rot)
+    }
+
+    fn simd_f(&self, b: u32x8) -> u32x8 {
+        self.lookup(b, 0)
+    }
+    fn simd_c(&self, b: u32x8) -> u32x8 {
+        self.lookup(b, 0)
+    }
+    fn simd_f_rot(&self, b: u32x8) -> u32x8 {
+        self.lookup(b, self.rot)
+    }
+    fn simd_c_rot(&self, b: u32x8) -> u32x8 {
+        self.lookup(b, self.rot)
+    }
+}
+
 /// Naively compute the 32-bit NT hash of a single k-mer.
 /// When `RC` is false, compute a forward hash.
 /// When `RC` is true, compute a canonical hash.
@@ -258,3 +328,169 @@ pub fn nthash_mapper<'s, const RC: bool, SEQ: Seq<'s>, H: CharHasher>(
         }
     }
 }
+
+/// Like [`nthash_mapper`], but returns the forward hash and the reverse complement hash separately.
+/// The reverse complement hash is the forward hash of the reverse complement k-mer.
+pub fn nthash_fw_rc_mapper<'s, SEQ: Seq<'s>, H: CharHasher>(
+    k: usize,
+    w: usize,
+) -> impl FnMut((S, S)) -> (S, S) + Clone {
+    let hasher = H::new::<SEQ>(k);
+
+    assert!(k > 0);
+    assert!(w > 0);
+
+    let mut fw = 0u32;
+    let mut rc = 0u32;
+    for _ in 0..k - 1 {
+        fw = fw.rotate_left(1) ^ hasher.f(0);
+        rc = rc.rotate_right(1) ^ hasher.c_rot(0);
+    }
+
+    let mut h_fw = S::splat(fw);
+    let mut h_rc = S::splat(rc);
+
+    move |(a, r)| {
+        let hfw_out = ((h_fw << 1) | (h_fw >> 31)) ^ hasher.simd_f(a);
+        h_fw = hfw_out ^ hasher.simd_f_rot(r);
+        let hrc_out = ((h_rc >> 1) | (h_rc << 31)) ^ hasher.simd_c_rot(a);
+        h_rc = hrc_out ^ hasher.simd_c(r);
+        (hfw_out, hrc_out)
+    }
+}
+
+/// A function that 'eats' k-mer hashes and the current minimizer positions, and returns the hashes at those positions.
+///
+/// Keeps a ring buffer of the last `w` hashes of each lane.
+/// `len` is the number of values in each chunk, as passed to `sliding_min_mapper`.
+/// The first `k+w-2` returned values are bogus, since they correspond to incomplete windows.
+pub fn hash_at_pos_mapper(k: usize, w: usize, len: usize) -> impl FnMut(S, S) -> S + Clone {
+    assert!(k > 0);
+    assert!(w > 0);
+
+    // Number of windows in each lane, and thus the offset of each lane.
+    let n = len.saturating_sub(k + w - 2);
+    let lane_offsets: [u32; 8] = from_fn(|l| (l * n) as u32);
+
+    let mut ring_buf = vec![S::splat(0); w];
+    // Index of the character that is added next.
+    let mut i = 0;
+
+    #[inline(always)]
+    move |hash, pos| {
+        // The k-mer starting at lane-local position `i-(k-1)` is now complete.
+        if i + 1 >= k {
+            ring_buf[(i + 1 - k) % w] = hash;
+        }
+        i += 1;
+
+        let pos = pos.to_array();
+        S::new(from_fn(|l| {
+            let local = pos[l].wrapping_sub(lane_offsets[l]) as usize;
+            ring_buf[local % w].as_array_ref()[l]
+        }))
+    }
+}
+
+/// The contiguous runs of care positions of a spaced seed `mask`, as `(offset, len)`.
+fn care_blocks(mask: &[bool]) -> Vec<(usize, usize)> {
+    let mut blocks = vec![];
+    let mut i = 0;
+    while i < mask.len() {
+        if !mask[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < mask.len() && mask[i] {
+            i += 1;
+        }
+        blocks.push((start, i - start));
+    }
+    assert!(
+        !blocks.is_empty(),
+        "Spaced seed must have at least one care position"
+    );
+    blocks
+}
+
+/// Returns a scalar iterator over the 32-bit forward hashes of all spaced k-mers in the sequence, with `k = mask.len()`.
+/// Only the characters at positions `i` with `mask[i]` (care positions) contribute to the hash.
+///
+/// The hash is the xor over the contiguous blocks of care positions of their ntHash, rotated by the
+/// distance from the end of the block to the end of the k-mer.
+/// When all positions are care positions, this equals `nthash_seq_scalar::<false, H>`.
+pub fn spaced_nthash_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    mask: &[bool],
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    let k = mask.len();
+    let n = (seq.len() + 1).saturating_sub(k);
+    let mut blocks = care_blocks(mask)
+        .into_iter()
+        .map(|(o, len)| (nthash_seq_scalar::<false, H>(seq, len).skip(o), k - o - len))
+        .collect::<Vec<_>>();
+    (0..n).map(move |_| {
+        blocks.iter_mut().fold(0, |h, (it, rot)| {
+            h ^ it.next().unwrap().rotate_left(*rot as u32)
+        })
+    })
+}
+
+/// A function that 'eats' added bases, and returns the updated forward hash of the spaced k-mer ending there, with `k = mask.len()`.
+/// The first k-1 added bases of each lane must be preceded by 0 bases, and the first k-1 returned values will be useless.
+///
+/// Keeps one ntHash per block of care positions, and a ring buffer of the last `k` bases to remove them again.
+/// Matches `spaced_nthash_seq_scalar`.
+pub fn spaced_nthash_mapper<'s, SEQ: Seq<'s>, H: CharHasher>(
+    mask: &[bool],
+) -> impl FnMut(S) -> S + Clone {
+    let k = mask.len();
+    assert!(k > 0);
+
+    #[derive(Clone)]
+    struct Block<M> {
+        len: usize,
+        rot: u32,
+        nthash: M,
+        /// The last `rot+1` hashes of the block.
+        hashes: Vec<S>,
+    }
+    let mut blocks = care_blocks(mask)
+        .into_iter()
+        .map(|(o, len)| {
+            let rot = k - o - len;
+            Block {
+                len,
+                rot: (rot % 32) as u32,
+                nthash: nthash_mapper::<false, SEQ, H>(len, 1),
+                hashes: vec![S::splat(0); rot + 1],
+            }
+        })
+        .collect::<Vec<_>>();
+    // The last `k` added bases.
+    let mut bases = vec![S::splat(0); k];
+    // Index of the base that is added next.
+    let mut i = 0;
+
+    move |a| {
+        bases[i % k] = a;
+        let mut h = S::splat(0);
+        for block in &mut blocks {
+            // The base added `len-1` steps ago leaves the block.
+            let r = bases[(i + k + 1 - block.len) % k];
+            let hb = (block.nthash)((a, r));
+            let n = block.hashes.len();
+            block.hashes[i % n] = hb;
+            // The block of the current k-mer ended `rot` bases ago.
+            let hb = block.hashes[(i + 1) % n];
+            h ^= if block.rot == 0 {
+                hb
+            } else {
+                (hb << block.rot) | (hb >> (32 - block.rot))
+            };
+        }
+        i += 1;
+        h
+    }
+}
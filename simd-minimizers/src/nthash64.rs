@@ -0,0 +1,265 @@
+//! 64-bit ntHash of the k-mers in a DNA sequence.
+//!
+//! The default pipeline uses 32-bit hashes, of which the sliding window minimum only compares the upper 16 bits.
+//! For large `k` on large genomes, this gives many ties between distinct k-mers.
+//! This module provides 64-bit ntHash, stored in 8 lanes as their low and high `u32x8` halves.
+//! The sliding window minimum compares only the upper 32 bits of each hash, and breaks ties towards the leftmost position.
+//! Only 2-bit DNA is supported.
+use std::array::from_fn;
+use std::ops::{Add, BitAnd, BitOr, BitXor};
+
+use crate::intrinsics::table_lookup;
+use crate::nthash::Captures;
+use crate::S;
+use packed_seq::{complement_base, Seq};
+
+/// Original 64-bit ntHash seed values.
+pub(crate) const HASHES_F: [u64; 4] = [
+    0x3c8b_fbb3_95c6_0474,
+    0x3193_c185_62a0_2b4c,
+    0x2032_3ed0_8257_2324,
+    0x2955_49f5_4be2_4456,
+];
+
+/// Eight 64-bit lanes, stored as their low and high 32-bit halves.
+///
+/// Splitting the lanes keeps all operations on `u32x8`, so that table lookups use [`table_lookup`]
+/// and the low halves are available without shuffling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct S64 {
+    /// The low 32 bits of each lane.
+    pub lo: S,
+    /// The high 32 bits of each lane.
+    pub hi: S,
+}
+
+impl S64 {
+    #[inline(always)]
+    pub fn splat(x: u64) -> Self {
+        S64 {
+            lo: S::splat(x as u32),
+            hi: S::splat((x >> 32) as u32),
+        }
+    }
+
+    #[inline(always)]
+    pub fn from_array(x: [u64; 8]) -> Self {
+        S64 {
+            lo: S::new(x.map(|x| x as u32)),
+            hi: S::new(x.map(|x| (x >> 32) as u32)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_array(self) -> [u64; 8] {
+        let (lo, hi) = (self.lo.as_array_ref(), self.hi.as_array_ref());
+        from_fn(|i| lo[i] as u64 | (hi[i] as u64) << 32)
+    }
+
+    /// Rotate each lane left by 1.
+    #[inline(always)]
+    pub fn rotate_left_1(self) -> Self {
+        S64 {
+            lo: (self.lo << 1) | (self.hi >> 31),
+            hi: (self.hi << 1) | (self.lo >> 31),
+        }
+    }
+
+    /// Rotate each lane right by 1.
+    #[inline(always)]
+    pub fn rotate_right_1(self) -> Self {
+        S64 {
+            lo: (self.lo >> 1) | (self.hi << 31),
+            hi: (self.hi >> 1) | (self.lo << 31),
+        }
+    }
+
+    /// Lane-wise unsigned minimum of the full 64-bit values.
+    #[inline(always)]
+    pub fn min(self, other: Self) -> Self {
+        // Compare the high halves first, and the low halves on ties.
+        let hi_eq = self.hi.cmp_eq(other.hi);
+        let hi_le = self.hi.min(other.hi).cmp_eq(self.hi);
+        let lo_le = self.lo.min(other.lo).cmp_eq(self.lo);
+        let le = hi_eq.blend(lo_le, hi_le);
+        S64 {
+            lo: le.blend(self.lo, other.lo),
+            hi: le.blend(self.hi, other.hi),
+        }
+    }
+
+    /// Lane-wise unsigned maximum of the full 64-bit values.
+    #[inline(always)]
+    pub fn max(self, other: Self) -> Self {
+        let hi_eq = self.hi.cmp_eq(other.hi);
+        let hi_ge = self.hi.max(other.hi).cmp_eq(self.hi);
+        let lo_ge = self.lo.max(other.lo).cmp_eq(self.lo);
+        let ge = hi_eq.blend(lo_ge, hi_ge);
+        S64 {
+            lo: ge.blend(self.lo, other.lo),
+            hi: ge.blend(self.hi, other.hi),
+        }
+    }
+
+    /// Truncate each lane to its low 32 bits.
+    #[inline(always)]
+    pub fn low_u32(self) -> S {
+        self.lo
+    }
+}
+
+macro_rules! impl_s64_op {
+    ($trait:ident, $fn:ident) => {
+        impl $trait for S64 {
+            type Output = S64;
+            #[inline(always)]
+            fn $fn(self, rhs: S64) -> S64 {
+                S64 {
+                    lo: self.lo.$fn(rhs.lo),
+                    hi: self.hi.$fn(rhs.hi),
+                }
+            }
+        }
+    };
+}
+impl_s64_op!(BitAnd, bitand);
+impl_s64_op!(BitOr, bitor);
+impl_s64_op!(BitXor, bitxor);
+
+/// Wrapping addition.
+impl Add for S64 {
+    type Output = S64;
+    #[inline(always)]
+    fn add(self, rhs: S64) -> S64 {
+        let lo = self.lo + rhs.lo;
+        // The low halves overflow when their sum is less than either of them.
+        // The mask is `u32::MAX` for each carry, so subtracting it adds the carry.
+        let carry = !lo.max(self.lo).cmp_eq(lo);
+        S64 {
+            lo,
+            hi: self.hi + rhs.hi - carry,
+        }
+    }
+}
+
+/// The 64-bit ntHash tables for a given `k`.
+#[derive(Clone)]
+pub struct NtHasher64 {
+    f: [u64; 4],
+    c: [u64; 4],
+    f_rot: [u64; 4],
+    c_rot: [u64; 4],
+    /// The low and high halves of the tables, repeated in both 128-bit halves of a SIMD register for [`table_lookup`].
+    simd_f: (S, S),
+    simd_c: (S, S),
+    simd_f_rot: (S, S),
+    simd_c_rot: (S, S),
+}
+
+impl NtHasher64 {
+    pub fn new<'s, SEQ: Seq<'s>>(k: usize) -> Self {
+        assert_eq!(
+            SEQ::BITS_PER_CHAR,
+            2,
+            "64-bit ntHash only supports 2-bit DNA."
+        );
+        let rot = k as u32 - 1;
+        let f = HASHES_F;
+        let c = from_fn(|i| HASHES_F[complement_base(i as u8) as usize]);
+        let f_rot = f.map(|h| h.rotate_left(rot));
+        let c_rot = c.map(|h| h.rotate_left(rot));
+        let simd = |t: [u64; 4]| {
+            (
+                S::new(from_fn(|i| t[i % 4] as u32)),
+                S::new(from_fn(|i| (t[i % 4] >> 32) as u32)),
+            )
+        };
+        NtHasher64 {
+            f,
+            c,
+            f_rot,
+            c_rot,
+            simd_f: simd(f),
+            simd_c: simd(c),
+            simd_f_rot: simd(f_rot),
+            simd_c_rot: simd(c_rot),
+        }
+    }
+
+    #[inline(always)]
+    fn lookup(table: (S, S), b: S) -> S64 {
+        S64 {
+            lo: table_lookup(table.0, b),
+            hi: table_lookup(table.1, b),
+        }
+    }
+}
+
+/// Returns an iterator over the 64-bit ntHashes of all k-mers in the sequence.
+/// Set `RC` to true for canonical ntHash.
+pub fn nthash64_seq_scalar<'s, const RC: bool, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+) -> impl ExactSizeIterator<Item = u64> + Captures<&'s ()> + Clone {
+    assert!(k > 0);
+    let hasher = NtHasher64::new::<SEQ>(k);
+
+    let mut hfw: u64 = 0;
+    let mut hrc: u64 = 0;
+    let mut add = seq.iter_bp();
+    let remove = seq.iter_bp();
+    add.by_ref().take(k - 1).for_each(|a| {
+        hfw = hfw.rotate_left(1) ^ hasher.f[a as usize];
+        if RC {
+            hrc = hrc.rotate_right(1) ^ hasher.c_rot[a as usize];
+        }
+    });
+    add.zip(remove).map(move |(a, r)| {
+        let hfw_out = hfw.rotate_left(1) ^ hasher.f[a as usize];
+        hfw = hfw_out ^ hasher.f_rot[r as usize];
+        if RC {
+            let hrc_out = hrc.rotate_right(1) ^ hasher.c_rot[a as usize];
+            hrc = hrc_out ^ hasher.c[r as usize];
+            hfw_out.wrapping_add(hrc_out)
+        } else {
+            hfw_out
+        }
+    })
+}
+
+/// A function that 'eats' added and removed bases, and returns the updated 64-bit hash.
+/// The distance between them must be k-1, and the first k-1 removed bases must be 0.
+/// The first k-1 returned values will be useless.
+///
+/// Set `RC` to true for canonical ntHash.
+pub fn nthash64_mapper<'s, const RC: bool, SEQ: Seq<'s>>(
+    k: usize,
+    w: usize,
+) -> impl FnMut((S, S)) -> S64 + Clone {
+    let hasher = NtHasher64::new::<SEQ>(k);
+
+    assert!(k > 0);
+    assert!(w > 0);
+
+    let mut fw = 0u64;
+    let mut rc = 0u64;
+    for _ in 0..k - 1 {
+        fw = fw.rotate_left(1) ^ hasher.f[0];
+        rc = rc.rotate_right(1) ^ hasher.c_rot[0];
+    }
+
+    let mut h_fw = S64::splat(fw);
+    let mut h_rc = S64::splat(rc);
+
+    move |(a, r)| {
+        let hfw_out = h_fw.rotate_left_1() ^ NtHasher64::lookup(hasher.simd_f, a);
+        h_fw = hfw_out ^ NtHasher64::lookup(hasher.simd_f_rot, r);
+        if RC {
+            let hrc_out = h_rc.rotate_right_1() ^ NtHasher64::lookup(hasher.simd_c_rot, a);
+            h_rc = hrc_out ^ NtHasher64::lookup(hasher.simd_c, r);
+            hfw_out + hrc_out
+        } else {
+            hfw_out
+        }
+    }
+}
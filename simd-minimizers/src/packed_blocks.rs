@@ -0,0 +1,171 @@
+//! Minimizers of a stream of 2-bit packed blocks with run-length `N` masks, as produced by some decoders of compressed archives.
+//!
+//! Each [`PackedBlock`] holds 2-bit packed bases in the layout of [`PackedSeq`], and the ranges of `N` (or other invalid) characters in it.
+//! The blocks are concatenated without decoding them to ASCII: the packed bytes of each run of valid bases are appended to a buffer,
+//! which is processed using SIMD once the run ends, or once it reaches `flush_len` bases.
+//! Windows containing an `N` are skipped, as for [`minimizer_positions_masked`](crate::minimizer_positions_masked).
+//! Positions are relative to the start of the first block.
+//!
+//! ```
+//! use simd_minimizers::packed_blocks::PackedBlock;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//!
+//! // `ACGTACGTAC` and `NNGTACGTAC`, each packed on its own.
+//! let a = PackedSeqVec::from_ascii(b"ACGTACGTAC");
+//! let b = PackedSeqVec::from_ascii(b"AAGTACGTAC");
+//! let blocks = [
+//!     PackedBlock { data: &a.seq, len: 10, n_runs: &[] },
+//!     PackedBlock { data: &b.seq, len: 10, n_runs: &[0..2] },
+//! ];
+//! let mut positions = vec![];
+//! simd_minimizers::minimizer_positions_packed_blocks(blocks, 5, 3, &mut positions);
+//! // K-mers overlapping the `N`s at positions 10 and 11 are never selected.
+//! assert!(positions.iter().all(|&p| p + 5 <= 10 || p >= 12));
+//! ```
+use std::ops::Range;
+
+use packed_seq::PackedSeq;
+
+use crate::packed_file::PADDING;
+
+/// A block of 2-bit packed bases, with the ranges of invalid characters in it.
+#[derive(Clone, Copy, Debug)]
+pub struct PackedBlock<'a> {
+    /// The bases, packed 4 per byte starting at the least significant bits, as in [`PackedSeq`].
+    pub data: &'a [u8],
+    /// The number of bases in the block.
+    pub len: usize,
+    /// Sorted, disjoint ranges of invalid characters, relative to the start of the block.
+    /// The bases of `data` in these ranges are ignored.
+    pub n_runs: &'a [Range<usize>],
+}
+
+/// The valid bases of the current run, packed as in [`PackedSeq`].
+struct Run {
+    bytes: Vec<u8>,
+    len: usize,
+    /// The position of the first base of the run in the stream.
+    start: usize,
+}
+
+impl Run {
+    /// Append the bases `range` of `data`.
+    fn push(&mut self, data: &[u8], range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        if self.len.is_multiple_of(4) && range.start.is_multiple_of(4) {
+            // Whole bytes can be copied, after which the bits past the end are cleared.
+            self.bytes
+                .extend_from_slice(&data[range.start / 4..range.end.div_ceil(4)]);
+            self.len += range.len();
+            if !self.len.is_multiple_of(4) {
+                *self.bytes.last_mut().unwrap() &= (1 << (2 * (self.len % 4))) - 1;
+            }
+            return;
+        }
+        for p in range {
+            let base = (data[p / 4] >> (2 * (p % 4))) & 3;
+            if self.len.is_multiple_of(4) {
+                self.bytes.push(0);
+            }
+            self.bytes[self.len / 4] |= base << (2 * (self.len % 4));
+            self.len += 1;
+        }
+    }
+
+    /// Run `positions` on the run, and append the positions, shifted by the start of the run, to `out_vec`.
+    /// A first position equal to the last position of `out_vec` is skipped.
+    fn process(
+        &mut self,
+        l: usize,
+        out_vec: &mut Vec<u32>,
+        run_pos: &mut Vec<u32>,
+        positions: &mut impl FnMut(PackedSeq, &mut Vec<u32>),
+    ) {
+        if self.len < l {
+            return;
+        }
+        let num_bytes = self.bytes.len();
+        // Pad the bytes, so that SIMD reads past the end stay in bounds.
+        self.bytes.resize(num_bytes + PADDING, 0);
+        let seq = PackedSeq {
+            seq: &self.bytes,
+            offset: 0,
+            len: self.len,
+        };
+        run_pos.clear();
+        positions(seq, run_pos);
+        self.bytes.truncate(num_bytes);
+        for &pos in run_pos.iter() {
+            let pos = pos + self.start as u32;
+            if out_vec.last() != Some(&pos) {
+                out_vec.push(pos);
+            }
+        }
+    }
+
+    /// Start a new run at position `start` of the stream.
+    fn restart(&mut self, start: usize) {
+        self.bytes.clear();
+        self.len = 0;
+        self.start = start;
+    }
+
+    /// Keep only the last `l-1` bases, so that the next window overlaps the processed ones.
+    fn keep_tail(&mut self, l: usize) {
+        let keep = self.len.min(l - 1);
+        let from = self.len - keep;
+        let bytes = std::mem::take(&mut self.bytes);
+        self.restart(self.start + from);
+        self.push(&bytes, from..from + keep);
+    }
+}
+
+/// Run `positions` on the runs of valid bases of the concatenated `blocks`, and append the deduplicated positions to `out_vec`.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(seq, out_vec)` must append the positions of all windows in `seq` to `out_vec`.
+/// Runs longer than `flush_len` are processed in parts that overlap by `l-1` bases.
+pub fn positions_in_packed_blocks<'a>(
+    blocks: impl IntoIterator<Item = PackedBlock<'a>>,
+    l: usize,
+    flush_len: usize,
+    out_vec: &mut Vec<u32>,
+    mut positions: impl FnMut(PackedSeq, &mut Vec<u32>),
+) {
+    let flush_len = flush_len.max(l);
+    let mut run = Run {
+        bytes: vec![],
+        len: 0,
+        start: 0,
+    };
+    let mut run_pos = vec![];
+    let mut block_start = 0;
+    for block in blocks {
+        assert!(
+            block.data.len() * 4 >= block.len,
+            "Block data is too short."
+        );
+        let mut i = 0;
+        for n_run in block.n_runs {
+            assert!(
+                i <= n_run.start && n_run.end <= block.len,
+                "N runs must be sorted, disjoint, and inside the block."
+            );
+            run.push(block.data, i..n_run.start);
+            if !n_run.is_empty() {
+                run.process(l, out_vec, &mut run_pos, &mut positions);
+                run.restart(block_start + n_run.end);
+            }
+            i = n_run.end;
+        }
+        run.push(block.data, i..block.len);
+        if run.len >= flush_len {
+            run.process(l, out_vec, &mut run_pos, &mut positions);
+            run.keep_tail(l);
+        }
+        block_start += block.len;
+    }
+    run.process(l, out_vec, &mut run_pos, &mut positions);
+}
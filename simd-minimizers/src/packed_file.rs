@@ -0,0 +1,93 @@
+//! A file format for packed sequences that can be used without copying, e.g. from a memory-mapped file.
+//!
+//! [`write_packed`] stores a [`PackedSeq`] as a small header followed by its 2-bit packed bytes.
+//! [`packed_seq_from_bytes`] returns a [`PackedSeq`] that points directly into these bytes,
+//! so that repeated analyses of the same reference skip the packing step entirely.
+//! The bytes can come from any source, e.g. `std::fs::read`. This crate does not memory-map files itself;
+//! callers that want to can pass the bytes of a memory map created with a crate such as `memmap2`.
+//!
+//! The header consists of four little-endian `u64`s: the magic [`MAGIC`], the length of the sequence in characters,
+//! the offset in characters of the sequence in its first byte, and the number of data bytes that follow the header.
+//! The data is followed by zero padding, so that SIMD reads past the end of the sequence stay in bounds.
+//!
+//! ```
+//! use packed_seq::{PackedSeqVec, SeqVec};
+//! use simd_minimizers::packed_file::{packed_seq_from_bytes, write_packed};
+//!
+//! let seq = PackedSeqVec::from_ascii(b"ACGTACGTTTGACCAGTACGATCAGTCGATCGA");
+//! let mut bytes = vec![];
+//! write_packed(seq.as_slice(), &mut bytes).unwrap();
+//!
+//! let mapped = packed_seq_from_bytes(&bytes).unwrap();
+//! let mut positions = vec![];
+//! simd_minimizers::minimizer_positions(mapped, 5, 7, &mut positions);
+//! ```
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use packed_seq::{PackedSeq, Seq};
+
+/// The first 8 bytes of a packed sequence file.
+pub const MAGIC: [u8; 8] = *b"SMPACK01";
+
+/// The length in bytes of the header.
+pub const HEADER_LEN: usize = 32;
+
+/// The number of zero bytes written after the data.
+pub const PADDING: usize = 48;
+
+/// Write `seq` in the packed sequence format to `writer`.
+pub fn write_packed(seq: PackedSeq, mut writer: impl Write) -> io::Result<()> {
+    let start = seq.offset / 4;
+    let offset = seq.offset % 4;
+    let data = &seq.seq[start..start + (offset + seq.len()).div_ceil(4)];
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&(seq.len() as u64).to_le_bytes())?;
+    writer.write_all(&(offset as u64).to_le_bytes())?;
+    writer.write_all(&(data.len() as u64).to_le_bytes())?;
+    writer.write_all(data)?;
+    writer.write_all(&[0; PADDING])
+}
+
+/// Write `seq` in the packed sequence format to the file at `path`.
+pub fn save_packed(seq: PackedSeq, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_packed(seq, &mut writer)?;
+    writer.flush()
+}
+
+/// A [`PackedSeq`] pointing into `bytes`, which must be in the packed sequence format.
+///
+/// No data is copied. Returns an error when the header is invalid, or the data or its padding is truncated.
+pub fn packed_seq_from_bytes(bytes: &[u8]) -> io::Result<PackedSeq<'_>> {
+    if bytes.len() < HEADER_LEN || bytes[..8] != MAGIC {
+        return Err(invalid_data("not a packed sequence file"));
+    }
+    let field = |i: usize| {
+        let value = u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap());
+        usize::try_from(value).map_err(|_| invalid_data("packed sequence header field overflows"))
+    };
+    let (len, offset, data_len) = (field(1)?, field(2)?, field(3)?);
+    let chars = offset
+        .checked_add(len)
+        .ok_or_else(|| invalid_data("packed sequence header field overflows"))?;
+    if offset >= 4 || data_len != chars.div_ceil(4) {
+        return Err(invalid_data("inconsistent packed sequence header"));
+    }
+    let total_len = data_len
+        .checked_add(HEADER_LEN + PADDING)
+        .ok_or_else(|| invalid_data("packed sequence header field overflows"))?;
+    if bytes.len() < total_len {
+        return Err(invalid_data("truncated packed sequence data"));
+    }
+    Ok(PackedSeq {
+        seq: &bytes[HEADER_LEN..],
+        offset,
+        len,
+    })
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,86 @@
+//! Multi-threaded minimizers of long sequences, using `rayon`.
+//!
+//! The sequence is split into chunks that overlap by `l-1=k+w-2` characters, so that each window is contained in exactly one chunk.
+//! Each chunk is processed independently using SIMD, and the results are shifted by the chunk start,
+//! concatenated, and deduplicated at the chunk boundaries.
+use packed_seq::Seq;
+use rayon::prelude::*;
+
+use crate::collect::run_len;
+
+/// Chunks are never shorter than this many windows, to amortize the per-chunk overhead.
+const MIN_CHUNK_WINDOWS: usize = 1 << 16;
+
+/// The `start..end` character ranges of the chunks to process.
+pub fn chunk_ranges(len: usize, l: usize, num_chunks: usize) -> Vec<(usize, usize)> {
+    let num_windows = (len + 1).saturating_sub(l);
+    if num_windows == 0 {
+        return vec![(0, len)];
+    }
+    let chunk_windows = num_windows
+        .div_ceil(num_chunks.max(1))
+        .max(MIN_CHUNK_WINDOWS);
+    (0..num_windows)
+        .step_by(chunk_windows)
+        .map(|start| (start, (start + chunk_windows + l - 1).min(len)))
+        .collect()
+}
+
+/// Run `positions` on overlapping chunks of `seq` in parallel, and append the deduplicated positions to `out_vec`.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(chunk, out_vec)` must append the positions of all windows in `chunk` to `out_vec`.
+///
+/// When `num_threads` is 0 or equals the number of threads of the current rayon thread pool,
+/// which is the global pool unless called inside [`rayon::ThreadPool::install`], the chunks are processed on that pool.
+/// Otherwise, a new pool with `num_threads` threads is built for this call.
+pub fn positions_par<'s, SEQ: Seq<'s> + Send + Sync>(
+    seq: SEQ,
+    l: usize,
+    num_threads: usize,
+    out_vec: &mut Vec<u32>,
+    positions: impl Fn(SEQ, &mut Vec<u32>) + Sync,
+) {
+    assert!(
+        seq.len() <= 1 << 32,
+        "Positions are 32 bit. Split the input into 4GB chunks first."
+    );
+    if num_threads == 0 || num_threads == rayon::current_num_threads() {
+        positions_par_in_pool(seq, l, out_vec, &positions);
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .expect("Failed to build the rayon thread pool.");
+        pool.install(|| positions_par_in_pool(seq, l, out_vec, &positions));
+    }
+}
+
+/// [`positions_par`] on the current rayon thread pool.
+fn positions_par_in_pool<'s, SEQ: Seq<'s> + Send + Sync>(
+    seq: SEQ,
+    l: usize,
+    out_vec: &mut Vec<u32>,
+    positions: &(impl Fn(SEQ, &mut Vec<u32>) + Sync),
+) {
+    let num_chunks = rayon::current_num_threads();
+    let chunks = chunk_ranges(seq.len(), l, num_chunks)
+        .into_par_iter()
+        .map(|(start, end)| {
+            let mut chunk_pos = vec![];
+            positions(seq.slice(start..end), &mut chunk_pos);
+            // Does not overflow, since all positions are less than `seq.len()`.
+            let start = start as u32;
+            for pos in &mut chunk_pos {
+                *pos += start;
+            }
+            chunk_pos
+        })
+        .collect::<Vec<_>>();
+
+    for chunk_pos in chunks {
+        // Chunks overlap by l-1 characters, so the first minimizer may equal the last one of the previous chunk.
+        let skip = run_len(out_vec, &chunk_pos);
+        out_vec.extend_from_slice(&chunk_pos[skip..]);
+    }
+}
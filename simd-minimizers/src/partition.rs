@@ -0,0 +1,36 @@
+//! Partitioning the super-k-mers of a sequence over buckets by their minimizer, as used for disk-based k-mer counting.
+//!
+//! Since all windows of a super-k-mer share their minimizer, and equal minimizers always go to the same bucket,
+//! every window of `l=k+w-1` characters ends up in the same bucket, whichever sequence it occurs in.
+use packed_seq::Seq;
+
+use crate::nthash::{nthash_seq_scalar, CharHasher, MulHasher, NtHasher};
+use crate::schemes::fmix32;
+
+/// The super-k-mers of a sequence, partitioned over buckets by their minimizer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Partition<V> {
+    /// For each bucket, the concatenation of its super-k-mers.
+    pub seqs: Vec<V>,
+    /// For each bucket, the offsets of its super-k-mers in `seqs[b]`, so that super-k-mer `i` is `offsets[b][i]..offsets[b][i+1]`.
+    /// Each list starts with 0.
+    pub offsets: Vec<Vec<usize>>,
+}
+
+/// The bucket in `0..num_buckets` of the super-k-mers with the given minimizer `kmer`.
+///
+/// The (forward) hash of the minimizer is small, since it is the minimum of its window.
+/// It is therefore mixed first, and then mapped to a bucket using a multiply-shift.
+pub fn minimizer_bucket<'s, S: Seq<'s>>(kmer: S, num_buckets: usize) -> usize {
+    let k = kmer.len();
+    let hash = if S::BITS_PER_CHAR == 2 {
+        kmer_hash::<NtHasher>(kmer, k)
+    } else {
+        kmer_hash::<MulHasher>(kmer, k)
+    };
+    ((fmix32(hash) as u64 * num_buckets as u64) >> 32) as usize
+}
+
+pub(crate) fn kmer_hash<'s, H: CharHasher>(kmer: impl Seq<'s>, k: usize) -> u32 {
+    nthash_seq_scalar::<false, H>(kmer, k).next().unwrap()
+}
@@ -0,0 +1,154 @@
+//! Typed wrappers around the raw `u32` positions returned by this crate.
+//!
+//! Both types are `#[repr(transparent)]`, so the output vectors of the functions taking `&mut Vec<u32>`
+//! can be viewed as typed slices without copying.
+
+/// The start position of a k-mer, counted in characters from the start of the (sub)sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Pos(pub u32);
+
+impl Pos {
+    /// The raw `u32` position.
+    #[inline(always)]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// The position as an index into the sequence.
+    #[inline(always)]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The position relative to a record starting at `record_start`.
+    ///
+    /// Panics (in debug mode) when the position is before the record start.
+    #[inline(always)]
+    pub const fn relative_to(self, record_start: u32) -> Pos {
+        Pos(self.0 - record_start)
+    }
+
+    /// The absolute position, for a position relative to a record starting at `record_start`.
+    #[inline(always)]
+    pub const fn offset_by(self, record_start: u32) -> Pos {
+        Pos(self.0 + record_start)
+    }
+
+    /// The SIMD chunk containing the position, when a sequence is split into chunks of `chunk_len` windows,
+    /// and the offset of the position in that chunk.
+    #[inline(always)]
+    pub const fn chunk(self, chunk_len: u32) -> (usize, Pos) {
+        ((self.0 / chunk_len) as usize, Pos(self.0 % chunk_len))
+    }
+
+    /// The absolute position of `offset` in SIMD chunk `chunk` of `chunk_len` windows.
+    #[inline(always)]
+    pub const fn from_chunk(chunk: usize, chunk_len: u32, offset: Pos) -> Pos {
+        Pos(chunk as u32 * chunk_len + offset.0)
+    }
+
+    /// The high and low 16 bits of the position.
+    ///
+    /// The sliding window minimum stores the high 16 bits of a hash above the low 16 bits of its position,
+    /// and recovers the high 16 bits of the position from the current window.
+    #[inline(always)]
+    pub const fn split16(self) -> (u16, u16) {
+        ((self.0 >> 16) as u16, self.0 as u16)
+    }
+
+    /// The position with the given high and low 16 bits.
+    #[inline(always)]
+    pub const fn from_split16(high: u16, low: u16) -> Pos {
+        Pos((high as u32) << 16 | low as u32)
+    }
+
+    /// View a slice of raw positions as typed positions.
+    pub fn from_raw_slice(positions: &[u32]) -> &[Pos] {
+        // SAFETY: `Pos` is `repr(transparent)` over `u32`.
+        unsafe { std::slice::from_raw_parts(positions.as_ptr() as *const Pos, positions.len()) }
+    }
+
+    /// View a slice of typed positions as raw positions.
+    pub fn as_raw_slice(positions: &[Pos]) -> &[u32] {
+        // SAFETY: `Pos` is `repr(transparent)` over `u32`.
+        unsafe { std::slice::from_raw_parts(positions.as_ptr() as *const u32, positions.len()) }
+    }
+}
+
+/// Run `f` on the typed `out_vec`, viewed as a vector of raw positions without copying.
+pub(crate) fn with_raw_vec<R>(out_vec: &mut Vec<Pos>, f: impl FnOnce(&mut Vec<u32>) -> R) -> R {
+    let mut typed = std::mem::ManuallyDrop::new(std::mem::take(out_vec));
+    // SAFETY: `Pos` is `repr(transparent)` over `u32`, so both vectors have the same layout.
+    let mut raw = unsafe {
+        Vec::from_raw_parts(
+            typed.as_mut_ptr() as *mut u32,
+            typed.len(),
+            typed.capacity(),
+        )
+    };
+    let result = f(&mut raw);
+    let mut raw = std::mem::ManuallyDrop::new(raw);
+    // SAFETY: As above.
+    *out_vec =
+        unsafe { Vec::from_raw_parts(raw.as_mut_ptr() as *mut Pos, raw.len(), raw.capacity()) };
+    result
+}
+
+impl From<u32> for Pos {
+    fn from(pos: u32) -> Self {
+        Pos(pos)
+    }
+}
+
+impl From<Pos> for u32 {
+    fn from(pos: Pos) -> Self {
+        pos.0
+    }
+}
+
+impl std::fmt::Display for Pos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A minimizer position together with the index of the first window of its super-k-mer.
+///
+/// As for the 16-bit split of the sliding window minimum, the key is stored in the high half and the position in the low half:
+/// the super-k-mer start is stored in the high 32 bits and the minimizer position in the low 32 bits,
+/// so that sorting by the packed value sorts by super-k-mer start.
+/// The super-k-mer start is the index written by the `SUPER` collectors, i.e., the second output of the `_and_superkmer` functions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct PackedSuperPos(pub u64);
+
+impl PackedSuperPos {
+    /// Pack a minimizer position and the index of the first window of its super-k-mer.
+    #[inline(always)]
+    pub const fn new(min_pos: Pos, superkmer_start: u32) -> Self {
+        PackedSuperPos((superkmer_start as u64) << 32 | min_pos.0 as u64)
+    }
+
+    /// The position of the minimizer.
+    #[inline(always)]
+    pub const fn min_pos(self) -> Pos {
+        Pos(self.0 as u32)
+    }
+
+    /// The index of the first window of the super-k-mer, which is also its start position.
+    #[inline(always)]
+    pub const fn superkmer_start(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// Pack the `min_pos_vec` and `sk_pos_vec` outputs of the `_and_superkmer` functions.
+    pub fn pack<'a>(
+        min_pos_vec: &'a [u32],
+        sk_pos_vec: &'a [u32],
+    ) -> impl ExactSizeIterator<Item = PackedSuperPos> + 'a {
+        assert_eq!(min_pos_vec.len(), sk_pos_vec.len());
+        std::iter::zip(min_pos_vec, sk_pos_vec)
+            .map(|(&min_pos, &sk_pos)| PackedSuperPos::new(Pos(min_pos), sk_pos))
+    }
+}
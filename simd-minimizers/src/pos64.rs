@@ -0,0 +1,34 @@
+//! `u64` positions for sequences and concatenations longer than `u32::MAX` characters.
+//!
+//! The SIMD pipeline uses `u32` positions internally.
+//! Long sequences are processed in blocks of at most [`MAX_BLOCK_LEN`] characters that overlap by `l-1` characters,
+//! and the `u32` positions in each block are offset by the start of the block.
+use packed_seq::Seq;
+
+/// Maximum number of characters processed at once, so that all positions in a block fit in a `u32`.
+pub const MAX_BLOCK_LEN: usize = 1 << 31;
+
+/// Run `positions` on overlapping blocks of `seq`, and append the deduplicated positions to `out_vec`.
+///
+/// `l=k+w-1` is the window length, and
+/// `positions(block, offset, out_vec)` must append the positions of all windows in `block` plus `offset` to `out_vec`,
+/// skipping a first position equal to the last position already in `out_vec`.
+pub fn positions_in_blocks_u64<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    l: usize,
+    block_len: usize,
+    out_vec: &mut Vec<u64>,
+    mut positions: impl FnMut(SEQ, u64, &mut Vec<u64>),
+) {
+    let block_len = block_len.min(MAX_BLOCK_LEN).max(l);
+    let mut start = 0;
+    loop {
+        let end = (start + block_len).min(seq.len());
+        positions(seq.slice(start..end), start as u64, out_vec);
+        if end == seq.len() {
+            return;
+        }
+        // The next block starts with the first window that was not yet processed.
+        start = end + 1 - l;
+    }
+}
@@ -0,0 +1,434 @@
+//! Minimizers of many (short) records, with each SIMD lane processing a different record.
+//!
+//! Instead of splitting a single record into 8 chunks, records are distributed over the 8 lanes.
+//! Each lane tracks its own records: as soon as one record is done, the next one is streamed in.
+//! The characters of each lane are read directly from its records, and the rolling hash and sliding window minimum simply continue
+//! over the boundary between two records.
+//! Windows that span such a boundary are masked out, and the deduplicated positions of each lane are then split per record.
+use std::array::from_fn;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::canonical::canonical_mapper;
+use crate::collect::with_dedup_lanes;
+use crate::nthash::{nthash_mapper, CharHasher};
+use crate::sliding_min::{sliding_lr_min_mapper, sliding_min_mapper};
+use crate::S;
+use packed_seq::{Seq, SeqVec, L};
+use wide::i32x8;
+
+/// The records assigned to each lane, and their start position in the stream of the lane.
+pub struct LaneAssignment {
+    /// For each lane, the `(record index, start in lane)` of its records.
+    pub lanes: [Vec<(usize, usize)>; L],
+    /// The length of the longest lane.
+    pub len: usize,
+}
+
+impl LaneAssignment {
+    /// Greedily assign each record to the lane that is currently the shortest,
+    /// i.e., to the lane that is the first to finish its previous records.
+    /// Records shorter than `l` do not have any windows and are skipped.
+    pub fn new(lens: impl IntoIterator<Item = usize>, l: usize) -> Self {
+        let mut lanes: [Vec<(usize, usize)>; L] = from_fn(|_| vec![]);
+        let mut lane_lens = [0; L];
+        for (i, len) in lens.into_iter().enumerate() {
+            if len < l {
+                continue;
+            }
+            let lane = (0..L).min_by_key(|&l| lane_lens[l]).unwrap();
+            lanes[lane].push((i, lane_lens[lane]));
+            lane_lens[lane] += len;
+        }
+        assert!(
+            lane_lens.iter().all(|&len| len < (1 << 32)),
+            "Each lane returns 32bit positions. Try splitting the input into 4GB chunks first."
+        );
+        LaneAssignment {
+            lanes,
+            len: lane_lens.into_iter().max().unwrap(),
+        }
+    }
+}
+
+/// Deduplicated positions of the minimizers of each record, using one SIMD lane per record.
+///
+/// When `CANONICAL` is true, canonical minimizers are computed.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+///
+/// `out_vecs` is resized to `seqs.len()` if needed, and the positions of record `i` are appended to `out_vecs[i]`.
+pub fn minimizers_records_simd<'s, const CANONICAL: bool, SEQ: Seq<'s>, H: CharHasher>(
+    seqs: &[SEQ],
+    k: usize,
+    w: usize,
+    out_vecs: &mut Vec<Vec<u32>>,
+) {
+    let l = k + w - 1;
+    if out_vecs.len() < seqs.len() {
+        out_vecs.resize(seqs.len(), vec![]);
+    }
+    let assignment = LaneAssignment::new(seqs.iter().map(|seq| seq.len()), l);
+    if assignment.len == 0 {
+        return;
+    }
+    stream_lanes::<CANONICAL, SEQ, H, _>(seqs, &assignment, k, w, |lane_positions| {
+        for (records, positions) in assignment.lanes.iter().zip(lane_positions) {
+            split_lane(seqs, records, positions, |r, start, range| {
+                out_vecs[r].extend(positions[range].iter().map(|&p| p - start));
+            });
+        }
+    });
+}
+
+/// Like [`minimizers_records_simd`], but appends the positions of all records to a single flat `positions` vector,
+/// in compressed sparse row (CSR) layout.
+///
+/// The positions of record `i` are `positions[offsets[o+i]..offsets[o+i+1]]`, where `o+1` is the length of `offsets` before the call.
+/// When `offsets` is empty, the current length of `positions` is pushed first, so that `o=0`.
+/// Otherwise, its last value must equal the length of `positions`, so that consecutive batches extend the same layout.
+///
+/// The positions of each record are copied from the deduplicated positions of its lane in record order,
+/// so that no vector is allocated per record.
+pub fn minimizers_records_csr_simd<'s, const CANONICAL: bool, SEQ: Seq<'s>, H: CharHasher>(
+    seqs: &[SEQ],
+    k: usize,
+    w: usize,
+    positions: &mut Vec<u32>,
+    offsets: &mut Vec<usize>,
+) {
+    let l = k + w - 1;
+    if offsets.is_empty() {
+        offsets.push(positions.len());
+    }
+    assert_eq!(
+        offsets.last(),
+        Some(&positions.len()),
+        "The last offset must be the number of positions."
+    );
+    let assignment = LaneAssignment::new(seqs.iter().map(|seq| seq.len()), l);
+    if assignment.len == 0 {
+        offsets.extend(std::iter::repeat(positions.len()).take(seqs.len()));
+        return;
+    }
+    stream_lanes::<CANONICAL, SEQ, H, _>(seqs, &assignment, k, w, |lane_positions| {
+        // The lane of each record, its start in the lane, and the range of its positions in the buffer of that lane.
+        let mut ranges = vec![(0, 0, 0..0); seqs.len()];
+        for (lane, (records, lane_pos)) in assignment.lanes.iter().zip(lane_positions).enumerate() {
+            split_lane(seqs, records, lane_pos, |r, start, range| {
+                ranges[r] = (lane, start, range);
+            });
+        }
+        positions.reserve(lane_positions.iter().map(Vec::len).sum());
+        for (lane, start, range) in ranges {
+            let lane_pos = &lane_positions[lane][range];
+            positions.extend(lane_pos.iter().map(|&p| p - start));
+            offsets.push(positions.len());
+        }
+    });
+}
+
+/// Like [`minimizers_records_simd`], but with separate parameters `params[i] = (k, w)` for each record.
+///
+/// Records are grouped by their parameters, and each group is processed as one batch.
+pub fn minimizers_records_with_params_simd<
+    's,
+    const CANONICAL: bool,
+    SEQ: Seq<'s>,
+    H: CharHasher,
+>(
+    seqs: &[SEQ],
+    params: &[(usize, usize)],
+    out_vecs: &mut Vec<Vec<u32>>,
+) {
+    assert_eq!(
+        seqs.len(),
+        params.len(),
+        "Each record needs its own (k, w) parameters."
+    );
+    if out_vecs.len() < seqs.len() {
+        out_vecs.resize(seqs.len(), vec![]);
+    }
+    let mut groups: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for (i, &kw) in params.iter().enumerate() {
+        groups.entry(kw).or_default().push(i);
+    }
+    let mut group_seqs = vec![];
+    let mut group_out = vec![];
+    for ((k, w), records) in groups {
+        group_seqs.clear();
+        group_seqs.extend(records.iter().map(|&i| seqs[i]));
+        group_out.iter_mut().for_each(Vec::clear);
+        minimizers_records_simd::<CANONICAL, SEQ, H>(&group_seqs, k, w, &mut group_out);
+        for (&i, positions) in records.iter().zip(&mut group_out) {
+            out_vecs[i].append(positions);
+        }
+    }
+}
+
+/// Split the sorted positions of a lane into the ranges of its `records`, and call `f(record, start, range)` for each,
+/// where `start` is the start of the record in the lane.
+///
+/// A leading `u32::MAX`, the value of the lane before its first complete window, is skipped.
+fn split_lane<'s, SEQ: Seq<'s>>(
+    seqs: &[SEQ],
+    records: &[(usize, usize)],
+    positions: &[u32],
+    mut f: impl FnMut(usize, u32, Range<usize>),
+) {
+    let mut i = positions.iter().take_while(|&&p| p == u32::MAX).count();
+    for &(r, start) in records {
+        let end = (start + seqs[r].len()) as u32;
+        let len = positions[i..].partition_point(|&p| p < end);
+        f(r, start as u32, i..i + len);
+        i += len;
+    }
+}
+
+/// The number of characters that a [`LaneCursor`] reads at once.
+const BLOCK: usize = 64;
+
+/// A cursor over the characters of the records of a single lane, that continues with the next record once the current one is done.
+///
+/// The first `delay` characters are `0`, as for the removed characters of `par_iter_bp_delayed`, and so are all characters after the last record.
+#[derive(Clone, Copy)]
+struct LaneCursor {
+    delay: usize,
+    record: usize,
+    pos: usize,
+}
+
+impl LaneCursor {
+    fn new(delay: usize) -> Self {
+        LaneCursor {
+            delay,
+            record: 0,
+            pos: 0,
+        }
+    }
+
+    /// Write the next [`BLOCK`] characters of the lane with the given `records` to `block[..][lane]`.
+    fn fill<'s, SEQ: Seq<'s>>(
+        &mut self,
+        seqs: &[SEQ],
+        records: &[(usize, usize)],
+        lane: usize,
+        block: &mut [[u32; L]; BLOCK],
+    ) {
+        let mut i = 0;
+        while i < BLOCK && self.delay > 0 {
+            block[i][lane] = 0;
+            self.delay -= 1;
+            i += 1;
+        }
+        while i < BLOCK {
+            let Some(&(r, _)) = records.get(self.record) else {
+                block[i..].iter_mut().for_each(|c| c[lane] = 0);
+                return;
+            };
+            let seq = seqs[r];
+            let n = (seq.len() - self.pos).min(BLOCK - i);
+            let chars = seq.slice(self.pos..self.pos + n).iter_bp();
+            for (c, a) in block[i..i + n].iter_mut().zip(chars) {
+                c[lane] = a as u32;
+            }
+            i += n;
+            self.pos += n;
+            if self.pos == seq.len() {
+                self.record += 1;
+                self.pos = 0;
+            }
+        }
+    }
+}
+
+/// For each lane, the windows that lie within a single record.
+struct LaneWindows {
+    /// For each lane, the `(start, end)` of its records in the lane.
+    records: [Vec<(u32, u32)>; L],
+    /// For each lane, the index of the current record.
+    cur: [usize; L],
+    /// For each lane, the step at which the first window of the current record ends, or `u32::MAX` after the last record.
+    valid_from: S,
+    /// For each lane, the end of the current record, or `u32::MAX` after the last record.
+    end: [u32; L],
+    /// The first step at which a lane moves to its next record.
+    next_end: u32,
+    /// The last returned value.
+    prev: S,
+}
+
+impl LaneWindows {
+    fn new<'s, SEQ: Seq<'s>>(seqs: &[SEQ], assignment: &LaneAssignment, l: usize) -> Self {
+        let records = from_fn(|lane| {
+            assignment.lanes[lane]
+                .iter()
+                .map(|&(r, start)| (start as u32, (start + seqs[r].len()) as u32))
+                .collect()
+        });
+        let mut windows = LaneWindows {
+            records,
+            cur: [0; L],
+            valid_from: S::splat(u32::MAX),
+            end: [u32::MAX; L],
+            next_end: u32::MAX,
+            prev: S::splat(u32::MAX),
+        };
+        for lane in 0..L {
+            windows.set_record(lane, l);
+        }
+        windows.next_end = windows.end.into_iter().min().unwrap();
+        windows
+    }
+
+    fn set_record(&mut self, lane: usize, l: usize) {
+        let (valid_from, end) = match self.records[lane].get(self.cur[lane]) {
+            Some(&(start, end)) => (start + l as u32 - 1, end),
+            None => (u32::MAX, u32::MAX),
+        };
+        self.valid_from.as_array_mut()[lane] = valid_from;
+        self.end[lane] = end;
+    }
+
+    /// The positions `pos` of the window ending at step `t` in each lane,
+    /// with positions of windows that span two records replaced by the previous value of the lane.
+    #[inline(always)]
+    fn mask(&mut self, t: usize, l: usize, pos: S) -> S {
+        let t = t as u32;
+        if t == self.next_end {
+            for lane in 0..L {
+                if self.end[lane] == t {
+                    self.cur[lane] += 1;
+                    self.set_record(lane, l);
+                }
+            }
+            self.next_end = self.end.into_iter().min().unwrap();
+        }
+        let t = S::splat(t);
+        let valid = self.valid_from.max(t).cmp_eq(t);
+        self.prev = valid.blend(pos, self.prev);
+        self.prev
+    }
+}
+
+/// Stream the records of each lane, and call `f` with the deduplicated positions of each lane,
+/// relative to the start of the lane and preceded by `u32::MAX` when the first window of the lane is not complete at the first step.
+///
+/// Each lane reads the characters of its records directly, and the positions are deduplicated using the SIMD collector.
+fn stream_lanes<'s, const CANONICAL: bool, SEQ: Seq<'s>, H: CharHasher, R>(
+    seqs: &[SEQ],
+    assignment: &LaneAssignment,
+    k: usize,
+    w: usize,
+    f: impl FnOnce(&[Vec<u32>]) -> R,
+) -> R {
+    let l = k + w - 1;
+    let lanes = &assignment.lanes;
+    let mut nthash = nthash_mapper::<CANONICAL, SEQ, H>(k, w);
+    let mut windows = LaneWindows::new(seqs, assignment, l);
+
+    // Cursors for the added character, and the characters leaving the k-mer and the l-mer.
+    let mut cursors = [0, k - 1, l].map(|delay| [LaneCursor::new(delay); L]);
+    let mut blocks = [[[0; L]; BLOCK]; 3];
+    let chars = (0..assignment.len).map(move |t| {
+        if t % BLOCK == 0 {
+            // The characters leaving the l-mer are only needed for the canonical strand.
+            let streams = if CANONICAL { 3 } else { 2 };
+            for (cursors, block) in cursors.iter_mut().zip(&mut blocks).take(streams) {
+                for (lane, cursor) in cursors.iter_mut().enumerate() {
+                    cursor.fill(seqs, &lanes[lane], lane, block);
+                }
+            }
+        }
+        let [a, rk, rl] = from_fn(|i| S::new(blocks[i][t % BLOCK]));
+        (t, a, rk, rl)
+    });
+
+    // Passing `len=k+w-2` gives all lanes offset 0, so positions are relative to the start of each lane.
+    if CANONICAL {
+        let mut canonical = canonical_mapper(k, w);
+        let mut sliding_lr_min = sliding_lr_min_mapper(w, k, k + w - 2);
+        let head = chars.map(move |(t, a, rk, rl)| {
+            let (lmin, rmin) = sliding_lr_min(nthash((a, rk)));
+            let canonical = canonical((a, rl));
+            let pos = unsafe { std::mem::transmute::<i32x8, S>(canonical).blend(lmin, rmin) };
+            windows.mask(t, l, pos)
+        });
+        with_dedup_lanes(head, f)
+    } else {
+        let mut sliding_min = sliding_min_mapper::<true>(w, k, k + w - 2);
+        let head = chars.map(move |(t, a, rk, _)| windows.mask(t, l, sliding_min(nthash((a, rk)))));
+        with_dedup_lanes(head, f)
+    }
+}
+
+/// The number of bases of the records that [`records_iter`] processes at once, unless a single record is longer.
+const ITER_BATCH_LEN: usize = 1 << 20;
+/// Records of at least this length are split over the lanes as usual, instead of filling a single lane.
+const ITER_LONG_RECORD: usize = ITER_BATCH_LEN / L;
+
+/// Lazily compute the deduplicated positions of the (canonical) minimizers of each `(id, record)`.
+///
+/// Records are pulled from `records` in batches of about [`ITER_BATCH_LEN`] bases only when the previous batch has been consumed,
+/// so that memory is bounded by the size of a batch.
+/// Short records in a batch are processed with one SIMD lane per record, and long records are split into 8 chunks.
+pub fn records_iter<const CANONICAL: bool, Id, V: SeqVec>(
+    records: impl IntoIterator<Item = (Id, V)>,
+    k: usize,
+    w: usize,
+) -> impl Iterator<Item = (Id, Vec<u32>)>
+where
+    for<'s> V::Seq<'s>: Seq<'s, SeqVec = V>,
+{
+    let mut records = records.into_iter();
+    let mut done = std::collections::VecDeque::new();
+    std::iter::from_fn(move || {
+        if done.is_empty() {
+            let mut batch = vec![];
+            let mut batch_len = 0;
+            while batch_len < ITER_BATCH_LEN {
+                let Some((id, seq)) = records.next() else {
+                    break;
+                };
+                batch_len += seq.len();
+                batch.push((id, seq));
+            }
+            let out_vecs = batch_positions::<CANONICAL, _, V>(&batch, k, w);
+            done.extend(batch.into_iter().map(|(id, _)| id).zip(out_vecs));
+        }
+        done.pop_front()
+    })
+}
+
+/// The positions of the (canonical) minimizers of each record in a batch of [`records_iter`].
+fn batch_positions<const CANONICAL: bool, Id, V: SeqVec>(
+    batch: &[(Id, V)],
+    k: usize,
+    w: usize,
+) -> Vec<Vec<u32>>
+where
+    for<'s> V::Seq<'s>: Seq<'s, SeqVec = V>,
+{
+    let seqs: Vec<V::Seq<'_>> = batch.iter().map(|(_, seq)| seq.as_slice()).collect();
+    let (long, short): (Vec<usize>, Vec<usize>) =
+        (0..seqs.len()).partition(|&i| seqs[i].len() >= ITER_LONG_RECORD);
+
+    let mut out_vecs = vec![vec![]; seqs.len()];
+    let short_seqs: Vec<_> = short.iter().map(|&i| seqs[i]).collect();
+    let mut short_out = vec![];
+    if CANONICAL {
+        crate::canonical_minimizer_positions_records(&short_seqs, k, w, &mut short_out);
+    } else {
+        crate::minimizer_positions_records(&short_seqs, k, w, &mut short_out);
+    }
+    for (i, out) in short.into_iter().zip(short_out) {
+        out_vecs[i] = out;
+    }
+    for i in long {
+        if CANONICAL {
+            crate::canonical_minimizer_positions(seqs[i], k, w, &mut out_vecs[i]);
+        } else {
+            crate::minimizer_positions(seqs[i], k, w, &mut out_vecs[i]);
+        }
+    }
+    out_vecs
+}
@@ -0,0 +1,146 @@
+//! Reference outputs to re-verify parity of (canonical) minimizers, enabled by the `test-data` feature.
+//!
+//! The reference cases in `data/reference.tsv` are shipped with the crate.
+//! Each case is a DNA sequence with parameters `k` and `w`, and the expected deduplicated
+//! forward and canonical minimizer positions, as computed with 2-bit ntHash.
+//!
+//! The expected positions are generated by the independent naive implementation in `data/reference.py`,
+//! which shares no code with this crate and follows the definitions of the simd-minimizers paper one window at a time:
+//! the smallest upper 16 bits of the (canonical) ntHash, the leftmost k-mer on ties,
+//! and the rightmost k-mer on ties for windows with fewer than half `G`s and `T`s.
+//! Run `python3 data/reference.py < data/reference.tsv` to regenerate them.
+//! [`verify_reference`] recomputes these on the current machine with both the SIMD and scalar backends,
+//! to detect silent behavioural drift across CPUs, SIMD backends, and releases.
+//!
+//! ```
+//! let num_cases = simd_minimizers::reference::verify_reference().unwrap();
+//! assert!(num_cases > 0);
+//! ```
+use packed_seq::{PackedSeqVec, SeqVec};
+
+const REFERENCE_TSV: &str = include_str!("../data/reference.tsv");
+
+/// A sequence with its expected minimizer positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReferenceCase {
+    /// The k-mer length.
+    pub k: usize,
+    /// The number of k-mers in each window.
+    pub w: usize,
+    /// The ASCII `ACGT` sequence.
+    pub seq: &'static str,
+    /// The expected output of [`minimizer_positions`](crate::minimizer_positions).
+    pub positions: Vec<u32>,
+    /// The expected output of [`canonical_minimizer_positions`](crate::canonical_minimizer_positions).
+    pub canonical_positions: Vec<u32>,
+}
+
+/// Error returned when the computed positions differ from a reference case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The index of the reference case.
+    pub case: usize,
+    /// The function that gave a different output.
+    pub function: &'static str,
+    /// The expected positions.
+    pub expected: Vec<u32>,
+    /// The computed positions.
+    pub found: Vec<u32>,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let first = self
+            .expected
+            .iter()
+            .zip(&self.found)
+            .position(|(a, b)| a != b)
+            .unwrap_or(self.expected.len().min(self.found.len()));
+        write!(
+            f,
+            "reference case {}: {} differs at index {first} ({} expected and {} found positions)",
+            self.case,
+            self.function,
+            self.expected.len(),
+            self.found.len()
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Parse a comma-separated list of positions.
+fn parse_positions(s: &str) -> Vec<u32> {
+    s.split(',')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().expect("invalid reference position"))
+        .collect()
+}
+
+/// The shipped reference cases.
+///
+/// Each non-comment line of `data/reference.tsv` contains `k`, `w`, the sequence,
+/// and the comma-separated forward and canonical positions, separated by tabs.
+pub fn reference_cases() -> Vec<ReferenceCase> {
+    REFERENCE_TSV
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            assert_eq!(fields.len(), 5, "invalid reference line");
+            ReferenceCase {
+                k: fields[0].parse().expect("invalid reference k"),
+                w: fields[1].parse().expect("invalid reference w"),
+                seq: fields[2],
+                positions: parse_positions(fields[3]),
+                canonical_positions: parse_positions(fields[4]),
+            }
+        })
+        .collect()
+}
+
+/// Recompute all reference cases with the SIMD and scalar backends, and return the number of cases checked.
+///
+/// Returns the first [`Mismatch`] when any output differs from the reference.
+pub fn verify_reference() -> Result<usize, Mismatch> {
+    let cases = reference_cases();
+    for (i, case) in cases.iter().enumerate() {
+        let packed = PackedSeqVec::from_ascii(case.seq.as_bytes());
+        let seq = packed.as_slice();
+        let check = |function, expected: &Vec<u32>, found: Vec<u32>| {
+            if *expected == found {
+                Ok(())
+            } else {
+                Err(Mismatch {
+                    case: i,
+                    function,
+                    expected: expected.clone(),
+                    found,
+                })
+            }
+        };
+
+        let mut found = vec![];
+        crate::minimizer_positions(seq, case.k, case.w, &mut found);
+        check("minimizer_positions", &case.positions, found)?;
+        let mut found = vec![];
+        crate::scalar::minimizer_positions_scalar(seq, case.k, case.w, &mut found);
+        check("minimizer_positions_scalar", &case.positions, found)?;
+
+        let mut found = vec![];
+        crate::canonical_minimizer_positions(seq, case.k, case.w, &mut found);
+        check(
+            "canonical_minimizer_positions",
+            &case.canonical_positions,
+            found,
+        )?;
+        let mut found = vec![];
+        crate::scalar::canonical_minimizer_positions_scalar(seq, case.k, case.w, &mut found);
+        check(
+            "canonical_minimizer_positions_scalar",
+            &case.canonical_positions,
+            found,
+        )?;
+    }
+    Ok(cases.len())
+}
@@ -0,0 +1,129 @@
+//! Coarse detection of repeats and segmental duplications, by comparing the minimizers of intervals of a sequence.
+//!
+//! The sequence is split into intervals of `interval_len` bases that overlap by half, and each interval is summarized by the multiset
+//! of canonical hashes of its canonical minimizers. Two intervals that do not overlap are reported as a [`RepeatPair`]
+//! when the Jaccard similarity of their multisets is at least `min_similarity`.
+//! Since canonical hashes do not depend on the strand, reverse-complemented copies are found as well.
+//!
+//! Candidate pairs are found through an inverted index from minimizer hashes to intervals, so that only pairs sharing at least one minimizer are compared.
+//! Minimizers that occur in more than [`MAX_OCCURRENCES`] intervals, such as those of high-copy repeats, are ignored,
+//! to avoid a quadratic number of candidate pairs.
+//!
+//! ```
+//! use simd_minimizers::repeats::find_repeats;
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//!
+//! let unique = PackedSeqVec::random(5_000);
+//! let copy = PackedSeqVec::random(2_000);
+//! let mut seq = PackedSeqVec::default();
+//! seq.push_seq(copy.as_slice());
+//! seq.push_seq(unique.as_slice());
+//! seq.push_seq(copy.as_slice());
+//!
+//! let mut repeats = vec![];
+//! find_repeats(seq.as_slice(), 21, 11, 1000, 0.5, &mut repeats);
+//! assert!(repeats.iter().any(|r| r.a == (0..1000) && r.b == (7000..8000)));
+//! ```
+use std::collections::HashMap;
+use std::ops::Range;
+
+use packed_seq::Seq;
+
+/// Minimizers occurring in more intervals than this are not used to find candidate pairs.
+pub const MAX_OCCURRENCES: usize = 64;
+
+/// Two non-overlapping intervals with similar minimizers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepeatPair {
+    /// The first interval.
+    pub a: Range<u32>,
+    /// The second interval, which starts after the end of the first.
+    pub b: Range<u32>,
+    /// The number of minimizers shared by both intervals, counted with multiplicity.
+    pub shared: u32,
+    /// The Jaccard similarity of the minimizer multisets of both intervals.
+    pub similarity: f64,
+}
+
+/// The start positions of the intervals of `len` bases that overlap by half.
+fn interval_starts(len: usize, interval_len: usize) -> impl Iterator<Item = usize> {
+    let step = interval_len / 2;
+    let num = len.saturating_sub(interval_len).div_ceil(step) + 1;
+    (0..num).map(move |i| i * step)
+}
+
+/// Append all pairs of non-overlapping intervals of `seq` with similar canonical minimizers to `out_vec`.
+///
+/// Intervals have length `interval_len`, except possibly the last one, and start every `interval_len/2` bases.
+/// A minimizer belongs to each interval that contains its start position.
+/// Pairs are appended ordered by the start of `a` and then the start of `b`.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+pub fn find_repeats<'s, S: Seq<'s>>(
+    seq: S,
+    k: usize,
+    w: usize,
+    interval_len: usize,
+    min_similarity: f64,
+    out_vec: &mut Vec<RepeatPair>,
+) {
+    assert!(interval_len >= 2, "Intervals must have length at least 2.");
+    assert!(
+        0. < min_similarity && min_similarity <= 1.,
+        "The minimum similarity must be in (0, 1], but got {min_similarity}."
+    );
+    let step = interval_len / 2;
+    let starts: Vec<usize> = interval_starts(seq.len(), interval_len).collect();
+    let interval = |i: usize| starts[i] as u32..(starts[i] + interval_len).min(seq.len()) as u32;
+
+    let mut positions = vec![];
+    let mut hashes = vec![];
+    crate::canonical_minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+
+    // The inverted index: all `(hash, interval)` pairs, sorted.
+    let mut index = vec![];
+    let mut sizes = vec![0u32; starts.len()];
+    for (&pos, &hash) in positions.iter().zip(&hashes) {
+        let pos = pos as usize;
+        let first = (pos + 1).saturating_sub(interval_len).div_ceil(step);
+        let last = (pos / step).min(starts.len() - 1);
+        index.extend((first..=last).map(|i| (hash, i as u32)));
+        sizes[first..=last].iter_mut().for_each(|size| *size += 1);
+    }
+    index.sort_unstable();
+
+    // Count the shared minimizers of each pair of non-overlapping intervals.
+    let mut shared: HashMap<(u32, u32), u32> = HashMap::new();
+    let mut occurrences = vec![];
+    for group in index.chunk_by(|x, y| x.0 == y.0) {
+        occurrences.clear();
+        occurrences.extend(
+            group
+                .chunk_by(|x, y| x.1 == y.1)
+                .map(|run| (run[0].1, run.len() as u32)),
+        );
+        if occurrences.len() > MAX_OCCURRENCES {
+            continue;
+        }
+        for (x, &(i, ci)) in occurrences.iter().enumerate() {
+            for &(j, cj) in &occurrences[x + 1..] {
+                if starts[j as usize] >= starts[i as usize] + interval_len {
+                    *shared.entry((i, j)).or_default() += ci.min(cj);
+                }
+            }
+        }
+    }
+
+    let start = out_vec.len();
+    out_vec.extend(shared.into_iter().filter_map(|((i, j), shared)| {
+        let union = sizes[i as usize] + sizes[j as usize] - shared;
+        let similarity = shared as f64 / union as f64;
+        (similarity >= min_similarity).then(|| RepeatPair {
+            a: interval(i as usize),
+            b: interval(j as usize),
+            shared,
+            similarity,
+        })
+    }));
+    out_vec[start..].sort_unstable_by_key(|pair| (pair.a.start, pair.b.start));
+}
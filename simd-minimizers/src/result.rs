@@ -0,0 +1,400 @@
+//! A self-describing container for the output of a minimizer computation.
+//!
+//! Instead of a number of loose `Vec<u32>`s that must be kept in sync by the caller,
+//! [`MinimizerResult`] stores the minimizer positions together with the optional per-minimizer data
+//! and the parameters that were used to compute them.
+//! It converts to a `Vec`, to raw columns via [`MinimizerResult::into_parts`], to TSV,
+//! and, with the `arrow` feature, to an Arrow `RecordBatch`.
+use std::io::{self, Write};
+
+use crate::pos::{PackedSuperPos, Pos};
+use packed_seq::Seq;
+
+/// The deduplicated minimizers of a sequence, with the parameters used to compute them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinimizerResult {
+    k: usize,
+    w: usize,
+    canonical: bool,
+    positions: Vec<u32>,
+    superkmer_starts: Option<Vec<u32>>,
+    kmers: Option<Vec<u64>>,
+    hashes: Option<Vec<u32>>,
+    strands: Option<Vec<bool>>,
+}
+
+/// A single minimizer of a [`MinimizerResult`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Minimizer {
+    /// The position of the minimizer.
+    pub pos: Pos,
+    /// The index of the first window of the super-k-mer, when super-k-mers were computed.
+    pub superkmer_start: Option<u32>,
+    /// The packed k-mer value of the minimizer, when k-mers were computed.
+    pub kmer: Option<u64>,
+    /// The hash of the minimizer, when hashes were computed.
+    pub hash: Option<u32>,
+    /// Whether the first window the minimizer is minimal in is on the forward strand, when strands were computed.
+    pub strand: Option<bool>,
+}
+
+/// The parameters and raw columns of a [`MinimizerResult`], for [`MinimizerResult::from_parts`] and [`MinimizerResult::into_parts`].
+///
+/// The optional columns must have the same length as `positions`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinimizerParts {
+    /// The k-mer length.
+    pub k: usize,
+    /// The number of k-mers in each window.
+    pub w: usize,
+    /// Whether these are canonical minimizers.
+    pub canonical: bool,
+    /// The minimizer positions.
+    pub positions: Vec<u32>,
+    /// The start of the super-k-mer of each minimizer.
+    pub superkmer_starts: Option<Vec<u32>>,
+    /// The packed k-mer value of each minimizer.
+    pub kmers: Option<Vec<u64>>,
+    /// The hash of each minimizer.
+    pub hashes: Option<Vec<u32>>,
+    /// The strand of each minimizer.
+    pub strands: Option<Vec<bool>>,
+}
+
+impl MinimizerResult {
+    /// The (canonical) minimizer positions of `seq`.
+    ///
+    /// When `canonical` is true, ties of even `l=w+k-1` are resolved towards the forward strand.
+    pub fn new<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, canonical: bool) -> Self {
+        let mut positions = vec![];
+        if canonical {
+            crate::canonical_minimizer_positions(seq, k, w, &mut positions);
+        } else {
+            crate::minimizer_positions(seq, k, w, &mut positions);
+        }
+        Self::from_parts(MinimizerParts {
+            k,
+            w,
+            canonical,
+            positions,
+            ..Default::default()
+        })
+    }
+
+    /// The (canonical) minimizer positions of `seq`, and the start of each corresponding super-k-mer.
+    ///
+    /// When `canonical` is true, ties of even `l=w+k-1` are resolved towards the forward strand.
+    pub fn with_superkmers<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, canonical: bool) -> Self {
+        let mut positions = vec![];
+        let mut superkmer_starts = vec![];
+        if canonical {
+            crate::canonical_minimizer_and_superkmer_positions(
+                seq,
+                k,
+                w,
+                &mut positions,
+                &mut superkmer_starts,
+            );
+        } else {
+            crate::minimizer_and_superkmer_positions(
+                seq,
+                k,
+                w,
+                &mut positions,
+                &mut superkmer_starts,
+            );
+        }
+        Self::from_parts(MinimizerParts {
+            k,
+            w,
+            canonical,
+            positions,
+            superkmer_starts: Some(superkmer_starts),
+            ..Default::default()
+        })
+    }
+
+    /// The (canonical) minimizer positions of `seq`, and the (canonical) packed k-mer value of each minimizer.
+    ///
+    /// When `canonical` is true, `seq` must be 2-bit DNA, and ties of even `l=w+k-1` are resolved towards the forward strand.
+    pub fn with_kmers<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, canonical: bool) -> Self {
+        let mut positions = vec![];
+        let mut kmers = vec![];
+        if canonical {
+            crate::canonical_minimizer_positions_and_kmers(seq, k, w, &mut positions, &mut kmers);
+        } else {
+            crate::minimizer_positions_and_kmers(seq, k, w, &mut positions, &mut kmers);
+        }
+        Self::from_parts(MinimizerParts {
+            k,
+            w,
+            canonical,
+            positions,
+            kmers: Some(kmers),
+            ..Default::default()
+        })
+    }
+
+    /// The (canonical) minimizer positions of `seq`, and the hash of each minimizer.
+    ///
+    /// When `canonical` is true, ties of even `l=w+k-1` are resolved towards the forward strand.
+    pub fn with_hashes<'s, S: Seq<'s>>(seq: S, k: usize, w: usize, canonical: bool) -> Self {
+        let mut positions = vec![];
+        let mut hashes = vec![];
+        if canonical {
+            crate::canonical_minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+        } else {
+            crate::minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+        }
+        Self::from_parts(MinimizerParts {
+            k,
+            w,
+            canonical,
+            positions,
+            hashes: Some(hashes),
+            ..Default::default()
+        })
+    }
+
+    /// The canonical minimizer positions of `seq`, and the strand of the first window each of them is minimal in.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    pub fn with_strands<'s, S: Seq<'s>>(seq: S, k: usize, w: usize) -> Self {
+        let mut positions = vec![];
+        let mut strands = vec![];
+        crate::canonical_minimizer_positions_and_strands(seq, k, w, &mut positions, &mut strands);
+        Self::from_parts(MinimizerParts {
+            k,
+            w,
+            canonical: true,
+            positions,
+            strands: Some(strands),
+            ..Default::default()
+        })
+    }
+
+    /// Wrap vectors returned by the functions in the crate root.
+    ///
+    /// Panics when the optional vectors do not have the same length as `positions`.
+    pub fn from_parts(parts: MinimizerParts) -> Self {
+        let MinimizerParts {
+            k,
+            w,
+            canonical,
+            positions,
+            superkmer_starts,
+            kmers,
+            hashes,
+            strands,
+        } = parts;
+        let n = positions.len();
+        assert!(superkmer_starts.as_ref().is_none_or(|v| v.len() == n));
+        assert!(kmers.as_ref().is_none_or(|v| v.len() == n));
+        assert!(hashes.as_ref().is_none_or(|v| v.len() == n));
+        assert!(strands.as_ref().is_none_or(|v| v.len() == n));
+        MinimizerResult {
+            k,
+            w,
+            canonical,
+            positions,
+            superkmer_starts,
+            kmers,
+            hashes,
+            strands,
+        }
+    }
+
+    /// Attach the hash of each minimizer, e.g. as returned by [`minimizer_positions_and_hashes`](crate::minimizer_positions_and_hashes).
+    ///
+    /// Panics when `hashes` does not have the same length as the positions.
+    pub fn set_hashes(&mut self, hashes: Vec<u32>) {
+        assert_eq!(hashes.len(), self.positions.len());
+        self.hashes = Some(hashes);
+    }
+
+    /// Attach the strand of each minimizer, as returned by [`canonical_minimizer_positions_and_strands`](crate::canonical_minimizer_positions_and_strands).
+    ///
+    /// Panics when `strands` does not have the same length as the positions.
+    pub fn set_strands(&mut self, strands: Vec<bool>) {
+        assert_eq!(strands.len(), self.positions.len());
+        self.strands = Some(strands);
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The number of k-mers in each window.
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    /// Whether these are canonical minimizers.
+    pub fn canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// The number of minimizers.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether there are no minimizers.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The raw minimizer positions.
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+
+    /// The start of the super-k-mer of each minimizer, if computed.
+    pub fn superkmer_starts(&self) -> Option<&[u32]> {
+        self.superkmer_starts.as_deref()
+    }
+
+    /// The packed k-mer value of each minimizer, if computed.
+    pub fn kmers(&self) -> Option<&[u64]> {
+        self.kmers.as_deref()
+    }
+
+    /// The hash of each minimizer, if computed.
+    pub fn hashes(&self) -> Option<&[u32]> {
+        self.hashes.as_deref()
+    }
+
+    /// The strand of each minimizer, if computed.
+    pub fn strands(&self) -> Option<&[bool]> {
+        self.strands.as_deref()
+    }
+
+    /// Iterate over the typed minimizer positions.
+    pub fn iter_pos(&self) -> impl ExactSizeIterator<Item = Pos> + '_ {
+        self.positions.iter().map(|&pos| Pos(pos))
+    }
+
+    /// Iterate over the packed super-k-mer positions, if super-k-mers were computed.
+    pub fn iter_super(&self) -> Option<impl ExactSizeIterator<Item = PackedSuperPos> + '_> {
+        let superkmer_starts = self.superkmer_starts.as_ref()?;
+        Some(PackedSuperPos::pack(&self.positions, superkmer_starts))
+    }
+
+    /// Iterate over all minimizers, with their optional data.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Minimizer> + '_ {
+        (0..self.len()).map(|i| Minimizer {
+            pos: Pos(self.positions[i]),
+            superkmer_start: self.superkmer_starts.as_ref().map(|v| v[i]),
+            kmer: self.kmers.as_ref().map(|v| v[i]),
+            hash: self.hashes.as_ref().map(|v| v[i]),
+            strand: self.strands.as_ref().map(|v| v[i]),
+        })
+    }
+
+    /// Return the raw minimizer positions, dropping all other data.
+    pub fn into_positions(self) -> Vec<u32> {
+        self.positions
+    }
+
+    /// Return the parameters and all columns, so that [`MinimizerResult::from_parts`] restores the result.
+    pub fn into_parts(self) -> MinimizerParts {
+        MinimizerParts {
+            k: self.k,
+            w: self.w,
+            canonical: self.canonical,
+            positions: self.positions,
+            superkmer_starts: self.superkmer_starts,
+            kmers: self.kmers,
+            hashes: self.hashes,
+            strands: self.strands,
+        }
+    }
+
+    /// Convert the minimizers to an Arrow `RecordBatch`, with one column for each computed column.
+    ///
+    /// The columns are named as in [`MinimizerResult::write_tsv`], and are not nullable.
+    /// `k`, `w`, and `canonical` are stored in the metadata of the schema.
+    #[cfg(feature = "arrow")]
+    pub fn to_arrow(&self) -> Result<arrow_array::RecordBatch, arrow_schema::ArrowError> {
+        use arrow_array::{ArrayRef, BooleanArray, RecordBatch, UInt32Array, UInt64Array};
+        use arrow_schema::{DataType, Field, Schema};
+        use std::{collections::HashMap, sync::Arc};
+
+        let mut fields = vec![Field::new("pos", DataType::UInt32, false)];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(UInt32Array::from(self.positions.clone()))];
+        if let Some(superkmer_starts) = &self.superkmer_starts {
+            fields.push(Field::new("superkmer_start", DataType::UInt32, false));
+            columns.push(Arc::new(UInt32Array::from(superkmer_starts.clone())));
+        }
+        if let Some(kmers) = &self.kmers {
+            fields.push(Field::new("kmer", DataType::UInt64, false));
+            columns.push(Arc::new(UInt64Array::from(kmers.clone())));
+        }
+        if let Some(hashes) = &self.hashes {
+            fields.push(Field::new("hash", DataType::UInt32, false));
+            columns.push(Arc::new(UInt32Array::from(hashes.clone())));
+        }
+        if let Some(strands) = &self.strands {
+            fields.push(Field::new("strand", DataType::Boolean, false));
+            columns.push(Arc::new(BooleanArray::from(strands.clone())));
+        }
+        let metadata = HashMap::from([
+            ("k".to_string(), self.k.to_string()),
+            ("w".to_string(), self.w.to_string()),
+            ("canonical".to_string(), self.canonical.to_string()),
+        ]);
+        let schema = Schema::new_with_metadata(fields, metadata);
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    /// Write the minimizers as tab-separated values.
+    ///
+    /// The first line is a `#` comment containing the parameters,
+    /// followed by a header and one line per minimizer.
+    /// Only columns for the computed data are written.
+    pub fn write_tsv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "# k={} w={} canonical={}",
+            self.k, self.w, self.canonical
+        )?;
+        write!(writer, "pos")?;
+        if self.superkmer_starts.is_some() {
+            write!(writer, "\tsuperkmer_start")?;
+        }
+        if self.kmers.is_some() {
+            write!(writer, "\tkmer")?;
+        }
+        if self.hashes.is_some() {
+            write!(writer, "\thash")?;
+        }
+        if self.strands.is_some() {
+            write!(writer, "\tstrand")?;
+        }
+        writeln!(writer)?;
+        for m in self.iter() {
+            write!(writer, "{}", m.pos)?;
+            if let Some(superkmer_start) = m.superkmer_start {
+                write!(writer, "\t{superkmer_start}")?;
+            }
+            if let Some(kmer) = m.kmer {
+                write!(writer, "\t{kmer}")?;
+            }
+            if let Some(hash) = m.hash {
+                write!(writer, "\t{hash}")?;
+            }
+            if let Some(strand) = m.strand {
+                write!(writer, "\t{}", if strand { '+' } else { '-' })?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<MinimizerResult> for Vec<u32> {
+    fn from(result: MinimizerResult) -> Self {
+        result.positions
+    }
+}
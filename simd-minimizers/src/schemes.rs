@@ -0,0 +1,581 @@
+//! A common interface for sampling schemes, to compare them on the same text.
+//!
+//! A sampling scheme selects one k-mer in every window of `w` consecutive k-mers, i.e., in every `l`-mer with `l=w+k-1`.
+//! [`SamplingScheme::sample`] does this for a single window, and [`SamplingScheme::stream`] for all windows of a text.
+//! The default `stream` calls `sample` on every window, and is overridden by SIMD-backed implementations where possible.
+//!
+//! Texts are arbitrary bytes, and are hashed using mulHash as for all `&[u8]` input of this crate.
+//!
+//! [`Minimizer`] is generic over the [`SimdOrder`] of the k-mers, which provides both a scalar key for [`SamplingScheme::sample`]
+//! and a mapper for the SIMD kernel, so that [`SamplingScheme::stream`] uses SIMD for every order.
+//! The included orders are [`Random`], [`RandomOrder`], [`Lex`], [`AntiLex`], and [`Decycling`].
+//!
+//! [`stream_to_positions`] and [`positions_to_stream`] convert between the per-window positions of [`SamplingScheme::stream`]
+//! and the deduplicated positions and super-k-mer starts of the fast pipeline, e.g.
+//! [`minimizer_and_superkmer_positions`](crate::minimizer_and_superkmer_positions), so that both can be compared and combined.
+//!
+//! [`Params`] describes a scheme by name and parameters, and can be written to and read from JSON,
+//! so that density experiments can record which schemes they compared.
+use crate::anti_lex::{
+    anti_lex_hash_mapper, anti_lex_hash_seq_scalar, lex_hash_mapper, lex_hash_seq_scalar,
+};
+use crate::collect::{collect_and_dedup_into, collect_into};
+use crate::minimizers::{canonical_minimizers_seq_scalar, canonical_minimizers_seq_simd};
+use crate::nthash::{nthash_mapper, nthash_seq_scalar, Captures, MulHasher};
+use crate::sliding_min::sliding_min_mapper;
+use crate::S;
+use itertools::Itertools;
+use packed_seq::Seq;
+use wide::{f32x8, CmpGe, CmpGt, CmpLe, CmpLt};
+
+/// A scheme that samples one k-mer from every window of `w` k-mers.
+///
+/// Schemes are immutable once constructed and hold no shared mutable state, so they are `Send + Sync`:
+/// a scheme can be built on any thread and shared between threads, e.g. as an `Arc<dyn SamplingScheme>` in a server.
+pub trait SamplingScheme: Send + Sync {
+    /// The k-mer length.
+    fn k(&self) -> usize;
+
+    /// The number of k-mers in each window.
+    fn w(&self) -> usize;
+
+    /// The window length `l=w+k-1`.
+    fn l(&self) -> usize {
+        self.w() + self.k() - 1
+    }
+
+    /// The position of the sampled k-mer in the window `lmer` of length `l`, in `0..w`.
+    fn sample(&self, lmer: &[u8]) -> usize;
+
+    /// The absolute position of the sampled k-mer in each window of `text`.
+    fn stream(&self, text: &[u8]) -> Vec<usize> {
+        stream_by_sample(self, text)
+    }
+
+    /// The deduplicated positions of the sampled k-mers of `text`, as returned by the fast pipeline,
+    /// e.g. [`minimizer_positions`](crate::minimizer_positions) for [`Minimizer`].
+    fn positions(&self, text: &[u8]) -> Vec<u32> {
+        let (mut min_pos, mut sk_pos) = (vec![], vec![]);
+        stream_to_positions(&self.stream(text), &mut min_pos, &mut sk_pos);
+        min_pos
+    }
+}
+
+/// Deduplicate the per-window positions of [`SamplingScheme::stream`] into the output format of the fast pipeline.
+///
+/// Like [`minimizer_and_superkmer_positions`](crate::minimizer_and_superkmer_positions), the positions of each run of windows
+/// that sample the same position are appended once to `min_pos_vec`, and the index of the first window of the run to `sk_pos_vec`.
+pub fn stream_to_positions(
+    stream: &[usize],
+    min_pos_vec: &mut Vec<u32>,
+    sk_pos_vec: &mut Vec<u32>,
+) {
+    for (i, &pos) in stream.iter().enumerate() {
+        if i == 0 || stream[i - 1] != pos {
+            min_pos_vec.push(pos as u32);
+            sk_pos_vec.push(i as u32);
+        }
+    }
+}
+
+/// Expand deduplicated positions and super-k-mer starts of the fast pipeline into the position sampled by each of `num_windows` windows.
+///
+/// This is the inverse of [`stream_to_positions`]: window `i` samples `min_pos[j]` for the last `j` with `sk_pos[j] <= i`.
+pub fn positions_to_stream(min_pos: &[u32], sk_pos: &[u32], num_windows: usize) -> Vec<usize> {
+    assert_eq!(min_pos.len(), sk_pos.len());
+    assert!(
+        sk_pos.first().is_none_or(|&start| start == 0),
+        "The first super-k-mer must start at window 0."
+    );
+    let mut stream = Vec::with_capacity(num_windows);
+    for (j, &pos) in min_pos.iter().enumerate() {
+        let end = sk_pos.get(j + 1).map_or(num_windows, |&end| end as usize);
+        stream.resize(end, pos as usize);
+    }
+    assert_eq!(stream.len(), num_windows);
+    stream
+}
+
+/// The absolute position of the sampled k-mer in each window of `text`, by calling [`SamplingScheme::sample`] on every window.
+pub fn stream_by_sample<S: SamplingScheme + ?Sized>(scheme: &S, text: &[u8]) -> Vec<usize> {
+    let l = scheme.l();
+    if text.len() < l {
+        return vec![];
+    }
+    text.windows(l)
+        .enumerate()
+        .map(|(i, lmer)| i + scheme.sample(lmer))
+        .collect()
+}
+
+/// An order on k-mers, given by a 32-bit key per k-mer of which the upper 16 bits are compared.
+///
+/// The scalar [`SimdOrder::keys`] and the SIMD [`SimdOrder::mapper`] must return the same keys.
+pub trait SimdOrder: Send + Sync {
+    /// The key of each k-mer of `text`.
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32>;
+
+    /// A function that 'eats' added characters and the characters `k-1` positions before them, and returns the key of the k-mer that ends at the added character.
+    /// The first `k-1` returned values are for incomplete k-mers.
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S;
+}
+
+/// The random order given by the mulHash of each k-mer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Random;
+
+/// A random order keyed by a `seed`, so that density experiments can average over independent random orders.
+///
+/// The key of each k-mer is its mulHash, xored with the low half of the seed and mixed using the murmur3 finalizer,
+/// and then xored with the high half of the seed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RandomOrder(pub u64);
+
+/// The lexicographic order on the last 4 bytes of each k-mer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lex;
+
+/// The anti-lexicographic order on the last 4 bytes of each k-mer, where the first of these bytes is inverted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AntiLex;
+
+impl SimdOrder for Random {
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32> {
+        nthash_seq_scalar::<false, MulHasher>(text, k).collect()
+    }
+
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S {
+        nthash_mapper::<false, &[u8], MulHasher>(k, w)
+    }
+}
+
+/// The murmur3 32-bit finalizer, a bijection that mixes all input bits into all output bits.
+pub(crate) fn fmix32(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+/// [`fmix32`] on each lane.
+fn fmix32_simd(mut h: S) -> S {
+    h ^= h >> 16;
+    h = h * S::splat(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h * S::splat(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+impl SimdOrder for RandomOrder {
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32> {
+        let (lo, hi) = (self.0 as u32, (self.0 >> 32) as u32);
+        nthash_seq_scalar::<false, MulHasher>(text, k)
+            .map(|h| fmix32(h ^ lo) ^ hi)
+            .collect()
+    }
+
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S {
+        let (lo, hi) = (S::splat(self.0 as u32), S::splat((self.0 >> 32) as u32));
+        let mut hash = nthash_mapper::<false, &[u8], MulHasher>(k, w);
+        move |a_r| fmix32_simd(hash(a_r) ^ lo) ^ hi
+    }
+}
+
+/// Shift a (anti-)lex key of bytes to the upper bits, so that the compared upper 16 bits contain its first characters.
+fn lex_shift(k: usize) -> u32 {
+    32 - (8 * k).min(32) as u32
+}
+
+impl SimdOrder for Lex {
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32> {
+        let shift = lex_shift(k);
+        lex_hash_seq_scalar(text, k).map(|h| h << shift).collect()
+    }
+
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S {
+        let shift = lex_shift(k);
+        let mut lex = lex_hash_mapper::<&[u8]>(k, w);
+        move |(a, _r)| lex(a) << shift
+    }
+}
+
+impl SimdOrder for AntiLex {
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32> {
+        let shift = lex_shift(k);
+        anti_lex_hash_seq_scalar(text, k)
+            .map(|h| h << shift)
+            .collect()
+    }
+
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S {
+        let shift = lex_shift(k);
+        let mut anti_lex = anti_lex_hash_mapper::<&[u8]>(k, w);
+        move |(a, _r)| anti_lex(a) << shift
+    }
+}
+
+/// The decycling-set based order of Pellow et al. (2023), which prefers k-mers in a minimum decycling set.
+///
+/// Each k-mer `x` is embedded in the complex plane as `w(x) = sum_i x_i ω^i`, with `ω = e^{2πi/k}` and `x_i` the byte value of character `i`.
+/// Rotating the k-mer to the left multiplies its embedding by `ω^{-1}`, so that every cycle of rotations with a non-zero embedding
+/// contains a k-mer with `Im w(x) > 0` and `Im ω^{-1} w(x) <= 0`. These k-mers form the decycling set `D`, which comes first in the order.
+/// The _double_ decycling order additionally puts the symmetric set `D'` with `Im w(x) < 0` and `Im ω^{-1} w(x) >= 0` second.
+/// Ties are broken by the mulHash of the k-mer.
+///
+/// Both imaginary parts are sums over the characters with fixed weights `sin(2πi/k)` and `sin(2π(i-1)/k)`,
+/// and are computed for 8 lanes at once using `f32` SIMD. Values within [`Decycling::EPS`] of `0` are treated as `0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Decycling {
+    double: bool,
+}
+
+impl Decycling {
+    /// The tolerance below which the imaginary part of an embedding is considered to be `0`.
+    pub const EPS: f32 = 1e-2;
+
+    /// The decycling order, preferring k-mers in `D`.
+    pub fn single() -> Self {
+        Decycling { double: false }
+    }
+
+    /// The double decycling order, preferring k-mers in `D` and then those in `D'`.
+    pub fn double() -> Self {
+        Decycling { double: true }
+    }
+
+    /// The rank of `kmer` in the order: `0` for k-mers in `D`, `1` for k-mers in `D'` when double, and `2` otherwise.
+    pub fn rank(&self, kmer: &[u8]) -> u32 {
+        let (sin, sin_rot) = embedding_weights(kmer.len());
+        self.rank_with_weights(kmer, &sin, &sin_rot)
+    }
+
+    fn rank_with_weights(&self, kmer: &[u8], sin: &[f32], sin_rot: &[f32]) -> u32 {
+        let mut im = 0.0;
+        let mut im_rot = 0.0;
+        for (j, &c) in kmer.iter().enumerate() {
+            im += c as f32 * sin[j];
+            im_rot += c as f32 * sin_rot[j];
+        }
+        if im > Self::EPS && im_rot <= Self::EPS {
+            0
+        } else if self.double && im < -Self::EPS && im_rot >= -Self::EPS {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// The weights `sin(2πj/k)` and `sin(2π(j-1)/k)` of character `j` in the imaginary part of the embedding of a k-mer and of its left rotation.
+fn embedding_weights(k: usize) -> (Vec<f32>, Vec<f32>) {
+    let angle = |j: f64| (2.0 * std::f64::consts::PI * j / k as f64).sin() as f32;
+    (
+        (0..k).map(|j| angle(j as f64)).collect(),
+        (0..k).map(|j| angle(j as f64 - 1.0)).collect(),
+    )
+}
+
+impl SimdOrder for Decycling {
+    fn keys(&self, text: &[u8], k: usize) -> Vec<u32> {
+        let (sin, sin_rot) = embedding_weights(k);
+        let hashes = nthash_seq_scalar::<false, MulHasher>(text, k);
+        hashes
+            .zip(text.windows(k))
+            .map(|(hash, kmer)| (self.rank_with_weights(kmer, &sin, &sin_rot) << 30) | (hash >> 2))
+            .collect()
+    }
+
+    fn mapper(&self, k: usize, w: usize) -> impl FnMut((S, S)) -> S {
+        let (sin, sin_rot) = embedding_weights(k);
+        let sin = sin.into_iter().map(f32x8::splat).collect_vec();
+        let sin_rot = sin_rot.into_iter().map(f32x8::splat).collect_vec();
+        let eps = f32x8::splat(Self::EPS);
+        let neg_eps = f32x8::splat(-Self::EPS);
+        let double = if self.double {
+            S::splat(1)
+        } else {
+            S::splat(0)
+        };
+        let mut hash = nthash_mapper::<false, &[u8], MulHasher>(k, w);
+        // The last `k` added characters of each lane.
+        let mut chars = vec![S::splat(0); k];
+        let mut i = 0;
+        move |(a, r)| {
+            chars[i % k] = a;
+            i += 1;
+            let mut im = f32x8::splat(0.0);
+            let mut im_rot = f32x8::splat(0.0);
+            for j in 0..k {
+                // Characters are bytes, so they fit in an `i32`.
+                let c = f32x8::from_i32x8(unsafe { std::mem::transmute(chars[(i + j) % k]) });
+                im += c * sin[j];
+                im_rot += c * sin_rot[j];
+            }
+            let in_d: S = unsafe { std::mem::transmute(im.cmp_gt(eps) & im_rot.cmp_le(eps)) };
+            let in_d2: S =
+                unsafe { std::mem::transmute(im.cmp_lt(neg_eps) & im_rot.cmp_ge(neg_eps)) };
+            let rank = S::splat(2) - (in_d & S::splat(2)) - (in_d2 & double);
+            (rank << 30) | (hash((a, r)) >> 2)
+        }
+    }
+}
+
+/// The positions of the minimizers of all windows of `text` for the given `order`, using SIMD.
+pub fn order_minimizers_seq_simd<'s, O: SimdOrder>(
+    text: &'s [u8],
+    k: usize,
+    w: usize,
+    order: &'s O,
+) -> (impl ExactSizeIterator<Item = S> + Captures<&'s ()>, usize) {
+    let l = k + w - 1;
+
+    let (add_remove, padding) = text.par_iter_bp_delayed(l, k - 1);
+
+    let mut keys = order.mapper(k, w);
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |a_rk| sliding_min(keys(a_rk)));
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Minimizers: the leftmost k-mer that is smallest in the order `O`, which is [`Random`] by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Minimizer<O = Random> {
+    k: usize,
+    w: usize,
+    order: O,
+}
+
+impl Minimizer {
+    /// Random minimizers of `w` consecutive k-mers.
+    pub fn new(k: usize, w: usize) -> Self {
+        Minimizer::with_order(k, w, Random)
+    }
+}
+
+impl<O: SimdOrder> Minimizer<O> {
+    /// Minimizers of `w` consecutive k-mers for the given `order`.
+    pub fn with_order(k: usize, w: usize, order: O) -> Self {
+        assert!(k > 0);
+        assert!(w > 0);
+        Minimizer { k, w, order }
+    }
+}
+
+impl<O: SimdOrder> SamplingScheme for Minimizer<O> {
+    fn k(&self) -> usize {
+        self.k
+    }
+
+    fn w(&self) -> usize {
+        self.w
+    }
+
+    fn sample(&self, lmer: &[u8]) -> usize {
+        self.order
+            .keys(lmer, self.k)
+            .into_iter()
+            .map(|key| key & 0xffff_0000)
+            .position_min()
+            .unwrap()
+    }
+
+    fn stream(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = vec![];
+        collect_into(
+            order_minimizers_seq_simd(text, self.k, self.w, &self.order),
+            &mut positions,
+        );
+        positions.into_iter().map(|p| p as usize).collect()
+    }
+
+    fn positions(&self, text: &[u8]) -> Vec<u32> {
+        let mut positions = vec![];
+        collect_and_dedup_into(
+            order_minimizers_seq_simd(text, self.k, self.w, &self.order),
+            &mut positions,
+        );
+        positions
+    }
+}
+
+/// Canonical minimizers, which are the same on both strands.
+///
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanonicalMinimizer {
+    k: usize,
+    w: usize,
+}
+
+impl CanonicalMinimizer {
+    /// Canonical minimizers of `w` consecutive k-mers.
+    pub fn new(k: usize, w: usize) -> Self {
+        assert!(k > 0);
+        assert!(w > 0);
+        CanonicalMinimizer { k, w }
+    }
+}
+
+impl SamplingScheme for CanonicalMinimizer {
+    fn k(&self) -> usize {
+        self.k
+    }
+
+    fn w(&self) -> usize {
+        self.w
+    }
+
+    fn sample(&self, lmer: &[u8]) -> usize {
+        canonical_minimizers_seq_scalar::<MulHasher>(lmer, self.k, self.w)
+            .next()
+            .unwrap() as usize
+    }
+
+    fn stream(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = vec![];
+        collect_into(
+            canonical_minimizers_seq_simd::<_, MulHasher>(text, self.k, self.w),
+            &mut positions,
+        );
+        positions.into_iter().map(|p| p as usize).collect()
+    }
+
+    fn positions(&self, text: &[u8]) -> Vec<u32> {
+        let mut positions = vec![];
+        collect_and_dedup_into(
+            canonical_minimizers_seq_simd::<_, MulHasher>(text, self.k, self.w),
+            &mut positions,
+        );
+        positions
+    }
+}
+
+/// The name and parameters of a scheme in this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Params {
+    /// [`Minimizer`] with the [`Random`] order.
+    Minimizer { k: usize, w: usize },
+    /// [`Minimizer`] with the seeded [`RandomOrder`].
+    RandomOrder { k: usize, w: usize, seed: u64 },
+    /// [`Minimizer`] with the [`Lex`] order.
+    Lex { k: usize, w: usize },
+    /// [`Minimizer`] with the [`AntiLex`] order.
+    AntiLex { k: usize, w: usize },
+    /// [`Minimizer`] with the single [`Decycling`] order.
+    Decycling { k: usize, w: usize },
+    /// [`Minimizer`] with the double [`Decycling`] order.
+    DoubleDecycling { k: usize, w: usize },
+    /// [`CanonicalMinimizer`].
+    Canonical { k: usize, w: usize },
+}
+
+impl Params {
+    /// The names of all schemes, as returned by [`Params::name`].
+    pub const NAMES: [&'static str; 7] = [
+        "minimizer",
+        "random-order",
+        "lex",
+        "anti-lex",
+        "decycling",
+        "double-decycling",
+        "canonical",
+    ];
+
+    /// The name of the scheme in JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Params::Minimizer { .. } => "minimizer",
+            Params::RandomOrder { .. } => "random-order",
+            Params::Lex { .. } => "lex",
+            Params::AntiLex { .. } => "anti-lex",
+            Params::Decycling { .. } => "decycling",
+            Params::DoubleDecycling { .. } => "double-decycling",
+            Params::Canonical { .. } => "canonical",
+        }
+    }
+
+    /// The `(k, w)` of the scheme.
+    pub fn k_w(&self) -> (usize, usize) {
+        match *self {
+            Params::Minimizer { k, w }
+            | Params::RandomOrder { k, w, .. }
+            | Params::Lex { k, w }
+            | Params::AntiLex { k, w }
+            | Params::Decycling { k, w }
+            | Params::DoubleDecycling { k, w }
+            | Params::Canonical { k, w } => (k, w),
+        }
+    }
+
+    /// Construct the scheme.
+    pub fn build(&self) -> Box<dyn SamplingScheme> {
+        match *self {
+            Params::Minimizer { k, w } => Box::new(Minimizer::new(k, w)),
+            Params::RandomOrder { k, w, seed } => {
+                Box::new(Minimizer::with_order(k, w, RandomOrder(seed)))
+            }
+            Params::Lex { k, w } => Box::new(Minimizer::with_order(k, w, Lex)),
+            Params::AntiLex { k, w } => Box::new(Minimizer::with_order(k, w, AntiLex)),
+            Params::Decycling { k, w } => {
+                Box::new(Minimizer::with_order(k, w, Decycling::single()))
+            }
+            Params::DoubleDecycling { k, w } => {
+                Box::new(Minimizer::with_order(k, w, Decycling::double()))
+            }
+            Params::Canonical { k, w } => Box::new(CanonicalMinimizer::new(k, w)),
+        }
+    }
+
+    /// A flat JSON object such as `{"scheme":"random-order","k":21,"w":11,"seed":42}`.
+    pub fn to_json(&self) -> String {
+        let (k, w) = self.k_w();
+        let seed = match self {
+            Params::RandomOrder { seed, .. } => format!(r#","seed":{seed}"#),
+            _ => String::new(),
+        };
+        format!(r#"{{"scheme":"{}","k":{k},"w":{w}{seed}}}"#, self.name())
+    }
+
+    /// Parse the output of [`Params::to_json`].
+    ///
+    /// Fields may be in any order and surrounded by whitespace.
+    /// Returns `None` for unknown schemes or fields, and when a field is missing.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let body = json.trim().strip_prefix('{')?.strip_suffix('}')?;
+        let mut scheme = None;
+        let (mut k, mut w, mut seed) = (None, None, None);
+        for field in body.split(',') {
+            let (key, value) = field.split_once(':')?;
+            let value = value.trim();
+            match key.trim() {
+                r#""scheme""# => scheme = Some(value.strip_prefix('"')?.strip_suffix('"')?),
+                r#""k""# => k = Some(value.parse().ok()?),
+                r#""w""# => w = Some(value.parse().ok()?),
+                r#""seed""# => seed = Some(value.parse().ok()?),
+                _ => return None,
+            }
+        }
+        Params::from_name(scheme?, k?, w?, seed)
+    }
+
+    /// The scheme with the given [`Params::name`] and parameters.
+    ///
+    /// Returns `None` for unknown schemes, and when `seed` is missing for `random-order`.
+    /// `seed` is ignored by the other schemes.
+    pub fn from_name(name: &str, k: usize, w: usize, seed: Option<u64>) -> Option<Self> {
+        Some(match name {
+            "minimizer" => Params::Minimizer { k, w },
+            "random-order" => Params::RandomOrder { k, w, seed: seed? },
+            "lex" => Params::Lex { k, w },
+            "anti-lex" => Params::AntiLex { k, w },
+            "decycling" => Params::Decycling { k, w },
+            "double-decycling" => Params::DoubleDecycling { k, w },
+            "canonical" => Params::Canonical { k, w },
+            _ => return None,
+        })
+    }
+}
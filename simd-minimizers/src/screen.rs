@@ -0,0 +1,104 @@
+//! Screen sequences against a set of minimizers, without collecting their positions.
+//!
+//! For each window, the canonical minimizer is computed as in `canonical_minimizers_seq_simd`,
+//! and the canonical hash of each selected minimizer position is directly passed to a user-provided filter, e.g. a Bloom filter.
+//! The selected positions are exactly those returned by `canonical_minimizer_positions`:
+//! adjacent equal positions are deduplicated, but the same k-mer at different positions is queried once for each position.
+//! Only the number of minimizers and the number of hits are returned, which is all that is needed
+//! for screen-only workloads like host-read removal.
+use std::array::from_fn;
+
+use crate::canonical::canonical_mapper;
+use crate::nthash::{hash_at_pos_mapper, nthash_mapper, nthash_seq_scalar, CharHasher};
+use crate::sliding_min::sliding_lr_min_mapper;
+use crate::S;
+use packed_seq::{Seq, L};
+use wide::i32x8;
+
+/// The number of selected canonical minimizer positions of a record, and how many of them are contained in the filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScreenCounts {
+    /// The number of selected canonical minimizer positions, i.e., the length of the output of `canonical_minimizer_positions`.
+    pub minimizers: usize,
+    /// The number of selected canonical minimizer positions whose hash is contained in the filter.
+    pub hits: usize,
+}
+
+/// The canonical hash of a single k-mer, as passed to the filter by `canonical_minimizer_hits_simd`.
+pub fn canonical_kmer_hash<'s, H: CharHasher>(kmer: impl Seq<'s>) -> u32 {
+    nthash_seq_scalar::<true, H>(kmer, kmer.len())
+        .next()
+        .unwrap()
+}
+
+/// Query the canonical hash of each selected canonical minimizer position of `seq` in `contains`.
+///
+/// Each position is queried exactly once, including minimizers shared by the end of a lane and the start of the next lane.
+/// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+pub fn canonical_minimizer_hits_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+    mut contains: impl FnMut(u32) -> bool,
+) -> ScreenCounts {
+    let l = k + w - 1;
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(l, k - 1, l);
+    let len = add_remove.len();
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, len);
+    let mut hash_at_pos = hash_at_pos_mapper(k, w, len);
+
+    // Number of windows in each lane, and the number of those that are not padding.
+    let n = len.saturating_sub(l - 1);
+    assert!(padding <= L * n, "padding {padding} <= L {L} * n {n}");
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+
+    let mut counts = ScreenCounts::default();
+    let mut query = |hash: u32| {
+        counts.minimizers += 1;
+        counts.hits += contains(hash) as usize;
+    };
+    // For each lane, the position and hash of the first minimizer, which is only queried once the previous lane is done,
+    // and the position of the last minimizer.
+    let mut first: [Option<(u32, u32)>; L] = [None; L];
+    let mut last: [Option<u32>; L] = [None; L];
+
+    for (i, (a, rk, rl)) in add_remove.enumerate() {
+        let hash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(hash);
+        let pos = unsafe { std::mem::transmute::<i32x8, S>(canonical).blend(lmin, rmin) };
+        let min_hash = hash_at_pos(hash, pos);
+        if i < l - 1 {
+            continue;
+        }
+        let i = i - (l - 1);
+        let pos = pos.as_array_ref();
+        let min_hash = min_hash.as_array_ref();
+        for lane in 0..L {
+            if i >= valid[lane] || last[lane] == Some(pos[lane]) {
+                continue;
+            }
+            if first[lane].is_none() {
+                first[lane] = Some((pos[lane], min_hash[lane]));
+            } else {
+                query(min_hash[lane]);
+            }
+            last[lane] = Some(pos[lane]);
+        }
+    }
+
+    // Query the first minimizer of each lane, unless it is shared with the end of the previous lane.
+    let mut prev = None;
+    for lane in 0..L {
+        if let Some((pos, hash)) = first[lane] {
+            if prev != Some(pos) {
+                query(hash);
+            }
+            prev = last[lane];
+        }
+    }
+    counts
+}
@@ -0,0 +1,131 @@
+//! Canonical minimizers as `(position, strand, hash)` seeds for chaining, like the output of minimap2's `mm_sketch`.
+//!
+//! [`sketch_seq_simd`] computes the canonical minimizer positions together with
+//! the forward and canonical packed k-mer value of each of them in a single SIMD pass.
+//! The strand of a seed is `1` when the k-mer at its position is the reverse complement of the canonical k-mer,
+//! and its hash is minimap2's invertible `hash64` of the canonical packed k-mer, so that equal hashes imply equal canonical k-mers.
+//! Since k-mers are packed with the first character in the lowest bits, the hashes differ from minimap2's.
+//!
+//! Minimizers are selected by their canonical ntHash, exactly as for [`canonical_minimizer_positions`](crate::canonical_minimizer_positions),
+//! and require 2-bit DNA and `k <= 31`. For even `l=w+k-1`, ties are resolved towards the forward strand.
+//!
+//! ```
+//! use simd_minimizers::packed_seq::{PackedSeqVec, Seq, SeqVec};
+//! use simd_minimizers::private::kmer::canonical_kmer_value;
+//!
+//! let seq = PackedSeqVec::random(1000);
+//! let (k, w) = (15, 11);
+//! let seeds = simd_minimizers::sketch_seq_simd(seq.as_slice(), k, w);
+//! let mut positions = vec![];
+//! simd_minimizers::canonical_minimizer_positions(seq.as_slice(), k, w, &mut positions);
+//! assert!(seeds.iter().map(|seed| seed.pos).eq(positions));
+//! for seed in &seeds {
+//!     let kmer = canonical_kmer_value(seq.slice(seed.pos as usize..seed.pos as usize + k));
+//!     assert_eq!(seed.hash, simd_minimizers::hash64(kmer, k));
+//! }
+//! ```
+use packed_seq::Seq;
+use wide::i32x8;
+
+use crate::canonical::canonical_mapper;
+use crate::collect::collect_and_dedup_with_vals_into;
+use crate::kmer::kmer_mapper;
+use crate::nthash::{nthash_mapper, Captures, CharHasher, NtHasher};
+use crate::sliding_min::sliding_lr_min_mapper;
+use crate::S;
+
+/// A canonical minimizer of a sequence, laid out as two 64-bit words for C and for sorting by hash.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Seed {
+    /// The invertible 64-bit hash of the canonical packed k-mer, see [`hash64`].
+    pub hash: u64,
+    /// The position of the k-mer in the sequence.
+    pub pos: u32,
+    /// `0` when the k-mer at `pos` is canonical, and `1` when its reverse complement is.
+    pub strand: u32,
+}
+
+/// Like `canonical_minimizer_kmers_seq_simd`, but the high bit of each canonical k-mer value is set
+/// when the k-mer at the minimizer position is not canonical.
+///
+/// The forward k-mer is rolled alongside the canonical one to determine the strand.
+/// Requires 2-bit DNA and `k <= 31`.
+pub fn canonical_minimizer_seeds_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, [S; 2])> + Captures<&'s ()>,
+    usize,
+) {
+    assert!(k <= 31, "k={k} leaves no bit for the strand of the seed.");
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed_2(k + w - 1, k - 1, l);
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w);
+    let mut canonical = canonical_mapper(k, w);
+    let mut sliding_min = sliding_lr_min_mapper(w, k, add_remove.len());
+    let mut fwd_kmers = kmer_mapper::<false, SEQ>(k, w, add_remove.len());
+    let mut kmers = kmer_mapper::<true, SEQ>(k, w, add_remove.len());
+    let strand_bit = S::splat(1 << 31);
+
+    let mut head = add_remove.map(move |(a, rk, rl)| {
+        let nthash = nthash((a, rk));
+        let canonical = canonical((a, rl));
+        let (lmin, rmin) = sliding_min(nthash);
+        let pos = unsafe { std::mem::transmute::<i32x8, S>(canonical).blend(lmin, rmin) };
+        let [fwd_lo, fwd_hi] = fwd_kmers(a, pos);
+        let [lo, hi] = kmers(a, pos);
+        let is_fwd = fwd_lo.cmp_eq(lo) & fwd_hi.cmp_eq(hi);
+        (pos, [lo, hi | (!is_fwd & strand_bit)])
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// minimap2's invertible 64-bit hash of a packed `k`-mer value, restricted to the low `2k` bits, for `k <= 32`.
+pub fn hash64(kmer: u64, k: usize) -> u64 {
+    let mask = u64::MAX >> (64 - 2 * k);
+    let mut key = kmer;
+    key = (!key).wrapping_add(key << 21) & mask;
+    key ^= key >> 24;
+    key = key.wrapping_add(key << 3).wrapping_add(key << 8) & mask;
+    key ^= key >> 14;
+    key = key.wrapping_add(key << 2).wrapping_add(key << 4) & mask;
+    key ^= key >> 28;
+    key = key.wrapping_add(key << 31) & mask;
+    key
+}
+
+/// The deduplicated canonical minimizers of a 2-bit sequence as [`Seed`]s, in order of position, using SIMD.
+///
+/// Requires `k <= 31`. For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+pub fn sketch_seq_simd<'s, SEQ: Seq<'s>>(seq: SEQ, k: usize, w: usize) -> Vec<Seed> {
+    let mut out_vec = vec![];
+    sketch_seq_simd_into(seq, k, w, &mut out_vec);
+    out_vec
+}
+
+/// Like [`sketch_seq_simd`], but appends the seeds to a reusable `out_vec` to avoid allocations.
+pub fn sketch_seq_simd_into<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    k: usize,
+    w: usize,
+    out_vec: &mut Vec<Seed>,
+) {
+    assert_eq!(SEQ::BITS_PER_CHAR, 2, "Seeds require 2-bit DNA.");
+    let mut positions = vec![];
+    let mut kmers = vec![];
+    let head_padding = canonical_minimizer_seeds_seq_simd::<_, NtHasher>(seq, k, w);
+    collect_and_dedup_with_vals_into(head_padding, &mut positions, &mut kmers);
+    out_vec.extend(
+        std::iter::zip(positions, kmers).map(|(pos, kmer): (u32, u64)| Seed {
+            hash: hash64(kmer & (u64::MAX >> 1), k),
+            pos,
+            strand: (kmer >> 63) as u32,
+        }),
+    );
+}
@@ -0,0 +1,183 @@
+//! Fixed-size signatures of sequences, for approximate nearest neighbour search.
+//!
+//! A [`Signer`] maps a sequence to a signature of [`Signer::signature_len`] values, such that the similarity of two signatures
+//! estimates the Jaccard similarity of the k-mer sets of the sequences. Since all signatures of a signer have the same length,
+//! they can be stored as rows of a matrix and indexed by approximate nearest neighbour structures such as HNSW, using
+//! [`Signer::distance`] as the metric.
+//!
+//! Two signers are provided:
+//! - [`BottomSigner`]: the `s` smallest distinct hashes of the canonical minimizers, as in [`SketchLevel`].
+//!   With `w=1`, this is a classic bottom-`s` MinHash sketch of all k-mers.
+//! - [`BinSigner`]: one-permutation MinHash, i.e., the smallest hash of all canonical k-mers in each of `bins` bins.
+//!   This takes a single pass over the hashes, without sorting.
+//!
+//! Empty entries, for sequences with fewer distinct k-mers than the signature length, are [`EMPTY`].
+//!
+//! ```
+//! use simd_minimizers::signer::{BinSigner, BottomSigner, Signer};
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//!
+//! let seq = PackedSeqVec::random(10_000);
+//! let signer = BottomSigner::new(21, 1, 128);
+//! let signature = signer.sign(seq.as_slice());
+//! assert_eq!(signature.len(), 128);
+//! assert_eq!(signer.distance(&signature, &signature), 0.0);
+//!
+//! let signer = BinSigner::new(21, 128);
+//! let a = signer.sign(seq.as_slice());
+//! let b = signer.sign(seq.slice(0..5_000));
+//! assert!(signer.similarity(&a, &b) > 0.3);
+//! ```
+use crate::SketchLevel;
+use packed_seq::Seq;
+
+/// The value of empty signature entries. A k-mer with this hash is indistinguishable from an empty entry.
+pub const EMPTY: u32 = u32::MAX;
+
+/// Computes fixed-size signatures of sequences.
+pub trait Signer {
+    /// The number of values in each signature.
+    fn signature_len(&self) -> usize;
+
+    /// Write the signature of `seq` to `out`, which must have length [`Signer::signature_len`].
+    fn sign_into<'s, S: Seq<'s>>(&self, seq: S, out: &mut [u32]);
+
+    /// The estimated Jaccard similarity of the k-mer sets of two sequences, given their signatures.
+    fn similarity(&self, a: &[u32], b: &[u32]) -> f64;
+
+    /// The signature of `seq`.
+    fn sign<'s, S: Seq<'s>>(&self, seq: S) -> Vec<u32> {
+        let mut out = vec![EMPTY; self.signature_len()];
+        self.sign_into(seq, &mut out);
+        out
+    }
+
+    /// The distance between two signatures, `1 - similarity`.
+    fn distance(&self, a: &[u32], b: &[u32]) -> f64 {
+        1. - self.similarity(a, b)
+    }
+}
+
+fn check_len(signer: &impl Signer, a: &[u32], b: &[u32]) {
+    assert!(
+        a.len() == signer.signature_len() && b.len() == signer.signature_len(),
+        "Signatures must have length {}.",
+        signer.signature_len()
+    );
+}
+
+/// Bottom-`s` MinHash of the canonical minimizers for `(k, w)`.
+///
+/// The signature is the sorted list of the `s` smallest distinct canonical minimizer hashes, padded with [`EMPTY`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BottomSigner {
+    k: usize,
+    w: usize,
+    s: usize,
+}
+
+impl BottomSigner {
+    /// Signatures of the `s` smallest minimizer hashes. Use `w=1` to sample from all k-mers.
+    pub fn new(k: usize, w: usize, s: usize) -> Self {
+        assert!(s > 0, "Signatures must not be empty.");
+        BottomSigner { k, w, s }
+    }
+}
+
+impl Signer for BottomSigner {
+    fn signature_len(&self) -> usize {
+        self.s
+    }
+
+    fn sign_into<'s, S: Seq<'s>>(&self, seq: S, out: &mut [u32]) {
+        assert_eq!(out.len(), self.s, "Signatures must have length {}.", self.s);
+        let hashes = SketchLevel::new(seq, self.k, self.w).hashes;
+        let n = hashes.len().min(self.s);
+        out[..n].copy_from_slice(&hashes[..n]);
+        out[n..].fill(EMPTY);
+    }
+
+    /// The fraction of the `s` smallest hashes of the union that is in both signatures.
+    fn similarity(&self, a: &[u32], b: &[u32]) -> f64 {
+        check_len(self, a, b);
+        let (mut i, mut j) = (0, 0);
+        let (mut union, mut shared) = (0, 0);
+        while union < self.s {
+            let x = a.get(i).copied().unwrap_or(EMPTY);
+            let y = b.get(j).copied().unwrap_or(EMPTY);
+            if x == EMPTY && y == EMPTY {
+                break;
+            }
+            match x.cmp(&y) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    shared += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+            union += 1;
+        }
+        if union == 0 {
+            return 0.;
+        }
+        shared as f64 / union as f64
+    }
+}
+
+/// One-permutation MinHash of all canonical k-mers.
+///
+/// The hash range is split into `bins` equal parts, and the signature contains the smallest canonical ntHash in each part,
+/// or [`EMPTY`] if there is none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BinSigner {
+    k: usize,
+    bins: usize,
+}
+
+impl BinSigner {
+    /// Signatures with `bins` bins.
+    pub fn new(k: usize, bins: usize) -> Self {
+        assert!(bins > 0, "Signatures must not be empty.");
+        BinSigner { k, bins }
+    }
+}
+
+impl Signer for BinSigner {
+    fn signature_len(&self) -> usize {
+        self.bins
+    }
+
+    fn sign_into<'s, S: Seq<'s>>(&self, seq: S, out: &mut [u32]) {
+        assert_eq!(
+            out.len(),
+            self.bins,
+            "Signatures must have length {}.",
+            self.bins
+        );
+        out.fill(EMPTY);
+        let mut hashes = vec![];
+        crate::canonical_fracminhash(seq, self.k, 1., &mut hashes);
+        for h in hashes {
+            let bin = ((h as u64 * self.bins as u64) >> 32) as usize;
+            out[bin] = out[bin].min(h);
+        }
+    }
+
+    /// The fraction of bins that are non-empty in either signature with equal values in both.
+    fn similarity(&self, a: &[u32], b: &[u32]) -> f64 {
+        check_len(self, a, b);
+        let (mut union, mut shared) = (0, 0);
+        for (&x, &y) in a.iter().zip(b) {
+            if x != EMPTY || y != EMPTY {
+                union += 1;
+                shared += (x == y) as usize;
+            }
+        }
+        if union == 0 {
+            return 0.;
+        }
+        shared as f64 / union as f64
+    }
+}
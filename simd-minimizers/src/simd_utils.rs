@@ -0,0 +1,390 @@
+//! Safe wrappers around the SIMD primitives used to collect the output of the minimizer pipeline.
+//!
+//! These are generally useful to convert the struct-of-arrays output of other 8-lane SIMD pipelines into flat vectors:
+//! [`transpose_u32x8x8`] transposes an 8x8 matrix of `u32`s, and
+//! [`compress_unique`] appends only the values that differ from their predecessor,
+//! [`compress_unique_2`] does the same for a key with two payloads, and [`DedupState`] keeps the last key across calls,
+//! so that external 8-lane pipelines can deduplicate a stream of `(key, payload)` pairs without handling the kernels' slack,
+//! [`dedup_vec`] uses it to deduplicate a vector in place, and
+//! [`dedup_vec_with_counts`] uses it to deduplicate a vector while counting the length of each run.
+//! [`merge_lanes`] stitches the per-lane outputs of manually driven mappers into a single deduplicated vector.
+//! [`compress_unique_u64`] and [`dedup_vec_u64`] do the same for 64-bit values, such as 64-bit hashes, 4 at a time.
+//!
+//! Each function has AVX2, NEON, and scalar implementations, selected at compile time.
+//! [`compress_unique`] uses AVX-512 `vpcompressd` when it is available.
+//!
+//! ```
+//! use simd_minimizers::packed_seq::u32x8;
+//! use simd_minimizers::simd_utils::compress_unique;
+//!
+//! let mut out = vec![];
+//! let prev = u32x8::splat(0);
+//! let new = u32x8::new([0, 0, 1, 1, 1, 2, 3, 3]);
+//! assert_eq!(compress_unique(prev, new, new, &mut out), 3);
+//! assert_eq!(out, [1, 2, 3]);
+//! ```
+use std::array::from_fn;
+
+use crate::S;
+pub use wide::u64x4;
+
+/// Transpose a matrix of 8 SIMD vectors of 8 `u32`s each, so that `out[i][j] = m[j][i]`.
+#[inline(always)]
+pub fn transpose_u32x8x8(m: [S; 8]) -> [S; 8] {
+    crate::intrinsics::transpose(m)
+}
+
+/// For each element of `new` that is different from its predecessor, append the corresponding element of `vals` to `out`.
+///
+/// The predecessor of `new[0]` is the last element of `prev`, so that a stream of vectors can be deduplicated by passing the previous vector.
+/// Returns the number of appended values.
+#[inline(always)]
+pub fn compress_unique(prev: S, new: S, vals: S, out: &mut Vec<u32>) -> usize {
+    let len = out.len();
+    // The intrinsics always write 8 values.
+    out.resize(len + 8, 0);
+    let mut write_idx = len;
+    unsafe { crate::intrinsics::append_unique_vals(prev, new, vals, out, &mut write_idx) };
+    out.truncate(write_idx);
+    write_idx - len
+}
+
+/// For each element of `new` that is different from its predecessor, append the corresponding elements of `vals` and `vals2` to `out` and `out2`.
+///
+/// Like [`compress_unique`], e.g. to append both a key and its payload.
+/// Returns the number of values appended to each of `out` and `out2`.
+#[inline(always)]
+pub fn compress_unique_2(
+    prev: S,
+    new: S,
+    vals: S,
+    vals2: S,
+    out: &mut Vec<u32>,
+    out2: &mut Vec<u32>,
+) -> usize {
+    let (len, len2) = (out.len(), out2.len());
+    // The intrinsics always write 8 values.
+    out.resize(len + 8, 0);
+    out2.resize(len2 + 8, 0);
+    let mut write_idx = 0;
+    unsafe {
+        crate::intrinsics::append_unique_vals_2(
+            prev,
+            new,
+            vals,
+            vals2,
+            &mut out[len..],
+            &mut out2[len2..],
+            &mut write_idx,
+        )
+    };
+    out.truncate(len + write_idx);
+    out2.truncate(len2 + write_idx);
+    write_idx
+}
+
+/// The state of deduplicating a stream of `u32` keys with `u32` payloads, 8 at a time.
+///
+/// Keeps the last key that was pushed, so that runs of equal keys are also deduplicated across calls,
+/// and the first key of the stream is always retained.
+///
+/// ```
+/// use simd_minimizers::packed_seq::u32x8;
+/// use simd_minimizers::simd_utils::DedupState;
+///
+/// let mut state = DedupState::new();
+/// let (mut keys, mut payloads) = (vec![], vec![]);
+/// let k = u32x8::new([5, 5, 6, 6, 6, 6, 6, 6]);
+/// let p = u32x8::new([0, 1, 2, 3, 4, 5, 6, 7]);
+/// assert_eq!(state.push_pairs(k, p, &mut keys, &mut payloads), 2);
+/// state.extend_pairs(&[6, 7, 7], &[8, 9, 10], &mut keys, &mut payloads);
+/// assert_eq!(keys, [5, 6, 7]);
+/// assert_eq!(payloads, [0, 2, 9]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DedupState {
+    last: Option<u32>,
+}
+
+impl DedupState {
+    /// The state at the start of a stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last key that was pushed, if any.
+    pub fn last(&self) -> Option<u32> {
+        self.last
+    }
+
+    /// The vector preceding `keys`: the last key, or a value different from `keys[0]`.
+    fn prev(&self, first: u32) -> S {
+        S::splat(self.last.unwrap_or(!first))
+    }
+
+    /// For each key that is different from its predecessor, append the corresponding payload to `out`.
+    /// Returns the number of appended payloads.
+    #[inline(always)]
+    pub fn push(&mut self, keys: S, payloads: S, out: &mut Vec<u32>) -> usize {
+        let keys_arr = keys.as_array_ref();
+        let count = compress_unique(self.prev(keys_arr[0]), keys, payloads, out);
+        self.last = Some(keys_arr[7]);
+        count
+    }
+
+    /// For each key that is different from its predecessor, append it to `keys_out` and the corresponding payload to `payloads_out`.
+    /// Returns the number of appended pairs.
+    #[inline(always)]
+    pub fn push_pairs(
+        &mut self,
+        keys: S,
+        payloads: S,
+        keys_out: &mut Vec<u32>,
+        payloads_out: &mut Vec<u32>,
+    ) -> usize {
+        let keys_arr = keys.as_array_ref();
+        let count = compress_unique_2(
+            self.prev(keys_arr[0]),
+            keys,
+            keys,
+            payloads,
+            keys_out,
+            payloads_out,
+        );
+        self.last = Some(keys_arr[7]);
+        count
+    }
+
+    /// Like [`DedupState::push_pairs`], for slices of any length.
+    ///
+    /// Full chunks of 8 pairs use SIMD, and the remaining pairs are deduplicated one by one.
+    pub fn extend_pairs(
+        &mut self,
+        keys: &[u32],
+        payloads: &[u32],
+        keys_out: &mut Vec<u32>,
+        payloads_out: &mut Vec<u32>,
+    ) {
+        assert_eq!(keys.len(), payloads.len());
+        let mut key_chunks = keys.chunks_exact(8);
+        for (k, p) in (&mut key_chunks).zip(payloads.chunks_exact(8)) {
+            let k = S::new(k.try_into().unwrap());
+            let p = S::new(p.try_into().unwrap());
+            self.push_pairs(k, p, keys_out, payloads_out);
+        }
+        let start = keys.len() - key_chunks.remainder().len();
+        for (&k, &p) in key_chunks.remainder().iter().zip(&payloads[start..]) {
+            if self.last != Some(k) {
+                keys_out.push(k);
+                payloads_out.push(p);
+                self.last = Some(k);
+            }
+        }
+    }
+}
+
+/// For each element of `cur` that is different from its successor, append the corresponding element of `vals` to `out`.
+///
+/// The successor of `cur[7]` is the first element of `next`, so that this keeps the _last_ element of each run of equal values.
+/// Returns the number of appended values.
+#[inline(always)]
+pub fn compress_unique_last(cur: S, next: S, vals: S, out: &mut Vec<u32>) -> usize {
+    let len = out.len();
+    // The intrinsics always write 8 values.
+    out.resize(len + 8, 0);
+    let mut write_idx = len;
+    unsafe { crate::intrinsics::append_unique_vals_last(cur, next, vals, out, &mut write_idx) };
+    out.truncate(write_idx);
+    write_idx - len
+}
+
+/// Remove adjacent duplicates from `v` in place, like [`Vec::dedup`].
+///
+/// Full chunks of 8 values are deduplicated using SIMD and written back into `v` itself,
+/// which is safe since the write index never exceeds the start of the current chunk.
+/// The remaining values are deduplicated one by one.
+///
+/// ```
+/// use simd_minimizers::simd_utils::dedup_vec;
+///
+/// let mut v = vec![3, 3, 5, 7, 7, 7, 7, 7, 7, 3, 3];
+/// dedup_vec(&mut v);
+/// assert_eq!(v, [3, 5, 7, 3]);
+/// ```
+pub fn dedup_vec(v: &mut Vec<u32>) {
+    let len = v.len();
+    if len == 0 {
+        return;
+    }
+    let mut write_idx = 0;
+    // Different from `v[0]`, so that it is retained.
+    let mut prev = S::splat(v[0] ^ 1);
+    let chunks = len / 8;
+    for i in 0..chunks {
+        let new = S::new(v[8 * i..8 * i + 8].try_into().unwrap());
+        // The intrinsics write 8 values starting at `write_idx <= 8 * i`, which stays within `v`.
+        unsafe { crate::intrinsics::append_unique_vals(prev, new, new, v, &mut write_idx) };
+        prev = new;
+    }
+    let mut last = prev.as_array_ref()[7];
+    let mut tail = [0; 8];
+    tail[..len % 8].copy_from_slice(&v[8 * chunks..]);
+    for &x in &tail[..len % 8] {
+        if x != last {
+            v[write_idx] = x;
+            write_idx += 1;
+            last = x;
+        }
+    }
+    v.truncate(write_idx);
+}
+
+/// For each element of `new` that is different from its predecessor, append the corresponding element of `vals` to `out`.
+///
+/// Like [`compress_unique`], for 4 `u64` values at a time. The predecessor of `new[0]` is the last element of `prev`.
+/// Returns the number of appended values.
+#[inline(always)]
+pub fn compress_unique_u64(prev: u64x4, new: u64x4, vals: u64x4, out: &mut Vec<u64>) -> usize {
+    let len = out.len();
+    // The intrinsics always write 4 values.
+    out.resize(len + 4, 0);
+    let mut write_idx = len;
+    unsafe { crate::intrinsics::append_unique_vals_u64(prev, new, vals, out, &mut write_idx) };
+    out.truncate(write_idx);
+    write_idx - len
+}
+
+/// Remove adjacent duplicates from `v` in place, like [`Vec::dedup`].
+///
+/// Like [`dedup_vec`], for `u64` values, with full chunks of 4 values deduplicated using SIMD.
+///
+/// ```
+/// use simd_minimizers::simd_utils::dedup_vec_u64;
+///
+/// let mut v = vec![3, 3, 5, 1 << 40, 1 << 40, 1 << 40, 3];
+/// dedup_vec_u64(&mut v);
+/// assert_eq!(v, [3, 5, 1 << 40, 3]);
+/// ```
+pub fn dedup_vec_u64(v: &mut Vec<u64>) {
+    let len = v.len();
+    if len == 0 {
+        return;
+    }
+    let mut write_idx = 0;
+    // Different from `v[0]`, so that it is retained.
+    let mut prev = u64x4::new([v[0] ^ 1; 4]);
+    let chunks = len / 4;
+    for i in 0..chunks {
+        let new = u64x4::new(v[4 * i..4 * i + 4].try_into().unwrap());
+        // The intrinsics write 4 values starting at `write_idx <= 4 * i`, which stays within `v`.
+        unsafe { crate::intrinsics::append_unique_vals_u64(prev, new, new, v, &mut write_idx) };
+        prev = new;
+    }
+    let mut last = prev.as_array_ref()[3];
+    let mut tail = [0; 4];
+    tail[..len % 4].copy_from_slice(&v[4 * chunks..]);
+    for &x in &tail[..len % 4] {
+        if x != last {
+            v[write_idx] = x;
+            write_idx += 1;
+            last = x;
+        }
+    }
+    v.truncate(write_idx);
+}
+
+/// Remove adjacent duplicates from `v`, and return the length of each run of equal values.
+///
+/// `counts[i]` is the number of adjacent copies of the retained value `v[i]`,
+/// e.g. the number of consecutive windows that share a minimizer.
+///
+/// ```
+/// use simd_minimizers::simd_utils::dedup_vec_with_counts;
+///
+/// let mut v = vec![3, 3, 5, 7, 7, 7, 3];
+/// let counts = dedup_vec_with_counts(&mut v);
+/// assert_eq!(v, [3, 5, 7, 3]);
+/// assert_eq!(counts, [2, 1, 3, 1]);
+/// ```
+pub fn dedup_vec_with_counts(v: &mut Vec<u32>) -> Vec<u32> {
+    let len = v.len();
+    if len == 0 {
+        return vec![];
+    }
+    // The intrinsics always write 8 values.
+    let mut values = vec![0; len + 8];
+    let mut starts = vec![0; len + 8];
+    let mut write_idx = 0;
+    // Different from `v[0]`, so that it is retained.
+    let mut prev = S::splat(v[0] ^ 1);
+    let mut idx = S::new(from_fn(|i| i as u32));
+    let mut chunks = v.chunks_exact(8);
+    for chunk in &mut chunks {
+        let new = S::new(chunk.try_into().unwrap());
+        let mut starts_idx = write_idx;
+        unsafe {
+            crate::intrinsics::append_unique_vals(prev, new, new, &mut values, &mut write_idx);
+            crate::intrinsics::append_unique_vals(prev, new, idx, &mut starts, &mut starts_idx);
+        }
+        prev = new;
+        idx += S::splat(8);
+    }
+    let mut last = prev.as_array_ref()[7];
+    let start = len - chunks.remainder().len();
+    for (i, &x) in chunks.remainder().iter().enumerate() {
+        if x != last {
+            values[write_idx] = x;
+            starts[write_idx] = (start + i) as u32;
+            write_idx += 1;
+            last = x;
+        }
+    }
+    values.truncate(write_idx);
+    starts.truncate(write_idx);
+
+    // Convert the start of each run into its length.
+    for i in 0..write_idx - 1 {
+        starts[i] = starts[i + 1] - starts[i];
+    }
+    starts[write_idx - 1] = len as u32 - starts[write_idx - 1];
+    *v = values;
+    starts
+}
+
+/// Concatenate the values of all lanes into a single vector, and deduplicate adjacent equal values.
+///
+/// `lane_vecs[j]` contains the values of lane `j` in order, of which only the first `lens[j]` are used,
+/// so that padding at the end of the last lanes is dropped.
+/// Like the collectors of this crate, duplicates are also removed across lane boundaries,
+/// where the windows of consecutive lanes overlap.
+///
+/// ```
+/// use simd_minimizers::simd_utils::merge_lanes;
+///
+/// let lanes = [vec![1, 1, 4], vec![4, 4, 6, 6], vec![9, 0, 0]];
+/// assert_eq!(merge_lanes(&lanes, &[3, 4, 1]), [1, 4, 6, 9]);
+/// ```
+pub fn merge_lanes(lane_vecs: &[impl AsRef<[u32]>], lens: &[usize]) -> Vec<u32> {
+    assert_eq!(lane_vecs.len(), lens.len());
+    let mut out = vec![];
+    for (lane, &len) in lane_vecs.iter().zip(lens) {
+        let lane = &lane.as_ref()[..len];
+        let Some(&first) = lane.first() else {
+            continue;
+        };
+        // The last value so far, or a value different from the first one, so that it is kept.
+        let mut prev = S::splat(out.last().copied().unwrap_or(!first));
+        let mut chunks = lane.chunks_exact(8);
+        for chunk in &mut chunks {
+            let new = S::new(chunk.try_into().unwrap());
+            compress_unique(prev, new, new, &mut out);
+            prev = new;
+        }
+        for &x in chunks.remainder() {
+            if out.last() != Some(&x) {
+                out.push(x);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,291 @@
+//! Sketches of a sequence at multiple `(k, w)` resolutions.
+//!
+//! Each level of a [`MultiSketch`] is the sorted set of canonical hashes of the canonical minimizers for one `(k, w)`.
+//! Coarse levels (large `w`) are small and cheap to compare, and can be used to prune candidates
+//! before comparing finer levels.
+use std::array::from_fn;
+use std::io::{self, Read, Write};
+
+use crate::canonical::canonical_mapper;
+use crate::collect::collect_and_dedup_with_hashes_into;
+use crate::minimizers::canonical_minimizer_hashes_seq_simd;
+use crate::nthash::{hash_at_pos_mapper, nthash_mapper, CharHasher, MulHasher, NtHasher};
+use crate::sliding_min::sliding_lr_min_mapper;
+use crate::S;
+use packed_seq::{Seq, L};
+use wide::i32x8;
+
+/// Magic bytes at the start of a serialized [`MultiSketch`].
+const MAGIC: [u8; 4] = *b"SMMS";
+/// Version of the serialization format.
+const VERSION: u32 = 1;
+
+/// The sketch of a sequence for a single `(k, w)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SketchLevel {
+    /// The k-mer length.
+    pub k: usize,
+    /// The number of k-mers in each window.
+    pub w: usize,
+    /// The sorted and distinct canonical hashes of all canonical minimizers.
+    pub hashes: Vec<u32>,
+}
+
+impl SketchLevel {
+    /// The sketch of `seq` for a single `(k, w)`.
+    ///
+    /// For even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    pub fn new<'s, S: Seq<'s>>(seq: S, k: usize, w: usize) -> Self {
+        let mut positions = vec![];
+        let mut hashes = vec![];
+        if S::BITS_PER_CHAR == 2 {
+            let head_padding = canonical_minimizer_hashes_seq_simd::<_, NtHasher>(seq, k, w);
+            collect_and_dedup_with_hashes_into(head_padding, &mut positions, &mut hashes);
+        } else {
+            let head_padding = canonical_minimizer_hashes_seq_simd::<_, MulHasher>(seq, k, w);
+            collect_and_dedup_with_hashes_into(head_padding, &mut positions, &mut hashes);
+        }
+        hashes.sort_unstable();
+        hashes.dedup();
+        SketchLevel { k, w, hashes }
+    }
+
+    /// The number of hashes shared by both sketches.
+    pub fn intersection(&self, other: &SketchLevel) -> usize {
+        assert_eq!(
+            (self.k, self.w),
+            (other.k, other.w),
+            "Sketches must use the same (k, w)."
+        );
+        let (mut i, mut j) = (0, 0);
+        let mut count = 0;
+        while i < self.hashes.len() && j < other.hashes.len() {
+            match self.hashes[i].cmp(&other.hashes[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    count += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// The Jaccard similarity of the two sketches.
+    pub fn jaccard(&self, other: &SketchLevel) -> f64 {
+        let intersection = self.intersection(other);
+        let union = self.hashes.len() + other.hashes.len() - intersection;
+        if union == 0 {
+            return 0.0;
+        }
+        intersection as f64 / union as f64
+    }
+
+    /// The fraction of hashes of `self` that are also in `other`.
+    pub fn containment(&self, other: &SketchLevel) -> f64 {
+        if self.hashes.is_empty() {
+            return 0.0;
+        }
+        self.intersection(other) as f64 / self.hashes.len() as f64
+    }
+}
+
+/// Sketches of a sequence at multiple `(k, w)` levels.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultiSketch {
+    levels: Vec<SketchLevel>,
+}
+
+impl MultiSketch {
+    /// Sketch `seq` at each of the given `(k, w)` levels.
+    ///
+    /// Levels with the same `k` are built in a single pass over `seq` that shares the character stream and rolling hash.
+    /// Levels with different `k` need separate passes.
+    ///
+    /// For levels with even `l=w+k-1`, windows with as many `GT` as `AC` characters are ties, which are resolved towards the forward strand.
+    pub fn new<'s, S: Seq<'s>>(seq: S, params: &[(usize, usize)]) -> Self {
+        let mut levels = vec![SketchLevel::default(); params.len()];
+        for (i, &(k, _)) in params.iter().enumerate() {
+            if params[..i].iter().any(|&(k2, _)| k2 == k) {
+                continue;
+            }
+            let idx: Vec<usize> = (i..params.len()).filter(|&j| params[j].0 == k).collect();
+            let ws: Vec<usize> = idx.iter().map(|&j| params[j].1).collect();
+            let l_max = k + ws.iter().max().unwrap() - 1;
+            if idx.len() == 1 || seq.len() < l_max {
+                for &j in &idx {
+                    levels[j] = SketchLevel::new(seq, k, params[j].1);
+                }
+                continue;
+            }
+            let hashes = if S::BITS_PER_CHAR == 2 {
+                shared_k_hashes::<_, NtHasher>(seq, k, &ws)
+            } else {
+                shared_k_hashes::<_, MulHasher>(seq, k, &ws)
+            };
+            for (j, hashes) in idx.into_iter().zip(hashes) {
+                levels[j] = SketchLevel {
+                    k,
+                    w: params[j].1,
+                    hashes,
+                };
+            }
+        }
+        MultiSketch { levels }
+    }
+
+    /// All levels, in the order of the `params` they were built with.
+    pub fn levels(&self) -> &[SketchLevel] {
+        &self.levels
+    }
+
+    /// The level with the given `(k, w)`, if present.
+    pub fn level(&self, k: usize, w: usize) -> Option<&SketchLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.k == k && level.w == w)
+    }
+
+    /// The Jaccard similarity of the two sketches at level `i`.
+    pub fn jaccard(&self, other: &MultiSketch, i: usize) -> f64 {
+        self.levels[i].jaccard(&other.levels[i])
+    }
+
+    /// The fraction of hashes of `self` at level `i` that are also in `other`.
+    pub fn containment(&self, other: &MultiSketch, i: usize) -> f64 {
+        self.levels[i].containment(&other.levels[i])
+    }
+
+    /// Write the sketch in a little-endian binary format.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.levels.len() as u32).to_le_bytes())?;
+        for level in &self.levels {
+            writer.write_all(&(level.k as u32).to_le_bytes())?;
+            writer.write_all(&(level.w as u32).to_le_bytes())?;
+            writer.write_all(&(level.hashes.len() as u64).to_le_bytes())?;
+            for hash in &level.hashes {
+                writer.write_all(&hash.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a sketch written by [`MultiSketch::write_to`].
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+            let mut buf = [0; 4];
+            reader.read_exact(&mut buf)?;
+            Ok(u32::from_le_bytes(buf))
+        }
+        fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+            let mut buf = [0; 8];
+            reader.read_exact(&mut buf)?;
+            Ok(u64::from_le_bytes(buf))
+        }
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid("not a simd-minimizers sketch"));
+        }
+        if read_u32(&mut reader)? != VERSION {
+            return Err(invalid("unsupported sketch version"));
+        }
+        let num_levels = read_u32(&mut reader)?;
+        let mut levels = vec![];
+        for _ in 0..num_levels {
+            let k = read_u32(&mut reader)? as usize;
+            let w = read_u32(&mut reader)? as usize;
+            let len = read_u64(&mut reader)?;
+            let hashes = (0..len)
+                .map(|_| read_u32(&mut reader))
+                .collect::<io::Result<Vec<_>>>()?;
+            if !hashes.is_sorted() {
+                return Err(invalid("sketch hashes are not sorted"));
+            }
+            levels.push(SketchLevel { k, w, hashes });
+        }
+        Ok(MultiSketch { levels })
+    }
+}
+
+/// The sorted and distinct canonical minimizer hashes of `seq` for each of the `ws`, sharing a single pass for the common `k`.
+///
+/// The characters are streamed with the context of the largest window, and each `w` has its own canonical strand,
+/// sliding window minimum, and hash lookup. Smaller windows near the end of a lane are also the first windows of the next lane,
+/// which only duplicates their hashes.
+///
+/// `seq` must contain at least one window of each length.
+fn shared_k_hashes<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    ws: &[usize],
+) -> Vec<Vec<u32>> {
+    let w_max = *ws.iter().max().unwrap();
+    let l_max = k + w_max - 1;
+    assert!(seq.len() >= l_max);
+    let (add_remove, _) = seq.par_iter_bp_delayed(l_max, k - 1);
+    // Number of windows of length `l_max` in each lane, and thus the offset of each lane.
+    let n = add_remove.len() - (l_max - 1);
+    // A window ending at lane-local position `i` is inside `seq` when `i < lane_ends[lane]`.
+    let lane_ends: [usize; L] = from_fn(|lane| seq.len().saturating_sub(lane * n));
+
+    let mut nthash = nthash_mapper::<true, SEQ, H>(k, w_max);
+    // Passing `len=n+k+w-2` gives each window length the same lane offsets.
+    let mut levels: Vec<_> = ws
+        .iter()
+        .map(|&w| {
+            (
+                k + w - 1,
+                canonical_mapper(k, w),
+                sliding_lr_min_mapper(w, k, n + k + w - 2),
+                hash_at_pos_mapper(k, w, n + k + w - 2),
+            )
+        })
+        .collect();
+    // The last `l_max` added characters, to remove them from the window of each length.
+    let mut chars = vec![S::ZERO; l_max + 1];
+    let mut last_pos = vec![S::splat(u32::MAX); ws.len()];
+    let mut hashes = vec![vec![]; ws.len()];
+
+    for (i, (a, rk)) in add_remove.enumerate() {
+        chars[i % (l_max + 1)] = a;
+        let hash = nthash((a, rk));
+        for (j, (l, canonical, sliding_min, hash_at_pos)) in levels.iter_mut().enumerate() {
+            let rl = if i >= *l {
+                chars[(i - *l) % (l_max + 1)]
+            } else {
+                S::ZERO
+            };
+            let canonical = canonical((a, rl));
+            let (lmin, rmin) = sliding_min(hash);
+            let pos = unsafe { std::mem::transmute::<i32x8, S>(canonical).blend(lmin, rmin) };
+            let min_hash = hash_at_pos(hash, pos);
+            if i + 1 < *l || pos == last_pos[j] {
+                continue;
+            }
+            let (p, h, lp) = (
+                pos.as_array_ref(),
+                min_hash.as_array_ref(),
+                last_pos[j].as_array_ref(),
+            );
+            for lane in 0..L {
+                if i < lane_ends[lane] && p[lane] != lp[lane] {
+                    hashes[j].push(h[lane]);
+                }
+            }
+            last_pos[j] = pos;
+        }
+    }
+
+    for hashes in &mut hashes {
+        hashes.sort_unstable();
+        hashes.dedup();
+    }
+    hashes
+}
@@ -6,8 +6,11 @@
 //! towards the leftmost minimum, and false for the rightmost minimum.
 //!
 //! All these methods take 32 bit input values, **but they only use the upper 16 bits!**
+//! The `sliding_min64` methods take 64 bit input values, and use the upper 32 bits.
 //!
 //! Positions ar
+use crate::long_k::S128;
+use crate::nthash64::S64;
 use crate::S;
 use core::array::from_fn;
 use std::hint::assert_unchecked;
@@ -61,17 +64,29 @@ impl<V> std::ops::DerefMut for RingBuf<V> {
     }
 }
 
+/// The largest window size supported by [`sliding_min_scalar`] and [`sliding_min_mapper`].
+///
+/// These store the position of each value in its lower 16 bits. Once positions reach `2^16-1`, all stored positions
+/// are decreased by `2^16-2-w`, which must be positive, and the removed amount is added to the output offset instead.
+/// Bounding `w` by `2^15-1` ensures that this happens at most once every `2^15-1` values, so that its `O(w)` cost stays negligible.
+/// Use [`sliding_min_checked_scalar`] for larger windows.
+pub const MAX_W: usize = (1 << 15) - 1;
+
+fn check_w(w: usize) {
+    assert!(w > 0);
+    assert!(
+        w <= MAX_W,
+        "sliding_min supports windows of at most {MAX_W} values, but got w={w}."
+    );
+}
+
 /// Scalar version. Takes an iterator over values and returns an iterator over positions.
 #[inline(always)]
 pub fn sliding_min_scalar<const LEFT: bool>(
     it: impl ExactSizeIterator<Item = u32>,
     w: usize,
 ) -> impl ExactSizeIterator<Item = u32> {
-    assert!(w > 0);
-    assert!(
-        w < (1 << 15),
-        "sliding_min is not tested for windows of length > 2^15."
-    );
+    check_w(w);
     assert!(
         it.len() < (1 << 32),
         "sliding_min returns 32bit indices. Try splitting the input into 4GB chunks first."
@@ -129,6 +144,45 @@ pub fn sliding_min_scalar<const LEFT: bool>(
     it
 }
 
+/// Scalar version that stores the compared upper 16 bits and the 32-bit position of each value in a `u64`.
+///
+/// Positions are never reset, so no intermediate value can overflow for any `w` and any input of less than `2^32` values.
+/// This is slower than [`sliding_min_scalar`], and serves as a checked reference for the extreme ends of the parameter space.
+pub fn sliding_min_checked_scalar<const LEFT: bool>(
+    it: impl ExactSizeIterator<Item = u32>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> {
+    assert!(w > 0);
+    assert!(
+        it.len() < (1 << 32),
+        "sliding_min returns 32bit indices. Try splitting the input into 4GB chunks first."
+    );
+    let mut prefix_min = if LEFT { u64::MAX } else { 0 };
+    let mut ring_buf = RingBuf::new(w, prefix_min);
+    let mut pos = 0u64;
+    let min = |a: u64, b: u64| if LEFT { a.min(b) } else { a.max(b) };
+
+    let mut it = it.map(move |val| {
+        let val = if LEFT { val } else { !val } & 0xffff_0000;
+        let elem = ((val as u64) << 32) | pos;
+        pos += 1;
+        ring_buf.push(elem);
+        prefix_min = min(prefix_min, elem);
+        if ring_buf.idx() == 0 {
+            let mut suffix_min = ring_buf[w - 1];
+            for i in (0..w - 1).rev() {
+                suffix_min = min(suffix_min, ring_buf[i]);
+                ring_buf[i] = suffix_min;
+            }
+            prefix_min = elem;
+        }
+        min(prefix_min, ring_buf[ring_buf.idx()]) as u32
+    });
+    it.by_ref().take(w - 1).for_each(drop);
+    it
+}
+
+#[inline(always)]
 fn simd_min<const LEFT: bool>(a: S, b: S) -> S {
     if LEFT {
         a.min(b)
@@ -152,13 +206,19 @@ pub fn sliding_min_simd<const LEFT: bool>(
     it
 }
 
+/// The largest window size for which [`minimizer_positions`](crate::minimizer_positions) uses [`sliding_min_tree_mapper`]
+/// instead of [`sliding_min_mapper`].
+///
+/// Chosen using [`bench::sliding_min`](crate::bench::sliding_min) and [`bench::sliding_min_tree`](crate::bench::sliding_min_tree):
+/// the min tree is faster up to `w=5`, about as fast for `w=6` and `w=7`, and slower from `w=8`.
+pub const SMALL_W: usize = 5;
+
 /// Mapper version, that returns a function that can be called with new inputs as needed.
 /// Output values are offset by `-(k-1)`, so that the k'th returned value (the first kmer) is at position 0.
 /// `len` is the number of values in each chunk. The SIMD lanes will be offset by `len-(k+w-2)`.
 /// The first `k+w-2` returned values are bogus, since they correspond to incomplete windows.
 pub fn sliding_min_mapper<const LEFT: bool>(w: usize, k: usize, len: usize) -> impl FnMut(S) -> S {
-    assert!(w > 0);
-    assert!(w < (1 << 15), "This method is not tested for large w.");
+    check_w(w);
     assert!(len * 8 < (1 << 32));
     let mut prefix_min = S::splat(u32::MAX);
     let mut ring_buf = RingBuf::new(w, prefix_min);
@@ -199,6 +259,56 @@ pub fn sliding_min_mapper<const LEFT: bool>(w: usize, k: usize, len: usize) -> i
     }
 }
 
+/// Like `sliding_min_mapper`, but for a window size `W` that is known at compile time.
+///
+/// The last `W` values are kept in registers that are shifted by one for each new value,
+/// and the minimum of each window is computed from scratch by a fully unrolled tree of `W-1` minima.
+/// This is only faster than the two-stacks algorithm for small `W`, see [`SMALL_W`].
+pub fn sliding_min_tree_mapper<const LEFT: bool, const W: usize>(
+    k: usize,
+    len: usize,
+) -> impl FnMut(S) -> S {
+    assert!(W > 0);
+    assert!(len * 8 < (1 << 32));
+    // The first `W-1` windows are incomplete, so the initial value does not matter.
+    let mut window = [S::splat(u32::MAX); W];
+    // We only compare the upper 16 bits of each hash.
+    // Ties are broken automatically in favour of lower pos.
+    let val_mask = S::splat(0xffff_0000);
+    let pos_mask = S::splat(0x0000_ffff);
+    let max_pos = S::splat((1 << 16) - 1);
+    let mut pos = S::splat(0);
+    let mut pos_offset: S =
+        from_fn(|l| (l * len.saturating_sub(k + W - 2)).wrapping_sub(k - 1) as u32).into();
+
+    #[inline(always)]
+    move |val| {
+        // Make sure the position does not interfere with the hash value.
+        if pos == max_pos {
+            let delta = S::splat((1 << 16) - 2 - W as u32);
+            pos -= delta;
+            pos_offset += delta;
+            for x in &mut window {
+                *x -= delta;
+            }
+        }
+        let elem = (if LEFT { val } else { !val } & val_mask) | pos;
+        pos += S::splat(1);
+        window = from_fn(|i| if i + 1 < W { window[i + 1] } else { elem });
+        // Halve the number of candidates until one remains.
+        let mut min = window;
+        let mut n = W;
+        while n > 1 {
+            let half = n / 2;
+            for i in 0..half {
+                min[i] = simd_min::<LEFT>(min[i], min[n - 1 - i]);
+            }
+            n -= half;
+        }
+        (min[0] & pos_mask) + pos_offset
+    }
+}
+
 fn suffix_minima<const LEFT: bool>(
     ring_buf: &mut RingBuf<S>,
     w: usize,
@@ -248,8 +358,7 @@ pub fn sliding_lr_min_simd(
 
 /// Like `sliding_min_mapper`, but returns both the leftmost and the rightmost minimum.
 pub fn sliding_lr_min_mapper(w: usize, k: usize, len: usize) -> impl FnMut(S) -> (S, S) {
-    assert!(w > 0);
-    assert!(w < (1 << 15), "This method is not tested for large w.");
+    check_w(w);
     assert!(len * 8 < (1 << 32));
     let mut prefix_lr_min = (S::splat(u32::MAX), S::splat(u32::MAX));
     let mut ring_buf = RingBuf::new(w, prefix_lr_min);
@@ -338,3 +447,339 @@ fn reset_positions_offsets_lr(
         x.1 -= delta;
     }
 }
+
+/// Combine two (minimum, tie mask) pairs: keep the smaller minimum, and the union of the masks when they are equal.
+#[inline(always)]
+fn tie_min((av, am): (u32, u32), (bv, bm): (u32, u32)) -> (u32, u32) {
+    let v = av.min(bv);
+    let m = if av == v { am } else { 0 } | if bv == v { bm } else { 0 };
+    (v, m)
+}
+
+#[inline(always)]
+fn simd_tie_min((av, am): (S, S), (bv, bm): (S, S)) -> (S, S) {
+    let v = av.min(bv);
+    (v, (am & v.cmp_eq(av)) | (bm & v.cmp_eq(bv)))
+}
+
+fn check_tie_mask_w(w: usize) {
+    assert!(
+        0 < w && w <= 32,
+        "Tie masks have 32 bits, so windows can have at most 32 values, but got w={w}."
+    );
+}
+
+/// Scalar version that returns, for each window, a bitmask of all positions attaining the minimum.
+///
+/// Bit `i` of the mask is set when the `i`th value of the window is minimal, so the leftmost and rightmost minimum
+/// are the lowest and highest set bit. Like the other methods, only the upper 16 bits of each value are compared.
+/// Requires `w <= 32`.
+pub fn sliding_min_all_scalar(
+    it: impl ExactSizeIterator<Item = u32>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> {
+    check_tie_mask_w(w);
+    // Masks are indexed by position modulo 32, and rotated to the start of the window at the end.
+    let empty = (u32::MAX, 0);
+    let mut prefix_min = empty;
+    let mut ring_buf = RingBuf::new(w, empty);
+    let val_mask = 0xffff_0000;
+    let mut pos = 0usize;
+
+    let mut it = it.map(
+        #[inline(always)]
+        move |val| {
+            let elem = (val & val_mask, 1 << (pos % 32));
+            pos += 1;
+            ring_buf.push(elem);
+            prefix_min = tie_min(prefix_min, elem);
+            // After a chunk has been filled, compute suffix minima.
+            if ring_buf.idx() == 0 {
+                let mut suffix_min = ring_buf[w - 1];
+                for i in (0..w - 1).rev() {
+                    suffix_min = tie_min(suffix_min, ring_buf[i]);
+                    ring_buf[i] = suffix_min;
+                }
+                // Masks are or-ed, so counting `elem` twice is fine.
+                prefix_min = elem;
+            }
+            let suffix_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+            let start = pos.wrapping_sub(w) % 32;
+            tie_min(prefix_min, suffix_min).1.rotate_right(start as u32)
+        },
+    );
+    it.by_ref().take(w - 1).for_each(drop);
+    it
+}
+
+/// SIMD version of [`sliding_min_all_scalar`]: for each window in each of the 8 lanes, a bitmask of all positions attaining the minimum.
+#[inline(always)]
+pub fn sliding_min_all_simd(
+    it: impl ExactSizeIterator<Item = S>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = S> {
+    let mut it = it.map(sliding_min_all_mapper(w));
+    it.by_ref().take(w - 1).for_each(drop);
+    it
+}
+
+/// Mapper version of [`sliding_min_all_simd`]. The first `w-1` returned masks are bogus, since they correspond to incomplete windows.
+///
+/// Each lane stores pairs of the minimum and its tie mask in the two stacks, so this does about twice the work of [`sliding_min_mapper`].
+pub fn sliding_min_all_mapper(w: usize) -> impl FnMut(S) -> S {
+    check_tie_mask_w(w);
+    let empty = (S::splat(u32::MAX), S::ZERO);
+    let mut prefix_min = empty;
+    let mut ring_buf = RingBuf::new(w, empty);
+    let val_mask = S::splat(0xffff_0000);
+    // All lanes are at the same position, so the bit and rotation are shared.
+    let mut pos = 0usize;
+
+    #[inline(always)]
+    move |val| {
+        let elem = (val & val_mask, S::splat(1 << (pos % 32)));
+        pos += 1;
+        ring_buf.push(elem);
+        prefix_min = simd_tie_min(prefix_min, elem);
+        if ring_buf.idx() == 0 {
+            let mut suffix_min = ring_buf[w - 1];
+            for i in (0..w - 1).rev() {
+                suffix_min = simd_tie_min(suffix_min, ring_buf[i]);
+                ring_buf[i] = suffix_min;
+            }
+            prefix_min = elem;
+        }
+        let suffix_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+        let mask = simd_tie_min(prefix_min, suffix_min).1;
+        let start = (pos.wrapping_sub(w) % 32) as u32;
+        if start == 0 {
+            mask
+        } else {
+            (mask >> start) | (mask << (32 - start))
+        }
+    }
+}
+
+/// Scalar version for 64-bit values, of which the upper 32 bits are compared. Always breaks ties towards the leftmost minimum.
+pub fn sliding_min64_scalar(
+    it: impl ExactSizeIterator<Item = u64>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> {
+    assert!(w > 0);
+    assert!(
+        it.len() < (1 << 32),
+        "sliding_min returns 32bit indices. Try splitting the input into 4GB chunks first."
+    );
+    let mut prefix_min = u64::MAX;
+    let mut ring_buf = RingBuf::new(w, prefix_min);
+    // The position is stored in the lower 32 bits, so that ties are broken in favour of lower pos.
+    let val_mask = 0xffff_ffff_0000_0000;
+    let mut pos = 0u64;
+
+    let mut it = it.map(
+        #[inline(always)]
+        move |val| {
+            let elem = (val & val_mask) | pos;
+            pos += 1;
+            ring_buf.push(elem);
+            prefix_min = prefix_min.min(elem);
+            // After a chunk has been filled, compute suffix minima.
+            if ring_buf.idx() == 0 {
+                let mut suffix_min = ring_buf[w - 1];
+                for i in (0..w - 1).rev() {
+                    suffix_min = suffix_min.min(ring_buf[i]);
+                    ring_buf[i] = suffix_min;
+                }
+                prefix_min = elem;
+            }
+            let suffix_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+            prefix_min.min(suffix_min) as u32
+        },
+    );
+    it.by_ref().take(w - 1).for_each(drop);
+    it
+}
+
+/// Mapper version for 64-bit values, of which the upper 32 bits are compared. Always breaks ties towards the leftmost minimum.
+///
+/// Like `sliding_min_mapper`, output positions are offset by `-(k-1)`, `len` is the number of values in each chunk,
+/// and the first `k+w-2` returned values are bogus.
+/// Since positions use the full lower 32 bits, they never need to be reset.
+pub fn sliding_min64_mapper(w: usize, k: usize, len: usize) -> impl FnMut(S64) -> S {
+    assert!(w > 0);
+    assert!(len * 8 < (1 << 32));
+    let mut prefix_min = S64::splat(u64::MAX);
+    let mut ring_buf = RingBuf::new(w, prefix_min);
+    let val_mask = S64::splat(0xffff_ffff_0000_0000);
+    // Lane-local positions, that are offset to absolute positions in the output.
+    let mut pos = 0u64;
+    let pos_offset: S =
+        from_fn(|l| (l * len.saturating_sub(k + w - 2)).wrapping_sub(k - 1) as u32).into();
+
+    #[inline(always)]
+    move |val| {
+        let elem = (val & val_mask) | S64::splat(pos);
+        pos += 1;
+        ring_buf.push(elem);
+        prefix_min = prefix_min.min(elem);
+        // After a chunk has been filled, compute suffix minima.
+        if ring_buf.idx() == 0 {
+            let mut suffix_min = ring_buf[w - 1];
+            for i in (0..w - 1).rev() {
+                suffix_min = suffix_min.min(ring_buf[i]);
+                ring_buf[i] = suffix_min;
+            }
+            prefix_min = elem;
+        }
+        let suffix_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+        prefix_min.min(suffix_min).low_u32() + pos_offset
+    }
+}
+
+/// Mapper version for 128-bit values, which are compared in full. Always breaks ties towards the leftmost minimum.
+///
+/// Like [`sliding_min64_mapper`], output positions are offset by `-(k-1)`, `len` is the number of values in each chunk,
+/// and the first `k+w-2` returned values are bogus.
+/// The position is kept next to the value and compared after it, so that it never needs to be reset.
+pub fn sliding_min128_mapper(w: usize, k: usize, len: usize) -> impl FnMut(S128) -> S {
+    assert!(w > 0);
+    assert!(len * 8 < (1 << 32));
+    let min = |(av, ap): (S128, S), (bv, bp): (S128, S)| {
+        let le = av.lt_or(bv, ap.min(bp).cmp_eq(ap));
+        (av.blend(bv, le), le.blend(ap, bp))
+    };
+    let mut prefix_min = (S128::splat(u128::MAX), S::splat(u32::MAX));
+    let mut ring_buf = RingBuf::new(w, prefix_min);
+    // Lane-local positions, that are offset to absolute positions in the output.
+    let mut pos = S::splat(0);
+    let pos_offset: S =
+        from_fn(|l| (l * len.saturating_sub(k + w - 2)).wrapping_sub(k - 1) as u32).into();
+
+    #[inline(always)]
+    move |val| {
+        let elem = (val, pos);
+        pos += S::splat(1);
+        ring_buf.push(elem);
+        prefix_min = min(prefix_min, elem);
+        // After a chunk has been filled, compute suffix minima.
+        if ring_buf.idx() == 0 {
+            let mut suffix_min = ring_buf[w - 1];
+            for i in (0..w - 1).rev() {
+                suffix_min = min(suffix_min, ring_buf[i]);
+                ring_buf[i] = suffix_min;
+            }
+            prefix_min = elem;
+        }
+        let suffix_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+        min(prefix_min, suffix_min).1 + pos_offset
+    }
+}
+
+/// Like [`sliding_min64_scalar`], but returns both the leftmost and the rightmost minimum.
+pub fn sliding_lr_min64_scalar(
+    it: impl ExactSizeIterator<Item = u64>,
+    w: usize,
+) -> impl ExactSizeIterator<Item = (u32, u32)> {
+    assert!(w > 0);
+    assert!(
+        it.len() < (1 << 32),
+        "sliding_min returns 32bit indices. Try splitting the input into 4GB chunks first."
+    );
+    let mut prefix_lr_min = (u64::MAX, u64::MAX);
+    let mut ring_buf = RingBuf::new(w, prefix_lr_min);
+    // As for `sliding_lr_min_mapper`, the rightmost minimum is the maximum of the inverted values,
+    // so that ties are broken in favour of higher pos.
+    let val_mask = 0xffff_ffff_0000_0000;
+    let mut pos = 0u64;
+    let lr_min = |(al, ar): (u64, u64), (bl, br): (u64, u64)| (al.min(bl), ar.max(br));
+
+    let mut it = it.map(
+        #[inline(always)]
+        move |val| {
+            let elem = ((val & val_mask) | pos, (!val & val_mask) | pos);
+            pos += 1;
+            ring_buf.push(elem);
+            prefix_lr_min = lr_min(prefix_lr_min, elem);
+            // After a chunk has been filled, compute suffix minima.
+            if ring_buf.idx() == 0 {
+                let mut suffix_lr_min = ring_buf[w - 1];
+                for i in (0..w - 1).rev() {
+                    suffix_lr_min = lr_min(suffix_lr_min, ring_buf[i]);
+                    ring_buf[i] = suffix_lr_min;
+                }
+                prefix_lr_min = elem;
+            }
+            let suffix_lr_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+            let (lmin, rmin) = lr_min(prefix_lr_min, suffix_lr_min);
+            (lmin as u32, rmin as u32)
+        },
+    );
+    it.by_ref().take(w - 1).for_each(drop);
+    it
+}
+
+/// Like [`sliding_min64_mapper`], but returns both the leftmost and the rightmost minimum.
+pub fn sliding_lr_min64_mapper(w: usize, k: usize, len: usize) -> impl FnMut(S64) -> (S, S) {
+    assert!(w > 0);
+    assert!(len * 8 < (1 << 32));
+    let mut prefix_lr_min = (S64::splat(u64::MAX), S64::splat(u64::MAX));
+    let mut ring_buf = RingBuf::new(w, prefix_lr_min);
+    let val_mask = S64::splat(0xffff_ffff_0000_0000);
+    let not = S64::splat(u64::MAX);
+    // Lane-local positions, that are offset to absolute positions in the output.
+    let mut pos = 0u64;
+    let pos_offset: S =
+        from_fn(|l| (l * len.saturating_sub(k + w - 2)).wrapping_sub(k - 1) as u32).into();
+    let lr_min = |(al, ar): (S64, S64), (bl, br): (S64, S64)| (al.min(bl), ar.max(br));
+
+    #[inline(always)]
+    move |val| {
+        let elem = (
+            (val & val_mask) | S64::splat(pos),
+            ((val ^ not) & val_mask) | S64::splat(pos),
+        );
+        pos += 1;
+        ring_buf.push(elem);
+        prefix_lr_min = lr_min(prefix_lr_min, elem);
+        // After a chunk has been filled, compute suffix minima.
+        if ring_buf.idx() == 0 {
+            let mut suffix_lr_min = ring_buf[w - 1];
+            for i in (0..w - 1).rev() {
+                suffix_lr_min = lr_min(suffix_lr_min, ring_buf[i]);
+                ring_buf[i] = suffix_lr_min;
+            }
+            prefix_lr_min = elem;
+        }
+        let suffix_lr_min = unsafe { *ring_buf.get_unchecked(ring_buf.idx()) };
+        let (lmin, rmin) = lr_min(prefix_lr_min, suffix_lr_min);
+        (lmin.low_u32() + pos_offset, rmin.low_u32() + pos_offset)
+    }
+}
+
+/// Positions of the leftmost minimum of each window of `w` consecutive `values`, comparing all 32 bits.
+///
+/// The values are split into 8 chunks of equal length that are processed in parallel using [`sliding_min64_mapper`].
+/// The padding at the end of the last chunks is returned separately.
+pub fn sliding_window_min_seq_simd(
+    values: &[u32],
+    w: usize,
+) -> (impl ExactSizeIterator<Item = S> + '_, usize) {
+    assert!(w > 0);
+    let num_windows = (values.len() + 1).saturating_sub(w);
+    // Number of windows in each lane.
+    let n = num_windows.div_ceil(8);
+    let padding = 8 * n - num_windows;
+    let len = n + w - 1;
+
+    let mut sliding_min = sliding_min64_mapper(w, 1, len);
+    let mut it = (0..len).map(move |i| {
+        let val = S64 {
+            lo: S::ZERO,
+            // Values after the end are padded with the maximum value, and their windows are discarded.
+            hi: S::new(from_fn(|l| *values.get(l * n + i).unwrap_or(&u32::MAX))),
+        };
+        sliding_min(val)
+    });
+    it.by_ref().take(w - 1).for_each(drop);
+    (it, padding)
+}
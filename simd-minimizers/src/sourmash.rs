@@ -0,0 +1,266 @@
+//! FracMinHash sketches that are compatible with [sourmash](https://sourmash.readthedocs.io).
+//!
+//! Like sourmash, each k-mer is canonicalized to the lexicographically smaller of its upper-case forward and reverse complement strings,
+//! hashed with the first 64 bits of MurmurHash3 `x64_128` with seed 42, and kept when the hash is at most `u64::MAX / scaled`.
+//! K-mers containing characters other than `ACGT` are skipped.
+//! [`FracMinHash::to_json`] writes a sourmash JSON signature that can be loaded by `sourmash sig` and friends.
+//!
+//! ```
+//! use simd_minimizers::sourmash::{hash_murmur, FracMinHash, DEFAULT_SEED};
+//! assert_eq!(hash_murmur(b"ACG", DEFAULT_SEED), 1731421407650554201);
+//!
+//! let mut sketch = FracMinHash::new(21, 1);
+//! sketch.add_sequence(b"ACGTACGTACGTACGTACGTACGTTTTGG");
+//! assert_eq!(sketch.hashes().len(), 7);
+//! assert!(sketch.to_json("example").contains(r#""ksize":21"#));
+//! ```
+use crate::validate::is_acgt;
+
+/// The seed used by sourmash.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// The first 64 bits of the MurmurHash3 `x64_128` hash of `data`, as used by sourmash.
+pub fn hash_murmur(data: &[u8], seed: u64) -> u64 {
+    const C1: u64 = 0x87c3_7b91_1142_53d5;
+    const C2: u64 = 0x4cf5_ad43_2745_937f;
+
+    fn fmix(mut k: u64) -> u64 {
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        k ^ (k >> 33)
+    }
+    fn mix_k1(k1: u64) -> u64 {
+        k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2)
+    }
+    fn mix_k2(k2: u64) -> u64 {
+        k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1)
+    }
+    fn read_u64(bytes: &[u8]) -> u64 {
+        let mut buf = [0; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(buf)
+    }
+
+    let mut h1 = seed;
+    let mut h2 = seed;
+    let mut blocks = data.chunks_exact(16);
+    for block in &mut blocks {
+        h1 ^= mix_k1(read_u64(&block[..8]));
+        h1 = h1.rotate_left(27).wrapping_add(h2);
+        h1 = h1.wrapping_mul(5).wrapping_add(0x52dc_e729);
+        h2 ^= mix_k2(read_u64(&block[8..]));
+        h2 = h2.rotate_left(31).wrapping_add(h1);
+        h2 = h2.wrapping_mul(5).wrapping_add(0x3849_5ab5);
+    }
+    let tail = blocks.remainder();
+    if tail.len() > 8 {
+        h2 ^= mix_k2(read_u64(&tail[8..]));
+    }
+    if !tail.is_empty() {
+        h1 ^= mix_k1(read_u64(&tail[..tail.len().min(8)]));
+    }
+
+    h1 ^= data.len() as u64;
+    h2 ^= data.len() as u64;
+    h1 = h1.wrapping_add(h2);
+    h2 = h2.wrapping_add(h1);
+    h1 = fmix(h1);
+    h2 = fmix(h2);
+    h1.wrapping_add(h2)
+}
+
+/// The largest hash that is kept for the given `scaled`, as computed by sourmash.
+pub fn max_hash_for_scaled(scaled: u64) -> u64 {
+    match scaled {
+        0 => 0,
+        1 => u64::MAX,
+        _ => (u64::MAX as f64 / scaled as f64) as u64,
+    }
+}
+
+/// A sourmash-compatible FracMinHash sketch of DNA sequences.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FracMinHash {
+    ksize: usize,
+    scaled: u64,
+    seed: u64,
+    max_hash: u64,
+    /// Sorted and distinct.
+    hashes: Vec<u64>,
+}
+
+impl FracMinHash {
+    /// An empty sketch of k-mers of length `ksize`, keeping a fraction `1/scaled` of hashes.
+    pub fn new(ksize: usize, scaled: u64) -> Self {
+        Self::with_seed(ksize, scaled, DEFAULT_SEED)
+    }
+
+    /// Like [`FracMinHash::new`], but with a custom `seed`.
+    pub fn with_seed(ksize: usize, scaled: u64, seed: u64) -> Self {
+        assert!(ksize > 0);
+        assert!(scaled > 0);
+        FracMinHash {
+            ksize,
+            scaled,
+            seed,
+            max_hash: max_hash_for_scaled(scaled),
+            hashes: vec![],
+        }
+    }
+
+    /// The k-mer length.
+    pub fn ksize(&self) -> usize {
+        self.ksize
+    }
+
+    /// The inverse of the fraction of hashes that is kept.
+    pub fn scaled(&self) -> u64 {
+        self.scaled
+    }
+
+    /// The largest hash that is kept.
+    pub fn max_hash(&self) -> u64 {
+        self.max_hash
+    }
+
+    /// The sorted and distinct hashes in the sketch.
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    /// Add the canonical k-mers of the ASCII DNA sequence `seq`.
+    pub fn add_sequence(&mut self, seq: &[u8]) {
+        let k = self.ksize;
+        if seq.len() < k {
+            return;
+        }
+        let fwd = seq.to_ascii_uppercase();
+        let rc = fwd.iter().rev().map(|&c| complement(c)).collect::<Vec<_>>();
+        // The number of consecutive ACGT characters ending at the current position.
+        let mut run = 0;
+        for (i, &c) in fwd.iter().enumerate() {
+            run = if is_acgt(c) { run + 1 } else { 0 };
+            if run < k {
+                continue;
+            }
+            let start = i + 1 - k;
+            let kmer = &fwd[start..start + k];
+            let kmer_rc = &rc[fwd.len() - start - k..fwd.len() - start];
+            let hash = hash_murmur(kmer.min(kmer_rc), self.seed);
+            if hash <= self.max_hash {
+                self.hashes.push(hash);
+            }
+        }
+        self.hashes.sort_unstable();
+        self.hashes.dedup();
+    }
+
+    /// The MD5 checksum that sourmash uses to identify the sketch.
+    pub fn md5sum(&self) -> String {
+        let mut data = self.ksize.to_string().into_bytes();
+        for hash in &self.hashes {
+            data.extend_from_slice(hash.to_string().as_bytes());
+        }
+        md5(&data).iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// A sourmash JSON signature containing this sketch, with the given `name`.
+    pub fn to_json(&self, name: &str) -> String {
+        let mins = self
+            .hashes
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            concat!(
+                r#"[{{"class":"sourmash_signature","email":"","hash_function":"0.murmur64","filename":"","#,
+                r#""name":{},"license":"CC0","signatures":[{{"num":0,"ksize":{},"seed":{},"max_hash":{},"#,
+                r#""mins":[{}],"md5sum":"{}","molecule":"DNA"}}],"version":0.4}}]"#
+            ),
+            json_string(name),
+            self.ksize,
+            self.seed,
+            self.max_hash,
+            mins,
+            self.md5sum(),
+        )
+    }
+}
+
+/// The complement of an upper-case base. Other characters are kept as is.
+fn complement(c: u8) -> u8 {
+    match c {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        c => c,
+    }
+}
+
+/// `s` as a quoted and escaped JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The MD5 digest of `data`.
+fn md5(data: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let k: [u32; 64] =
+        std::array::from_fn(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32);
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+    for chunk in msg.chunks_exact(64) {
+        let m: [u32; 16] = std::array::from_fn(|i| {
+            u32::from_le_bytes(chunk[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+        for (s, x) in state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(x);
+        }
+    }
+
+    let mut digest = [0; 16];
+    for (i, s) in state.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&s.to_le_bytes());
+    }
+    digest
+}
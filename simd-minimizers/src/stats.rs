@@ -0,0 +1,114 @@
+//! Empirical density statistics of sampling schemes.
+//!
+//! [`density`] measures random minimizers, and [`measure_scheme`] measures any scheme that appends
+//! the positions it selects in a sequence to a vector, e.g. [`canonical_minimizer_positions`](crate::canonical_minimizer_positions)
+//! or [`mod_minimizer_positions`](crate::mod_minimizer_positions). Both use the SIMD pipeline.
+//!
+//! ```
+//! use simd_minimizers::packed_seq::{PackedSeqVec, SeqVec};
+//! use simd_minimizers::stats;
+//! let seq = PackedSeqVec::random(100_000);
+//! let (k, w) = (21, 11);
+//! let random = stats::density(seq.as_slice(), k, w);
+//! let modmini = stats::measure_scheme(
+//!     |seq, out_vec| simd_minimizers::mod_minimizer_positions(seq, k, w, 4 + (k - 4) % w, out_vec),
+//!     &[seq.as_slice()],
+//!     k,
+//! );
+//! assert!(modmini.density() < random.density());
+//! ```
+use packed_seq::Seq;
+
+/// Statistics of the positions selected by a scheme in one or more sequences.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DensityStats {
+    /// The total number of k-mers in the sequences.
+    pub num_kmers: usize,
+    /// The number of selected positions, after removing adjacent duplicates.
+    pub num_selected: usize,
+    /// The number of distinct selected positions.
+    /// This is smaller than `num_selected` for non-forward schemes such as canonical minimizers.
+    pub num_distinct: usize,
+    /// `distances[d]` is the number of pairs of consecutive selected positions at distance `d`.
+    pub distances: Vec<usize>,
+}
+
+impl DensityStats {
+    /// Add the selected `positions` of a sequence with `num_kmers` k-mers.
+    pub fn add(&mut self, positions: &[u32], num_kmers: usize) {
+        self.num_kmers += num_kmers;
+        self.num_selected += positions.len();
+        for (&a, &b) in positions.iter().zip(positions.iter().skip(1)) {
+            let d = a.abs_diff(b) as usize;
+            if d >= self.distances.len() {
+                self.distances.resize(d + 1, 0);
+            }
+            self.distances[d] += 1;
+        }
+        let mut distinct = positions.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        self.num_distinct += distinct.len();
+    }
+
+    /// Combine the statistics of two sets of sequences.
+    pub fn merge(&mut self, other: &DensityStats) {
+        self.num_kmers += other.num_kmers;
+        self.num_selected += other.num_selected;
+        self.num_distinct += other.num_distinct;
+        if other.distances.len() > self.distances.len() {
+            self.distances.resize(other.distances.len(), 0);
+        }
+        for (d, &count) in other.distances.iter().enumerate() {
+            self.distances[d] += count;
+        }
+    }
+
+    /// The fraction of k-mers that is selected.
+    pub fn density(&self) -> f64 {
+        if self.num_kmers == 0 {
+            return 0.0;
+        }
+        self.num_distinct as f64 / self.num_kmers as f64
+    }
+
+    /// The mean distance between consecutive selected positions.
+    pub fn mean_distance(&self) -> f64 {
+        let (count, sum) = self
+            .distances
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(count, sum), (d, &c)| (count + c, sum + d * c));
+        if count == 0 {
+            return 0.0;
+        }
+        sum as f64 / count as f64
+    }
+}
+
+/// Density statistics of the random minimizers of `seq`.
+pub fn density<'s, S: Seq<'s>>(seq: S, k: usize, w: usize) -> DensityStats {
+    measure_scheme(
+        |seq, out_vec| crate::minimizer_positions(seq, k, w, out_vec),
+        &[seq],
+        k,
+    )
+}
+
+/// Density statistics of a scheme on the given sequences.
+///
+/// `scheme(seq, out_vec)` must append the positions selected in `seq` to `out_vec`.
+pub fn measure_scheme<'s, S: Seq<'s>>(
+    mut scheme: impl FnMut(S, &mut Vec<u32>),
+    seqs: &[S],
+    k: usize,
+) -> DensityStats {
+    let mut stats = DensityStats::default();
+    let mut positions = vec![];
+    for &seq in seqs {
+        positions.clear();
+        scheme(seq, &mut positions);
+        stats.add(&positions, (seq.len() + 1).saturating_sub(k));
+    }
+    stats
+}
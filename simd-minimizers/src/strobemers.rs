@@ -0,0 +1,196 @@
+//! Randstrobes and minstrobes of order 2, as used as seeds by strobealign.
+//!
+//! A strobemer links the k-mer at position `pos1` (the first strobe) to a second k-mer at `pos2` in `pos1+w_min..=pos1+w_max`.
+//! For minstrobes, the second strobe is the k-mer with the smallest hash in this window.
+//! For randstrobes, it is the k-mer whose hash xor the hash of the first strobe is smallest, so that it depends on the first strobe.
+//! As for minimizers, only the upper 16 bits of these keys are compared, and ties are broken towards the leftmost k-mer.
+//!
+//! The hash of the strobemer is `h1 ^ h2.rotate_left(16)`, where `h1` and `h2` are the forward ntHashes of the two strobes.
+//! Only first strobes whose window of second strobes is fully contained in the sequence are returned.
+//!
+//! The k-mer hashes reuse the rolling hash of the minimizer pipeline, and a ring buffer of the last `w_max+1` hashes.
+//! The second strobe is found by a linear scan over the window, in parallel for all 8 lanes.
+use std::array::from_fn;
+
+use crate::nthash::{nthash_mapper, nthash_seq_scalar, Captures, CharHasher};
+use crate::S;
+use packed_seq::{Seq, L};
+
+/// A seed of two linked k-mers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Strobemer {
+    /// The combined hash of both strobes.
+    pub hash: u32,
+    /// The position of the first strobe.
+    pub pos1: u32,
+    /// The position of the second strobe.
+    pub pos2: u32,
+}
+
+fn check_window(w_min: usize, w_max: usize) {
+    assert!(
+        0 < w_min && w_min <= w_max,
+        "Strobemers require 0 < w_min <= w_max."
+    );
+    assert!(
+        w_max - w_min < 1 << 16,
+        "Strobemers require a window of less than 2^16 k-mers."
+    );
+}
+
+/// The key of a second strobe candidate with hash `h`, of which the smallest is chosen.
+fn key<const RAND: bool>(h1: u32, h: u32) -> u32 {
+    (if RAND { h1 ^ h } else { h }) & 0xffff_0000
+}
+
+/// Returns an iterator over the strobemers of a sequence.
+/// Set `RAND` to `true` for randstrobes, and to `false` for minstrobes.
+pub fn strobemers_seq_scalar<'s, const RAND: bool, H: CharHasher>(
+    seq: impl Seq<'s>,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+) -> impl ExactSizeIterator<Item = Strobemer> + Captures<&'s ()> {
+    check_window(w_min, w_max);
+    let hashes: Vec<u32> = nthash_seq_scalar::<false, H>(seq, k).collect();
+    let n = hashes.len().saturating_sub(w_max);
+    (0..n).map(move |i| {
+        let h1 = hashes[i];
+        let (_, o) = (w_min..=w_max)
+            .map(|o| (key::<RAND>(h1, hashes[i + o]), o))
+            .min()
+            .unwrap();
+        Strobemer {
+            hash: h1 ^ hashes[i + o].rotate_left(16),
+            pos1: i as u32,
+            pos2: (i + o) as u32,
+        }
+    })
+}
+
+/// Split the strobemers of the sequence into 8 chunks of equal length, and return their hash and the positions of both strobes
+/// in parallel using SIMD.
+/// Set `RAND` to `true` for randstrobes, and to `false` for minstrobes.
+pub fn strobemers_seq_simd<'s, const RAND: bool, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, S, S)> + Captures<&'s ()>,
+    usize,
+) {
+    check_window(w_min, w_max);
+    // Both strobes span `k+w_max` characters.
+    let l = k + w_max;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(l, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w_max + 1);
+    // Number of strobemers in each lane.
+    let n = add_remove.len().saturating_sub(l - 1);
+    // The position of the first strobe of the strobemer that ends at the added character.
+    let mut pos1 = S::new(from_fn(|lane| (lane * n).wrapping_sub(l - 1) as u32));
+    // The hashes of the last `w_max+1` k-mers, indexed by the position of the added character.
+    let mut ring_buf = vec![S::splat(0); w_max + 1];
+    let mut i = 0;
+    let one = S::splat(1);
+    let val_mask = S::splat(0xffff_0000);
+    let pos_mask = S::splat(0x0000_ffff);
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        ring_buf[i % (w_max + 1)] = nthash((a, rk));
+        // The oldest hash is the one of the first strobe.
+        let h1 = ring_buf[(i + 1) % (w_max + 1)];
+        let mut best = S::splat(u32::MAX);
+        let mut h2 = S::splat(0);
+        for o in w_min..=w_max {
+            let h = ring_buf[(i + 1 + o) % (w_max + 1)];
+            let key = ((if RAND { h1 ^ h } else { h }) & val_mask) | S::splat((o - w_min) as u32);
+            let new_best = best.min(key);
+            // Keys at different offsets are distinct, so this is the case exactly when `key` is the new minimum.
+            h2 = new_best.cmp_eq(key).blend(h, h2);
+            best = new_best;
+        }
+        i += 1;
+        let strobe1 = pos1;
+        pos1 += one;
+        let hash = h1 ^ ((h2 << 16_i32) | (h2 >> 16_i32));
+        let strobe2 = strobe1 + (best & pos_mask) + S::splat(w_min as u32);
+        (hash, strobe1, strobe2)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
+
+/// Randstrobes of the sequence, using SIMD. See `strobemers_seq_simd`.
+pub fn randstrobes_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, S, S)> + Captures<&'s ()>,
+    usize,
+) {
+    strobemers_seq_simd::<true, SEQ, H>(seq, k, w_min, w_max)
+}
+
+/// Minstrobes of the sequence, using SIMD. See `strobemers_seq_simd`.
+pub fn minstrobes_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, S, S)> + Captures<&'s ()>,
+    usize,
+) {
+    strobemers_seq_simd::<false, SEQ, H>(seq, k, w_min, w_max)
+}
+
+/// All strobemers of the sequence, using SIMD.
+///
+/// Strobemers are appended to `out_vec` in order of their first strobe.
+pub fn strobemers_into<'s, const RAND: bool, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    w_min: usize,
+    w_max: usize,
+    out_vec: &mut Vec<Strobemer>,
+) {
+    let (head, padding) = strobemers_seq_simd::<RAND, SEQ, H>(seq, k, w_min, w_max);
+
+    let n = head.len();
+    assert!(padding <= L * n, "padding {padding} <= L {L} * n {n}");
+    // Number of non-padding strobemers in each lane.
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+
+    let base = out_vec.len();
+    out_vec.resize(
+        base + L * n - padding,
+        Strobemer {
+            hash: 0,
+            pos1: 0,
+            pos2: 0,
+        },
+    );
+    let out = &mut out_vec[base..];
+    head.enumerate().for_each(|(i, (hash, pos1, pos2))| {
+        let (hash, pos1, pos2) = (
+            hash.as_array_ref(),
+            pos1.as_array_ref(),
+            pos2.as_array_ref(),
+        );
+        for lane in 0..L {
+            if i < valid[lane] {
+                out[lane * n + i] = Strobemer {
+                    hash: hash[lane],
+                    pos1: pos1[lane],
+                    pos2: pos2[lane],
+                };
+            }
+        }
+    });
+}
@@ -0,0 +1,102 @@
+//! Open and closed syncmers.
+//!
+//! A k-mer is a syncmer when the position of its smallest s-mer is one of a given set of offsets.
+//! Open syncmers use a single offset `t`, and closed syncmers use offsets `0` and `k-s`.
+//! Unlike minimizers, whether a k-mer is selected does not depend on its neighbouring k-mers.
+//!
+//! The smallest s-mer of each k-mer is the minimizer of a window of `k-s+1` s-mers,
+//! so this reuses the rolling hash and sliding window minimum of the minimizer pipeline.
+use std::array::from_fn;
+
+use crate::minimizers::{minimizers_seq_scalar, minimizers_seq_simd};
+use crate::nthash::{Captures, CharHasher};
+use crate::S;
+use packed_seq::{Seq, L};
+
+/// A lookup table of the offsets of the smallest s-mer at which a k-mer is a syncmer.
+fn offset_table(k: usize, s: usize, offsets: &[usize]) -> Vec<bool> {
+    assert!(0 < s && s <= k, "Syncmers require 0 < s <= k.");
+    let mut table = vec![false; k - s + 1];
+    for &t in offsets {
+        assert!(
+            t <= k - s,
+            "Syncmer offset {t} must be at most k-s={}.",
+            k - s
+        );
+        table[t] = true;
+    }
+    table
+}
+
+/// Returns an iterator over the positions of all syncmers of a sequence.
+///
+/// The smallest s-mer of each k-mer is the leftmost s-mer with the smallest hash.
+pub fn syncmers_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    k: usize,
+    s: usize,
+    offsets: &[usize],
+) -> impl Iterator<Item = u32> + Captures<&'s ()> {
+    let table = offset_table(k, s, offsets);
+    minimizers_seq_scalar::<H>(seq, s, k - s + 1)
+        .enumerate()
+        .filter(move |&(i, min_pos)| table[min_pos as usize - i])
+        .map(|(i, _)| i as u32)
+}
+
+/// Split the k-mers of the sequence into 8 chunks, and return for each k-mer its position
+/// and the offset of its smallest s-mer in parallel using SIMD.
+pub fn syncmer_offsets_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    s: usize,
+) -> (
+    impl ExactSizeIterator<Item = (S, S)> + Captures<&'s ()>,
+    usize,
+) {
+    assert!(0 < s && s <= k, "Syncmers require 0 < s <= k.");
+    let (head, padding) = minimizers_seq_simd::<SEQ, H>(seq, s, k - s + 1);
+    let n = head.len();
+    let mut kmer_pos = S::new(from_fn(|lane| (lane * n) as u32));
+    let head = head.map(move |min_pos| {
+        let pos = kmer_pos;
+        kmer_pos += S::splat(1);
+        (pos, min_pos - pos)
+    });
+    (head, padding)
+}
+
+/// Positions of all syncmers of the sequence, using SIMD.
+///
+/// A k-mer is a syncmer when the offset of its smallest s-mer is one of `offsets`.
+/// Positions are appended to `out_vec` in increasing order.
+pub fn syncmers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    k: usize,
+    s: usize,
+    offsets: &[usize],
+    out_vec: &mut Vec<u32>,
+) {
+    let table = offset_table(k, s, offsets);
+    let (head, padding) = syncmer_offsets_seq_simd::<SEQ, H>(seq, k, s);
+
+    let n = head.len();
+    assert!(padding <= L * n, "padding {padding} <= L {L} * n {n}");
+    // Number of non-padding k-mers in each lane.
+    let valid: [usize; L] = from_fn(|lane| (L * n - padding).saturating_sub(lane * n).min(n));
+
+    let mut lanes: [Vec<u32>; L] = from_fn(|_| Vec::new());
+    head.enumerate().for_each(|(i, (pos, offset))| {
+        let pos = pos.as_array_ref();
+        let offset = offset.as_array_ref();
+        for lane in 0..L {
+            if i < valid[lane] && table[offset[lane] as usize] {
+                lanes[lane].push(pos[lane]);
+            }
+        }
+    });
+    // Lanes contain disjoint ranges of k-mers, so no deduplication is needed.
+    for lane in lanes {
+        out_vec.extend(lane);
+    }
+}
@@ -4,7 +4,7 @@ use collect::collect;
 use itertools::Itertools;
 use packed_seq::{AsciiSeq, AsciiSeqVec, PackedSeq, PackedSeqVec, SeqVec};
 use rand::{random_range, Rng};
-use std::sync::LazyLock;
+use std::sync::{atomic::AtomicBool, LazyLock};
 
 /// Swap G and T, so that the lex order is the same as for the packed version.
 fn swap_gt(c: u8) -> u8 {
@@ -89,6 +89,11 @@ fn nthash_forward_mul() {
     test_nthash::<false, MulHasher>();
 }
 
+#[test]
+fn nthash_forward_byte() {
+    test_nthash::<false, ByteHasher>();
+}
+
 #[test]
 fn nthash_canonical_mul() {
     test_nthash::<true, MulHasher>();
@@ -159,6 +164,105 @@ fn test_anti_lex_hash() {
     });
 }
 
+#[test]
+fn test_canonical_anti_lex_hash() {
+    use anti_lex::*;
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if w > 1 {
+            return;
+        }
+        let naive = ascii_seq
+            .0
+            .windows(k)
+            .map(|seq| canonical_anti_lex_hash_kmer(AsciiSeq(seq)))
+            .collect::<Vec<_>>();
+        let scalar_ascii = canonical_anti_lex_hash_seq_scalar(ascii_seq, k).collect::<Vec<_>>();
+        let scalar_packed = canonical_anti_lex_hash_seq_scalar(packed_seq, k).collect::<Vec<_>>();
+        let simd_ascii = collect(canonical_anti_lex_hash_seq_simd(ascii_seq, k, 1));
+        let simd_packed = collect(canonical_anti_lex_hash_seq_simd(packed_seq, k, 1));
+        let len = ascii_seq.len();
+        assert_eq!(scalar_ascii, naive, "k={k}, len={len}");
+        assert_eq!(scalar_packed, naive, "k={k}, len={len}");
+        assert_eq!(simd_ascii, naive, "k={k}, len={len}");
+        assert_eq!(simd_packed, naive, "k={k}, len={len}");
+
+        // Both strands have the same hash.
+        for kmer in ascii_seq.0.windows(k).take(10) {
+            let rc = kmer
+                .iter()
+                .rev()
+                .map(|&c| packed_seq::complement_char(c))
+                .collect_vec();
+            assert_eq!(
+                canonical_anti_lex_hash_kmer(AsciiSeq(kmer)),
+                canonical_anti_lex_hash_kmer(AsciiSeq(&rc)),
+                "k={k}"
+            );
+        }
+    });
+}
+
+#[test]
+fn canonical_anti_lex_minimizers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let scalar_ascii = canonical_anti_lex_minimizers_seq_scalar(ascii_seq, k, w).collect_vec();
+        let simd_ascii = collect(canonical_anti_lex_minimizers_seq_simd(ascii_seq, k, w));
+        let simd_packed = collect(canonical_anti_lex_minimizers_seq_simd(packed_seq, k, w));
+        let len = ascii_seq.len();
+        assert_eq!(scalar_ascii, simd_ascii, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_ascii, simd_packed, "k={k}, w={w}, len={len}");
+    });
+
+    // Canonical minimizers are the same on both strands.
+    let seq = &ASCII_SEQ.seq;
+    let rc = seq
+        .iter()
+        .rev()
+        .map(|&c| packed_seq::complement_char(c))
+        .collect_vec();
+    for (k, w) in [(5, 11), (21, 11), (31, 19)] {
+        let fwd = canonical_anti_lex_minimizers_seq_scalar(AsciiSeq(seq), k, w).collect_vec();
+        let rc = canonical_anti_lex_minimizers_seq_scalar(AsciiSeq(&rc), k, w)
+            .map(|p| (seq.len() - k) as u32 - p)
+            .collect_vec();
+        assert_eq!(fwd, rc.into_iter().rev().collect_vec(), "k={k}, w={w}");
+    }
+}
+
+#[test]
+fn canonical_anti_lex_minimizers_naive() {
+    // For small k, the whole hash must be compared, not only its low bits.
+    let seq = &ASCII_SEQ.seq[..2000];
+    for k in 1..=8 {
+        for w in [1, 2, 3, 4, 5, 11, 20] {
+            if (k + w - 1) % 2 == 0 {
+                continue;
+            }
+            let hashes = seq
+                .windows(k)
+                .map(|kmer| anti_lex::canonical_anti_lex_hash_kmer(AsciiSeq(kmer)))
+                .collect_vec();
+            let canonical = canonical::canonical_windows_seq_scalar(AsciiSeq(seq), k, w);
+            let naive = zip(hashes.windows(w), canonical)
+                .enumerate()
+                .map(|(i, (window, canonical))| {
+                    let min = (0..w).min_by_key(|&j| window[j]).unwrap();
+                    let max = (0..w).rev().min_by_key(|&j| window[j]).unwrap();
+                    (i + if canonical { min } else { max }) as u32
+                })
+                .collect_vec();
+            let scalar =
+                canonical_anti_lex_minimizers_seq_scalar(AsciiSeq(seq), k, w).collect_vec();
+            let simd = collect(canonical_anti_lex_minimizers_seq_simd(AsciiSeq(seq), k, w));
+            assert_eq!(naive, scalar, "k={k}, w={w}");
+            assert_eq!(naive, simd, "k={k}, w={w}");
+        }
+    }
+}
+
 #[test]
 fn minimizers_fwd() {
     fn f<H: CharHasher>() {
@@ -184,15 +288,13 @@ fn minimizers_fwd() {
     }
     f::<NtHasher>();
     f::<MulHasher>();
+    f::<ByteHasher>();
 }
 
 #[test]
 fn minimizers_canonical() {
     fn f<H: CharHasher>() {
         test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
-            if (k + w - 1) % 2 == 0 {
-                return;
-            }
             let scalar_ascii =
                 canonical_minimizers_seq_scalar::<H>(ascii_seq, k, w).collect::<Vec<_>>();
             let scalar_packed =
@@ -232,9 +334,6 @@ fn minimizer_positions() {
 #[test]
 fn canonical_minimizer_positions() {
     test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
-        if (k + w - 1) % 2 == 0 {
-            return;
-        }
         let mut scalar_ascii = vec![];
         scalar::canonical_minimizer_positions_scalar(ascii_seq, k, w, &mut scalar_ascii);
         let mut scalar_packed = vec![];
@@ -321,9 +420,6 @@ fn minimizer_and_superkmer_positions() {
 #[test]
 fn canonical_minimizer_and_superkmer_positions() {
     test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
-        if (k + w - 1) % 2 == 0 {
-            return;
-        }
         let mut scalar_ascii = vec![];
         let mut scalar_ascii_skmer = vec![];
         scalar::canonical_minimizer_and_superkmer_positions_scalar(
@@ -399,3 +495,4224 @@ fn canonical_minimizer_and_superkmer_positions() {
         );
     });
 }
+
+#[test]
+fn minimizer_positions_and_kmers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if k > 32 {
+            return;
+        }
+        let mut scalar_pos = vec![];
+        let mut scalar_kmers = vec![];
+        scalar::minimizer_positions_and_kmers_scalar(
+            ascii_seq,
+            k,
+            w,
+            &mut scalar_pos,
+            &mut scalar_kmers,
+        );
+        let mut simd_ascii_pos = vec![];
+        let mut simd_ascii_kmers = vec![];
+        super::minimizer_positions_and_kmers(
+            ascii_seq,
+            k,
+            w,
+            &mut simd_ascii_pos,
+            &mut simd_ascii_kmers,
+        );
+        let mut simd_packed_pos = vec![];
+        let mut simd_packed_kmers = vec![];
+        super::minimizer_positions_and_kmers(
+            packed_seq,
+            k,
+            w,
+            &mut simd_packed_pos,
+            &mut simd_packed_kmers,
+        );
+        let mut pos = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut pos);
+
+        let len = ascii_seq.len();
+        assert_eq!(scalar_pos, pos, "k={k}, w={w}, len={len}");
+        assert_eq!(simd_ascii_pos, pos, "k={k}, w={w}, len={len}");
+        assert_eq!(simd_packed_pos, pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_kmers, simd_ascii_kmers, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_kmers, simd_packed_kmers, "k={k}, w={w}, len={len}");
+
+        // Appending keeps a first position that equals the last existing one.
+        let mut appended_pos = pos[..pos.len().min(1)].to_vec();
+        let mut appended_kmers = scalar_kmers[..pos.len().min(1)].to_vec();
+        super::minimizer_positions_and_kmers(
+            packed_seq,
+            k,
+            w,
+            &mut appended_pos,
+            &mut appended_kmers,
+        );
+        assert_eq!(
+            appended_pos,
+            [&pos[..pos.len().min(1)], &pos].concat(),
+            "k={k}, w={w}, len={len}"
+        );
+        assert_eq!(
+            appended_kmers,
+            [&scalar_kmers[..pos.len().min(1)], &scalar_kmers].concat(),
+            "k={k}, w={w}, len={len}"
+        );
+    });
+}
+
+#[test]
+fn canonical_minimizer_positions_and_kmers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if k > 32 || (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let mut scalar_pos = vec![];
+        let mut scalar_kmers = vec![];
+        scalar::canonical_minimizer_positions_and_kmers_scalar(
+            ascii_seq,
+            k,
+            w,
+            &mut scalar_pos,
+            &mut scalar_kmers,
+        );
+        let mut simd_pos = vec![];
+        let mut simd_kmers = vec![];
+        super::canonical_minimizer_positions_and_kmers(
+            packed_seq,
+            k,
+            w,
+            &mut simd_pos,
+            &mut simd_kmers,
+        );
+        let mut pos = vec![];
+        super::canonical_minimizer_positions(packed_seq, k, w, &mut pos);
+
+        let len = ascii_seq.len();
+        assert_eq!(scalar_pos, pos, "k={k}, w={w}, len={len}");
+        assert_eq!(simd_pos, pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_kmers, simd_kmers, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn minimizer_positions_and_hashes() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        let len = ascii_seq.len();
+        for canonical in [false, true] {
+            if canonical && (k + w - 1) % 2 == 0 {
+                continue;
+            }
+            let mut pos = vec![];
+            let mut scalar_pos = vec![];
+            let mut scalar_hashes = vec![];
+            let mut simd_pos = vec![];
+            let mut simd_hashes = vec![];
+            if canonical {
+                super::canonical_minimizer_positions(packed_seq, k, w, &mut pos);
+                scalar::canonical_minimizer_positions_and_hashes_scalar(
+                    ascii_seq,
+                    k,
+                    w,
+                    &mut scalar_pos,
+                    &mut scalar_hashes,
+                );
+                super::canonical_minimizer_positions_and_hashes(
+                    packed_seq,
+                    k,
+                    w,
+                    &mut simd_pos,
+                    &mut simd_hashes,
+                );
+            } else {
+                super::minimizer_positions(packed_seq, k, w, &mut pos);
+                scalar::minimizer_positions_and_hashes_scalar(
+                    ascii_seq,
+                    k,
+                    w,
+                    &mut scalar_pos,
+                    &mut scalar_hashes,
+                );
+                super::minimizer_positions_and_hashes(
+                    packed_seq,
+                    k,
+                    w,
+                    &mut simd_pos,
+                    &mut simd_hashes,
+                );
+            }
+            assert_eq!(scalar_pos, pos, "k={k}, w={w}, len={len}");
+            assert_eq!(simd_pos, pos, "k={k}, w={w}, len={len}");
+            assert_eq!(scalar_hashes, simd_hashes, "k={k}, w={w}, len={len}");
+        }
+
+        let mut scalar_pos = vec![];
+        let mut scalar_hashes = vec![];
+        scalar::minimizer_positions_and_hashes_scalar(
+            slice,
+            k,
+            w,
+            &mut scalar_pos,
+            &mut scalar_hashes,
+        );
+        let mut simd_pos = vec![];
+        let mut simd_hashes = vec![];
+        super::minimizer_positions_and_hashes(slice, k, w, &mut simd_pos, &mut simd_hashes);
+        assert_eq!(scalar_pos, simd_pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_hashes, simd_hashes, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn super_kmers() {
+    fn naive(windows: Vec<u32>, l: usize) -> Vec<(u32, u32, u32)> {
+        let mut out: Vec<(u32, u32, u32)> = vec![];
+        for (i, pos) in windows.into_iter().enumerate() {
+            let end = (i + l) as u32;
+            match out.last_mut() {
+                Some(sk) if sk.2 == pos => sk.1 = end,
+                _ => out.push((i as u32, end, pos)),
+            }
+        }
+        out
+    }
+
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = ascii_seq.len();
+        let expected = naive(
+            minimizers_seq_scalar::<NtHasher>(ascii_seq, k, w).collect(),
+            l,
+        );
+        let mut simd = vec![];
+        super::super_kmers(packed_seq, k, w, &mut simd);
+        assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+
+        if l % 2 == 1 {
+            let expected = naive(
+                canonical_minimizers_seq_scalar::<NtHasher>(ascii_seq, k, w).collect(),
+                l,
+            );
+            let mut simd = vec![];
+            super::canonical_super_kmers(packed_seq, k, w, &mut simd);
+            assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+        }
+    });
+}
+
+#[test]
+fn super_kmer_ranges() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = ascii_seq.len();
+        let num_windows = (len + 1).saturating_sub(l) as u32;
+
+        let mut min_pos_vec = vec![];
+        let mut sk_pos_vec = vec![];
+        super::minimizer_and_superkmer_positions(
+            packed_seq,
+            k,
+            w,
+            &mut min_pos_vec,
+            &mut sk_pos_vec,
+        );
+        let mut ranges = vec![];
+        super::super_kmer_ranges(len, k, w, &min_pos_vec, &sk_pos_vec, &mut ranges);
+        let mut expected = vec![];
+        super::super_kmers(packed_seq, k, w, &mut expected);
+        assert_eq!(expected, ranges, "k={k}, w={w}, len={len}");
+
+        ranges.clear();
+        super::super_kmer_ranges_from_positions(len, k, w, &min_pos_vec, &mut ranges);
+        // The windows of the super-k-mers partition all windows.
+        let mut next_window = 0;
+        for &(start, end, min_pos) in &ranges {
+            let end_window = end + 1 - l as u32;
+            assert_eq!(start, next_window, "k={k}, w={w}, len={len}");
+            assert!(start < end_window, "k={k}, w={w}, len={len}");
+            // Each window contains its minimizer.
+            assert!(
+                end_window - 1 <= min_pos && min_pos < start + w as u32,
+                "k={k}, w={w}, len={len}"
+            );
+            next_window = end_window;
+        }
+        if !ranges.is_empty() {
+            assert_eq!(next_window, num_windows, "k={k}, w={w}, len={len}");
+        }
+    });
+}
+
+#[test]
+fn minimizer_positions_records() {
+    let mut rng = rand::rng();
+    for k in [1, 2, 5, 16, 31, 32, 33] {
+        for w in [1, 2, 4, 11, 19, 32] {
+            let l = k + w - 1;
+            let num = rng.random_range(0..40);
+            let seqs = (0..num)
+                .map(|_| PackedSeqVec::random(rng.random_range(0..200)))
+                .collect_vec();
+            let slices = seqs.iter().map(|seq| seq.as_slice()).collect_vec();
+
+            let mut out = vec![];
+            super::minimizer_positions_records(&slices, k, w, &mut out);
+            assert_eq!(out.len(), num, "k={k}, w={w}");
+            for (seq, out) in zip(&slices, &out) {
+                let mut expected = vec![];
+                super::minimizer_positions(*seq, k, w, &mut expected);
+                assert_eq!(&expected, out, "k={k}, w={w}, len={}", seq.len());
+            }
+
+            let texts = slices
+                .iter()
+                .map(|seq| (0..seq.len()).map(|i| seq.get_ascii(i)).collect_vec())
+                .collect_vec();
+            let texts = texts.iter().map(|text| text.as_slice()).collect_vec();
+            let mut out = vec![];
+            super::minimizer_positions_records(&texts, k, w, &mut out);
+            for (text, out) in zip(&texts, &out) {
+                let mut expected = vec![];
+                super::minimizer_positions(*text, k, w, &mut expected);
+                assert_eq!(&expected, out, "k={k}, w={w}, len={}", text.len());
+            }
+
+            if l % 2 == 1 {
+                let mut out = vec![];
+                super::canonical_minimizer_positions_records(&slices, k, w, &mut out);
+                for (seq, out) in zip(&slices, &out) {
+                    let mut expected = vec![];
+                    super::canonical_minimizer_positions(*seq, k, w, &mut expected);
+                    assert_eq!(&expected, out, "k={k}, w={w}, len={}", seq.len());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn minimizer_positions_records_with_params() {
+    let mut rng = rand::rng();
+    let params = [(5, 11), (21, 11), (31, 19), (16, 4)];
+    let num = 50;
+    let seqs = (0..num)
+        .map(|_| PackedSeqVec::random(rng.random_range(0..300)))
+        .collect_vec();
+    let slices = seqs.iter().map(|seq| seq.as_slice()).collect_vec();
+    let record_params = (0..num)
+        .map(|_| params[rng.random_range(0..params.len())])
+        .collect_vec();
+
+    let mut out = vec![];
+    super::minimizer_positions_records_with_params(&slices, &record_params, &mut out);
+    let mut canonical_out = vec![];
+    super::canonical_minimizer_positions_records_with_params(
+        &slices,
+        &record_params,
+        &mut canonical_out,
+    );
+    assert_eq!(out.len(), num);
+    for ((seq, &(k, w)), (out, canonical_out)) in
+        zip(zip(&slices, &record_params), zip(&out, &canonical_out))
+    {
+        let mut expected = vec![];
+        super::minimizer_positions(*seq, k, w, &mut expected);
+        assert_eq!(&expected, out, "k={k}, w={w}, len={}", seq.len());
+        let mut expected = vec![];
+        super::canonical_minimizer_positions(*seq, k, w, &mut expected);
+        assert_eq!(&expected, canonical_out, "k={k}, w={w}, len={}", seq.len());
+    }
+}
+
+#[test]
+fn positions_in_blocks() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+
+        let mut out = vec![];
+        let mut last_progress = 0;
+        let cancel = AtomicBool::new(false);
+        cancel::positions_in_blocks(
+            packed_seq,
+            l,
+            1000,
+            &mut out,
+            &cancel,
+            |p| {
+                assert!(p >= last_progress);
+                last_progress = p;
+            },
+            |block, out| super::minimizer_positions(block, k, w, out),
+        )
+        .unwrap();
+        assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+        assert_eq!(last_progress, len);
+
+        let cancel = AtomicBool::new(true);
+        let mut out = vec![];
+        let result =
+            super::minimizer_positions_cancellable(packed_seq, k, w, &mut out, &cancel, |_| {
+                panic!("No progress expected after cancelling.")
+            });
+        assert_eq!(result, Err(Cancelled));
+        assert!(out.is_empty());
+    });
+}
+
+#[test]
+fn first_minimizer_positions() {
+    // The prefix of `all` up to and including the first occurrence of the `m`'th distinct k-mer.
+    fn naive(seq: &[u8], k: usize, m: usize, canonical: bool, all: &[u32]) -> Vec<u32> {
+        let mut distinct = std::collections::HashSet::new();
+        let mut out = vec![];
+        for &pos in all {
+            if distinct.len() == m {
+                break;
+            }
+            out.push(pos);
+            let kmer = seq[pos as usize..pos as usize + k].to_vec();
+            let rc = kmer
+                .iter()
+                .rev()
+                .map(|&c| packed_seq::complement_char(c))
+                .collect_vec();
+            distinct.insert(if canonical { kmer.min(rc) } else { kmer });
+        }
+        out
+    }
+
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut all = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut all);
+        let mut all_canonical = vec![];
+        let canonical = (k + w - 1) % 2 == 1;
+        if canonical {
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut all_canonical);
+        }
+        for m in [0, 1, 2, 10, random_range(0..100)] {
+            let mut out = vec![];
+            super::first_minimizer_positions(packed_seq, k, w, m, &mut out);
+            let expected = naive(ascii_seq.0, k, m, false, &all);
+            assert_eq!(out, expected, "k={k}, w={w}, m={m}, len={len}");
+
+            if canonical {
+                let mut out = vec![];
+                super::first_canonical_minimizer_positions(packed_seq, k, w, m, &mut out);
+                let expected = naive(ascii_seq.0, k, m, true, &all_canonical);
+                assert_eq!(out, expected, "k={k}, w={w}, m={m}, len={len}");
+            }
+        }
+    });
+
+    // Repeated minimizers are not counted twice.
+    let seq = PackedSeqVec::from_ascii(&b"ACGTTGCA".repeat(100));
+    let mut all = vec![];
+    super::minimizer_positions(seq.as_slice(), 3, 4, &mut all);
+    let mut out = vec![];
+    super::first_minimizer_positions(seq.as_slice(), 3, 4, 100, &mut out);
+    assert_eq!(out, all);
+}
+
+#[test]
+fn positions_u64() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+        let expected = expected.into_iter().map(|p| p as u64).collect_vec();
+
+        let mut out = vec![];
+        super::minimizer_positions_u64(packed_seq, k, w, &mut out);
+        assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+
+        let mut out = vec![];
+        pos64::positions_in_blocks_u64(packed_seq, l, 1000, &mut out, |block, offset, out| {
+            let head_padding = minimizers_seq_simd::<_, NtHasher>(block, k, w);
+            collect::collect_and_dedup_into_u64(head_padding, offset, out);
+        });
+        assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+
+        if l % 2 == 1 {
+            let mut expected = vec![];
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut expected);
+            let expected = expected.into_iter().map(|p| p as u64).collect_vec();
+            let mut out = vec![];
+            super::canonical_minimizer_positions_u64(packed_seq, k, w, &mut out);
+            assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+        }
+
+        // Offsets beyond `u32::MAX`.
+        let offset = 1 << 33;
+        let mut out = vec![];
+        collect::collect_into_u64(
+            minimizers_seq_simd::<_, NtHasher>(packed_seq, k, w),
+            offset,
+            &mut out,
+        );
+        let naive = collect(minimizers_seq_simd::<_, NtHasher>(packed_seq, k, w));
+        assert_eq!(
+            out,
+            naive.into_iter().map(|p| p as u64 + offset).collect_vec()
+        );
+    });
+}
+
+#[test]
+fn sliding_window_min() {
+    let mut rng = rand::rng();
+    for len in (0..100).chain([1000, 10000]) {
+        // Small values, so that there are many ties.
+        let values = (0..len).map(|_| rng.random_range(0..40)).collect_vec();
+        for w in [1, 2, 3, 4, 5, 8, 31, 32, 33, 64, 100] {
+            let naive = values
+                .windows(w)
+                .enumerate()
+                .map(|(i, window)| (i + window.iter().position_min().unwrap()) as u32)
+                .collect_vec();
+            let mut simd = vec![];
+            super::sliding_window_min_simd(&values, w, &mut simd);
+            let scalar = super::sliding_window_min(values.iter().copied(), w).collect_vec();
+            assert_eq!(naive, simd, "w={w}, len={len}");
+            assert_eq!(naive, scalar, "w={w}, len={len}");
+            // Positions are appended.
+            super::sliding_window_min_simd(&values, w, &mut simd);
+            assert_eq!(simd[..naive.len()], simd[naive.len()..], "w={w}, len={len}");
+        }
+    }
+}
+
+#[test]
+fn mod_minimizers() {
+    use mod_minimizers::*;
+    fn f<H: CharHasher>() {
+        test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+            for t in [1, 4, 4 + (k.saturating_sub(4)) % w, k] {
+                if t == 0 || t > k {
+                    continue;
+                }
+                let naive = ascii_seq
+                    .0
+                    .windows(w + k - 1)
+                    .enumerate()
+                    .map(|(pos, seq)| (pos + mod_minimizer::<H>(AsciiSeq(seq), k, t)) as u32)
+                    .collect::<Vec<_>>();
+
+                let scalar_ascii =
+                    mod_minimizers_seq_scalar::<H>(ascii_seq, k, w, t).collect::<Vec<_>>();
+                let simd_ascii = collect(mod_minimizers_seq_simd::<_, H>(ascii_seq, k, w, t));
+                let simd_packed = collect(mod_minimizers_seq_simd::<_, H>(packed_seq, k, w, t));
+
+                let len = ascii_seq.len();
+                assert_eq!(naive, scalar_ascii, "k={k}, w={w}, t={t}, len={len}");
+                assert_eq!(naive, simd_ascii, "k={k}, w={w}, t={t}, len={len}");
+                assert_eq!(naive, simd_packed, "k={k}, w={w}, t={t}, len={len}");
+            }
+        });
+    }
+    f::<NtHasher>();
+    f::<MulHasher>();
+}
+
+#[test]
+fn mod_minimizer_positions() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let t = 4.min(k) + (k - 4.min(k)) % w;
+        let mut scalar = vec![];
+        scalar::mod_minimizer_positions_scalar(ascii_seq, k, w, t, &mut scalar);
+        let mut simd = vec![];
+        super::mod_minimizer_positions(packed_seq, k, w, t, &mut simd);
+        let len = ascii_seq.len();
+        assert_eq!(scalar, simd, "k={k}, w={w}, t={t}, len={len}");
+    });
+}
+
+#[test]
+fn packed_super_pos() {
+    let ascii_seq = &*ASCII_SEQ;
+    let (k, w) = (5, 11);
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    super::minimizer_and_superkmer_positions(
+        ascii_seq.as_slice(),
+        k,
+        w,
+        &mut min_pos_vec,
+        &mut sk_pos_vec,
+    );
+    let packed = PackedSuperPos::pack(&min_pos_vec, &sk_pos_vec).collect_vec();
+    assert!(packed.is_sorted());
+    for (p, (&min_pos, &sk_pos)) in zip(&packed, zip(&min_pos_vec, &sk_pos_vec)) {
+        assert_eq!(p.min_pos(), Pos(min_pos));
+        assert_eq!(p.superkmer_start(), sk_pos);
+    }
+
+    let positions = Pos::from_raw_slice(&min_pos_vec);
+    assert_eq!(Pos::as_raw_slice(positions), &min_pos_vec[..]);
+    for &pos in positions {
+        assert_eq!(pos.offset_by(100).relative_to(100), pos);
+        assert_eq!(pos.index(), pos.get() as usize);
+        let (high, low) = pos.split16();
+        assert_eq!(Pos::from_split16(high, low), pos);
+        assert_eq!(low as u32, pos.get() & 0xffff);
+        let (chunk, offset) = pos.chunk(1000);
+        assert!(offset.get() < 1000);
+        assert_eq!(Pos::from_chunk(chunk, 1000, offset), pos);
+    }
+
+    // The typed variants append exactly what the raw functions append, also when called twice.
+    let seq = ascii_seq.slice(0..1000);
+    for _ in 0..2 {
+        super::minimizer_and_superkmer_positions(seq, k, w, &mut min_pos_vec, &mut sk_pos_vec);
+    }
+    let mut typed = vec![];
+    let mut packed = vec![];
+    for _ in 0..2 {
+        super::minimizer_positions_typed(seq, k, w, &mut typed);
+        super::minimizer_and_superkmer_positions_packed(seq, k, w, &mut packed);
+    }
+    let start = min_pos_vec.len() - packed.len();
+    assert_eq!(Pos::as_raw_slice(&typed), &min_pos_vec[start..]);
+    assert!(PackedSuperPos::pack(&min_pos_vec[start..], &sk_pos_vec[start..]).eq(packed));
+
+    let mut raw = vec![];
+    super::canonical_minimizer_positions(seq, k, w, &mut raw);
+    let mut typed = vec![];
+    super::canonical_minimizer_positions_typed(seq, k, w, &mut typed);
+    assert_eq!(Pos::as_raw_slice(&typed), raw);
+    let (mut raw, mut raw_sk) = (vec![], vec![]);
+    super::canonical_minimizer_and_superkmer_positions(seq, k, w, &mut raw, &mut raw_sk);
+    let mut packed = vec![];
+    super::canonical_minimizer_and_superkmer_positions_packed(seq, k, w, &mut packed);
+    assert!(PackedSuperPos::pack(&raw, &raw_sk).eq(packed));
+}
+
+#[test]
+fn minimizer_result() {
+    let packed_seq = &*PACKED_SEQ;
+    let (k, w) = (5, 11);
+    let mut min_pos_vec = vec![];
+    let mut sk_pos_vec = vec![];
+    super::canonical_minimizer_and_superkmer_positions(
+        packed_seq.as_slice(),
+        k,
+        w,
+        &mut min_pos_vec,
+        &mut sk_pos_vec,
+    );
+
+    let result = MinimizerResult::with_superkmers(packed_seq.as_slice(), k, w, true);
+    assert!(result.canonical());
+    assert_eq!(result.positions(), min_pos_vec);
+    assert_eq!(result.superkmer_starts(), Some(&sk_pos_vec[..]));
+    assert_eq!(result.kmers(), None);
+    assert_eq!(result.iter_super().unwrap().len(), min_pos_vec.len());
+    for (m, &pos) in zip(result.iter(), &min_pos_vec) {
+        assert_eq!(m.pos, Pos(pos));
+        assert_eq!(m.kmer, None);
+    }
+
+    let mut tsv = vec![];
+    result.write_tsv(&mut tsv).unwrap();
+    let tsv = String::from_utf8(tsv).unwrap();
+    assert_eq!(tsv.lines().count(), 2 + min_pos_vec.len());
+    assert_eq!(tsv.lines().nth(1), Some("pos\tsuperkmer_start"));
+
+    let positions = MinimizerResult::new(packed_seq.as_slice(), k, w, true).into_positions();
+    assert_eq!(positions, min_pos_vec);
+
+    let mut hashes = vec![];
+    super::canonical_minimizer_positions_and_hashes(
+        packed_seq.as_slice(),
+        k,
+        w,
+        &mut vec![],
+        &mut hashes,
+    );
+    let result = MinimizerResult::with_hashes(packed_seq.as_slice(), k, w, true);
+    assert_eq!(result.positions(), min_pos_vec);
+    assert_eq!(result.hashes(), Some(&hashes[..]));
+    assert_eq!(result.strands(), None);
+
+    let mut strands = vec![];
+    super::canonical_minimizer_positions_and_strands(
+        packed_seq.as_slice(),
+        k,
+        w,
+        &mut vec![],
+        &mut strands,
+    );
+    let mut result = MinimizerResult::with_strands(packed_seq.as_slice(), k, w);
+    assert_eq!(result.strands(), Some(&strands[..]));
+    result.set_hashes(hashes.clone());
+    for (m, (&hash, &strand)) in zip(result.iter(), zip(&hashes, &strands)) {
+        assert_eq!(m.hash, Some(hash));
+        assert_eq!(m.strand, Some(strand));
+    }
+    let mut tsv = vec![];
+    result.write_tsv(&mut tsv).unwrap();
+    let tsv = String::from_utf8(tsv).unwrap();
+    assert_eq!(tsv.lines().nth(1), Some("pos\thash\tstrand"));
+
+    // All columns round-trip.
+    let parts = result.clone().into_parts();
+    assert_eq!(parts.hashes.as_ref(), Some(&hashes));
+    assert_eq!(parts.strands.as_ref(), Some(&strands));
+    assert_eq!(MinimizerResult::from_parts(parts), result);
+
+    #[cfg(feature = "arrow")]
+    {
+        let batch = result.to_arrow().unwrap();
+        assert_eq!(batch.num_rows(), min_pos_vec.len());
+        assert_eq!(batch.num_columns(), 3);
+        assert_eq!(batch.schema().field(2).name(), "strand");
+        assert_eq!(batch.schema().metadata()["k"], k.to_string());
+    }
+}
+
+#[test]
+fn canonical_minimizer_hits() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let contains = |hash: u32| hash % 3 == 0;
+        let mut positions = vec![];
+        super::canonical_minimizer_positions(packed_seq, k, w, &mut positions);
+        let hits = positions
+            .iter()
+            .filter(|&&p| {
+                contains(canonical_kmer_hash(
+                    packed_seq.slice(p as usize..p as usize + k),
+                ))
+            })
+            .count();
+        let mut queries = 0;
+        let counts = super::canonical_minimizer_hits(packed_seq, k, w, |hash| {
+            queries += 1;
+            contains(hash)
+        });
+        let len = ascii_seq.len();
+        assert_eq!(queries, positions.len(), "k={k}, w={w}, len={len}");
+        assert_eq!(
+            counts.minimizers,
+            positions.len(),
+            "k={k}, w={w}, len={len}"
+        );
+        assert_eq!(counts.hits, hits, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn multi_sketch() {
+    let packed_seq = &*PACKED_SEQ;
+    let rc_ascii = ASCII_SEQ
+        .seq
+        .iter()
+        .rev()
+        .map(|&c| packed_seq::complement_char(c))
+        .collect_vec();
+    let rc_packed_seq = PackedSeqVec::from_ascii(&rc_ascii);
+    let params = [(21, 11), (15, 31), (21, 51), (21, 5)];
+
+    let sketch = MultiSketch::new(packed_seq.as_slice(), &params);
+    let rc_sketch = MultiSketch::new(rc_packed_seq.as_slice(), &params);
+    for (i, &(k, w)) in params.iter().enumerate() {
+        let level = sketch.level(k, w).unwrap();
+        let mut positions = vec![];
+        super::canonical_minimizer_positions(packed_seq.as_slice(), k, w, &mut positions);
+        let mut hashes = positions
+            .iter()
+            .map(|&p| canonical_kmer_hash(packed_seq.slice(p as usize..p as usize + k)))
+            .collect_vec();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(level.hashes, hashes, "k={k}, w={w}");
+        // Canonical sketches do not depend on the strand.
+        assert_eq!(sketch.jaccard(&rc_sketch, i), 1.0, "k={k}, w={w}");
+    }
+
+    let half = MultiSketch::new(packed_seq.slice(0..packed_seq.len() / 2), &params);
+    let containment = half.containment(&sketch, 0);
+    assert!(containment == 1.0, "containment={containment}");
+    let jaccard = half.jaccard(&sketch, 0);
+    assert!(0.3 < jaccard && jaccard < 0.7, "jaccard={jaccard}");
+
+    let mut bytes = vec![];
+    sketch.write_to(&mut bytes).unwrap();
+    assert_eq!(MultiSketch::read_from(&bytes[..]).unwrap(), sketch);
+    assert!(MultiSketch::read_from(&bytes[1..]).is_err());
+}
+
+#[test]
+fn multi_sketch_shared_k() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        if (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let params = [(k, w + 4), (k, w), (k, w + 2)];
+        let len = ascii_seq.len();
+        let sketch = MultiSketch::new(packed_seq, &params);
+        let text_sketch = MultiSketch::new(slice, &params);
+        for (i, &(k, w)) in params.iter().enumerate() {
+            assert_eq!(
+                sketch.levels()[i],
+                SketchLevel::new(packed_seq, k, w),
+                "k={k}, w={w}, len={len}"
+            );
+            assert_eq!(
+                text_sketch.levels()[i],
+                SketchLevel::new(slice, k, w),
+                "k={k}, w={w}, len={len}"
+            );
+        }
+    });
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn minimizers_par_chunks() {
+    let packed_seq = PackedSeqVec::random(1 << 19);
+    for (k, w) in [(5, 11), (21, 11), (31, 20)] {
+        for num_threads in [0, 1, 3, 4] {
+            let mut seq_pos = vec![];
+            let mut par_pos = vec![];
+            super::minimizer_positions(packed_seq.as_slice(), k, w, &mut seq_pos);
+            super::minimizers_par_chunks(packed_seq.as_slice(), k, w, num_threads, &mut par_pos);
+            assert_eq!(seq_pos, par_pos, "k={k}, w={w}, num_threads={num_threads}");
+
+            // Inside a pool of the same size, that pool is used.
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            par_pos.clear();
+            pool.install(|| {
+                super::minimizers_par_chunks(packed_seq.as_slice(), k, w, 0, &mut par_pos)
+            });
+            assert_eq!(seq_pos, par_pos, "k={k}, w={w}, num_threads={num_threads}");
+
+            if (k + w - 1) % 2 == 1 {
+                seq_pos.clear();
+                par_pos.clear();
+                super::canonical_minimizer_positions(packed_seq.as_slice(), k, w, &mut seq_pos);
+                super::canonical_minimizers_par_chunks(
+                    packed_seq.as_slice(),
+                    k,
+                    w,
+                    num_threads,
+                    &mut par_pos,
+                );
+                assert_eq!(seq_pos, par_pos, "k={k}, w={w}, num_threads={num_threads}");
+            }
+        }
+    }
+}
+
+#[test]
+fn bench_stages() {
+    for (k, w) in [(5, 11), (21, 11)] {
+        let input = bench::BenchInput::random(10_000, k, w);
+        let mut dedup_pos = vec![];
+        let mut end_to_end_pos = vec![];
+        bench::dedup(&input, &mut dedup_pos);
+        bench::end_to_end(&input, &mut end_to_end_pos);
+        assert_eq!(dedup_pos, end_to_end_pos, "k={k}, w={w}");
+        for stage in bench::Stage::ALL {
+            bench::measure(&input, stage, 1);
+        }
+    }
+}
+
+#[test]
+fn minimizer_positions_hash64() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let mut scalar = vec![];
+        scalar::minimizer_positions_hash64_scalar(ascii_seq, k, w, &mut scalar);
+        let mut simd_ascii = vec![];
+        super::minimizer_positions_hash64(ascii_seq, k, w, &mut simd_ascii);
+        let mut simd_packed = vec![];
+        super::minimizer_positions_hash64(packed_seq, k, w, &mut simd_packed);
+
+        let len = ascii_seq.len();
+        let naive = (0..(len + 1).saturating_sub(k + w - 1))
+            .map(|i| {
+                let window = ascii_seq.slice(i..i + k + w - 1);
+                let hashes = nthash64::nthash64_seq_scalar::<false, _>(window, k);
+                (i + hashes.map(|h| h >> 32).position_min().unwrap()) as u32
+            })
+            .dedup()
+            .collect_vec();
+        assert_eq!(naive, scalar, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar, simd_ascii, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar, simd_packed, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn canonical_nthash64() {
+    let ascii = &ASCII_SEQ.seq[..1000];
+    let rc_ascii = ascii
+        .iter()
+        .rev()
+        .map(|&c| packed_seq::complement_char(c))
+        .collect_vec();
+    for k in [1, 16, 31, 63] {
+        let fwd = nthash64::nthash64_seq_scalar::<true, _>(AsciiSeq(ascii), k).collect_vec();
+        let mut rc = nthash64::nthash64_seq_scalar::<true, _>(AsciiSeq(&rc_ascii), k).collect_vec();
+        rc.reverse();
+        assert_eq!(fwd, rc, "k={k}");
+    }
+}
+
+#[test]
+fn verify_coverage() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut positions = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut positions);
+        assert_eq!(
+            super::verify_coverage(&positions, len, k, w),
+            Ok(()),
+            "k={k}, w={w}, len={len}"
+        );
+        if (k + w - 1) % 2 == 1 {
+            positions.clear();
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut positions);
+            assert_eq!(
+                super::verify_coverage(&positions, len, k, w),
+                Ok(()),
+                "k={k}, w={w}, len={len}"
+            );
+        }
+    });
+
+    // 10 k-mers and 8 windows of 3 k-mers.
+    assert_eq!(super::verify_coverage(&[2, 5, 7], 10, 1, 3), Ok(()));
+    assert_eq!(super::verify_coverage(&[7, 2, 5, 5], 10, 1, 3), Ok(()));
+    assert_eq!(
+        super::verify_coverage(&[2, 6, 7], 10, 1, 3),
+        Err(Gap { window: 3 })
+    );
+    assert_eq!(
+        super::verify_coverage(&[3, 5, 7], 10, 1, 3),
+        Err(Gap { window: 0 })
+    );
+    assert_eq!(
+        super::verify_coverage(&[2, 5, 10], 10, 1, 3),
+        Err(Gap { window: 6 })
+    );
+    assert_eq!(super::verify_coverage(&[], 2, 1, 3), Ok(()));
+}
+
+#[test]
+fn minimizer_builder() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        let mut simd = vec![];
+        let mut scalar = vec![];
+
+        let builder = MinimizerBuilder::new(k, w);
+        let config = builder.build().unwrap();
+        assert_eq!(config.hash_width(), HashWidth::Bits32);
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+        config.positions(packed_seq, &mut simd);
+        config.positions_scalar(ascii_seq, &mut scalar);
+        assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+        assert_eq!(expected, scalar, "k={k}, w={w}, len={len}");
+
+        let builder = builder.hash_width(HashWidth::Bits64);
+        let config = builder.build().unwrap();
+        assert_eq!(config.hash_width(), HashWidth::Bits64);
+        expected.clear();
+        simd.clear();
+        scalar.clear();
+        super::minimizer_positions_hash64(packed_seq, k, w, &mut expected);
+        config.positions(packed_seq, &mut simd);
+        config.positions_scalar(ascii_seq, &mut scalar);
+        assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+        assert_eq!(expected, scalar, "k={k}, w={w}, len={len}");
+
+        if (k + w - 1) % 2 == 1 {
+            let config = builder
+                .hash_width(HashWidth::Bits32)
+                .canonical(true)
+                .build()
+                .unwrap();
+            assert!(config.is_canonical());
+            expected.clear();
+            simd.clear();
+            scalar.clear();
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut expected);
+            config.positions(packed_seq, &mut simd);
+            config.positions_scalar(ascii_seq, &mut scalar);
+            assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+            assert_eq!(expected, scalar, "k={k}, w={w}, len={len}");
+        }
+    });
+}
+
+#[test]
+fn canonical_minimizer_positions_hash64() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let mut scalar = vec![];
+        scalar::canonical_minimizer_positions_hash64_scalar(ascii_seq, k, w, &mut scalar);
+        let mut simd_ascii = vec![];
+        super::canonical_minimizer_positions_hash64(ascii_seq, k, w, &mut simd_ascii);
+        let mut simd_packed = vec![];
+        super::canonical_minimizer_positions_hash64(packed_seq, k, w, &mut simd_packed);
+
+        let len = ascii_seq.len();
+        let l = k + w - 1;
+        let naive = (0..(len + 1).saturating_sub(l))
+            .map(|i| {
+                let window = ascii_seq.slice(i..i + l);
+                let gt = window.iter_bp().filter(|&c| c & 2 != 0).count();
+                let hashes = nthash64::nthash64_seq_scalar::<true, _>(window, k)
+                    .map(|h| h >> 32)
+                    .collect_vec();
+                // Canonical windows take the leftmost minimum, and others the rightmost.
+                let offset = if 2 * gt >= l {
+                    hashes.iter().position_min().unwrap()
+                } else {
+                    w - 1 - hashes.iter().rev().position_min().unwrap()
+                };
+                (i + offset) as u32
+            })
+            .dedup()
+            .collect_vec();
+        assert_eq!(naive, scalar, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar, simd_ascii, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar, simd_packed, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn minimizer_builder_canonical_hash64() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let config = MinimizerBuilder::new(k, w)
+            .canonical(true)
+            .hash_width(HashWidth::Bits64)
+            .build()
+            .unwrap();
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        super::canonical_minimizer_positions_hash64(packed_seq, k, w, &mut expected);
+        let mut simd = vec![];
+        config.positions(packed_seq, &mut simd);
+        let mut scalar = vec![];
+        config.positions_scalar(ascii_seq, &mut scalar);
+        assert_eq!(expected, simd, "k={k}, w={w}, len={len}");
+        assert_eq!(expected, scalar, "k={k}, w={w}, len={len}");
+
+        let (mut starts, mut ends) = (vec![], vec![]);
+        config.spans(packed_seq, &mut starts, &mut ends);
+        assert_eq!(expected, starts, "k={k}, w={w}, len={len}");
+        assert!(zip(&starts, &ends).all(|(&s, &e)| e == s + k as u32));
+    });
+}
+
+#[test]
+fn syncmers() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        // Use `w` as the s-mer length.
+        let s = w;
+        if s > k {
+            return;
+        }
+        let len = ascii_seq.len();
+        let offsets = [vec![0], vec![(k - s) / 2], vec![0, k - s]];
+        for offsets in &offsets {
+            let naive = (0..(len + 1).saturating_sub(k))
+                .filter(|&i| {
+                    let min = minimizer::<NtHasher>(ascii_seq.slice(i..i + k), s);
+                    offsets.contains(&min)
+                })
+                .map(|i| i as u32)
+                .collect_vec();
+            let mut scalar = vec![];
+            scalar::syncmer_positions_scalar(ascii_seq, k, s, offsets, &mut scalar);
+            let mut simd = vec![];
+            super::syncmer_positions(packed_seq, k, s, offsets, &mut simd);
+            assert_eq!(naive, scalar, "k={k}, s={s}, len={len}");
+            assert_eq!(naive, simd, "k={k}, s={s}, len={len}");
+
+            let mut scalar = vec![];
+            scalar::syncmer_positions_scalar(slice, k, s, offsets, &mut scalar);
+            let mut simd = vec![];
+            super::syncmer_positions(slice, k, s, offsets, &mut simd);
+            assert_eq!(scalar, simd, "k={k}, s={s}, len={len}");
+        }
+
+        let mut open = vec![];
+        super::open_syncmer_positions(packed_seq, k, s, 0, &mut open);
+        let mut closed = vec![];
+        super::closed_syncmer_positions(packed_seq, k, s, &mut closed);
+        assert!(
+            open.iter().all(|p| closed.contains(p)),
+            "k={k}, s={s}, len={len}"
+        );
+    });
+}
+
+#[test]
+fn collect_and_dedup_full_range() {
+    let mut rng = rand::rng();
+    for len in 0..50 {
+        for padding in [0, 1, len / 2, len, len + 1, 3 * len, 8 * len] {
+            if padding > 8 * len {
+                continue;
+            }
+            // Sorted values per lane, so that the output is the same as a plain dedup.
+            let mut vals = (0..8 * len)
+                .map(|_| [0, 1, u32::MAX - 1, u32::MAX][rng.random_range(0..4)])
+                .collect_vec();
+            for lane in vals.chunks_mut(len.max(1)) {
+                lane.sort();
+            }
+            let head = (0..len).map(|i| S::new(std::array::from_fn(|l| vals[l * len + i])));
+            let (head, head2) = (head.clone(), head);
+
+            let mut expected = vals[..8 * len - padding].to_vec();
+            expected.dedup();
+
+            let mut out = vec![];
+            collect::collect_and_dedup_into((head, padding), &mut out);
+            assert_eq!(expected, out, "len={len}, padding={padding}");
+
+            let mut out = vec![];
+            let mut idx = vec![];
+            collect::collect_and_dedup_with_index_into((head2, padding), &mut out, &mut idx);
+            assert_eq!(expected, out, "len={len}, padding={padding}");
+            let expected_idx = (0..8 * len - padding)
+                .filter(|&i| i == 0 || vals[i] != vals[i - 1])
+                .map(|i| i as u32)
+                .collect_vec();
+            assert_eq!(expected_idx, idx, "len={len}, padding={padding}");
+        }
+    }
+}
+
+#[test]
+fn minimizer_positions_masked() {
+    let mut rng = rand::rng();
+    for k in [1, 2, 5, 16, 31] {
+        for w in [1, 2, 5, 11, 32] {
+            let l = k + w - 1;
+            for len in [0, 10, 100, 1000] {
+                let mut ascii = AsciiSeqVec::random(len).seq;
+                // Insert some runs of N.
+                for _ in 0..rng.random_range(0..5) {
+                    let start = rng.random_range(0..=len);
+                    let end = (start + rng.random_range(0..20)).min(len);
+                    ascii[start..end].fill(b'N');
+                }
+                let valid = acgt_mask(&ascii);
+                let seq = AsciiSeq(&ascii);
+
+                for canonical in [false, true] {
+                    if canonical && l % 2 == 0 {
+                        continue;
+                    }
+                    let mut all = vec![];
+                    let mut masked = vec![];
+                    if canonical {
+                        scalar::canonical_minimizer_positions_scalar(seq, k, w, &mut all);
+                        super::canonical_minimizer_positions_masked(seq, k, w, &valid, &mut masked);
+                    } else {
+                        scalar::minimizer_positions_scalar(seq, k, w, &mut all);
+                        super::minimizer_positions_masked(seq, k, w, &valid, &mut masked);
+                    }
+                    // The minimizers of all fully valid windows.
+                    let windows = if canonical {
+                        canonical_minimizers_seq_scalar::<NtHasher>(seq, k, w).collect_vec()
+                    } else {
+                        minimizers_seq_scalar::<NtHasher>(seq, k, w).collect_vec()
+                    };
+                    let expected = windows
+                        .into_iter()
+                        .enumerate()
+                        .filter(|&(i, _)| valid[i..i + l].iter().all(|&v| v))
+                        .map(|(_, pos)| pos)
+                        .dedup()
+                        .collect_vec();
+                    assert_eq!(expected, masked, "k={k}, w={w}, len={len}");
+                    if valid.iter().all(|&v| v) {
+                        assert_eq!(all, masked, "k={k}, w={w}, len={len}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn validate_acgt() {
+    let mut rng = rand::rng();
+    for len in [0, 1, 63, 64, 65, 200, 1000] {
+        let mut seq = AsciiSeqVec::random(len).seq;
+        seq.make_ascii_lowercase();
+        assert_eq!(super::validate_acgt(&seq), Ok(()));
+        assert_eq!(first_non_acgt(&seq), None);
+        let mut expected = vec![];
+        for _ in 0..rng.random_range(0..5).min(len) {
+            let pos = rng.random_range(0..len);
+            seq[pos] = [b'N', b'n', b'R', b'-', b'\n', 0, b'A' + 128][rng.random_range(0..7)];
+            expected.push(pos);
+        }
+        expected.sort();
+        expected.dedup();
+        let mut positions = vec![];
+        non_acgt_positions(&seq, &mut positions);
+        assert_eq!(expected, positions, "len={len}");
+        assert_eq!(first_non_acgt(&seq), expected.first().copied(), "len={len}");
+        assert_eq!(
+            super::validate_acgt(&seq),
+            match expected.first() {
+                None => Ok(()),
+                Some(&pos) => Err(InvalidBase {
+                    pos,
+                    byte: seq[pos]
+                }),
+            },
+            "len={len}"
+        );
+        assert_eq!(
+            acgt_mask(&seq).iter().filter(|&&v| !v).count(),
+            expected.len()
+        );
+    }
+}
+
+#[test]
+fn collect_buffers() {
+    let buffers = std::cell::RefCell::new(CollectBuffers::new());
+    assert_eq!(buffers.borrow().capacity(), 0);
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let buffers = &mut *buffers.borrow_mut();
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        let mut out = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+        super::minimizer_positions_with_buffers(packed_seq, k, w, buffers, &mut out);
+        assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+
+        if (k + w - 1) % 2 == 1 {
+            expected.clear();
+            out.clear();
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut expected);
+            super::canonical_minimizer_positions_with_buffers(packed_seq, k, w, buffers, &mut out);
+            assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+        }
+    });
+    let mut buffers = buffers.into_inner();
+    assert!(buffers.capacity() > 0);
+    buffers.shrink_to(0);
+    assert_eq!(buffers.capacity(), 0);
+}
+
+#[test]
+fn density_stats() {
+    let seq = PackedSeqVec::random(100_000);
+    for (k, w) in [(5, 5), (21, 11), (31, 19)] {
+        let s = stats::density(seq.as_slice(), k, w);
+        assert_eq!(s.num_kmers, seq.len() - k + 1);
+        assert_eq!(s.num_selected, s.num_distinct, "k={k}, w={w}");
+        assert_eq!(s.distances.iter().sum::<usize>(), s.num_selected - 1);
+        assert!(s.distances.len() <= w + 1, "k={k}, w={w}");
+        assert_eq!(s.distances[0], 0, "k={k}, w={w}");
+        // The density of random minimizers is close to 2/(w+1).
+        let expected = 2.0 / (w + 1) as f64;
+        assert!(
+            (s.density() - expected).abs() < 0.1 * expected,
+            "k={k}, w={w}"
+        );
+        assert!((s.mean_distance() - 1.0 / expected).abs() < 0.1 / expected);
+
+        // Merging the statistics of parts is the same as measuring them together.
+        let slices = [
+            seq.as_slice().slice(0..50_000),
+            seq.as_slice().slice(50_000..100_000),
+        ];
+        let mut merged = stats::density(slices[0], k, w);
+        merged.merge(&stats::density(slices[1], k, w));
+        let measured = stats::measure_scheme(
+            |seq, out_vec| super::minimizer_positions(seq, k, w, out_vec),
+            &slices,
+            k,
+        );
+        assert_eq!(merged.num_kmers, s.num_kmers - (k - 1));
+        assert_eq!(merged, measured, "k={k}, w={w}");
+    }
+}
+
+#[test]
+fn minimizer_positions_and_gc() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = ascii_seq.len();
+        for canonical in [false, true] {
+            if canonical && l % 2 == 0 {
+                continue;
+            }
+            let windows = if canonical {
+                canonical_minimizers_seq_scalar::<NtHasher>(ascii_seq, k, w).collect_vec()
+            } else {
+                minimizers_seq_scalar::<NtHasher>(ascii_seq, k, w).collect_vec()
+            };
+            let gc = gc::gc_windows_seq_scalar(ascii_seq, l).collect_vec();
+            for (i, &gc) in gc.iter().enumerate().take(10) {
+                let naive = ascii_seq.0[i..i + l]
+                    .iter()
+                    .filter(|&&c| c == b'C' || c == b'G')
+                    .count();
+                assert_eq!(gc as usize, naive, "k={k}, w={w}, len={len}");
+            }
+            let expected = zip(windows, gc).dedup_by(|a, b| a.0 == b.0).collect_vec();
+
+            let mut pos = vec![];
+            let mut gc = vec![];
+            if canonical {
+                super::canonical_minimizer_positions_and_gc(packed_seq, k, w, &mut pos, &mut gc);
+            } else {
+                super::minimizer_positions_and_gc(packed_seq, k, w, &mut pos, &mut gc);
+            }
+            assert_eq!(
+                expected,
+                zip(pos, gc).collect_vec(),
+                "k={k}, w={w}, len={len}"
+            );
+        }
+    });
+}
+
+#[test]
+fn minimizer_neighborhoods() {
+    let mut rng = rand::rng();
+    for (k, w, flank) in [(1, 1, 0), (5, 11, 3), (21, 11, 10), (31, 19, 100)] {
+        let seqs = (0..10)
+            .map(|_| PackedSeqVec::random(rng.random_range(0..500)))
+            .collect_vec();
+        let slices = seqs.iter().map(|seq| seq.as_slice()).collect_vec();
+        let mut positions = vec![];
+        super::minimizer_positions_records(&slices, k, w, &mut positions);
+
+        let mut out_seq = PackedSeqVec::default();
+        let mut out_ranges = vec![];
+        super::minimizer_neighborhoods_records(
+            &slices,
+            &positions,
+            k,
+            flank,
+            &mut out_seq,
+            &mut out_ranges,
+        );
+        assert_eq!(out_ranges.len(), positions.iter().map(|p| p.len()).sum());
+
+        let mut out_ranges = out_ranges.into_iter();
+        for (seq, positions) in zip(&slices, &positions) {
+            for &pos in positions {
+                let range = neighborhood_range(pos, seq.len(), k, flank);
+                assert!(range.start <= pos as usize && pos as usize + k <= range.end);
+                assert!(range.len() <= k + 2 * flank);
+                let expected = seq.slice(range).iter_bp().collect_vec();
+                let actual = out_seq
+                    .as_slice()
+                    .slice(out_ranges.next().unwrap())
+                    .iter_bp()
+                    .collect_vec();
+                assert_eq!(expected, actual, "k={k}, w={w}, flank={flank}");
+            }
+        }
+    }
+}
+
+#[test]
+fn sampling_schemes() {
+    use schemes::{
+        stream_by_sample, AntiLex, CanonicalMinimizer, Decycling, Lex, Minimizer, RandomOrder,
+        SamplingScheme,
+    };
+    test_on_inputs(|k, w, slice, _ascii_seq, _packed_seq| {
+        let len = slice.len();
+        let mut schemes: Vec<Box<dyn SamplingScheme>> = vec![
+            Box::new(Minimizer::new(k, w)),
+            Box::new(Minimizer::with_order(k, w, Lex)),
+            Box::new(Minimizer::with_order(k, w, AntiLex)),
+            Box::new(Minimizer::with_order(k, w, Decycling::single())),
+            Box::new(Minimizer::with_order(k, w, Decycling::double())),
+            Box::new(Minimizer::with_order(
+                k,
+                w,
+                RandomOrder(0x1234_5678_9abc_def0),
+            )),
+        ];
+        if (k + w - 1) % 2 == 1 {
+            schemes.push(Box::new(CanonicalMinimizer::new(k, w)));
+        }
+        for scheme in &schemes {
+            let stream = scheme.stream(slice);
+            assert_eq!(stream.len(), (len + 1).saturating_sub(scheme.l()));
+            if len >= scheme.l() {
+                assert_eq!(stream[0], scheme.sample(&slice[..scheme.l()]));
+            }
+            assert_eq!(
+                stream,
+                stream_by_sample(&**scheme, slice),
+                "k={k}, w={w}, len={len}"
+            );
+        }
+    });
+}
+
+#[test]
+fn sampling_scheme_orders() {
+    use schemes::{Lex, Minimizer, SamplingScheme};
+    // All characters of short k-mers are compared.
+    let scheme = Minimizer::with_order(2, 3, Lex);
+    assert_eq!(scheme.sample(b"BBBA"), 2);
+    assert_eq!(scheme.sample(b"BAAB"), 1);
+    assert_eq!(scheme.stream(b"CBBBABAAB"), [1, 3, 4, 4, 6, 6]);
+}
+
+#[test]
+fn sampling_schemes_threads() {
+    use schemes::{CanonicalMinimizer, Minimizer, SamplingScheme};
+    use std::sync::Arc;
+    let text = &*SLICE;
+    let params = [(5, 11), (21, 11), (31, 19)];
+    let expected = params.map(|(k, w)| Minimizer::new(k, w).stream(text));
+
+    // Build schemes concurrently, and share them between threads.
+    let schemes: Vec<Arc<dyn SamplingScheme>> = std::thread::scope(|scope| {
+        let handles = params.map(|(k, w)| {
+            scope.spawn(move || Arc::new(Minimizer::new(k, w)) as Arc<dyn SamplingScheme>)
+        });
+        handles.map(|h| h.join().unwrap()).to_vec()
+    });
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let schemes = &schemes;
+            let expected = &expected;
+            scope.spawn(move || {
+                for (scheme, expected) in schemes.iter().zip(expected) {
+                    assert_eq!(&scheme.stream(text), expected);
+                }
+                let canonical: Arc<dyn SamplingScheme> = Arc::new(CanonicalMinimizer::new(5, 11));
+                assert_eq!(canonical.stream(text).len(), text.len() + 1 - 15);
+            });
+        }
+    });
+}
+
+#[test]
+fn spaced_minimizers() {
+    fn f<H: CharHasher>() {
+        test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+            let len = ascii_seq.len();
+            let mask = (0..k)
+                .map(|i| i == 0 || i == k - 1 || i % 3 != 1)
+                .collect_vec();
+
+            if w == 1 {
+                let hasher = H::new_from_val(k, ascii_seq);
+                let naive = ascii_seq
+                    .0
+                    .windows(k)
+                    .map(|kmer| {
+                        (0..k).filter(|&i| mask[i]).fold(0u32, |h, i| {
+                            let c = AsciiSeq(&kmer[i..i + 1]).iter_bp().next().unwrap();
+                            h ^ hasher.f(c).rotate_left((k - 1 - i) as u32)
+                        })
+                    })
+                    .collect_vec();
+                let scalar = spaced_nthash_seq_scalar::<H>(ascii_seq, &mask).collect_vec();
+                assert_eq!(naive, scalar, "k={k}, len={len}");
+            }
+
+            let scalar_ascii = spaced_minimizers_seq_scalar::<H>(ascii_seq, &mask, w).collect_vec();
+            let simd_ascii = collect(spaced_minimizers_seq_simd::<_, H>(ascii_seq, &mask, w));
+            let simd_packed = collect(spaced_minimizers_seq_simd::<_, H>(packed_seq, &mask, w));
+            assert_eq!(scalar_ascii, simd_ascii, "k={k}, w={w}, len={len}");
+            assert_eq!(scalar_ascii, simd_packed, "k={k}, w={w}, len={len}");
+
+            // Without don't-care positions, these are plain minimizers.
+            let mask = vec![true; k];
+            let spaced = collect(spaced_minimizers_seq_simd::<_, H>(packed_seq, &mask, w));
+            let plain = collect(minimizers_seq_simd::<_, H>(packed_seq, k, w));
+            assert_eq!(spaced, plain, "k={k}, w={w}, len={len}");
+        });
+    }
+    f::<NtHasher>();
+    f::<MulHasher>();
+}
+
+#[test]
+fn spaced_minimizer_positions() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let mask = (0..k).map(|i| i % 4 != 2).collect_vec();
+        let mut scalar = vec![];
+        scalar::spaced_minimizer_positions_scalar(ascii_seq, &mask, w, &mut scalar);
+        let mut simd = vec![];
+        super::spaced_minimizer_positions(packed_seq, &mask, w, &mut simd);
+        let len = ascii_seq.len();
+        assert_eq!(scalar, simd, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[cfg(feature = "fasta")]
+#[test]
+fn fasta_records() {
+    use fasta::{minimizers_from_reader, Record, Records};
+    let fasta = b">chr1 description\nACGTAC\nGTNNAC\n\n>chr2\r\nacgt\r\n";
+    let fastq = b"@read1\nACGT\n+\nIIII\n@read2 x\nGG\n+read2\nII\n";
+    let records = |input: &[u8]| {
+        Records::new(input)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap()
+    };
+    let record = |id: &str, seq: &[u8]| Record {
+        id: id.into(),
+        seq: seq.to_vec(),
+    };
+    assert_eq!(
+        records(fasta),
+        [record("chr1", b"ACGTACGTNNAC"), record("chr2", b"acgt")]
+    );
+    assert_eq!(
+        records(fastq),
+        [record("read1", b"ACGT"), record("read2", b"GG")]
+    );
+
+    let bad = b"@read1\nACGT\n+\nIII\n";
+    assert!(Records::new(&bad[..]).next().unwrap().is_err());
+
+    // Each record is processed separately, with positions relative to the record.
+    let (k, w) = (5, 11);
+    let seqs = (0..5)
+        .map(|i| AsciiSeqVec::random(100 + 1000 * i).seq)
+        .collect_vec();
+    let mut fasta = vec![];
+    for (i, seq) in seqs.iter().enumerate() {
+        fasta.extend_from_slice(format!(">seq{i}\n").as_bytes());
+        for line in seq.chunks(60) {
+            fasta.extend_from_slice(line);
+            fasta.push(b'\n');
+        }
+    }
+    let out = minimizers_from_reader(&fasta[..], k, w)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(out.len(), seqs.len());
+    for (i, (seq, (id, positions))) in seqs.iter().zip(out).enumerate() {
+        let mut expected = vec![];
+        super::canonical_minimizer_positions(
+            PackedSeqVec::from_ascii(seq).as_slice(),
+            k,
+            w,
+            &mut expected,
+        );
+        assert_eq!(id, format!("seq{i}"));
+        assert_eq!(positions, expected);
+    }
+}
+
+#[test]
+fn minimizer_counts() {
+    let (k, w) = (15, 11);
+    let a = PackedSeqVec::random(10000);
+    let b = PackedSeqVec::random(5000);
+    let dataset = [&a, &a, &b];
+    for canonical in [false, true] {
+        let minimizers = |seq: &PackedSeqVec| {
+            let mut positions = vec![];
+            let mut hashes = vec![];
+            if canonical {
+                super::canonical_minimizer_positions_and_hashes(
+                    seq.as_slice(),
+                    k,
+                    w,
+                    &mut positions,
+                    &mut hashes,
+                );
+            } else {
+                super::minimizer_positions_and_hashes(
+                    seq.as_slice(),
+                    k,
+                    w,
+                    &mut positions,
+                    &mut hashes,
+                );
+            }
+            (positions, hashes)
+        };
+
+        let mut counts = MinimizerCounts::new(k, w, canonical);
+        assert!(counts.is_empty());
+        let mut naive = std::collections::HashMap::<u32, u32>::new();
+        for seq in dataset {
+            counts.add(seq.as_slice());
+            for h in minimizers(seq).1 {
+                *naive.entry(h).or_default() += 1;
+            }
+        }
+        assert_eq!(counts.len(), naive.len());
+
+        let mut annotated = vec![];
+        counts.annotate(a.as_slice(), &mut annotated);
+        let (positions, hashes) = minimizers(&a);
+        assert_eq!(annotated.len(), positions.len());
+        for ((&(pos, count), p), h) in annotated.iter().zip(positions).zip(hashes) {
+            assert_eq!(pos, p);
+            assert_eq!(count, naive[&h]);
+            // `a` was added twice.
+            assert!(count >= 2);
+        }
+    }
+}
+
+#[test]
+fn canonical_minimizer_positions_and_strands() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if (k + w - 1) % 2 == 0 {
+            return;
+        }
+        let len = ascii_seq.len();
+        let mut expected = vec![];
+        super::canonical_minimizer_positions(packed_seq, k, w, &mut expected);
+
+        let mut scalar_pos = vec![];
+        let mut scalar_strands = vec![];
+        scalar::canonical_minimizer_positions_and_strands_scalar(
+            ascii_seq,
+            k,
+            w,
+            &mut scalar_pos,
+            &mut scalar_strands,
+        );
+        let mut simd_pos = vec![];
+        let mut simd_strands = vec![];
+        super::canonical_minimizer_positions_and_strands(
+            packed_seq,
+            k,
+            w,
+            &mut simd_pos,
+            &mut simd_strands,
+        );
+        assert_eq!(expected, simd_pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_pos, simd_pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar_strands, simd_strands, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn sourmash_sketch() {
+    use sourmash::*;
+    assert_eq!(hash_murmur(b"ACG", DEFAULT_SEED), 1731421407650554201);
+    assert_eq!(hash_murmur(b"", DEFAULT_SEED), 17305828677633410339);
+    assert_eq!(
+        hash_murmur(b"ATGGCAGTGACGATGCCAG", DEFAULT_SEED),
+        9130652839826474325
+    );
+    assert_eq!(
+        hash_murmur(b"GATTACAGATTACAGATTACAGATTACAGATTACA", DEFAULT_SEED),
+        8766530302275029212
+    );
+
+    // Reference values computed with an independent implementation of the sourmash definitions.
+    let seq = b"GATTACAcagtNNNNacgtacgatcgatcgatgctagctagctagcatcgatcgatcgatcgatgcatgcatcgacgttttttttgggggcccc";
+    let mut sketch = FracMinHash::new(7, 3);
+    sketch.add_sequence(seq);
+    assert_eq!(
+        sketch.hashes(),
+        [
+            479287907524885401,
+            1753476284951911448,
+            2175174253188108418,
+            2542939437405271313,
+            2752571113360070946,
+            3002142756356835194,
+            3125574351765521431,
+            3209348253706632831,
+            3411150483758155077,
+            5608822071594223102
+        ]
+    );
+    assert_eq!(sketch.md5sum(), "55d56e1254fa0f60a7221eef9774f2d6");
+
+    let mut sketch = FracMinHash::new(5, 1);
+    sketch.add_sequence(seq);
+    assert_eq!(sketch.hashes().len(), 35);
+    assert_eq!(sketch.md5sum(), "ecfc94a02be15d9f55a4c4a866cef062");
+
+    // Both strands give the same sketch.
+    let rc = seq
+        .iter()
+        .rev()
+        .map(|&c| packed_seq::complement_char(c))
+        .collect_vec();
+    let mut rc_sketch = FracMinHash::new(5, 1);
+    rc_sketch.add_sequence(&rc);
+    assert_eq!(sketch, rc_sketch);
+
+    let json = sketch.to_json("a \"quoted\" name");
+    assert!(json.starts_with(r#"[{"class":"sourmash_signature""#));
+    assert!(json.contains(r#""name":"a \"quoted\" name""#));
+    assert!(json.contains(r#""md5sum":"ecfc94a02be15d9f55a4c4a866cef062""#));
+}
+
+#[test]
+fn seed_uniqueness() {
+    let (k, w) = (15, 11);
+    let unique = PackedSeqVec::random(10000);
+    let repeat = PackedSeqVec::random(2000);
+    let mut index = MinimizerCounts::new(k, w, true);
+    index.add(unique.as_slice());
+    index.add(repeat.as_slice());
+    index.add(repeat.as_slice());
+
+    let unique_read = unique.slice(1000..1500);
+    let repeat_read = repeat.slice(500..1000);
+    let novel = PackedSeqVec::random(500);
+    let mut stats = vec![];
+    index.seed_uniqueness_records(
+        &[
+            unique_read,
+            repeat_read,
+            novel.as_slice(),
+            unique.slice(0..0),
+        ],
+        &mut stats,
+    );
+
+    let mut positions = vec![];
+    super::canonical_minimizer_positions(unique_read, k, w, &mut positions);
+    assert_eq!(stats[0].num_seeds, positions.len());
+    assert!(stats[0].fraction_unique() > 0.9, "{:?}", stats[0]);
+    assert!(stats[0].score() >= 10, "{:?}", stats[0]);
+
+    assert_eq!(stats[1].num_unique, 0);
+    assert_eq!(stats[1].score(), 0);
+
+    assert_eq!(stats[2].score(), 0);
+    assert!(
+        stats[2].num_absent > stats[2].num_seeds / 2,
+        "{:?}",
+        stats[2]
+    );
+
+    assert_eq!(stats[3], SeedUniqueness::default());
+    assert_eq!(stats[3].score(), 0);
+
+    let all_unique = SeedUniqueness {
+        num_seeds: 10,
+        num_unique: 10,
+        num_absent: 0,
+    };
+    assert_eq!(all_unique.score(), SeedUniqueness::MAX_SCORE);
+    let half = SeedUniqueness {
+        num_seeds: 10,
+        num_unique: 5,
+        num_absent: 0,
+    };
+    assert_eq!(half.score(), 3);
+}
+
+#[test]
+fn minimizer_space() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        if k > 32 {
+            return;
+        }
+        let len = packed_seq.len();
+        let mut positions = vec![];
+        let mut kmers = vec![];
+        super::minimizer_positions_and_kmers(packed_seq, k, w, &mut positions, &mut kmers);
+        let mut space = vec![];
+        super::minimizer_space(packed_seq, k, w, &mut space);
+        assert_eq!(kmers, space, "k={k}, w={w}, len={len}");
+
+        if (k + w - 1) % 2 == 1 {
+            let mut positions = vec![];
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut positions);
+            let expected = positions
+                .iter()
+                .map(|&p| kmer::canonical_kmer_value(packed_seq.slice(p as usize..p as usize + k)))
+                .collect_vec();
+            let mut space = vec![];
+            super::canonical_minimizer_space(packed_seq, k, w, &mut space);
+            assert_eq!(expected, space, "k={k}, w={w}, len={len}");
+        }
+    });
+}
+
+#[test]
+fn par_windows() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = packed_seq.len();
+        let num_windows = (len + 1).saturating_sub(l);
+
+        let (it, padding) = super::par_windows(packed_seq, l);
+        let mut starts = vec![];
+        for (lane_starts, views) in it {
+            for (start, view) in lane_starts.to_array().into_iter().zip(views) {
+                starts.push(start);
+                if (start as usize) < len {
+                    assert_eq!(view, packed_seq.slice(start as usize..start as usize + l));
+                } else {
+                    assert_eq!(view.len(), 0);
+                }
+            }
+        }
+        starts.sort_unstable();
+        starts.truncate(starts.len() - padding);
+        assert_eq!(
+            starts,
+            (0..num_windows as u32).collect_vec(),
+            "k={k}, w={w}, len={len}"
+        );
+
+        // The window starts are appended in order.
+        let mut out = vec![u32::MAX];
+        super::map_windows_simd(packed_seq, l, |starts, _views| starts, &mut out);
+        assert_eq!(
+            out[1..],
+            (0..num_windows as u32).collect_vec(),
+            "k={k}, w={w}, len={len}"
+        );
+
+        // Per-window values match the scalar windows.
+        let mut out = vec![];
+        super::map_windows_simd(
+            packed_seq,
+            l,
+            |_starts, views| S::new(views.map(|view| view.iter_bp().map(|c| c as u32).sum())),
+            &mut out,
+        );
+        let expected = (0..num_windows)
+            .map(|i| {
+                packed_seq
+                    .slice(i..i + l)
+                    .iter_bp()
+                    .map(|c| c as u32)
+                    .sum::<u32>()
+            })
+            .collect_vec();
+        assert_eq!(out, expected, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[cfg(feature = "test-data")]
+#[test]
+fn reference_parity() {
+    let cases = reference::reference_cases();
+    assert!(!cases.is_empty());
+    for case in &cases {
+        assert_eq!((case.k + case.w - 1) % 2, 1);
+        assert!(case
+            .positions
+            .iter()
+            .all(|&p| p as usize + case.k <= case.seq.len()));
+
+        // The forward positions, including those for k>32, match the naive minimizer of each window.
+        let (k, w) = (case.k, case.w);
+        let naive = case
+            .seq
+            .as_bytes()
+            .windows(k + w - 1)
+            .enumerate()
+            .map(|(pos, window)| (pos + minimizer::<NtHasher>(AsciiSeq(window), k)) as u32)
+            .dedup()
+            .collect_vec();
+        assert_eq!(case.positions, naive, "k={k}, w={w}");
+    }
+    assert_eq!(reference::verify_reference(), Ok(cases.len()));
+}
+
+#[test]
+fn packed_file() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        for start in [0, 1, 2, 3, 5].into_iter().filter(|&s| s <= len) {
+            let seq = packed_seq.slice(start..len);
+            let mut bytes = vec![];
+            packed_file::write_packed(seq, &mut bytes).unwrap();
+            let mapped = packed_file::packed_seq_from_bytes(&bytes).unwrap();
+            assert_eq!(mapped.len(), seq.len());
+            assert_eq!(mapped.to_vec(), seq.to_vec(), "start={start}, len={len}");
+
+            let mut expected = vec![];
+            super::minimizer_positions(seq, k, w, &mut expected);
+            let mut positions = vec![];
+            super::minimizer_positions(mapped, k, w, &mut positions);
+            assert_eq!(
+                positions, expected,
+                "k={k}, w={w}, start={start}, len={len}"
+            );
+        }
+    });
+
+    let seq = PackedSeqVec::from_ascii(b"ACGTTGCA");
+    let mut bytes = vec![];
+    packed_file::write_packed(seq.as_slice(), &mut bytes).unwrap();
+    assert!(packed_file::packed_seq_from_bytes(&bytes[..packed_file::HEADER_LEN + 1]).is_err());
+    // The padding is required.
+    assert!(packed_file::packed_seq_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(packed_file::packed_seq_from_bytes(&bytes).is_ok());
+    // A malformed header is an error, not an overflow.
+    let mut malformed = bytes.clone();
+    malformed[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+    let err = packed_file::packed_seq_from_bytes(&malformed).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    bytes[0] = b'X';
+    assert!(packed_file::packed_seq_from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn superkmer_end_positions() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let check = |f: &dyn Fn(&mut Vec<u32>, &mut Vec<u32>),
+                     g: &dyn Fn(&mut Vec<u32>, &mut Vec<u32>)| {
+            let (mut pos, mut end) = (vec![], vec![]);
+            f(&mut pos, &mut end);
+            let (mut pos_scalar, mut end_scalar) = (vec![], vec![]);
+            g(&mut pos_scalar, &mut end_scalar);
+            assert_eq!(pos, pos_scalar, "k={k}, w={w}, len={len}");
+            assert_eq!(end, end_scalar, "k={k}, w={w}, len={len}");
+            // Appending again continues the run of the last minimizer, which then ends in the second call.
+            f(&mut pos, &mut end);
+            g(&mut pos_scalar, &mut end_scalar);
+            assert_eq!(pos, pos_scalar, "k={k}, w={w}, len={len}");
+            assert_eq!(end, end_scalar, "k={k}, w={w}, len={len}");
+        };
+        check(
+            &|p, e| super::minimizer_and_superkmer_end_positions(packed_seq, k, w, p, e),
+            &|p, e| scalar::minimizer_and_superkmer_end_positions_scalar(packed_seq, k, w, p, e),
+        );
+        check(
+            &|p, e| super::minimizer_and_superkmer_end_positions(ascii_seq, k, w, p, e),
+            &|p, e| scalar::minimizer_and_superkmer_end_positions_scalar(ascii_seq, k, w, p, e),
+        );
+        check(
+            &|p, e| super::minimizer_and_superkmer_end_positions(slice, k, w, p, e),
+            &|p, e| scalar::minimizer_and_superkmer_end_positions_scalar(slice, k, w, p, e),
+        );
+
+        // The same minimizers as when keeping the first occurrence.
+        let (mut pos, mut end) = (vec![], vec![]);
+        super::minimizer_and_superkmer_end_positions(packed_seq, k, w, &mut pos, &mut end);
+        let (mut pos_first, mut start) = (vec![], vec![]);
+        super::minimizer_and_superkmer_positions(packed_seq, k, w, &mut pos_first, &mut start);
+        assert_eq!(pos, pos_first, "k={k}, w={w}, len={len}");
+
+        if (k + w - 1) % 2 == 1 {
+            check(
+                &|p, e| {
+                    super::canonical_minimizer_and_superkmer_end_positions(packed_seq, k, w, p, e)
+                },
+                &|p, e| {
+                    scalar::canonical_minimizer_and_superkmer_end_positions_scalar(
+                        packed_seq, k, w, p, e,
+                    )
+                },
+            );
+        }
+    });
+}
+
+#[test]
+fn simd_utils() {
+    let mut rng = rand::rng();
+    for max in [2, 5, 100] {
+        let values = (0..8 * 1000)
+            .map(|_| rng.random_range(0..max))
+            .collect_vec();
+        let rows = values
+            .chunks_exact(8)
+            .map(|c| S::new(c.try_into().unwrap()))
+            .collect_vec();
+
+        for m in rows.chunks_exact(8) {
+            let m: [S; 8] = m.try_into().unwrap();
+            let t = simd_utils::transpose_u32x8x8(m);
+            for i in 0..8 {
+                for j in 0..8 {
+                    assert_eq!(t[i].to_array()[j], m[j].to_array()[i]);
+                }
+            }
+        }
+
+        let mut expected = values.clone();
+        expected.dedup();
+        let mut out = vec![];
+        let mut prev = S::splat(!values[0]);
+        for &row in &rows {
+            simd_utils::compress_unique(prev, row, row, &mut out);
+            prev = row;
+        }
+        assert_eq!(out, expected, "max={max}");
+
+        let mut out = vec![];
+        for (i, &row) in rows.iter().enumerate() {
+            let next = rows.get(i + 1).copied().unwrap_or(!row);
+            simd_utils::compress_unique_last(row, next, row, &mut out);
+        }
+        assert_eq!(out, expected, "max={max}");
+
+        for len in [0, 1, 7, 8, 9, 100, values.len()] {
+            let mut v = values[..len].to_vec();
+            let mut expected = v.clone();
+            expected.dedup();
+            simd_utils::dedup_vec(&mut v);
+            assert_eq!(v, expected, "max={max}, len={len}");
+        }
+    }
+}
+
+#[test]
+fn frequent_minimizers() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        for max_freq in [1, 2, u32::MAX] {
+            let mut positions = vec![];
+            super::minimizer_positions(packed_seq, k, w, &mut positions);
+            let all = positions.clone();
+            super::filter_frequent_minimizers(&mut positions, packed_seq, k, max_freq);
+            let mut filtered = vec![];
+            super::minimizer_positions_max_freq(packed_seq, k, w, max_freq, &mut filtered);
+            assert_eq!(
+                positions, filtered,
+                "k={k}, w={w}, len={len}, max_freq={max_freq}"
+            );
+            if max_freq == u32::MAX {
+                assert_eq!(positions, all);
+            }
+
+            // Counting by k-mer instead of by hash, unless distinct k-mers have colliding hashes.
+            let kmer = |p: u32| {
+                packed_seq
+                    .slice(p as usize..p as usize + k)
+                    .iter_bp()
+                    .collect_vec()
+            };
+            let counts = all.iter().map(|&p| kmer(p)).counts();
+            let num_hashes = all
+                .iter()
+                .map(|&p| {
+                    nthash_kmer::<false, NtHasher>(packed_seq.slice(p as usize..p as usize + k))
+                })
+                .unique()
+                .count();
+            if num_hashes == counts.len() {
+                let expected = all
+                    .iter()
+                    .copied()
+                    .filter(|&p| counts[&kmer(p)] as u32 <= max_freq)
+                    .collect_vec();
+                assert_eq!(
+                    positions, expected,
+                    "k={k}, w={w}, len={len}, max_freq={max_freq}"
+                );
+            }
+
+            // Appending keeps earlier positions.
+            let mut appended = vec![u32::MAX];
+            super::minimizer_positions_max_freq(packed_seq, k, w, max_freq, &mut appended);
+            assert_eq!(appended[0], u32::MAX);
+            assert_eq!(appended[1..], filtered, "k={k}, w={w}, len={len}");
+
+            if (k + w - 1) % 2 == 1 {
+                let mut positions = vec![];
+                super::canonical_minimizer_positions(packed_seq, k, w, &mut positions);
+                super::filter_frequent_canonical_minimizers(
+                    &mut positions,
+                    packed_seq,
+                    k,
+                    max_freq,
+                );
+                let mut filtered = vec![];
+                super::canonical_minimizer_positions_max_freq(
+                    packed_seq,
+                    k,
+                    w,
+                    max_freq,
+                    &mut filtered,
+                );
+                assert_eq!(
+                    positions, filtered,
+                    "k={k}, w={w}, len={len}, max_freq={max_freq}"
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn position_diff() {
+    test_on_inputs(|k, w, _slice, ascii_seq, _packed_seq| {
+        let len = ascii_seq.len();
+        let mut old = vec![];
+        super::minimizer_positions(ascii_seq, k, w, &mut old);
+        // Mutate a few characters.
+        let mut mutated = ascii_seq.0.to_vec();
+        for _ in 0..3 {
+            if len > 0 {
+                mutated[random_range(0..len)] = b"ACGT"[random_range(0..4)];
+            }
+        }
+        let mut new = vec![];
+        super::minimizer_positions(AsciiSeq(&mutated), k, w, &mut new);
+
+        let patch = diff::diff_positions(&old, &new);
+        let naive_removed = old
+            .iter()
+            .copied()
+            .filter(|p| !new.contains(p))
+            .collect_vec();
+        let naive_added = new
+            .iter()
+            .copied()
+            .filter(|p| !old.contains(p))
+            .collect_vec();
+        assert_eq!(patch.removed, naive_removed, "k={k}, w={w}, len={len}");
+        assert_eq!(patch.added, naive_added, "k={k}, w={w}, len={len}");
+        assert!(diff::diff_positions(&old, &old).is_empty());
+
+        let decoded = diff::PositionPatch::from_bytes(&patch.to_bytes()).unwrap();
+        assert_eq!(decoded, patch, "k={k}, w={w}, len={len}");
+        let mut out = vec![];
+        diff::apply_patch(&old, &decoded, &mut out).unwrap();
+        assert_eq!(out, new, "k={k}, w={w}, len={len}");
+    });
+
+    // A patch applied to the wrong list is an error.
+    let patch = diff::diff_positions(&[1, 5, 9], &[1, 9, 12]);
+    let mut out = vec![];
+    assert_eq!(
+        diff::apply_patch(&[1, 9], &patch, &mut out),
+        Err(diff::PatchMismatch { pos: 5 })
+    );
+    let mut out = vec![];
+    assert_eq!(
+        diff::apply_patch(&[1, 5, 9, 12], &patch, &mut out),
+        Err(diff::PatchMismatch { pos: 12 })
+    );
+    // Truncated and large positions.
+    let bytes = patch.to_bytes();
+    assert!(diff::PositionPatch::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    let patch = diff::PositionPatch {
+        removed: vec![0, u32::MAX],
+        added: vec![],
+    };
+    assert_eq!(
+        diff::PositionPatch::from_bytes(&patch.to_bytes()).unwrap(),
+        patch
+    );
+}
+
+#[test]
+fn incremental_nthash() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        if w > 1 {
+            return;
+        }
+        let len = packed_seq.len();
+        let fwd = nthash_seq_scalar::<false, NtHasher>(packed_seq, k).collect_vec();
+        let canonical = nthash_seq_scalar::<true, NtHasher>(packed_seq, k).collect_vec();
+        let bases = packed_seq.iter_bp().collect_vec();
+
+        let mut hasher = incremental::NtHasher::new(k);
+        for (i, &base) in bases.iter().enumerate() {
+            hasher.push(base);
+            if i + 1 < k {
+                assert_eq!(hasher.current_fwd(), None);
+                continue;
+            }
+            let pos = i + 1 - k;
+            assert_eq!(hasher.current_fwd(), Some(fwd[pos]), "k={k}, len={len}");
+            assert_eq!(
+                hasher.current_canonical(),
+                Some(canonical[pos]),
+                "k={k}, len={len}"
+            );
+        }
+
+        // Extend the reverse complement strand, i.e., walk the forward strand backwards.
+        let mut hasher = incremental::NtHasher::new(k);
+        for (i, &base) in bases.iter().enumerate().rev() {
+            hasher.push_rc(packed_seq::complement_base(base));
+            if i + k > len {
+                continue;
+            }
+            assert_eq!(hasher.current_fwd(), Some(fwd[i]), "k={k}, len={len}");
+            assert_eq!(
+                hasher.current_canonical(),
+                Some(canonical[i]),
+                "k={k}, len={len}"
+            );
+        }
+    });
+}
+
+#[test]
+fn dedup_vec_with_counts() {
+    for len in 0..100 {
+        for max in [1, 2, 5, 1000] {
+            let v = (0..len).map(|_| random_range(0..max)).collect_vec();
+            let expected = v.iter().copied().dedup_with_count().collect_vec();
+            let mut dedup = v.clone();
+            let counts = simd_utils::dedup_vec_with_counts(&mut dedup);
+            let got = zip(counts, dedup)
+                .map(|(c, x)| (c as usize, x))
+                .collect_vec();
+            assert_eq!(got, expected, "len={len}, max={max}");
+        }
+    }
+    // Values are retained regardless of their value.
+    let mut v = vec![u32::MAX, u32::MAX, 0, 1];
+    assert_eq!(simd_utils::dedup_vec_with_counts(&mut v), [2, 1, 1]);
+    assert_eq!(v, [u32::MAX, 0, 1]);
+}
+
+#[test]
+fn weighted_minimizers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let num_kmers = (len + 1).saturating_sub(k);
+        for max_weight in [1, 100, u16::MAX] {
+            let weights = (0..num_kmers)
+                .map(|_| random_range(0..=max_weight))
+                .collect_vec();
+            let hashes = nthash_seq_scalar::<false, NtHasher>(packed_seq, k).collect_vec();
+            let naive = (0..(len + 2).saturating_sub(k + w))
+                .map(|i| {
+                    let min = (i..i + w)
+                        .min_by_key(|&j| {
+                            weighted::weighted_hash(hashes[j], weights[j]) & 0xffff_0000
+                        })
+                        .unwrap();
+                    min as u32
+                })
+                .dedup()
+                .collect_vec();
+
+            let mut simd = vec![];
+            super::weighted_minimizer_positions(packed_seq, &weights, k, w, &mut simd);
+            let mut simd_ascii = vec![];
+            super::weighted_minimizer_positions(ascii_seq, &weights, k, w, &mut simd_ascii);
+            let mut scalar = vec![];
+            scalar::weighted_minimizer_positions_scalar(packed_seq, &weights, k, w, &mut scalar);
+            assert_eq!(naive, scalar, "k={k}, w={w}, len={len}");
+            assert_eq!(naive, simd, "k={k}, w={w}, len={len}");
+            assert_eq!(naive, simd_ascii, "k={k}, w={w}, len={len}");
+        }
+
+        // Zero weights give the plain minimizers.
+        let weights = vec![0; num_kmers];
+        let mut expected = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+        let mut out = vec![];
+        super::weighted_minimizer_positions(packed_seq, &weights, k, w, &mut out);
+        assert_eq!(expected, out, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn merge_lanes() {
+    test_on_inputs(|k, w, _slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut expected);
+
+        // Drive the SIMD iterator manually, and collect the raw output of each lane.
+        let (head, padding) = minimizers_seq_simd::<_, NtHasher>(packed_seq, k, w);
+        let n = head.len();
+        let mut lanes = vec![vec![]; 8];
+        for x in head {
+            for (lane, &x) in zip(&mut lanes, x.as_array_ref()) {
+                lane.push(x);
+            }
+        }
+        // The padding is at the end of the last lanes.
+        let total = 8 * n - padding;
+        let lens = (0..8)
+            .map(|j| total.saturating_sub(j * n).min(n))
+            .collect_vec();
+        assert_eq!(
+            simd_utils::merge_lanes(&lanes, &lens),
+            expected,
+            "k={k}, w={w}, len={len}"
+        );
+    });
+}
+
+#[test]
+fn hash_quality() {
+    use hash_quality::*;
+    for k in [5, 21, 31] {
+        let nt = analyze_random(k, 2000, k as u64, nthash);
+        assert_eq!(nt.num_kmers, 2000);
+        assert!(nt.avalanche > 0.3, "k={k}: {nt:?}");
+        assert!(nt.max_bias < 0.1, "k={k}: {nt:?}");
+        assert!(
+            (nt.collisions_upper16 as f64) < 2.0 * nt.expected_collisions_upper16 + 10.0,
+            "k={k}: {nt:?}"
+        );
+        let canonical = analyze_random(k, 2000, k as u64, canonical_nthash);
+        assert_eq!(canonical.num_distinct, nt.num_distinct);
+
+        // Substituting one base changes at most 2 bits of the anti-lex hash.
+        let lex = analyze_random(k, 2000, k as u64, anti_lex);
+        assert!(lex.avalanche <= 2.0 / 32.0, "k={k}: {lex:?}");
+    }
+
+    // A constant hash.
+    let kmers = hash_quality::random_kmers(11, 100, 0);
+    let report = analyze(&kmers, |_| 0);
+    assert_eq!(report.avalanche, 0.0);
+    assert_eq!(report.max_bias, 0.5);
+    assert_eq!(report.collisions, report.num_distinct - 1);
+
+    // Real k-mers, with their multiplicity.
+    let seq = &ASCII_SEQ.seq;
+    let kmers = seq.windows(21).take(1000).collect_vec();
+    let report = analyze(&kmers, nthash);
+    assert_eq!(report.num_kmers, 1000);
+    assert!(report.num_distinct <= 1000);
+}
+
+#[test]
+fn minimizer_offsets() {
+    test_on_inputs(|k, w, slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let naive = minimizers_seq_scalar::<NtHasher>(packed_seq, k, w)
+            .enumerate()
+            .map(|(i, p)| (p as usize - i) as u16)
+            .collect_vec();
+        assert!(naive.iter().all(|&o| (o as usize) < w));
+
+        let mut simd = vec![1];
+        super::minimizer_offsets(packed_seq, k, w, &mut simd);
+        let mut scalar = vec![1];
+        scalar::minimizer_offsets_scalar(packed_seq, k, w, &mut scalar);
+        assert_eq!(simd[1..], naive, "k={k}, w={w}, len={len}");
+        assert_eq!(scalar[1..], naive, "k={k}, w={w}, len={len}");
+
+        let naive = minimizers_seq_scalar::<MulHasher>(slice, k, w)
+            .enumerate()
+            .map(|(i, p)| (p as usize - i) as u16)
+            .collect_vec();
+        let mut simd = vec![];
+        super::minimizer_offsets(slice, k, w, &mut simd);
+        assert_eq!(simd, naive, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn packed_blocks() {
+    use packed_blocks::{positions_in_packed_blocks, PackedBlock};
+    test_on_inputs(|k, w, _slice, ascii_seq, _packed_seq| {
+        let len = ascii_seq.len();
+        // Insert runs of Ns.
+        let mut ascii = ascii_seq.0.to_vec();
+        for _ in 0..3 {
+            if len > 0 {
+                let start = random_range(0..len);
+                let end = (start + random_range(1..20)).min(len);
+                ascii[start..end].fill(b'N');
+            }
+        }
+        let valid = acgt_mask(&ascii);
+        let packed = PackedSeqVec::from_ascii(&ascii);
+        let mut expected = vec![];
+        super::minimizer_positions_masked(packed.as_slice(), k, w, &valid, &mut expected);
+        let canonical = (k + w - 1) % 2 == 1;
+        let mut expected_canonical = vec![];
+        if canonical {
+            super::canonical_minimizer_positions_masked(
+                packed.as_slice(),
+                k,
+                w,
+                &valid,
+                &mut expected_canonical,
+            );
+        }
+
+        // Split into blocks of random lengths, each packed on its own.
+        let mut block_data = vec![];
+        let mut start = 0;
+        while start < len {
+            let end = (start + random_range(1..300)).min(len);
+            let block = &ascii[start..end];
+            let n_runs = valid[start..end]
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| !**v)
+                .map(|(i, _)| i..i + 1)
+                .collect_vec();
+            block_data.push((PackedSeqVec::from_ascii(block), block.len(), n_runs));
+            start = end;
+        }
+        let blocks = || {
+            block_data.iter().map(|(data, len, n_runs)| PackedBlock {
+                data: &data.seq,
+                len: *len,
+                n_runs,
+            })
+        };
+
+        let mut out = vec![];
+        super::minimizer_positions_packed_blocks(blocks(), k, w, &mut out);
+        assert_eq!(out, expected, "k={k}, w={w}, len={len}");
+        for flush_len in [1, 100, 1000] {
+            let mut out = vec![];
+            positions_in_packed_blocks(blocks(), k + w - 1, flush_len, &mut out, |seq, out| {
+                super::minimizer_positions(seq, k, w, out)
+            });
+            assert_eq!(
+                out, expected,
+                "k={k}, w={w}, len={len}, flush_len={flush_len}"
+            );
+        }
+        if canonical {
+            let mut out = vec![];
+            super::canonical_minimizer_positions_packed_blocks(blocks(), k, w, &mut out);
+            assert_eq!(out, expected_canonical, "k={k}, w={w}, len={len}");
+        }
+    });
+}
+
+#[test]
+fn sliding_min_tree() {
+    use sliding_min::{sliding_min_mapper, sliding_min_tree_mapper};
+    fn check<const LEFT: bool, const W: usize>(hashes: &[S], k: usize) {
+        let len = hashes.len();
+        let mut tree = sliding_min_tree_mapper::<LEFT, W>(k, len);
+        let mut two_stacks = sliding_min_mapper::<LEFT>(W, k, len);
+        for (i, &h) in hashes.iter().enumerate() {
+            let (a, b) = (tree(h), two_stacks(h));
+            if i + 1 >= W {
+                assert_eq!(a, b, "w={W}, k={k}, i={i}, left={LEFT}");
+            }
+        }
+    }
+    // Long enough for the positions to be reset, and with few distinct values to test tie-breaking.
+    let hashes = (0..200_000)
+        .map(|_| S::new(std::array::from_fn(|_| random_range(0..4u32) << 30)))
+        .collect_vec();
+    for k in [1, 5] {
+        check::<true, 1>(&hashes, k);
+        check::<true, 2>(&hashes, k);
+        check::<true, 5>(&hashes, k);
+        check::<true, 8>(&hashes, k);
+        check::<false, 3>(&hashes, k);
+        check::<false, 4>(&hashes, k);
+    }
+}
+
+#[test]
+fn strobemers() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        for (w_min, w_max) in [(1, 1), (w, w), (w, w + 7), (1, w)] {
+            let len = packed_seq.len();
+            let hashes = nthash_seq_scalar::<false, NtHasher>(packed_seq, k).collect_vec();
+            for rand in [false, true] {
+                let mut scalar = vec![];
+                let mut simd = vec![];
+                if rand {
+                    super::scalar::randstrobes_scalar(packed_seq, k, w_min, w_max, &mut scalar);
+                    super::randstrobes(packed_seq, k, w_min, w_max, &mut simd);
+                } else {
+                    super::scalar::minstrobes_scalar(packed_seq, k, w_min, w_max, &mut scalar);
+                    super::minstrobes(packed_seq, k, w_min, w_max, &mut simd);
+                }
+                assert_eq!(
+                    simd, scalar,
+                    "k={k}, w_min={w_min}, w_max={w_max}, len={len}, rand={rand}"
+                );
+                assert_eq!(scalar.len(), (len + 1).saturating_sub(k + w_max));
+
+                // Naive check of the definition.
+                for (i, s) in scalar.iter().enumerate() {
+                    let h1 = hashes[i];
+                    let key = |j: usize| (if rand { h1 ^ hashes[j] } else { hashes[j] }) >> 16;
+                    let j = (i + w_min..=i + w_max).min_by_key(|&j| key(j)).unwrap();
+                    assert_eq!(s.pos1 as usize, i);
+                    assert_eq!(
+                        s.pos2 as usize, j,
+                        "k={k}, w_min={w_min}, w_max={w_max}, len={len}"
+                    );
+                    assert_eq!(s.hash, h1 ^ hashes[j].rotate_left(16));
+                }
+            }
+
+            // Other alphabets use a different hasher.
+            let mut scalar = vec![];
+            let mut simd = vec![];
+            super::scalar::randstrobes_scalar(ascii_seq, k, w_min, w_max, &mut scalar);
+            super::randstrobes(ascii_seq, k, w_min, w_max, &mut simd);
+            assert_eq!(
+                simd, scalar,
+                "k={k}, w_min={w_min}, w_max={w_max}, len={len}"
+            );
+            let mut scalar = vec![];
+            let mut simd = vec![];
+            super::scalar::minstrobes_scalar(slice, k, w_min, w_max, &mut scalar);
+            super::minstrobes(slice, k, w_min, w_max, &mut simd);
+            assert_eq!(
+                simd, scalar,
+                "k={k}, w_min={w_min}, w_max={w_max}, len={len}"
+            );
+        }
+    });
+}
+
+#[test]
+fn most_frequent_minimizers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, _packed_seq| {
+        let len = ascii_seq.len();
+        // Repeat the sequence to create frequent minimizers.
+        let ascii = ascii_seq.0;
+        let repeated =
+            PackedSeqVec::from_ascii(&[&ascii[..], &ascii[..len / 2], &ascii[..]].concat());
+        let seq = repeated.as_slice();
+        for canonical in [false, true] {
+            if canonical && (k + w - 1) % 2 == 0 {
+                continue;
+            }
+            let mut positions = vec![];
+            let mut hashes = vec![];
+            if canonical {
+                super::canonical_minimizer_positions_and_hashes(
+                    seq,
+                    k,
+                    w,
+                    &mut positions,
+                    &mut hashes,
+                );
+            } else {
+                super::minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+            }
+            let counts = hashes.iter().copied().counts();
+            let mut expected = counts
+                .into_iter()
+                .map(|(hash, count)| (hash, count as u32))
+                .collect_vec();
+            expected.sort_by_key(|&(hash, count)| (std::cmp::Reverse(count), hash));
+            for n in [0, 1, 5, usize::MAX] {
+                let mut top = vec![(0, 0)];
+                if canonical {
+                    super::canonical_most_frequent_minimizers(seq, k, w, n, &mut top);
+                } else {
+                    super::most_frequent_minimizers(seq, k, w, n, &mut top);
+                }
+                assert_eq!(top[0], (0, 0));
+                assert_eq!(
+                    top[1..],
+                    expected[..n.min(expected.len())],
+                    "k={k}, w={w}, len={len}, n={n}, canonical={canonical}"
+                );
+            }
+        }
+    });
+
+    let seqs = (0..10).map(|i| PackedSeqVec::random(i * 100)).collect_vec();
+    let slices = seqs.iter().map(|s| s.as_slice()).collect_vec();
+    let mut out_vecs = vec![];
+    super::most_frequent_minimizers_records(&slices, 21, 11, 3, &mut out_vecs);
+    assert_eq!(out_vecs.len(), seqs.len());
+    for (seq, top) in zip(&slices, &out_vecs) {
+        let mut expected = vec![];
+        super::most_frequent_minimizers(*seq, 21, 11, 3, &mut expected);
+        assert_eq!(*top, expected);
+    }
+    let mut out_vecs = vec![];
+    super::canonical_most_frequent_minimizers_records(&slices, 21, 11, 3, &mut out_vecs);
+    for (seq, top) in zip(&slices, &out_vecs) {
+        let mut expected = vec![];
+        super::canonical_most_frequent_minimizers(*seq, 21, 11, 3, &mut expected);
+        assert_eq!(*top, expected);
+    }
+}
+
+#[test]
+fn sorted_positions() {
+    test_on_inputs(|k, w, slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let check = |positions: &[u32], name: &str| {
+            assert!(
+                is_sorted(positions),
+                "{name}: k={k}, w={w}, len={len}, positions={positions:?}"
+            );
+        };
+        let mut out = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut out);
+        check(&out, "minimizer_positions");
+        out.clear();
+        super::minimizer_positions(ascii_seq, k, w, &mut out);
+        check(&out, "minimizer_positions (ascii)");
+        out.clear();
+        super::minimizer_positions(slice, k, w, &mut out);
+        check(&out, "minimizer_positions (text)");
+        out.clear();
+        super::minimizer_positions_hash64(packed_seq, k, w, &mut out);
+        check(&out, "minimizer_positions_hash64");
+        out.clear();
+        // Mod-minimizers are forward when `t` is congruent to `k` modulo `w`.
+        let r = k.min(4);
+        super::mod_minimizer_positions(packed_seq, k, w, r + (k - r) % w, &mut out);
+        check(&out, "mod_minimizer_positions");
+        out.clear();
+        let mask = (0..k).map(|i| i % 3 != 1 || i + 1 == k).collect_vec();
+        super::spaced_minimizer_positions(packed_seq, &mask, w, &mut out);
+        check(&out, "spaced_minimizer_positions");
+        out.clear();
+        let weights = (0..len).map(|_| random_range(0..1000u16)).collect_vec();
+        super::weighted_minimizer_positions(packed_seq, &weights, k, w, &mut out);
+        check(&out, "weighted_minimizer_positions");
+        out.clear();
+        let valid = (0..len).map(|_| random_range(0..100) > 0).collect_vec();
+        super::minimizer_positions_masked(packed_seq, k, w, &valid, &mut out);
+        check(&out, "minimizer_positions_masked");
+        out.clear();
+        if k > 1 {
+            super::syncmer_positions(packed_seq, k, k / 2, &[0, 1], &mut out);
+            check(&out, "syncmer_positions");
+        }
+        let mut out64 = vec![];
+        super::minimizer_positions_u64(packed_seq, k, w, &mut out64);
+        assert!(
+            out64.is_sorted(),
+            "minimizer_positions_u64: k={k}, w={w}, len={len}"
+        );
+    });
+
+    // Records are processed in different lanes.
+    let seqs = (0..20)
+        .map(|_| PackedSeqVec::random(random_range(0..1000)))
+        .collect_vec();
+    let slices = seqs.iter().map(|s| s.as_slice()).collect_vec();
+    let mut out_vecs = vec![];
+    super::minimizer_positions_records(&slices, 5, 11, &mut out_vecs);
+    assert!(out_vecs.iter().all(|out| is_sorted(out)));
+
+    // Chunks processed by different threads.
+    #[cfg(feature = "rayon")]
+    {
+        let seq = PackedSeqVec::random(1_000_000);
+        let mut out = vec![];
+        super::minimizers_par_chunks(seq.as_slice(), 21, 11, 4, &mut out);
+        assert!(is_sorted(&out));
+    }
+}
+
+#[test]
+fn minimizer_positions_bitvec() {
+    test_on_inputs(|k, w, slice, _ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut positions = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut positions);
+        let mut bitvec = BitVec::new();
+        super::minimizer_positions_bitvec(packed_seq, k, w, &mut bitvec);
+        assert_eq!(bitvec.len(), len);
+        assert_eq!(
+            bitvec.iter_ones().collect_vec(),
+            positions.iter().map(|&p| p as usize).collect_vec(),
+            "k={k}, w={w}, len={len}"
+        );
+        assert_eq!(bitvec.count_ones(), positions.len());
+
+        let mut positions = vec![];
+        super::minimizer_positions(slice, k, w, &mut positions);
+        let mut bitvec = BitVec::new();
+        super::minimizer_positions_bitvec(slice, k, w, &mut bitvec);
+        assert!(positions.iter().all(|&p| bitvec.get(p as usize)));
+        assert_eq!(bitvec.count_ones(), positions.len());
+
+        if (k + w - 1) % 2 == 1 {
+            let mut positions = vec![];
+            super::canonical_minimizer_positions(packed_seq, k, w, &mut positions);
+            positions.sort_unstable();
+            positions.dedup();
+            let mut bitvec = BitVec::with_len(len + 100);
+            super::canonical_minimizer_positions_bitvec(packed_seq, k, w, &mut bitvec);
+            assert_eq!(bitvec.len(), len + 100);
+            assert_eq!(
+                bitvec.iter_ones().collect_vec(),
+                positions.iter().map(|&p| p as usize).collect_vec(),
+                "k={k}, w={w}, len={len}"
+            );
+        }
+    });
+
+    // Shrinking clears the bits past the end.
+    let mut bitvec = BitVec::with_len(100);
+    bitvec.set(70);
+    bitvec.set(99);
+    bitvec.resize(80);
+    bitvec.resize(100);
+    assert_eq!(bitvec.iter_ones().collect_vec(), [70]);
+    assert_eq!(bitvec.as_words(), [0, 1 << 6]);
+}
+
+#[test]
+fn minimizer_mismatches() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = ascii_seq.len();
+        // A few substitutions, as between two haplotypes.
+        let mut ascii_b = ascii_seq.0.to_vec();
+        for i in (7..len).step_by(300) {
+            ascii_b[i] = if ascii_b[i] == b'A' { b'C' } else { b'A' };
+        }
+        let packed_b = PackedSeqVec::from_ascii(&ascii_b);
+        let packed_b = packed_b.as_slice();
+        let ascii_b = AsciiSeq(&ascii_b);
+
+        let mut simd = vec![];
+        super::minimizer_mismatches(packed_seq, packed_b, k, w, &mut simd);
+        let mut scalar = vec![];
+        super::scalar::minimizer_mismatches_scalar(packed_seq, packed_b, k, w, &mut scalar);
+        assert_eq!(simd, scalar, "k={k}, w={w}, len={len}");
+
+        // Naive check of the definition.
+        let pos_a = minimizers_seq_scalar::<NtHasher>(packed_seq, k, w).collect_vec();
+        let pos_b = minimizers_seq_scalar::<NtHasher>(packed_b, k, w).collect_vec();
+        let hashes_a = nthash_seq_scalar::<false, NtHasher>(packed_seq, k).collect_vec();
+        let hashes_b = nthash_seq_scalar::<false, NtHasher>(packed_b, k).collect_vec();
+        let naive = (0..pos_a.len())
+            .filter(|&i| {
+                let (pa, pb) = (pos_a[i] as usize, pos_b[i] as usize);
+                pa != pb || hashes_a[pa] != hashes_b[pb]
+            })
+            .map(|i| MinimizerMismatch {
+                window: i as u32,
+                pos_a: pos_a[i],
+                pos_b: pos_b[i],
+            })
+            .collect_vec();
+        assert_eq!(simd, naive, "k={k}, w={w}, len={len}");
+        // Windows without a changed base never differ.
+        let changed = |i: usize| (i..i + k + w - 1).any(|j| j >= 7 && (j - 7) % 300 == 0);
+        assert!(
+            simd.iter().all(|m| changed(m.window as usize)),
+            "k={k}, w={w}, len={len}"
+        );
+
+        let mut simd = vec![];
+        super::minimizer_mismatches(ascii_seq, ascii_b, k, w, &mut simd);
+        let mut scalar = vec![];
+        super::scalar::minimizer_mismatches_scalar(ascii_seq, ascii_b, k, w, &mut scalar);
+        assert_eq!(simd, scalar, "k={k}, w={w}, len={len}");
+
+        // Equal sequences have no mismatches.
+        let mut simd = vec![];
+        super::minimizer_mismatches(packed_seq, packed_seq, k, w, &mut simd);
+        assert!(simd.is_empty());
+    });
+}
+
+#[test]
+fn minimizer_spans() {
+    use CoordinateConvention::*;
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        let mut positions = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut positions);
+        for convention in [ZeroBasedHalfOpen, ZeroBasedClosed, OneBasedClosed] {
+            let (mut starts, mut ends) = (vec![], vec![]);
+            super::minimizer_spans(packed_seq, k, w, convention, &mut starts, &mut ends);
+            let (mut scalar_starts, mut scalar_ends) = (vec![], vec![]);
+            super::scalar::minimizer_spans_scalar(
+                packed_seq,
+                k,
+                w,
+                convention,
+                &mut scalar_starts,
+                &mut scalar_ends,
+            );
+            assert_eq!(starts, scalar_starts, "k={k}, w={w}, len={len}");
+            assert_eq!(ends, scalar_ends, "k={k}, w={w}, len={len}");
+            for ((&p, &s), &e) in zip(zip(&positions, &starts), &ends) {
+                let (s, e) = (s as usize, e as usize);
+                let p = p as usize;
+                match convention {
+                    ZeroBasedHalfOpen => assert_eq!((s, e), (p, p + k)),
+                    ZeroBasedClosed => assert_eq!((s, e), (p, p + k - 1)),
+                    OneBasedClosed => assert_eq!((s, e), (p + 1, p + k)),
+                }
+            }
+
+            let (mut starts, mut ends) = (vec![], vec![]);
+            super::minimizer_spans(ascii_seq, k, w, convention, &mut starts, &mut ends);
+            let (mut scalar_starts, mut scalar_ends) = (vec![], vec![]);
+            super::scalar::minimizer_spans_scalar(
+                ascii_seq,
+                k,
+                w,
+                convention,
+                &mut scalar_starts,
+                &mut scalar_ends,
+            );
+            assert_eq!(starts, scalar_starts, "k={k}, w={w}, len={len}");
+            assert_eq!(ends, scalar_ends, "k={k}, w={w}, len={len}");
+
+            if (k + w - 1) % 2 == 1 {
+                let (mut starts, mut ends) = (vec![], vec![]);
+                super::canonical_minimizer_spans(
+                    packed_seq,
+                    k,
+                    w,
+                    convention,
+                    &mut starts,
+                    &mut ends,
+                );
+                let (mut scalar_starts, mut scalar_ends) = (vec![], vec![]);
+                super::scalar::canonical_minimizer_spans_scalar(
+                    packed_seq,
+                    k,
+                    w,
+                    convention,
+                    &mut scalar_starts,
+                    &mut scalar_ends,
+                );
+                assert_eq!(starts, scalar_starts, "k={k}, w={w}, len={len}");
+                assert_eq!(ends, scalar_ends, "k={k}, w={w}, len={len}");
+            }
+
+            // The builder applies the same convention.
+            for hash_width in [HashWidth::Bits32, HashWidth::Bits64] {
+                let config = MinimizerBuilder::new(k, w)
+                    .hash_width(hash_width)
+                    .coordinates(convention)
+                    .build()
+                    .unwrap();
+                let (mut starts, mut ends) = (vec![], vec![]);
+                config.spans(packed_seq, &mut starts, &mut ends);
+                let mut config_starts = vec![];
+                config.positions(packed_seq, &mut config_starts);
+                assert_eq!(starts, config_starts, "k={k}, w={w}, len={len}");
+                let mut scalar_starts = vec![];
+                config.positions_scalar(packed_seq, &mut scalar_starts);
+                assert_eq!(starts, scalar_starts, "k={k}, w={w}, len={len}");
+                assert!(zip(&starts, &ends)
+                    .all(|(&s, &e)| e - s == convention.end_offset(k) - convention.start_offset()));
+            }
+        }
+    });
+}
+
+#[test]
+fn canonical_minimizers_even_l() {
+    // For even l, windows with as many GT as AC characters prefer the forward strand.
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        if l % 2 == 1 {
+            return;
+        }
+        let len = ascii_seq.len();
+        let hashes = nthash_seq_scalar::<true, NtHasher>(packed_seq, k).collect_vec();
+        let naive = (0..(len + 1).saturating_sub(l))
+            .map(|i| {
+                let gt = ascii_seq.0[i..i + l]
+                    .iter()
+                    .filter(|&&c| matches!(c, b'G' | b'T' | b'g' | b't'))
+                    .count();
+                let min = (i..i + w).map(|j| hashes[j] >> 16).min().unwrap();
+                let mut argmins = (i..i + w).filter(|&j| hashes[j] >> 16 == min);
+                let pos = if 2 * gt >= l {
+                    argmins.next()
+                } else {
+                    argmins.last()
+                };
+                pos.unwrap() as u32
+            })
+            .collect_vec();
+        let scalar = canonical_minimizers_seq_scalar::<NtHasher>(packed_seq, k, w).collect_vec();
+        assert_eq!(scalar, naive, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn fracminhash() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if w > 1 {
+            return;
+        }
+        let len = packed_seq.len();
+        for fraction in [1.0, 0.5, 0.1, 0.001] {
+            for canonical in [false, true] {
+                let (mut simd, mut scalar) = (vec![], vec![]);
+                let (mut simd64, mut scalar64) = (vec![], vec![]);
+                if canonical {
+                    super::canonical_fracminhash(packed_seq, k, fraction, &mut simd);
+                    scalar::canonical_fracminhash_scalar(packed_seq, k, fraction, &mut scalar);
+                    super::canonical_fracminhash64(packed_seq, k, fraction, &mut simd64);
+                    scalar::canonical_fracminhash64_scalar(packed_seq, k, fraction, &mut scalar64);
+                } else {
+                    super::fracminhash(packed_seq, k, fraction, &mut simd);
+                    scalar::fracminhash_scalar(packed_seq, k, fraction, &mut scalar);
+                    super::fracminhash64(packed_seq, k, fraction, &mut simd64);
+                    scalar::fracminhash64_scalar(packed_seq, k, fraction, &mut scalar64);
+                }
+                let msg = format!("k={k}, len={len}, fraction={fraction}, canonical={canonical}");
+                assert_eq!(simd, scalar, "{msg}");
+                assert_eq!(simd64, scalar64, "{msg}");
+                if fraction == 1.0 {
+                    assert_eq!(simd.len(), (len + 1).saturating_sub(k), "{msg}");
+                    assert_eq!(simd64.len(), (len + 1).saturating_sub(k), "{msg}");
+                }
+            }
+
+            let (mut simd, mut scalar) = (vec![], vec![]);
+            super::fracminhash(ascii_seq, k, fraction, &mut simd);
+            scalar::fracminhash_scalar(ascii_seq, k, fraction, &mut scalar);
+            assert_eq!(simd, scalar, "k={k}, len={len}, fraction={fraction}");
+        }
+    });
+
+    // Roughly the given fraction of k-mers is kept.
+    let seq = PackedSeqVec::random(1 << 20);
+    let mut hashes = vec![];
+    super::fracminhash(seq.as_slice(), 21, 0.01, &mut hashes);
+    assert!((9_000..11_000).contains(&hashes.len()), "{}", hashes.len());
+}
+
+#[test]
+fn backend_cache() {
+    use backend::{build_id, Backend, BackendCache, Function};
+    use packed_seq::{AsciiSeq, PackedSeq};
+
+    let seq = PackedSeqVec::random(10_000);
+    let seq = seq.as_slice();
+    let (mut simd, mut scalar) = (vec![], vec![]);
+    Backend::Simd.minimizer_positions(seq, 21, 11, &mut simd);
+    Backend::Scalar.minimizer_positions(seq, 21, 11, &mut scalar);
+    assert_eq!(simd, scalar);
+    let mut auto = vec![];
+    backend::minimizer_positions_auto(seq, 21, 11, &mut auto);
+    assert_eq!(simd, auto);
+    let cache = backend::global_cache();
+    assert!(cache
+        .get::<PackedSeq>(Function::Forward, 21, 11, 10_000)
+        .is_some());
+    // Functions and sequence types are cached separately.
+    assert_eq!(
+        cache.get::<PackedSeq>(Function::Canonical, 21, 11, 10_000),
+        None
+    );
+    assert_eq!(
+        cache.get::<AsciiSeq>(Function::Forward, 21, 11, 10_000),
+        None
+    );
+
+    let (mut simd, mut auto) = (vec![], vec![]);
+    super::canonical_minimizer_positions(seq, 21, 11, &mut simd);
+    backend::canonical_minimizer_positions_auto(seq, 21, 11, &mut auto);
+    assert_eq!(simd, auto);
+    assert!(backend::global_cache()
+        .get::<PackedSeq>(Function::Canonical, 21, 11, 10_000)
+        .is_some());
+
+    // Lengths are rounded up to a power of two.
+    let mut cache = BackendCache::new();
+    let backend = cache.select::<PackedSeq>(Function::Forward, 5, 5, 100);
+    assert_eq!(
+        cache.get::<PackedSeq>(Function::Forward, 5, 5, 128),
+        Some(backend)
+    );
+    assert_eq!(cache.get::<PackedSeq>(Function::Forward, 5, 5, 129), None);
+    cache.select::<AsciiSeq>(Function::Canonical, 5, 5, 100);
+
+    let mut file = vec![];
+    cache.write_to(&mut file).unwrap();
+    assert_eq!(BackendCache::read_from(&file[..]).unwrap(), cache);
+
+    // Caches of other builds are ignored.
+    let other = String::from_utf8(file)
+        .unwrap()
+        .replace(&build_id(), "other build");
+    let read = BackendCache::read_from(other.as_bytes()).unwrap();
+    assert_eq!(read, BackendCache::new());
+
+    let header = format!("simd-minimizers backends {}\n", build_id());
+    for bad in [
+        "",
+        "forward seq 5 5 128 simd\n",
+        &(header.clone() + "forward seq 5 5 128 gpu\n"),
+        &(header.clone() + "reverse seq 5 5 128 simd\n"),
+        &(header + "5 5 128 simd\n"),
+    ] {
+        let err = BackendCache::read_from(bad.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn extract_kmers() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        if k > 32 {
+            return;
+        }
+        let len = packed_seq.len();
+        let mut positions = vec![];
+        super::minimizer_positions(packed_seq, k, w, &mut positions);
+        let mut kmers = vec![];
+        let mut kmer_vec = vec![];
+        super::minimizer_positions_and_kmers(packed_seq, k, w, &mut vec![], &mut kmer_vec);
+
+        super::extract_kmers_simd(packed_seq, &positions, k, &mut kmers);
+        assert_eq!(kmers, kmer_vec, "k={k}, w={w}, len={len}");
+        let mut scalar = vec![];
+        scalar::extract_kmers_scalar(packed_seq, &positions, k, &mut scalar);
+        assert_eq!(kmers, scalar, "k={k}, w={w}, len={len}");
+        let mut scalar = vec![];
+        scalar::extract_kmers_scalar(ascii_seq, &positions, k, &mut scalar);
+        assert_eq!(kmers, scalar, "k={k}, w={w}, len={len}");
+
+        // All k-mers, including the last ones of the sequence, in any order.
+        let all = (0..(len + 1).saturating_sub(k) as u32).rev().collect_vec();
+        let mut kmers = vec![];
+        super::extract_kmers_simd(packed_seq, &all, k, &mut kmers);
+        let mut scalar = vec![];
+        scalar::extract_kmers_scalar(packed_seq, &all, k, &mut scalar);
+        assert_eq!(kmers, scalar, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn signers() {
+    use signer::{BinSigner, BottomSigner, Signer, EMPTY};
+
+    let a = PackedSeqVec::random(20_000);
+    let a = a.as_slice();
+    // The second half of `a`, and an unrelated sequence.
+    let b = a.slice(10_000..20_000);
+    let c = PackedSeqVec::random(10_000);
+    let c = c.as_slice();
+    let k = 21;
+
+    fn check(signer: &impl Signer, a: PackedSeq, b: PackedSeq, c: PackedSeq) {
+        let (sa, sb, sc) = (signer.sign(a), signer.sign(b), signer.sign(c));
+        assert_eq!(sa.len(), signer.signature_len());
+        assert_eq!(signer.similarity(&sa, &sa), 1.0);
+        assert_eq!(signer.distance(&sa, &sa), 0.0);
+        // The true Jaccard similarity of `a` and `b` is about 1/2.
+        let ab = signer.similarity(&sa, &sb);
+        assert!((0.35..0.65).contains(&ab), "{ab}");
+        assert_eq!(ab, signer.similarity(&sb, &sa));
+        assert!(signer.similarity(&sa, &sc) < 0.05);
+    }
+    check(&BottomSigner::new(k, 1, 512), a, b, c);
+    check(&BinSigner::new(k, 512), a, b, c);
+
+    // Bottom signatures are the smallest canonical k-mer hashes.
+    let mut hashes = vec![];
+    super::canonical_fracminhash(a, k, 1., &mut hashes);
+    hashes.sort_unstable();
+    hashes.dedup();
+    assert_eq!(BottomSigner::new(k, 1, 512).sign(a), hashes[..512]);
+
+    // Short sequences have empty entries.
+    let short = a.slice(0..100);
+    let sig = BottomSigner::new(k, 1, 512).sign(short);
+    assert_eq!(sig.iter().filter(|&&x| x != EMPTY).count(), 80);
+    let sig = BinSigner::new(k, 512).sign(short);
+    assert!(sig.iter().filter(|&&x| x != EMPTY).count() <= 80);
+    let empty = BinSigner::new(k, 512).sign(a.slice(0..10));
+    assert!(empty.iter().all(|&x| x == EMPTY));
+    assert_eq!(BinSigner::new(k, 512).similarity(&empty, &empty), 0.0);
+}
+
+#[test]
+fn dedup_scope() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = packed_seq.len();
+        for canonical in [false, true] {
+            let adjacent = MinimizerBuilder::new(k, w)
+                .canonical(canonical)
+                .build()
+                .unwrap();
+            assert_eq!(adjacent.dedup(), DedupScope::Adjacent);
+            let global = MinimizerBuilder::new(k, w)
+                .canonical(canonical)
+                .dedup(DedupScope::Global)
+                .coordinates(CoordinateConvention::OneBasedClosed)
+                .build()
+                .unwrap();
+
+            let mut expected = vec![];
+            adjacent.positions(packed_seq, &mut expected);
+            expected.sort_unstable();
+            expected.dedup();
+            expected.iter_mut().for_each(|p| *p += 1);
+
+            // Values already in the output are left untouched.
+            let mut simd = vec![u32::MAX];
+            global.positions(packed_seq, &mut simd);
+            assert_eq!(simd[0], u32::MAX);
+            assert_eq!(expected, simd[1..], "k={k}, w={w}, len={len}");
+            let mut scalar = vec![];
+            global.positions_scalar(ascii_seq, &mut scalar);
+            assert_eq!(expected, scalar, "k={k}, w={w}, len={len}");
+
+            let (mut starts, mut ends) = (vec![], vec![]);
+            global.spans(packed_seq, &mut starts, &mut ends);
+            assert_eq!(expected, starts, "k={k}, w={w}, len={len}");
+            assert!(zip(&starts, &ends).all(|(&s, &e)| e + 1 - s == k as u32));
+        }
+    });
+}
+
+#[test]
+fn sliding_min_all() {
+    use sliding_min::{sliding_min_all_scalar, sliding_min_all_simd};
+    let mut rng = rand::rng();
+    for len in (0..100).chain([1000, 10000]) {
+        // Few distinct upper 16 bits, so that there are many ties.
+        let lanes: [Vec<u32>; 8] = std::array::from_fn(|_| {
+            (0..len)
+                .map(|_| (rng.random_range(0..4) << 16) | rng.random_range(0..1 << 16))
+                .collect()
+        });
+        for w in [1, 2, 3, 4, 5, 8, 16, 31, 32] {
+            let naive = |values: &[u32]| {
+                values
+                    .windows(w)
+                    .map(|window| {
+                        let min = window.iter().map(|v| v >> 16).min().unwrap();
+                        (0..w)
+                            .filter(|&i| window[i] >> 16 == min)
+                            .map(|i| 1u32 << i)
+                            .sum::<u32>()
+                    })
+                    .collect_vec()
+            };
+            let simd = sliding_min_all_simd(
+                (0..len).map(|i| S::new(std::array::from_fn(|l| lanes[l][i]))),
+                w,
+            )
+            .map(|m| *m.as_array_ref())
+            .collect_vec();
+            for (l, values) in lanes.iter().enumerate() {
+                let expected = naive(values);
+                let scalar = sliding_min_all_scalar(values.iter().copied(), w).collect_vec();
+                assert_eq!(expected, scalar, "w={w}, len={len}");
+                let simd = simd.iter().map(|m| m[l]).collect_vec();
+                assert_eq!(expected, simd, "w={w}, len={len}, lane={l}");
+            }
+        }
+    }
+}
+
+#[test]
+fn sliding_min_extreme_params() {
+    use sliding_min::{
+        sliding_lr_min_mapper, sliding_min_checked_scalar, sliding_min_mapper, sliding_min_scalar,
+        MAX_W,
+    };
+    let mut rng = rand::rng();
+    // Long enough for several resets of the 16-bit positions.
+    let len = 3 << 16;
+    // Few distinct upper 16 bits, so that ties are broken in every window.
+    let values = (0..len)
+        .map(|_| (rng.random_range(0..4) << 16) | rng.random_range(0..1 << 16))
+        .collect_vec();
+
+    // The checked version agrees with a naive minimum for small windows.
+    for w in [1, 2, 5, 31] {
+        let key = |i: usize| values[i] >> 16;
+        let left = (0..=len - w)
+            .map(|i| (i..i + w).min_by_key(|&j| key(j)).unwrap() as u32)
+            .collect_vec();
+        let right = (0..=len - w)
+            .map(|i| (i..i + w).rev().min_by_key(|&j| key(j)).unwrap() as u32)
+            .collect_vec();
+        let checked = sliding_min_checked_scalar::<true>(values.iter().copied(), w).collect_vec();
+        assert_eq!(left, checked, "w={w}");
+        let checked = sliding_min_checked_scalar::<false>(values.iter().copied(), w).collect_vec();
+        assert_eq!(right, checked, "w={w}");
+    }
+
+    // The largest lanes, so that the last lane has positions close to 2^32.
+    let lane_len = (1 << 29) - 1;
+    for w in [1, 2, 1000, MAX_W / 2, MAX_W - 1, MAX_W] {
+        let left = sliding_min_checked_scalar::<true>(values.iter().copied(), w).collect_vec();
+        let right = sliding_min_checked_scalar::<false>(values.iter().copied(), w).collect_vec();
+        let scalar = sliding_min_scalar::<true>(values.iter().copied(), w).collect_vec();
+        assert_eq!(left, scalar, "w={w}");
+        let scalar = sliding_min_scalar::<false>(values.iter().copied(), w).collect_vec();
+        assert_eq!(right, scalar, "w={w}");
+
+        let mut simd = sliding_min_mapper::<true>(w, 1, lane_len);
+        let mut simd_lr = sliding_lr_min_mapper(w, 1, lane_len);
+        let lane_offset = |lane: usize| (lane * (lane_len - (w - 1))) as u32;
+        for (i, &val) in values.iter().enumerate() {
+            let pos = *simd(S::splat(val)).as_array_ref();
+            let (l, r) = simd_lr(S::splat(val));
+            if i + 1 < w {
+                continue;
+            }
+            let (l, r) = (l.as_array_ref(), r.as_array_ref());
+            for lane in 0..8 {
+                let offset = lane_offset(lane);
+                assert_eq!(offset + left[i + 1 - w], pos[lane], "w={w}, i={i}");
+                assert_eq!(offset + left[i + 1 - w], l[lane], "w={w}, i={i}");
+                assert_eq!(offset + right[i + 1 - w], r[lane], "w={w}, i={i}");
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn sliding_min_too_large_w() {
+    sliding_min::sliding_min_scalar::<true>(0..100_000u32, sliding_min::MAX_W + 1).for_each(drop);
+}
+
+#[test]
+fn find_repeats() {
+    use repeats::{find_repeats, RepeatPair};
+    let mut ascii = AsciiSeqVec::random(20_000).seq;
+    let segment = ascii[2000..6000].to_vec();
+    let rc = segment
+        .iter()
+        .rev()
+        .map(|&c| packed_seq::complement_char(c))
+        .collect_vec();
+    for (copy, offset_ok) in [
+        (&segment, (|a, b| b - a == 10_000) as fn(u32, u32) -> bool),
+        (&rc, |a, b| a + b == 17_000),
+    ] {
+        ascii[12_000..16_000].copy_from_slice(copy);
+        let packed = PackedSeqVec::from_ascii(&ascii);
+        for (k, w) in [(21, 11), (15, 5)] {
+            let mut repeats = vec![];
+            find_repeats(packed.as_slice(), k, w, 1000, 0.5, &mut repeats);
+            // The fully duplicated intervals of 1000 bases starting at 2000, 2500, ..., 5000.
+            assert_eq!(repeats.len(), 7, "k={k}, w={w}: {repeats:?}");
+            for RepeatPair {
+                a, b, similarity, ..
+            } in &repeats
+            {
+                assert!((2000..=5000).contains(&a.start), "k={k}, w={w}: {a:?}");
+                assert!(offset_ok(a.start, b.start), "k={k}, w={w}: {a:?} {b:?}");
+                assert!(*similarity > 0.8, "k={k}, w={w}: {similarity}");
+            }
+            assert!(repeats.is_sorted_by_key(|r| (r.a.start, r.b.start)));
+        }
+    }
+
+    let mut repeats = vec![];
+    find_repeats(
+        PackedSeqVec::random(20_000).as_slice(),
+        21,
+        11,
+        1000,
+        0.2,
+        &mut repeats,
+    );
+    assert_eq!(repeats, vec![]);
+}
+
+#[test]
+fn collect_into_slice() {
+    let mut rng = rand::rng();
+    for len in 0..50 {
+        for padding in [0, 1, len / 2, len, len + 1, 3 * len, 8 * len] {
+            if padding > 8 * len {
+                continue;
+            }
+            let mut vals = (0..8 * len).map(|_| rng.random_range(0..4)).collect_vec();
+            for lane in vals.chunks_mut(len.max(1)) {
+                lane.sort();
+            }
+            let head = || (0..len).map(|i| S::new(std::array::from_fn(|l| vals[l * len + i])));
+
+            let expected = &vals[..8 * len - padding];
+            let mut out = vec![u32::MAX; 8 * len + 1];
+            let n = collect::collect_into_slice((head(), padding), &mut out);
+            assert_eq!(n, Ok(expected.len()), "len={len}, padding={padding}");
+            assert_eq!(
+                expected,
+                &out[..expected.len()],
+                "len={len}, padding={padding}"
+            );
+            assert!(out[expected.len()..].iter().all(|&x| x == u32::MAX));
+            if !expected.is_empty() {
+                let short = &mut out[..expected.len() - 1];
+                let result = collect::collect_into_slice((head(), padding), short);
+                assert_eq!(
+                    result,
+                    Err(NeedCapacity {
+                        needed: expected.len()
+                    })
+                );
+            }
+
+            let mut expected = expected.to_vec();
+            expected.dedup();
+            let n = collect::collect_and_dedup_into_slice((head(), padding), &mut out);
+            assert_eq!(n, Ok(expected.len()), "len={len}, padding={padding}");
+            assert_eq!(
+                expected,
+                out[..expected.len()],
+                "len={len}, padding={padding}"
+            );
+            if !expected.is_empty() {
+                let short = &mut out[..expected.len() - 1];
+                let result = collect::collect_and_dedup_into_slice((head(), padding), short);
+                assert_eq!(
+                    result,
+                    Err(NeedCapacity {
+                        needed: expected.len()
+                    })
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "capi")]
+#[test]
+fn capi() {
+    use capi::*;
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let len = ascii_seq.len();
+        let ascii = ascii_seq.0;
+        for canonical in [false, true] {
+            let mut expected = vec![];
+            let f = if canonical {
+                super::canonical_minimizer_positions(packed_seq, k, w, &mut expected);
+                simd_minimizers_canonical_positions
+            } else {
+                super::minimizer_positions(packed_seq, k, w, &mut expected);
+                simd_minimizers_positions
+            };
+            let needed = unsafe { f(ascii.as_ptr(), len, k, w, std::ptr::null_mut(), 0) };
+            assert_eq!(needed, expected.len(), "k={k}, w={w}, len={len}");
+            let mut out = vec![u32::MAX; needed + 1];
+            let n = unsafe { f(ascii.as_ptr(), len, k, w, out.as_mut_ptr(), out.len()) };
+            assert_eq!(n, needed, "k={k}, w={w}, len={len}");
+            assert_eq!(expected, out[..n], "k={k}, w={w}, len={len}");
+            assert_eq!(out[n], u32::MAX);
+        }
+    });
+    let mut out = [0; 4];
+    let seq = b"ACGTACGT";
+    unsafe {
+        let f = simd_minimizers_positions;
+        assert_eq!(
+            f(seq.as_ptr(), 8, 0, 2, out.as_mut_ptr(), 4),
+            SIMD_MINIMIZERS_ERROR
+        );
+        assert_eq!(
+            f(seq.as_ptr(), 8, 3, 0, out.as_mut_ptr(), 4),
+            SIMD_MINIMIZERS_ERROR
+        );
+        assert_eq!(
+            f(std::ptr::null(), 8, 3, 2, out.as_mut_ptr(), 4),
+            SIMD_MINIMIZERS_ERROR
+        );
+        assert_eq!(
+            f(seq.as_ptr(), 8, 3, 2, std::ptr::null_mut(), 4),
+            SIMD_MINIMIZERS_ERROR
+        );
+        assert_eq!(f(std::ptr::null(), 0, 3, 2, std::ptr::null_mut(), 0), 0);
+    }
+}
+
+#[test]
+fn minimizer_positions_records_iter() {
+    let mut rng = rand::rng();
+    let mut lens = (0..300).map(|_| rng.random_range(0..500)).collect_vec();
+    // Long records, and enough bases for multiple batches.
+    lens.extend([200_000, 1 << 20, 7, 300_000]);
+    let seqs = lens
+        .iter()
+        .map(|&len| PackedSeqVec::random(len))
+        .collect_vec();
+    for (k, w) in [(1, 1), (5, 11), (21, 11), (31, 31)] {
+        let records = seqs.iter().cloned().enumerate();
+        let mut pulled = 0;
+        let mut it =
+            super::minimizer_positions_records_iter(records.inspect(|_| pulled += 1), k, w);
+        // Only the first batch is pulled.
+        let (id, positions) = it.next().unwrap();
+        assert_eq!(id, 0);
+        let mut expected = vec![];
+        super::minimizer_positions(seqs[0].as_slice(), k, w, &mut expected);
+        assert_eq!(expected, positions, "k={k}, w={w}");
+        drop(it);
+        assert!(pulled < seqs.len(), "k={k}, w={w}");
+
+        let records = seqs.iter().cloned().enumerate();
+        let all = super::minimizer_positions_records_iter(records.clone(), k, w).collect_vec();
+        let canonical =
+            super::canonical_minimizer_positions_records_iter(records, k, w).collect_vec();
+        assert_eq!(all.len(), seqs.len());
+        assert_eq!(canonical.len(), seqs.len());
+        for (i, seq) in seqs.iter().enumerate() {
+            let mut expected = vec![];
+            super::minimizer_positions(seq.as_slice(), k, w, &mut expected);
+            assert_eq!((i, expected), all[i], "k={k}, w={w}, len={}", seq.len());
+            let mut expected = vec![];
+            super::canonical_minimizer_positions(seq.as_slice(), k, w, &mut expected);
+            assert_eq!(
+                (i, expected),
+                canonical[i],
+                "k={k}, w={w}, len={}",
+                seq.len()
+            );
+        }
+    }
+}
+
+#[test]
+fn minimizer_positions_bytes() {
+    const AMINO_ACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+    let mut rng = rand::rng();
+    let protein = (0..4096)
+        .map(|_| AMINO_ACIDS[rng.random_range(0..AMINO_ACIDS.len())])
+        .collect_vec();
+    for k in [1, 2, 5, 10, 31, 33] {
+        for w in [1, 2, 5, 11, 32] {
+            for len in (0..50).chain([1000, 4096]) {
+                let seq = &protein[..len];
+                let mut naive = seq
+                    .windows(w + k - 1)
+                    .enumerate()
+                    .map(|(pos, window)| (pos + minimizer::<ByteHasher>(window, k)) as u32)
+                    .collect_vec();
+                naive.dedup();
+                let mut scalar = vec![];
+                scalar::minimizer_positions_bytes_scalar(seq, k, w, &mut scalar);
+                let mut simd = vec![];
+                super::minimizer_positions_bytes(seq, k, w, &mut simd);
+                assert_eq!(naive, scalar, "k={k}, w={w}, len={len}");
+                assert_eq!(naive, simd, "k={k}, w={w}, len={len}");
+            }
+        }
+    }
+}
+
+#[test]
+fn decycling_sets() {
+    use schemes::Decycling;
+    use std::f64::consts::PI;
+    for k in 3..=8 {
+        for kmer in (0..1usize << (2 * k))
+            .map(|x| (0..k).map(|i| b"ACGT"[(x >> (2 * i)) & 3]).collect_vec())
+        {
+            // Cycles with an embedding close to 0 are not hit.
+            let (re, im) = kmer
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (j, &c)| {
+                    let angle = 2.0 * PI * j as f64 / k as f64;
+                    (re + c as f64 * angle.cos(), im + c as f64 * angle.sin())
+                });
+            if re * re + im * im < 1.0 {
+                continue;
+            }
+            let rotations = (0..k)
+                .map(|r| {
+                    let mut rot = kmer.clone();
+                    rot.rotate_left(r);
+                    rot
+                })
+                .collect_vec();
+            let ranks =
+                |order: Decycling| rotations.iter().map(|rot| order.rank(rot)).collect_vec();
+            let kmer = String::from_utf8_lossy(&kmer);
+            assert!(
+                ranks(Decycling::single()).contains(&0),
+                "k={k}, kmer={kmer}"
+            );
+            let double = ranks(Decycling::double());
+            assert!(double.contains(&0), "k={k}, kmer={kmer}");
+            assert!(double.contains(&1), "k={k}, kmer={kmer}");
+        }
+    }
+}
+
+#[test]
+fn random_order_params() {
+    use schemes::{Params, SamplingScheme};
+    let (k, w) = (11, 12);
+    let text = (0..100_000)
+        .map(|_| b"ACGT"[random_range(0..4)])
+        .collect_vec();
+    let streams = (0..4)
+        .map(|seed| {
+            let params = Params::RandomOrder { k, w, seed };
+            let mut stream = params.build().stream(&text);
+            stream.dedup();
+            // Random orders have density close to 2/(w+1).
+            let density = stream.len() as f64 / (text.len() - k + 1) as f64;
+            assert!(
+                (density * (w + 1) as f64 / 2.0 - 1.0).abs() < 0.05,
+                "seed={seed}, density={density}"
+            );
+            stream
+        })
+        .collect_vec();
+    // Different seeds give different orders.
+    assert!(streams.iter().all_unique());
+
+    for params in [
+        Params::Minimizer { k, w },
+        Params::RandomOrder {
+            k,
+            w,
+            seed: u64::MAX,
+        },
+        Params::Lex { k, w },
+        Params::AntiLex { k, w },
+        Params::Decycling { k, w },
+        Params::DoubleDecycling { k, w },
+        Params::Canonical { k, w },
+    ] {
+        let json = params.to_json();
+        assert_eq!(Params::from_json(&json), Some(params), "{json}");
+        assert!(Params::NAMES.contains(&params.name()));
+        assert_eq!(
+            Params::from_name(params.name(), k, w, Some(u64::MAX)),
+            Some(params)
+        );
+        assert_eq!(params.build().k(), k);
+        assert_eq!(params.build().w(), w);
+    }
+    assert_eq!(
+        Params::from_json(r#" { "w": 5, "seed": 7, "k": 3, "scheme": "random-order" } "#),
+        Some(Params::RandomOrder {
+            k: 3,
+            w: 5,
+            seed: 7
+        })
+    );
+    assert_eq!(
+        Params::from_json(r#"{"scheme":"random-order","k":3,"w":5}"#),
+        None
+    );
+    assert_eq!(
+        Params::from_json(r#"{"scheme":"unknown","k":3,"w":5}"#),
+        None
+    );
+}
+
+#[test]
+fn canonical_windows_bitvec() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let expected = canonical::canonical_windows_seq_scalar(packed_seq, k, w).collect_vec();
+        let mut bitvec = BitVec::with_len(3);
+        bitvec.set(1);
+        super::canonical_windows_bitvec(packed_seq, k, w, &mut bitvec);
+        let len = packed_seq.len();
+        assert_eq!(bitvec.len(), expected.len(), "k={k}, w={w}, len={len}");
+        let bits = (0..bitvec.len()).map(|i| bitvec.get(i)).collect_vec();
+        assert_eq!(bits, expected, "k={k}, w={w}, len={len}");
+        super::canonical_windows_bitvec(ascii_seq, k, w, &mut bitvec);
+        let bits = (0..bitvec.len()).map(|i| bitvec.get(i)).collect_vec();
+        assert_eq!(bits, expected, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn scheme_pipeline_conversion() {
+    use schemes::{
+        positions_to_stream, stream_to_positions, CanonicalMinimizer, Minimizer, SamplingScheme,
+    };
+    test_on_inputs(|k, w, slice, _ascii_seq, _packed_seq| {
+        let len = slice.len();
+        let scheme = Minimizer::new(k, w);
+        let stream = scheme.stream(slice);
+
+        let (mut min_pos, mut sk_pos) = (vec![], vec![]);
+        super::minimizer_and_superkmer_positions(slice, k, w, &mut min_pos, &mut sk_pos);
+        let (mut scheme_min_pos, mut scheme_sk_pos) = (vec![], vec![]);
+        stream_to_positions(&stream, &mut scheme_min_pos, &mut scheme_sk_pos);
+        assert_eq!(scheme_min_pos, min_pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scheme_sk_pos, sk_pos, "k={k}, w={w}, len={len}");
+        assert_eq!(scheme.positions(slice), min_pos, "k={k}, w={w}, len={len}");
+
+        let expanded = positions_to_stream(&min_pos, &sk_pos, stream.len());
+        assert_eq!(expanded, stream, "k={k}, w={w}, len={len}");
+
+        let canonical = CanonicalMinimizer::new(k, w);
+        let (mut can_min_pos, mut can_sk_pos) = (vec![], vec![]);
+        stream_to_positions(&canonical.stream(slice), &mut can_min_pos, &mut can_sk_pos);
+        assert_eq!(
+            canonical.positions(slice),
+            can_min_pos,
+            "k={k}, w={w}, len={len}"
+        );
+    });
+}
+
+#[test]
+fn cache_blocked() {
+    let packed = PackedSeqVec::random(5 * CACHE_BLOCK_BYTES);
+    let ascii = AsciiSeqVec::random(3 * CACHE_BLOCK_BYTES / 2);
+    for (k, w) in [(1, 1), (5, 11), (21, 11), (31, 31)] {
+        for len in [1000, packed.len()] {
+            let seq = packed.slice(0..len);
+            let mut expected = vec![];
+            super::minimizer_positions(seq, k, w, &mut expected);
+            let mut positions = vec![];
+            super::minimizer_positions_cache_blocked(seq, k, w, &mut positions);
+            assert_eq!(positions, expected, "k={k}, w={w}, len={len}");
+
+            let mut expected = vec![];
+            super::canonical_minimizer_positions(seq, k, w, &mut expected);
+            let mut positions = vec![];
+            super::canonical_minimizer_positions_cache_blocked(seq, k, w, &mut positions);
+            assert_eq!(positions, expected, "k={k}, w={w}, len={len}");
+        }
+        let mut expected = vec![];
+        super::minimizer_positions(ascii.as_slice(), k, w, &mut expected);
+        let mut positions = vec![];
+        super::minimizer_positions_cache_blocked(ascii.as_slice(), k, w, &mut positions);
+        assert_eq!(positions, expected, "k={k}, w={w}");
+
+        let bytes = &ascii.seq[..];
+        let mut expected = vec![];
+        super::minimizer_positions(bytes, k, w, &mut expected);
+        let mut positions = vec![];
+        super::minimizer_positions_cache_blocked(bytes, k, w, &mut positions);
+        assert_eq!(positions, expected, "k={k}, w={w}");
+    }
+}
+
+#[test]
+fn long_k() {
+    use crate::long_k::*;
+    let seq = PackedSeqVec::random(2000);
+    for k in [1, 31, 64, 65, 128, 129, 200, MAX_K] {
+        let hashes = nthash128_seq_scalar::<false, _>(seq.as_slice(), k).collect_vec();
+        let naive = (0..=seq.len() - k)
+            .map(|i| {
+                nthash128_seq_scalar::<false, _>(seq.slice(i..i + k), k)
+                    .next()
+                    .unwrap()
+            })
+            .collect_vec();
+        assert_eq!(hashes, naive, "k={k}");
+        let hashes64 =
+            crate::nthash64::nthash64_seq_scalar::<false, _>(seq.as_slice(), k).collect_vec();
+        assert_eq!(hashes.iter().map(|&h| fold64(h)).collect_vec(), hashes64);
+
+        for w in [1, 5, 31] {
+            let mut positions = vec![];
+            super::scalar::minimizer_positions_long_k_scalar(seq.as_slice(), k, w, &mut positions);
+            let expected = hashes
+                .windows(w)
+                .enumerate()
+                .map(|(i, window)| (i + window.iter().position_min().unwrap()) as u32)
+                .dedup()
+                .collect_vec();
+            assert_eq!(positions, expected, "k={k}, w={w}");
+
+            let mut simd = vec![];
+            super::minimizer_positions_long_k(seq.as_slice(), k, w, &mut simd);
+            assert_eq!(simd, positions, "k={k}, w={w}");
+        }
+    }
+}
+
+#[test]
+fn builder_presets() {
+    let seq = PackedSeqVec::random(5000);
+    for (preset, k, w, canonical, hash_width) in [
+        (Preset::Minimap2ShortRead, 21, 11, true, HashWidth::Bits32),
+        (Preset::Kraken2, 31, 5, true, HashWidth::Bits32),
+        (
+            Preset::SSHash { k: 19, w: 13 },
+            19,
+            13,
+            false,
+            HashWidth::Bits64,
+        ),
+    ] {
+        let config = MinimizerBuilder::preset(preset).build().unwrap();
+        assert_eq!(
+            (
+                config.k(),
+                config.w(),
+                config.is_canonical(),
+                config.hash_width()
+            ),
+            (k, w, canonical, hash_width),
+            "{preset:?}"
+        );
+        let mut positions = vec![];
+        config.positions(seq.as_slice(), &mut positions);
+        let expected = MinimizerBuilder::new(k, w)
+            .canonical(canonical)
+            .hash_width(hash_width)
+            .build()
+            .unwrap();
+        let mut expected_positions = vec![];
+        expected.positions(seq.as_slice(), &mut expected_positions);
+        assert_eq!(positions, expected_positions, "{preset:?}");
+    }
+    let config = MinimizerBuilder::preset(Preset::Kraken2)
+        .dedup(DedupScope::Global)
+        .build()
+        .unwrap();
+    assert_eq!(config.dedup(), DedupScope::Global);
+}
+
+#[test]
+fn try_positions() {
+    let seq = PackedSeqVec::random(1000);
+    let seq = seq.as_slice();
+    let mut positions = vec![];
+    assert_eq!(
+        super::try_minimizer_positions(seq, 0, 5, &mut positions),
+        Err(Error::KZero)
+    );
+    assert_eq!(
+        super::try_minimizer_positions(seq, MAX_K + 1, 5, &mut positions),
+        Err(Error::KTooLarge { k: MAX_K + 1 })
+    );
+    assert_eq!(
+        super::try_minimizer_positions(seq, 5, 0, &mut positions),
+        Err(Error::WZero)
+    );
+    assert_eq!(
+        super::try_minimizer_positions(seq, 5, 1 << 15, &mut positions),
+        Err(Error::WTooLarge { w: 1 << 15 })
+    );
+    assert_eq!(
+        super::try_canonical_minimizer_positions(seq, 500, 502, &mut positions),
+        Err(Error::KTooLarge { k: 500 })
+    );
+    assert_eq!(
+        super::try_canonical_minimizer_positions(seq.slice(0..100), 21, 81, &mut positions),
+        Err(Error::WindowLargerThanSequence { l: 101, len: 100 })
+    );
+    assert!(positions.is_empty());
+
+    for (k, w) in [(1, 1), (5, 11), (21, 80)] {
+        super::try_minimizer_positions(seq.slice(0..100), k, w, &mut positions).unwrap();
+        let mut expected = vec![];
+        super::minimizer_positions(seq.slice(0..100), k, w, &mut expected);
+        assert_eq!(positions, expected);
+        positions.clear();
+
+        super::try_canonical_minimizer_positions(seq, k, w, &mut positions).unwrap();
+        let mut expected = vec![];
+        super::canonical_minimizer_positions(seq, k, w, &mut expected);
+        assert_eq!(positions, expected);
+        positions.clear();
+    }
+    assert!(try_minimizers_seq_simd::<_, NtHasher>(seq, 0, 5).is_err());
+    assert!(try_minimizers_seq_simd::<_, NtHasher>(seq, 5, 11).is_ok());
+}
+
+#[test]
+fn minimizer_positions_fwd_rc() {
+    for len in [0, 1, 10, 100, 1000, 4321] {
+        let ascii = AsciiSeqVec::random(len);
+        let seq = PackedSeqVec::from_ascii(&ascii.seq);
+        let rc_ascii = ascii
+            .seq
+            .iter()
+            .rev()
+            .map(|&c| packed_seq::complement_char(c))
+            .collect_vec();
+        let rc = PackedSeqVec::from_ascii(&rc_ascii);
+        for (k, w) in [(1, 1), (5, 2), (5, 11), (21, 11), (31, 40), (40, 31)] {
+            if k + w - 1 > len {
+                continue;
+            }
+            let mut fwd_pos = vec![];
+            let mut rc_pos = vec![];
+            super::minimizer_positions_fwd_rc(seq.as_slice(), k, w, &mut fwd_pos, &mut rc_pos);
+            let mut expected = vec![];
+            super::minimizer_positions(seq.as_slice(), k, w, &mut expected);
+            assert_eq!(fwd_pos, expected, "len={len}, k={k}, w={w}");
+            let mut expected = vec![];
+            super::scalar::minimizer_positions_scalar(rc.as_slice(), k, w, &mut expected);
+            assert_eq!(rc_pos, expected, "len={len}, k={k}, w={w}");
+
+            // Appending to the same vectors deduplicates against their last position, as for `minimizer_positions`.
+            super::minimizer_positions_fwd_rc(seq.as_slice(), k, w, &mut fwd_pos, &mut rc_pos);
+            let mut expected = vec![];
+            super::minimizer_positions(rc.as_slice(), k, w, &mut expected);
+            super::minimizer_positions(rc.as_slice(), k, w, &mut expected);
+            assert_eq!(rc_pos, expected, "len={len}, k={k}, w={w}");
+        }
+    }
+}
+
+#[test]
+fn minimizer_positions_records_csr() {
+    let mut rng = rand::rng();
+    for (k, w) in [(1, 1), (5, 11), (21, 11), (31, 19), (16, 4)] {
+        let mut positions = vec![7];
+        let mut offsets = vec![];
+        let mut expected = vec![vec![7]];
+        for _ in 0..3 {
+            let num = rng.random_range(0..40);
+            let seqs = (0..num)
+                .map(|_| PackedSeqVec::random(rng.random_range(0..200)))
+                .collect_vec();
+            let slices = seqs.iter().map(|seq| seq.as_slice()).collect_vec();
+            super::minimizer_positions_records_csr(&slices, k, w, &mut positions, &mut offsets);
+            let mut out = vec![];
+            super::minimizer_positions_records(&slices, k, w, &mut out);
+            expected.extend(out);
+
+            if (k + w - 1) % 2 == 1 {
+                let mut canonical_positions = vec![];
+                let mut canonical_offsets = vec![];
+                super::canonical_minimizer_positions_records_csr(
+                    &slices,
+                    k,
+                    w,
+                    &mut canonical_positions,
+                    &mut canonical_offsets,
+                );
+                let mut out = vec![];
+                super::canonical_minimizer_positions_records(&slices, k, w, &mut out);
+                assert_eq!(canonical_offsets.len(), num + 1);
+                for (i, out) in out.iter().enumerate() {
+                    let range = canonical_offsets[i]..canonical_offsets[i + 1];
+                    assert_eq!(&canonical_positions[range], out, "k={k}, w={w}");
+                }
+            }
+        }
+        assert_eq!(offsets.len(), expected.len(), "k={k}, w={w}");
+        assert_eq!(offsets[0], 1);
+        assert_eq!(positions[0], 7);
+        for (i, out) in expected.iter().enumerate().skip(1) {
+            assert_eq!(&positions[offsets[i - 1]..offsets[i]], out, "k={k}, w={w}");
+        }
+    }
+}
+
+#[test]
+fn dedup_state() {
+    let mut rng = rand::rng();
+    for max in [2, 5, 100] {
+        let keys = (0..1000).map(|_| rng.random_range(0..max)).collect_vec();
+        let payloads = (0..1000).map(|_| rng.random()).collect_vec();
+        let mut expected_keys = vec![];
+        let mut expected_payloads = vec![];
+        for (i, &k) in keys.iter().enumerate() {
+            if i == 0 || keys[i - 1] != k {
+                expected_keys.push(k);
+                expected_payloads.push(payloads[i]);
+            }
+        }
+
+        // Split the stream at random points.
+        let mut state = simd_utils::DedupState::new();
+        let (mut keys_out, mut payloads_out) = (vec![], vec![]);
+        let mut start = 0;
+        while start < keys.len() {
+            let end = (start + rng.random_range(0..30)).min(keys.len());
+            state.extend_pairs(
+                &keys[start..end],
+                &payloads[start..end],
+                &mut keys_out,
+                &mut payloads_out,
+            );
+            start = end;
+        }
+        assert_eq!(keys_out, expected_keys, "max={max}");
+        assert_eq!(payloads_out, expected_payloads, "max={max}");
+        assert_eq!(state.last(), keys.last().copied());
+
+        let mut state = simd_utils::DedupState::new();
+        let mut out = vec![];
+        for (k, p) in keys.chunks_exact(8).zip(payloads.chunks_exact(8)) {
+            state.push(
+                S::new(k.try_into().unwrap()),
+                S::new(p.try_into().unwrap()),
+                &mut out,
+            );
+        }
+        assert_eq!(out, expected_payloads, "max={max}");
+    }
+}
+
+#[test]
+fn dedup_u64() {
+    let mut rng = rand::rng();
+    for max in [2, 5, 100] {
+        let values = (0..4 * 1000)
+            .map(|_| rng.random_range(0..max) << 35 | 7)
+            .collect_vec();
+        let mut expected = values.clone();
+        expected.dedup();
+
+        let mut out = vec![];
+        let mut prev = simd_utils::u64x4::new([!values[0]; 4]);
+        for chunk in values.chunks_exact(4) {
+            let new = simd_utils::u64x4::new(chunk.try_into().unwrap());
+            simd_utils::compress_unique_u64(prev, new, new, &mut out);
+            prev = new;
+        }
+        assert_eq!(out, expected, "max={max}");
+
+        for len in [0, 1, 3, 4, 5, 100, values.len()] {
+            let mut v = values[..len].to_vec();
+            let mut expected = v.clone();
+            expected.dedup();
+            simd_utils::dedup_vec_u64(&mut v);
+            assert_eq!(v, expected, "max={max}, len={len}");
+        }
+    }
+}
+
+#[test]
+fn dictionary_sampler() {
+    use crate::dictionary::DictionarySampler;
+    let (k, w) = (15, 11);
+    let records = (0..20)
+        .map(|i| PackedSeqVec::random(1000 * i))
+        .collect_vec();
+
+    let mut sampler = DictionarySampler::new(k, w, 0.25, usize::MAX);
+    for record in &records {
+        sampler.add(record.as_slice());
+    }
+    assert_eq!(sampler.density(), 0.25);
+    let samples = sampler.samples().map(|s| s.to_vec()).collect_vec();
+    assert!(!samples.is_empty());
+    // Each sample is a super-k-mer of one of the records.
+    let texts = records
+        .iter()
+        .map(|r| {
+            (0..r.len())
+                .map(|i| r.as_slice().get_ascii(i))
+                .collect_vec()
+        })
+        .collect_vec();
+    for sample in &samples {
+        assert!(sample.len() >= k + w - 1);
+        assert!(texts
+            .iter()
+            .any(|text| text.windows(sample.len()).any(|win| win == sample)));
+    }
+    // Adding the same records again does not add samples.
+    for record in &records {
+        sampler.add(record.as_slice());
+    }
+    assert_eq!(sampler.samples().count(), samples.len());
+
+    // With bounded memory, the density is lowered, and the result equals sampling with that density from the start.
+    let max_bytes = sampler.bytes() / 3;
+    let mut bounded = DictionarySampler::new(k, w, 0.25, max_bytes);
+    for record in &records {
+        bounded.add(record.as_slice());
+    }
+    assert!(bounded.bytes() <= max_bytes);
+    assert!(bounded.density() < 0.25);
+    let mut direct = DictionarySampler::new(k, w, bounded.density(), usize::MAX);
+    for record in &records {
+        direct.add(record.as_slice());
+    }
+    assert!(bounded.samples().eq(direct.samples()));
+    let (data, sizes) = bounded.continuous_samples();
+    assert_eq!(data, bounded.samples().collect_vec().concat());
+    assert_eq!(sizes, bounded.samples().map(|s| s.len()).collect_vec());
+}
+
+#[test]
+fn partition_by_minimizer() {
+    let seq = PackedSeqVec::random(5000);
+    let seq = seq.as_slice();
+    for (k, w, num_buckets) in [(5, 11, 1), (11, 5, 7), (21, 11, 16)] {
+        let l = k + w - 1;
+        let partition = super::partition_by_minimizer(seq, k, w, num_buckets);
+        assert_eq!(partition.seqs.len(), num_buckets);
+        let num_super_kmers: usize = partition.offsets.iter().map(|o| o.len() - 1).sum();
+        let mut super_kmers = vec![];
+        super::super_kmers(seq, k, w, &mut super_kmers);
+        assert_eq!(num_super_kmers, super_kmers.len());
+        for (bucket, (bucket_seq, offsets)) in zip(&partition.seqs, &partition.offsets).enumerate()
+        {
+            assert_eq!(*offsets.last().unwrap(), bucket_seq.len());
+            for (&start, &end) in offsets.iter().tuple_windows() {
+                let super_kmer = bucket_seq.slice(start..end);
+                assert!(super_kmer.len() >= l);
+                // Every window of the super-k-mer has its minimizer in this bucket.
+                for i in 0..=super_kmer.len() - l {
+                    let window = super_kmer.slice(i..i + l);
+                    let pos = i + minimizer::<NtHasher>(window, k);
+                    let kmer = super_kmer.slice(pos..pos + k);
+                    assert_eq!(super::minimizer_bucket(kmer, num_buckets), bucket);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn downsample_with_coverage() {
+    let seq = PackedSeqVec::random(20000);
+    let seq = seq.as_slice();
+    for (k, w) in [(5, 11), (21, 11), (31, 19)] {
+        let mut pos = vec![];
+        let mut hashes = vec![];
+        super::minimizer_positions_and_hashes(seq, k, w, &mut pos, &mut hashes);
+        for max_window in [w, 2 * w, 5 * w] {
+            for mode in [
+                Downsample::Fraction(0.0),
+                Downsample::Fraction(0.1),
+                Downsample::Fraction(1.0),
+                Downsample::TopK(0),
+                Downsample::TopK(pos.len() / 10),
+                Downsample::TopK(pos.len()),
+            ] {
+                let mut out = vec![];
+                super::downsample_with_coverage(
+                    &pos,
+                    &hashes,
+                    seq.len(),
+                    k,
+                    max_window,
+                    mode,
+                    &mut out,
+                );
+                assert!(is_sorted(&out));
+                assert!(verify_coverage(&out, seq.len(), k, max_window).is_ok());
+                // All selected seeds are kept.
+                let selected = match mode {
+                    Downsample::Fraction(f) => zip(&pos, &hashes)
+                        .filter(|(_, &h)| (h as f64) < f * (1u64 << 32) as f64)
+                        .map(|(&p, _)| p)
+                        .collect_vec(),
+                    Downsample::TopK(n) => {
+                        let mut by_hash = zip(&hashes, &pos).collect_vec();
+                        by_hash.sort();
+                        by_hash[..n].iter().map(|&(_, &p)| p).sorted().collect_vec()
+                    }
+                };
+                assert!(selected.iter().all(|p| out.binary_search(p).is_ok()));
+                assert!(out.iter().all(|p| pos.binary_search(p).is_ok()));
+                if mode == Downsample::Fraction(1.0) || mode == Downsample::TopK(pos.len()) {
+                    assert_eq!(out, pos);
+                }
+            }
+        }
+        let mut out = vec![];
+        super::downsampled_minimizer_positions(
+            seq,
+            k,
+            w,
+            3 * w,
+            Downsample::Fraction(0.1),
+            &mut out,
+        );
+        assert!(verify_coverage(&out, seq.len(), k, 3 * w).is_ok());
+        assert!(out.len() < pos.len());
+    }
+}
+
+#[test]
+fn linearize() {
+    for len in [0, 1, 7, 8, 63, 64, 65, 100, 1000, 5000] {
+        let seq = PackedSeqVec::random(len);
+        let ascii = AsciiSeqVec::random(len);
+        for (k, w) in [(1, 1), (5, 11), (21, 11), (31, 64)] {
+            if len < k + w - 1 {
+                continue;
+            }
+            let head_padding = minimizers_seq_simd::<_, NtHasher>(seq.as_slice(), k, w);
+            let linear = super::linearize(head_padding);
+            assert_eq!(linear.len(), len - (k + w - 1) + 1);
+            let expected = collect(minimizers_seq_simd::<_, NtHasher>(seq.as_slice(), k, w));
+            assert_eq!(linear.collect_vec(), expected);
+
+            for (window, pos) in window_minimizer_positions(ascii.as_slice(), k, w).enumerate() {
+                assert!(window <= pos as usize && (pos as usize) < window + w);
+            }
+            let mut positions = vec![];
+            super::minimizer_positions(ascii.as_slice(), k, w, &mut positions);
+            assert_eq!(
+                window_minimizer_positions(ascii.as_slice(), k, w)
+                    .dedup()
+                    .collect_vec(),
+                positions
+            );
+        }
+    }
+}
+
+#[test]
+fn window_strandedness() {
+    test_on_inputs(|k, w, _slice, ascii_seq, packed_seq| {
+        let l = k + w - 1;
+        let len = packed_seq.len();
+        let mut expected = vec![];
+        scalar::window_strandedness_scalar(ascii_seq, l, &mut expected);
+        assert_eq!(expected.len(), (len + 1).saturating_sub(l));
+        // Scores are `#GT - #AC`.
+        let gt_prefix = (0..len)
+            .scan(0, |gt, j| {
+                *gt += matches!(ascii_seq.get_ascii(j), b'G' | b'T') as i32;
+                Some(*gt)
+            })
+            .collect_vec();
+        for (i, &score) in expected.iter().enumerate() {
+            let gt = gt_prefix[i + l - 1] - if i > 0 { gt_prefix[i - 1] } else { 0 };
+            assert_eq!(score, 2 * gt - l as i32);
+        }
+        let canonical = canonical::canonical_windows_seq_scalar(packed_seq, k, w).collect_vec();
+        assert_eq!(expected.iter().map(|&s| s >= 0).collect_vec(), canonical);
+
+        let mut scores = vec![];
+        super::window_strandedness(packed_seq, l, &mut scores);
+        assert_eq!(scores, expected, "k={k}, w={w}, len={len}");
+        scores.clear();
+        super::window_strandedness(ascii_seq, l, &mut scores);
+        assert_eq!(scores, expected, "k={k}, w={w}, len={len}");
+    });
+}
+
+#[test]
+fn check_joins() {
+    for len in [0, 10, 63, 64, 65, 100, 1000, 4321] {
+        let ascii_seq = AsciiSeqVec::random(len);
+        let packed_seq = PackedSeqVec::from_ascii(&ascii_seq.seq);
+        for (k, w) in [(1, 1), (5, 4), (21, 11), (31, 19), (63, 64)] {
+            assert_eq!(check_minimizer_joins(ascii_seq.as_slice(), k, w), Ok(()));
+            assert_eq!(check_minimizer_joins(packed_seq.as_slice(), k, w), Ok(()));
+            assert_eq!(check_minimizer_joins(&ascii_seq.seq[..], k, w), Ok(()));
+            assert_eq!(
+                check_canonical_minimizer_joins(packed_seq.as_slice(), k, w),
+                Ok(())
+            );
+        }
+    }
+    let mismatch = JoinMismatch {
+        lane: 3,
+        window: 120,
+        simd: 121,
+        scalar: 125,
+    };
+    assert_eq!(
+        mismatch.to_string(),
+        "Window 120 in lane 3 has its minimizer at position 121 using SIMD, but at 125 using scalar code."
+    );
+}
+
+#[test]
+fn minimizer_index() {
+    use crate::index::MinimizerIndex;
+    use std::collections::HashMap;
+
+    for len in [0, 50, 1000, 20000] {
+        let seq = PackedSeqVec::random(len);
+        let seq = seq.as_slice();
+        for (k, w) in [(5, 11), (21, 11), (31, 19)] {
+            for canonical in [false, true] {
+                let (mut positions, mut hashes) = (vec![], vec![]);
+                let index = if canonical {
+                    super::canonical_minimizer_positions_and_hashes(
+                        seq,
+                        k,
+                        w,
+                        &mut positions,
+                        &mut hashes,
+                    );
+                    MinimizerIndex::build_canonical(seq, k, w)
+                } else {
+                    super::minimizer_positions_and_hashes(seq, k, w, &mut positions, &mut hashes);
+                    MinimizerIndex::build(seq, k, w)
+                };
+                let mut expected: HashMap<u32, Vec<u32>> = HashMap::new();
+                for (&pos, &hash) in zip(&positions, &hashes) {
+                    expected.entry(hash).or_default().push(pos);
+                }
+                for v in expected.values_mut() {
+                    v.sort_unstable();
+                    v.dedup();
+                }
+                assert_eq!(index.len(), expected.len());
+                assert_eq!(
+                    index.num_positions(),
+                    expected.values().map(|v| v.len()).sum::<usize>()
+                );
+                for (&pos, &hash) in zip(&positions, &hashes) {
+                    assert_eq!(index.lookup_hash(hash), expected[&hash]);
+                    let kmer = seq.slice(pos as usize..pos as usize + k);
+                    assert_eq!(index.lookup(kmer), expected[&hash]);
+                }
+                assert!((0..100)
+                    .map(|_| rand::random::<u32>())
+                    .filter(|h| !expected.contains_key(h))
+                    .all(|h| index.lookup_hash(h).is_empty()));
+            }
+        }
+    }
+}
+
+#[test]
+fn sketch_seeds() {
+    use crate::kmer::{canonical_kmer_value, kmer_value};
+
+    assert_eq!(std::mem::size_of::<super::Seed>(), 16);
+
+    for len in [0, 50, 1000, 20000] {
+        let seq = PackedSeqVec::random(len);
+        let seq = seq.as_slice();
+        for (k, w) in [(1, 5), (5, 11), (15, 11), (20, 20), (31, 19)] {
+            let seeds = super::sketch_seq_simd(seq, k, w);
+            let mut positions = vec![];
+            scalar::canonical_minimizer_positions_scalar(seq, k, w, &mut positions);
+            assert!(seeds.iter().map(|seed| seed.pos).eq(positions));
+            for seed in &seeds {
+                let kmer = seq.slice(seed.pos as usize..seed.pos as usize + k);
+                let canonical = canonical_kmer_value(kmer);
+                assert_eq!(seed.hash, super::hash64(canonical, k));
+                assert_eq!(seed.strand, (kmer_value(kmer) != canonical) as u32);
+            }
+        }
+    }
+}
+
+#[test]
+fn append_dedup() {
+    // A single window, so that the second call appends the same minimizer again.
+    let (k, w) = (5, 11);
+    let seq = PackedSeqVec::random(k + w - 1);
+    let seq = seq.as_slice();
+    let mut expected = vec![];
+    let (mut pos, mut hashes) = (vec![], vec![]);
+    let mut masked = vec![];
+    let valid = vec![true; seq.len()];
+    for _ in 0..2 {
+        super::minimizer_positions(seq, k, w, &mut expected);
+        super::minimizer_positions_and_hashes(seq, k, w, &mut pos, &mut hashes);
+        super::minimizer_positions_masked(seq, k, w, &valid, &mut masked);
+    }
+    assert_eq!(expected.len(), 1);
+    assert_eq!(pos, expected);
+    assert_eq!(hashes.len(), 1);
+    assert_eq!(masked, expected);
+
+    #[cfg(feature = "rayon")]
+    {
+        let mut par_pos = vec![];
+        for _ in 0..2 {
+            super::minimizers_par_chunks(seq, k, w, 2, &mut par_pos);
+        }
+        assert_eq!(par_pos, expected);
+    }
+}
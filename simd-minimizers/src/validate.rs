@@ -0,0 +1,89 @@
+//! Validation that ASCII DNA input only contains `ACGT` characters.
+//!
+//! Other characters are silently mapped to arbitrary bases when packing or hashing,
+//! so services should check their input first, and report the offending positions.
+//! The scans work on fixed-size chunks with branchless checks, so that they are auto-vectorized.
+
+/// Number of bytes that are checked at once.
+const CHUNK: usize = 64;
+
+/// Error returned when the input contains a character other than `ACGTacgt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBase {
+    /// The byte offset of the first invalid character.
+    pub pos: usize,
+    /// The invalid character.
+    pub byte: u8,
+}
+
+impl std::fmt::Display for InvalidBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.byte.is_ascii_graphic() {
+            write!(
+                f,
+                "invalid base '{}' at position {}",
+                self.byte as char, self.pos
+            )
+        } else {
+            write!(
+                f,
+                "invalid byte 0x{:02x} at position {}",
+                self.byte, self.pos
+            )
+        }
+    }
+}
+
+impl std::error::Error for InvalidBase {}
+
+/// Whether `c` is one of `ACGTacgt`.
+#[inline(always)]
+pub fn is_acgt(c: u8) -> bool {
+    // Setting the 0x20 bit maps upper case to lower case, and no other byte to a lower case base.
+    let c = c | 0x20;
+    (c == b'a') | (c == b'c') | (c == b'g') | (c == b't')
+}
+
+/// Whether all characters in `chunk` are `ACGTacgt`, without early exit.
+#[inline(always)]
+fn all_acgt(chunk: &[u8]) -> bool {
+    chunk.iter().fold(true, |acc, &c| acc & is_acgt(c))
+}
+
+/// Check that `seq` only contains `ACGTacgt`, and return the first invalid character otherwise.
+pub fn validate_acgt(seq: &[u8]) -> Result<(), InvalidBase> {
+    match first_non_acgt(seq) {
+        None => Ok(()),
+        Some(pos) => Err(InvalidBase {
+            pos,
+            byte: seq[pos],
+        }),
+    }
+}
+
+/// The offset of the first character of `seq` that is not `ACGTacgt`.
+pub fn first_non_acgt(seq: &[u8]) -> Option<usize> {
+    for (i, chunk) in seq.chunks(CHUNK).enumerate() {
+        if !all_acgt(chunk) {
+            let p = chunk.iter().position(|&c| !is_acgt(c)).unwrap();
+            return Some(i * CHUNK + p);
+        }
+    }
+    None
+}
+
+/// Append the offsets of all characters of `seq` that are not `ACGTacgt` to `out_vec`.
+pub fn non_acgt_positions(seq: &[u8], out_vec: &mut Vec<usize>) {
+    for (i, chunk) in seq.chunks(CHUNK).enumerate() {
+        if all_acgt(chunk) {
+            continue;
+        }
+        out_vec.extend(
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|&(_, &c)| !is_acgt(c))
+                .map(|(p, _)| i * CHUNK + p),
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Verification of the window guarantee of a set of sampled positions.
+//!
+//! Any sampling scheme with window size `w` must select at least one k-mer in every window of `w` consecutive k-mers.
+//!
+//! [`is_sorted`] checks the order of positions that consumers such as binary searches rely on.
+use std::borrow::Cow;
+use std::iter::zip;
+
+/// Error returned when some window does not contain any of the selected positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// The index of the first window, i.e. the position of its first k-mer, that contains no selected position.
+    pub window: usize,
+}
+
+impl std::fmt::Display for Gap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "window {} does not contain a selected position",
+            self.window
+        )
+    }
+}
+
+impl std::error::Error for Gap {}
+
+/// Whether `positions` are non-decreasing.
+///
+/// This holds for the output of all _forward_ schemes, such as [`minimizer_positions`](crate::minimizer_positions),
+/// also across the boundaries of the chunks that are processed in parallel.
+/// Canonical minimizer positions are not sorted, since the strand of consecutive windows can differ.
+pub fn is_sorted(positions: &[u32]) -> bool {
+    positions.is_sorted()
+}
+
+/// Check that every window of `w` consecutive k-mers in a sequence of length `seq_len` contains one of `positions`.
+///
+/// Positions do not need to be sorted or distinct. Positions of k-mers past the end of the sequence are ignored.
+pub fn verify_coverage(positions: &[u32], seq_len: usize, k: usize, w: usize) -> Result<(), Gap> {
+    assert!(k > 0);
+    assert!(w > 0);
+    let num_windows = (seq_len + 1).saturating_sub(k + w - 1);
+    if num_windows == 0 {
+        return Ok(());
+    }
+    let num_kmers = seq_len - k + 1;
+
+    let positions = if positions.is_sorted() {
+        Cow::Borrowed(positions)
+    } else {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        Cow::Owned(positions)
+    };
+    let end = positions.partition_point(|&p| (p as usize) < num_kmers);
+    let positions = &positions[..end];
+
+    // Fast path: the largest gap between consecutive positions, which vectorizes well.
+    let max_gap = zip(positions, &positions[1.min(end)..])
+        .map(|(&a, &b)| b - a)
+        .max()
+        .unwrap_or(0);
+    if let (Some(&first), Some(&last)) = (positions.first(), positions.last()) {
+        if (first as usize) < w && max_gap as usize <= w && last as usize >= num_windows - 1 {
+            return Ok(());
+        }
+    }
+
+    // Slow path: find the first uncovered window.
+    // All windows before `next` are covered.
+    let mut next = 0;
+    for &p in positions {
+        let p = p as usize;
+        if p >= next + w {
+            return Err(Gap { window: next });
+        }
+        next = p + 1;
+    }
+    if next < num_windows {
+        return Err(Gap { window: next });
+    }
+    Ok(())
+}
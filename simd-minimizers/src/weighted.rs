@@ -0,0 +1,100 @@
+//! Minimizers with an ordering that combines the hash of each k-mer with a per-position weight.
+//!
+//! Each k-mer has a `u16` weight, e.g. derived from its mappability or base qualities.
+//! The weight is added (saturating) to the upper 16 bits of the hash, which are the bits compared by the sliding window minimum,
+//! so that k-mers with a higher weight are less likely to be selected.
+//! A weight of `0` gives the plain random minimizer.
+//!
+//! The weights are a second input stream next to the sequence, and are read in the same SIMD pass as the hashes.
+use std::array::from_fn;
+
+use crate::nthash::{nthash_mapper, nthash_seq_scalar, Captures, CharHasher};
+use crate::sliding_min::{sliding_min_mapper, sliding_min_scalar};
+use crate::S;
+use packed_seq::Seq;
+
+/// Add `weight` to the upper 16 bits of `hash`, saturating at `u16::MAX`.
+pub fn weighted_hash(hash: u32, weight: u16) -> u32 {
+    ((hash >> 16) + weight as u32).min(0xffff) << 16 | (hash & 0xffff)
+}
+
+/// A function that 'eats' k-mer hashes and returns them with the weight of their k-mer added.
+///
+/// `weights[i]` is the weight of the k-mer starting at position `i`.
+/// `len` is the number of values in each chunk, as passed to `sliding_min_mapper`.
+/// The first `k-1` returned values are bogus, since they correspond to incomplete k-mers.
+pub fn weighted_hash_mapper(
+    weights: &[u16],
+    k: usize,
+    w: usize,
+    len: usize,
+) -> impl FnMut(S) -> S + Clone + '_ {
+    assert!(k > 0);
+    assert!(w > 0);
+
+    // Number of windows in each lane, and thus the offset of each lane.
+    let n = len.saturating_sub(k + w - 2);
+    let lane_offsets: [usize; 8] = from_fn(|l| l * n);
+    let max_hi = S::splat(0xffff);
+    let lo_mask = S::splat(0xffff);
+    // Index of the character that is added next.
+    let mut i: usize = 0;
+
+    #[inline(always)]
+    move |hash: S| {
+        // The k-mer starting at lane-local position `i-(k-1)` is now complete.
+        let start = (i + 1).wrapping_sub(k);
+        i += 1;
+        // K-mers in the padding past the end of the sequence get weight 0.
+        let weight = S::new(from_fn(|l| {
+            weights
+                .get(lane_offsets[l].wrapping_add(start))
+                .map_or(0, |&w| w as u32)
+        }));
+        (((hash >> 16_i32) + weight).min(max_hi) << 16_i32) | (hash & lo_mask)
+    }
+}
+
+/// Returns an iterator over the absolute positions of the weighted minimizers of a sequence,
+/// with one value for each window.
+///
+/// `weights[i]` is the weight of the k-mer starting at position `i`.
+pub fn weighted_minimizers_seq_scalar<'s, H: CharHasher>(
+    seq: impl Seq<'s>,
+    weights: &'s [u16],
+    k: usize,
+    w: usize,
+) -> impl ExactSizeIterator<Item = u32> + Captures<&'s ()> {
+    assert!(weights.len() >= (seq.len() + 1).saturating_sub(k));
+    let it = nthash_seq_scalar::<false, H>(seq, k)
+        .zip(weights)
+        .map(|(hash, &weight)| weighted_hash(hash, weight));
+    sliding_min_scalar::<true>(it, w)
+}
+
+/// Like `minimizers_seq_simd`, but with the weight of each k-mer added to its hash.
+///
+/// `weights[i]` is the weight of the k-mer starting at position `i`.
+pub fn weighted_minimizers_seq_simd<'s, SEQ: Seq<'s>, H: CharHasher>(
+    seq: SEQ,
+    weights: &'s [u16],
+    k: usize,
+    w: usize,
+) -> (impl ExactSizeIterator<Item = S> + Captures<&'s ()>, usize) {
+    assert!(weights.len() >= (seq.len() + 1).saturating_sub(k));
+    let l = k + w - 1;
+
+    let (add_remove, padding) = seq.par_iter_bp_delayed(k + w - 1, k - 1);
+
+    let mut nthash = nthash_mapper::<false, SEQ, H>(k, w);
+    let mut weighted = weighted_hash_mapper(weights, k, w, add_remove.len());
+    let mut sliding_min = sliding_min_mapper::<true>(w, k, add_remove.len());
+
+    let mut head = add_remove.map(move |(a, rk)| {
+        let hash = weighted(nthash((a, rk)));
+        sliding_min(hash)
+    });
+
+    head.by_ref().take(l - 1).for_each(drop);
+    (head, padding)
+}
@@ -0,0 +1,49 @@
+//! SIMD-chunked traversal of all windows of a sequence, for external per-window algorithms.
+//!
+//! As for the minimizer pipeline, the windows are split into 8 chunks of consecutive windows,
+//! and the `i`th item contains the `i`th window of each chunk, one per SIMD lane.
+//! Lanes past the last window are _padding_: their start is the length of the sequence and their view is empty.
+use packed_seq::Seq;
+
+use crate::S;
+
+/// The number of windows in each of the 8 chunks.
+#[inline(always)]
+fn chunk_len(num_windows: usize) -> usize {
+    num_windows.div_ceil(8)
+}
+
+/// Iterate over the windows of length `l` of `seq` in 8 parallel chunks.
+///
+/// Each item contains the start of the window in each lane, and a view on the window in each lane.
+/// Also returns the number of padding lanes at the end of the last chunk, as for the other `_seq_simd` functions.
+pub fn par_windows_seq<'s, SEQ: Seq<'s>>(
+    seq: SEQ,
+    l: usize,
+) -> (impl ExactSizeIterator<Item = (S, [SEQ; 8])>, usize) {
+    assert!(l > 0);
+    assert!(seq.len() <= u32::MAX as usize);
+    let len = seq.len();
+    let num_windows = (len + 1).saturating_sub(l);
+    let n = chunk_len(num_windows);
+    let padding = 8 * n - num_windows;
+    let it = (0..n).map(move |i| {
+        let starts: [usize; 8] = std::array::from_fn(|lane| lane * n + i);
+        let views = starts.map(|start| {
+            if start < num_windows {
+                seq.slice(start..start + l)
+            } else {
+                seq.slice(len..len)
+            }
+        });
+        let starts = starts.map(|start| {
+            if start < num_windows {
+                start as u32
+            } else {
+                len as u32
+            }
+        });
+        (S::new(starts), views)
+    });
+    (it, padding)
+}